@@ -0,0 +1,167 @@
+use anyhow::{Context, Result, bail};
+use librbufr::core::FXY;
+use librbufr::decode::Decoders;
+use librbufr::decoder::DecodeVisitor;
+use librbufr::parser::MessageReader;
+use librbufr::{Decoder, Value};
+use std::ops::ControlFlow;
+use std::path::Path;
+
+/// What `grep` looks for; at least one of `descriptor` and `name` is set
+pub struct GrepQuery {
+    pub descriptor: Option<FXY>,
+    /// Element name, matched ignoring case
+    pub name: Option<String>,
+    /// Value the element must have; any value that isn't missing when `None`
+    pub value: Option<String>,
+}
+
+impl GrepQuery {
+    fn matches_value(&self, value: &Value) -> bool {
+        match (&self.value, value) {
+            (_, Value::Missing) => false,
+            (None, _) => true,
+            (Some(wanted), Value::Number(n)) => wanted
+                .parse::<f64>()
+                .is_ok_and(|wanted| (n - wanted).abs() <= 1e-9 * wanted.abs().max(1.0)),
+            (Some(wanted), Value::String(s)) => s.trim_end_matches([' ', '\0']) == wanted,
+            (Some(_), Value::Bytes(_)) => false,
+        }
+    }
+}
+
+/// Finds, subset by subset, the first element matching the query
+struct Matcher<'q> {
+    query: &'q GrepQuery,
+    /// Descriptors the query's name stands for in the current message
+    named: Vec<FXY>,
+    subset: usize,
+    matched: Vec<(usize, FXY, Value<'static>)>,
+}
+
+impl DecodeVisitor for Matcher<'_> {
+    fn element(&mut self, fxy: FXY, value: Value) -> ControlFlow<()> {
+        let already = self
+            .matched
+            .last()
+            .is_some_and(|(subset, _, _)| *subset == self.subset);
+        let descriptor = self.query.descriptor.is_none_or(|wanted| wanted == fxy);
+        let named = self.query.name.is_none() || self.named.contains(&fxy);
+        if !already && descriptor && named && self.query.matches_value(&value) {
+            self.matched.push((self.subset, fxy, value.into_owned()));
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn end_subset(&mut self, _index: usize) -> ControlFlow<()> {
+        self.subset += 1;
+        ControlFlow::Continue(())
+    }
+}
+
+/// Print the message offsets and subsets of `input` holding an element
+/// that matches `query`
+pub fn grep(input: &Path, query: &GrepQuery) -> Result<()> {
+    if query.descriptor.is_none() && query.name.is_none() {
+        bail!("Give --descriptor or --name");
+    }
+
+    let mut reader = MessageReader::open(input)
+        .with_context(|| format!("Failed to read {}", input.display()))?;
+    let offsets = reader.offsets().to_vec();
+    let mut decoders = Decoders::default();
+    for (index, (offset, message)) in offsets.into_iter().zip(&mut reader).enumerate() {
+        let Ok(message) = message else {
+            eprintln!("Skipping message {}: failed to read", index);
+            continue;
+        };
+        let decoder = match decoders.get(&message) {
+            Ok(decoder) => decoder,
+            Err(e) => {
+                eprintln!("Skipping message {}: {}", index, e);
+                continue;
+            }
+        };
+
+        let mut matcher = Matcher {
+            query,
+            named: named_descriptors(decoder, &message, query.name.as_deref()),
+            subset: 0,
+            matched: vec![],
+        };
+        if let Err(e) = decoder.decode_with(&message, &mut matcher) {
+            eprintln!("Message {}: {}", index, e);
+        }
+        for (subset, fxy, value) in matcher.matched {
+            println!(
+                "{}: message {} (offset {}) subset {}: {}-{:02}-{:03} = {}",
+                input.display(),
+                index,
+                offset,
+                subset,
+                fxy.f,
+                fxy.x,
+                fxy.y,
+                value
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Descriptors of `message` whose element name is `name`, ignoring case
+fn named_descriptors(
+    decoder: &Decoder,
+    message: &librbufr::block::MessageBlock,
+    name: Option<&str>,
+) -> Vec<FXY> {
+    let Some(name) = name else {
+        return vec![];
+    };
+    let Ok(expanded) = message.expanded_descriptors(decoder) else {
+        return vec![];
+    };
+    expanded
+        .into_iter()
+        .filter(|entry| entry.name.trim().eq_ignore_ascii_case(name.trim()))
+        .map(|entry| entry.fxy)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(value: Option<&str>) -> GrepQuery {
+        GrepQuery {
+            descriptor: None,
+            name: Some("AIR TEMPERATURE".to_string()),
+            value: value.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_matches_value() {
+        // Without a value, anything that isn't missing
+        let any = query(None);
+        assert!(any.matches_value(&Value::Number(0.0)));
+        assert!(any.matches_value(&Value::Bytes(vec![0xFF].into())));
+        assert!(!any.matches_value(&Value::Missing));
+
+        // Numbers compare within rounding of the decoded scale
+        let number = query(Some("283.15"));
+        assert!(number.matches_value(&Value::Number(283.15)));
+        assert!(number.matches_value(&Value::Number(28315.0 * 0.01)));
+        assert!(!number.matches_value(&Value::Number(283.16)));
+        assert!(!number.matches_value(&Value::Missing));
+        assert!(query(Some("0")).matches_value(&Value::Number(1e-12)));
+        assert!(!query(Some("warm")).matches_value(&Value::Number(283.15)));
+
+        // Strings compare without their padding
+        let text = query(Some("EGLL"));
+        assert!(text.matches_value(&Value::String("EGLL    ".into())));
+        assert!(text.matches_value(&Value::String("EGLL\0\0".into())));
+        assert!(!text.matches_value(&Value::String("EGLLX".into())));
+        assert!(!text.matches_value(&Value::Bytes(b"EGLL".to_vec().into())));
+    }
+}
@@ -0,0 +1,43 @@
+use anyhow::{Context, Result};
+use librbufr::index::IndexEntry;
+use librbufr::parser::MessageReader;
+use std::path::Path;
+
+/// Print a one-line summary of each of the first `count` messages of
+/// `input`
+pub fn head(input: &Path, count: usize) -> Result<()> {
+    let mut reader = MessageReader::open(input)
+        .with_context(|| format!("Failed to read {}", input.display()))?;
+    let offsets = reader.offsets().to_vec();
+
+    println!(
+        "{:>6} {:>3} {:>6} {:>4} {:>19} {:>7} {:>4} {:>5}",
+        "index", "ed", "centre", "cat", "datetime", "subsets", "comp", "descs"
+    );
+    for (index, (offset, message)) in offsets.into_iter().zip(&mut reader).take(count).enumerate() {
+        let message = match message {
+            Ok(message) => message,
+            Err(e) => {
+                println!("{:>6} failed to read: {}", index, e);
+                continue;
+            }
+        };
+        let entry = IndexEntry::new(offset, &message);
+        let time = entry.obs_time.map_or_else(
+            || "-".to_string(),
+            |t| t.format("%Y-%m-%d %H:%M:%S").to_string(),
+        );
+        println!(
+            "{:>6} {:>3} {:>6} {:>4} {:>19} {:>7} {:>4} {:>5}",
+            index,
+            entry.header.edition,
+            entry.header.centre,
+            entry.header.data_category,
+            time,
+            entry.subsets,
+            if entry.compressed { "yes" } else { "no" },
+            entry.descriptors
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use anyhow::{Context, Result};
+use librbufr::index::{FileIndex, INDEX_EXTENSION, IndexEntry};
+use std::path::{Path, PathBuf};
+
+/// Which messages `ls` lists; `None` fields match every message
+pub struct LsFilter {
+    pub category: Option<u8>,
+    pub centre: Option<u16>,
+}
+
+impl LsFilter {
+    fn matches(&self, entry: &IndexEntry) -> bool {
+        self.category
+            .is_none_or(|category| entry.header.data_category == category)
+            && self
+                .centre
+                .is_none_or(|centre| entry.header.centre == centre)
+    }
+}
+
+pub fn write_index(input: &Path, output: Option<PathBuf>) -> Result<()> {
+    let output = output.unwrap_or_else(|| input.with_extension(INDEX_EXTENSION));
+    let index =
+        FileIndex::build(input).with_context(|| format!("Failed to index {}", input.display()))?;
+    index
+        .save(&output)
+        .with_context(|| format!("Failed to write {}", output.display()))?;
+    println!(
+        "Indexed {} messages into {}",
+        index.entries.len(),
+        output.display()
+    );
+    Ok(())
+}
+
+pub fn list(input: Option<&Path>, index: Option<&Path>, filter: &LsFilter) -> Result<()> {
+    let index = match (index, input) {
+        (Some(index), _) => FileIndex::load(index)
+            .with_context(|| format!("Failed to read index {}", index.display()))?,
+        (None, Some(input)) => FileIndex::build(input)
+            .with_context(|| format!("Failed to read {}", input.display()))?,
+        (None, None) => anyhow::bail!("Give a BUFR file or --index"),
+    };
+
+    println!(
+        "{:>6} {:>12} {:>8} {:>3} {:>6} {:>4} {:>19} {:>7}",
+        "index", "offset", "length", "ed", "centre", "cat", "datetime", "subsets"
+    );
+    for (i, entry) in index.entries.iter().enumerate() {
+        if !filter.matches(entry) {
+            continue;
+        }
+        let time = entry.obs_time.map_or_else(
+            || "-".to_string(),
+            |t| t.format("%Y-%m-%d %H:%M:%S").to_string(),
+        );
+        println!(
+            "{:>6} {:>12} {:>8} {:>3} {:>6} {:>4} {:>19} {:>7}",
+            i,
+            entry.offset,
+            entry.length,
+            entry.header.edition,
+            entry.header.centre,
+            entry.header.data_category,
+            time,
+            entry.subsets
+        );
+    }
+    Ok(())
+}
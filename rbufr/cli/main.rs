@@ -0,0 +1,145 @@
+mod grep;
+mod head;
+mod index;
+mod split;
+mod validate;
+
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use librbufr::core::FXY;
+use std::path::PathBuf;
+
+#[derive(Parser)]
+#[command(name = "rbufr")]
+#[command(about = "Inspect and process BUFR files", long_about = None)]
+struct Cli {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Write an index of the messages of a BUFR file
+    Index {
+        /// Input BUFR file, gzipped or not
+        input: PathBuf,
+
+        /// Output index (default: the input with a .bufridx extension)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// List the messages of a BUFR file, or of an index of one
+    Ls {
+        /// Input BUFR file; not needed with --index
+        #[arg(required_unless_present = "index")]
+        input: Option<PathBuf>,
+
+        /// Answer from this index instead of reading the file
+        #[arg(long)]
+        index: Option<PathBuf>,
+
+        /// Only messages of this data category
+        #[arg(long)]
+        category: Option<u8>,
+
+        /// Only messages from this originating centre
+        #[arg(long)]
+        centre: Option<u16>,
+    },
+    /// Write the messages of a BUFR file into one file per category, centre
+    /// or day
+    Split {
+        /// Input BUFR file, gzipped or not
+        input: PathBuf,
+
+        /// What to split by: "category", "centre" or "day"
+        #[arg(long)]
+        by: String,
+
+        /// Directory the files are written to, created if needed
+        #[arg(short, long)]
+        output_dir: PathBuf,
+    },
+    /// Print the messages and subsets holding an element with a given value
+    Grep {
+        /// Input BUFR file, gzipped or not
+        input: PathBuf,
+
+        /// Element descriptor, e.g. 001101
+        #[arg(short, long)]
+        descriptor: Option<String>,
+
+        /// Element name, ignoring case, e.g. "STATION NUMBER"
+        #[arg(short, long)]
+        name: Option<String>,
+
+        /// Value the element must have, a number or text; without it any
+        /// value that isn't missing matches
+        #[arg(short, long)]
+        value: Option<String>,
+    },
+    /// Print a one-line summary of the first messages of a BUFR file
+    Head {
+        /// Input BUFR file, gzipped or not
+        input: PathBuf,
+
+        /// Number of messages to summarize
+        #[arg(short = 'n', long, default_value_t = 10)]
+        count: usize,
+    },
+    /// Check every message of a BUFR file and print a JSON report; exits
+    /// with status 1 when a check fails
+    Validate {
+        /// Input BUFR file, gzipped or not
+        input: PathBuf,
+    },
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    match cli.command {
+        Commands::Index { input, output } => {
+            index::write_index(&input, output)?;
+        }
+        Commands::Ls {
+            input,
+            index,
+            category,
+            centre,
+        } => {
+            let filter = index::LsFilter { category, centre };
+            index::list(input.as_deref(), index.as_deref(), &filter)?;
+        }
+        Commands::Split {
+            input,
+            by,
+            output_dir,
+        } => {
+            split::split(&input, &by, &output_dir)?;
+        }
+        Commands::Grep {
+            input,
+            descriptor,
+            name,
+            value,
+        } => {
+            let query = grep::GrepQuery {
+                descriptor: descriptor.as_deref().map(FXY::from_fxxyyy).transpose()?,
+                name,
+                value,
+            };
+            grep::grep(&input, &query)?;
+        }
+        Commands::Head { input, count } => {
+            head::head(&input, count)?;
+        }
+        Commands::Validate { input } => {
+            if !validate::validate(&input)? {
+                std::process::exit(1);
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use anyhow::{Context, Result, bail};
+use chrono::NaiveDateTime;
+use librbufr::parser::{MessageHeader, parse_with_filter};
+use std::path::Path;
+
+/// The file name, without extension, `by` puts a message with `header`
+/// and observation time `time` in
+fn split_key(by: &str, header: &MessageHeader, time: Option<NaiveDateTime>) -> String {
+    match by {
+        "category" => format!("category_{:03}", header.data_category),
+        "centre" => format!("centre_{:05}", header.centre),
+        _ => time.map_or_else(
+            || "unknown".to_string(),
+            |t| t.format("%Y-%m-%d").to_string(),
+        ),
+    }
+}
+
+/// Write the messages of `input` to one file per key of `by` under
+/// `output_dir`
+pub fn split(input: &Path, by: &str, output_dir: &Path) -> Result<()> {
+    let by = by.to_lowercase();
+    if !matches!(by.as_str(), "category" | "centre" | "day") {
+        bail!(
+            "Invalid split key: {}. Use 'category', 'centre' or 'day'",
+            by
+        );
+    }
+
+    let file = parse_with_filter(input, |_| true)
+        .with_context(|| format!("Failed to read {}", input.display()))?;
+    let paths = file
+        .split_by(output_dir, |message| {
+            split_key(&by, &message.header(), message.obs_time())
+        })
+        .with_context(|| format!("Failed to write to {}", output_dir.display()))?;
+    for path in &paths {
+        println!("{}", path.display());
+    }
+    println!(
+        "Split {} messages into {} files",
+        file.message_count(),
+        paths.len()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_split_key() {
+        let header = MessageHeader {
+            edition: 4,
+            centre: 98,
+            subcentre: 0,
+            update_sequence_number: 0,
+            data_category: 2,
+            data_subcategory: 4,
+            master_table_version: 35,
+            local_table_version: 0,
+        };
+        let time = NaiveDate::from_ymd_opt(2024, 3, 9)
+            .unwrap()
+            .and_hms_opt(23, 30, 0);
+
+        assert_eq!(split_key("category", &header, time), "category_002");
+        assert_eq!(split_key("centre", &header, time), "centre_00098");
+        assert_eq!(split_key("day", &header, time), "2024-03-09");
+        assert_eq!(split_key("day", &header, None), "unknown");
+    }
+}
@@ -0,0 +1,238 @@
+use anyhow::{Context, Result};
+use librbufr::block::MessageBlock;
+use librbufr::decode::Decoders;
+use librbufr::parser::MessageReader;
+use librbufr::structs::versions::MessageVersion;
+use serde_json::{Value as Json, json};
+use std::path::Path;
+
+/// The outcome of one check of one message
+enum Check {
+    Pass,
+    Fail(String),
+    Skip(String),
+}
+
+impl Check {
+    fn to_json(&self, name: &str) -> Json {
+        match self {
+            Check::Pass => json!({ "check": name, "status": "pass" }),
+            Check::Fail(detail) => json!({ "check": name, "status": "fail", "detail": detail }),
+            Check::Skip(detail) => json!({ "check": name, "status": "skip", "detail": detail }),
+        }
+    }
+}
+
+/// Check every message of `input` and print the report as JSON; returns
+/// whether every message passed
+pub fn validate(input: &Path) -> Result<bool> {
+    let mut reader = MessageReader::open(input)
+        .with_context(|| format!("Failed to read {}", input.display()))?;
+    let offsets = reader.offsets().to_vec();
+    let mut decoders = Decoders::default();
+
+    let mut messages = Vec::with_capacity(offsets.len());
+    for (index, (offset, message)) in offsets.into_iter().zip(&mut reader).enumerate() {
+        let checks = match message {
+            Ok(message) => check_message(&message, &mut decoders),
+            Err(e) => vec![("read", Check::Fail(e.to_string()))],
+        };
+        let passed = checks
+            .iter()
+            .all(|(_, check)| !matches!(check, Check::Fail(_)));
+        messages.push(json!({
+            "index": index,
+            "offset": offset,
+            "passed": passed,
+            "checks": checks
+                .iter()
+                .map(|(name, check)| check.to_json(name))
+                .collect::<Vec<_>>(),
+        }));
+    }
+
+    let passed = messages.iter().all(|message| message["passed"] == true);
+    let report = json!({
+        "file": input.display().to_string(),
+        "passed": passed,
+        "messages": messages,
+    });
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    Ok(passed)
+}
+
+fn check_message(message: &MessageBlock, decoders: &mut Decoders) -> Vec<(&'static str, Check)> {
+    let mut checks = vec![
+        ("read", Check::Pass),
+        ("header", check_header(message)),
+        ("sections", check_sections(message.as_bytes())),
+        ("terminator", check_terminator(message.as_bytes())),
+    ];
+
+    let decoder = match decoders.get(message) {
+        Ok(decoder) => {
+            checks.push(("tables", Check::Pass));
+            decoder
+        }
+        Err(e) => {
+            checks.push(("tables", Check::Fail(e.to_string())));
+            checks.push(("bits", Check::Skip("no tables".to_string())));
+            return checks;
+        }
+    };
+
+    let bits = match decoder.validate(message) {
+        Ok(validation) if validation.is_consistent() => Check::Pass,
+        Ok(validation) => Check::Fail(format!(
+            "{} of {} bits of Section 4 left unused",
+            validation.unused_bits(),
+            validation.bits_available
+        )),
+        Err(e) => Check::Fail(e.to_string()),
+    };
+    checks.push(("bits", bits));
+    checks
+}
+
+/// Section 1 carries a valid date and Section 3 at least one subset
+fn check_header(message: &MessageBlock) -> Check {
+    if message.obs_time().is_none() {
+        return Check::Fail("invalid date or time in Section 1".to_string());
+    }
+    if message.subsets_count() == 0 {
+        return Check::Fail("no subsets in Section 3".to_string());
+    }
+    Check::Pass
+}
+
+/// The lengths of Sections 1 to 4 and the end section add up to the total
+/// length of Section 0
+fn check_sections(bytes: &[u8]) -> Check {
+    let u24 = |at: usize| {
+        bytes
+            .get(at..at + 3)
+            .map(|b| u32::from_be_bytes([0, b[0], b[1], b[2]]) as usize)
+    };
+    let edition = bytes[7];
+    if edition < 2 {
+        return Check::Skip(format!("no total length in edition {}", edition));
+    }
+
+    // The flag of the optional section, in Section 1
+    let flag_octet = if edition == 4 { 9 } else { 7 };
+    let mut end = 8;
+    let mut sections = vec![1];
+    let Some(section1) = u24(end) else {
+        return Check::Fail("truncated Section 1".to_string());
+    };
+    if bytes
+        .get(end + flag_octet)
+        .is_some_and(|flags| flags & 0x80 != 0)
+    {
+        sections.push(2);
+    }
+    sections.extend([3, 4]);
+    end += section1;
+    for section in &sections[1..] {
+        match u24(end) {
+            Some(length) if length > 0 => end += length,
+            _ => return Check::Fail(format!("truncated Section {}", section)),
+        }
+    }
+    end += 4;
+
+    let total = u24(4).unwrap_or(0);
+    if end != total {
+        return Check::Fail(format!(
+            "sections add up to {} bytes, Section 0 says {}",
+            end, total
+        ));
+    }
+    Check::Pass
+}
+
+fn check_terminator(bytes: &[u8]) -> Check {
+    if bytes.ends_with(b"7777") {
+        Check::Pass
+    } else {
+        Check::Fail("message does not end with 7777".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A message of `edition` with the optional section's flag set at
+    /// `flag`, an octet of Section 1, and Section 2 present if `section2`
+    fn message(edition: u8, flag: Option<usize>, section2: bool) -> Vec<u8> {
+        let section = |length: usize| {
+            let mut bytes = vec![0u8; length];
+            bytes[..3].copy_from_slice(&(length as u32).to_be_bytes()[1..]);
+            bytes
+        };
+        let mut section1 = section(if edition == 4 { 22 } else { 18 });
+        if let Some(flag) = flag {
+            section1[flag] = 0x80;
+        }
+
+        let mut bytes = b"BUFR\0\0\0".to_vec();
+        bytes.push(edition);
+        bytes.extend(section1);
+        if section2 {
+            bytes.extend(section(6));
+        }
+        bytes.extend(section(10));
+        bytes.extend(section(8));
+        bytes.extend(b"7777");
+        let total = (bytes.len() as u32).to_be_bytes();
+        bytes[4..7].copy_from_slice(&total[1..]);
+        bytes
+    }
+
+    #[test]
+    fn test_check_sections() {
+        assert!(matches!(
+            check_sections(&message(4, None, false)),
+            Check::Pass
+        ));
+        assert!(matches!(
+            check_sections(&message(3, None, false)),
+            Check::Pass
+        ));
+        // The flag is octet 10 of Section 1 in edition 4, octet 8 before
+        assert!(matches!(
+            check_sections(&message(4, Some(9), true)),
+            Check::Pass
+        ));
+        assert!(matches!(
+            check_sections(&message(3, Some(7), true)),
+            Check::Pass
+        ));
+        assert!(matches!(
+            check_sections(&message(4, Some(7), true)),
+            Check::Fail(_)
+        ));
+        assert!(matches!(
+            check_sections(&message(3, Some(9), true)),
+            Check::Fail(_)
+        ));
+
+        let mut truncated = message(4, None, false);
+        truncated.truncate(40);
+        assert!(matches!(check_sections(&truncated), Check::Fail(_)));
+
+        let mut edition1 = message(4, None, false);
+        edition1[7] = 1;
+        assert!(matches!(check_sections(&edition1), Check::Skip(_)));
+    }
+
+    #[test]
+    fn test_check_terminator() {
+        assert!(matches!(
+            check_terminator(&message(4, None, false)),
+            Check::Pass
+        ));
+        assert!(matches!(check_terminator(b"BUFR7777 "), Check::Fail(_)));
+    }
+}
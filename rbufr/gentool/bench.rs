@@ -0,0 +1,172 @@
+use anyhow::Result;
+use librbufr::core::{BUFRTableMPH, FXY, tables::TableEntry, tables::TableTypeTrait};
+use librbufr::decode::Decoders;
+use librbufr::parser::parse_with_filter;
+use rkyv::Archive;
+use rkyv::de::Pool;
+use rkyv::rancor::{Error, Strategy};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::collections::HashMap;
+use std::hint::black_box;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// The system allocator, counting the allocations `bench_decode` reports
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+/// Time loading `input` and looking every entry up `rounds` times, from the
+/// memory-mapped MPH table and from a `HashMap` of the same entries
+pub fn bench_table<T: TableTypeTrait>(input: &Path, rounds: usize) -> Result<()>
+where
+    <T::EntryType as Archive>::Archived: rkyv::Deserialize<T::EntryType, Strategy<Pool, Error>>,
+{
+    let rounds = rounds.max(1);
+    println!("Benchmarking {} ({} rounds)", input.display(), rounds);
+
+    let (mph_load, table) = time_loads(rounds, || BUFRTableMPH::<T>::load_from_disk(input))?;
+    let (map_load, map) = time_loads(rounds, || {
+        let table = BUFRTableMPH::<T>::load_from_disk(input)?;
+        let mut map = HashMap::new();
+        for entry in table.get_all_entries() {
+            let entry = rkyv::deserialize::<T::EntryType, Error>(entry)?;
+            map.insert(entry.fxy(), entry);
+        }
+        anyhow::Ok(map)
+    })?;
+
+    let keys: Vec<FXY> = map.keys().copied().collect();
+    let lookups = keys.len() * rounds;
+
+    let start = Instant::now();
+    let mut mph_hits = 0;
+    for _ in 0..rounds {
+        for key in &keys {
+            mph_hits += black_box(table.lookup(key)).is_some() as usize;
+        }
+    }
+    let mph_lookup = start.elapsed();
+
+    let start = Instant::now();
+    let mut map_hits = 0;
+    for _ in 0..rounds {
+        for key in &keys {
+            map_hits += black_box(map.get(key)).is_some() as usize;
+        }
+    }
+    let map_lookup = start.elapsed();
+
+    if mph_hits != lookups || map_hits != lookups {
+        anyhow::bail!(
+            "Lookups missed: MPH found {}, HashMap {} of {}",
+            mph_hits,
+            map_hits,
+            lookups
+        );
+    }
+
+    println!("Entries: {}", keys.len());
+    println!();
+    println!(
+        "{:<10} | {:>14} | {:>16} | {:>12}",
+        "Format", "Load (avg)", "Lookups/s", "ns/lookup"
+    );
+    println!("{}", "-".repeat(62));
+    for (name, load, lookup) in [
+        ("MPH", mph_load, mph_lookup),
+        ("HashMap", map_load, map_lookup),
+    ] {
+        println!(
+            "{:<10} | {:>14?} | {:>16.0} | {:>12.1}",
+            name,
+            load,
+            lookups as f64 / lookup.as_secs_f64(),
+            lookup.as_nanos() as f64 / lookups as f64
+        );
+    }
+
+    Ok(())
+}
+
+/// Average time of `rounds` calls of `load`, with the last result
+fn time_loads<R>(rounds: usize, mut load: impl FnMut() -> Result<R>) -> Result<(Duration, R)> {
+    let start = Instant::now();
+    let mut result = load()?;
+    for _ in 1..rounds {
+        result = black_box(load()?);
+    }
+    Ok((start.elapsed() / rounds as u32, result))
+}
+
+/// Time decoding every message of the BUFR file `input` `rounds` times, and
+/// count the heap allocations each decode makes
+///
+/// Decoders are built before timing starts; messages whose tables can't be
+/// loaded are left out.
+pub fn bench_decode(input: &Path, rounds: usize) -> Result<()> {
+    let rounds = rounds.max(1);
+    println!("Benchmarking {} ({} rounds)", input.display(), rounds);
+
+    let file = parse_with_filter(input, |_| true)?;
+    let mut decoders = Decoders::default();
+    let messages: Vec<_> = file
+        .messages()
+        .iter()
+        .filter(|message| decoders.get(message).is_ok())
+        .collect();
+    if messages.is_empty() {
+        anyhow::bail!("No message of {} could be decoded", input.display());
+    }
+
+    let mut records = 0;
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    for _ in 0..rounds {
+        for message in &messages {
+            let parsed = black_box(decoders.get(message)?.decode(*message)?);
+            records += parsed.record_count();
+        }
+    }
+    let elapsed = start.elapsed();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+
+    let decodes = messages.len() * rounds;
+    println!("Messages: {}", messages.len());
+    println!("Records:  {}", records / rounds);
+    println!();
+    println!(
+        "{:<14} | {:>14} | {:>16} | {:>16}",
+        "", "Time (avg)", "Allocations", "Allocs/record"
+    );
+    println!("{}", "-".repeat(70));
+    println!(
+        "{:<14} | {:>14?} | {:>16.1} | {:>16.2}",
+        "Per message",
+        elapsed / decodes as u32,
+        allocations as f64 / decodes as f64,
+        allocations as f64 / records.max(1) as f64
+    );
+
+    Ok(())
+}
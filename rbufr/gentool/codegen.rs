@@ -0,0 +1,83 @@
+use anyhow::{Context, Result};
+use librbufr::core::{
+    BUFRKey,
+    prelude::{BUFRTableB, BUFRTableD},
+};
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// Write the entries of a .bufrtbl as a Rust source file holding a
+/// `StaticTable` named `ident`
+pub fn generate(input: &Path, output: &Path, table_type: &str, ident: &str) -> Result<()> {
+    let mut code = format!(
+        "// Generated by gen-ctl codegen from {}. Do not edit.\n\n",
+        input.display()
+    );
+
+    let count = match table_type.to_lowercase().as_str() {
+        "b" => table_b(input, ident, &mut code)?,
+        "d" => table_d(input, ident, &mut code)?,
+        _ => anyhow::bail!("Invalid table type: {}. Use 'd' or 'b'", table_type),
+    };
+
+    std::fs::write(output, code)
+        .with_context(|| format!("Failed to write {}", output.display()))?;
+    println!("Wrote {} entries to {}", count, output.display());
+    Ok(())
+}
+
+fn fxy(fxy: &impl BUFRKey) -> String {
+    format!("FXY::new({}, {}, {})", fxy.f(), fxy.x(), fxy.y())
+}
+
+fn table_b(input: &Path, ident: &str, code: &mut String) -> Result<usize> {
+    let table: BUFRTableB = BUFRTableB::load_from_disk(input)?;
+    let entries: Vec<_> = table.iter_sorted().collect();
+
+    code.push_str("use librbufr::core::FXY;\n");
+    code.push_str("use librbufr::core::statics::{StaticBEntry, StaticTable};\n\n");
+    writeln!(
+        code,
+        "pub static {}: StaticTable<StaticBEntry> = StaticTable::new(&[",
+        ident
+    )?;
+    for entry in &entries {
+        writeln!(
+            code,
+            "    StaticBEntry {{ fxy: {}, element_name: {:?}, unit: {:?}, scale: {}, reference: {}, width_bits: {} }},",
+            fxy(&entry.fxy),
+            entry.element_name_en.as_str(),
+            entry.bufr_unit.as_str(),
+            entry.bufr_scale.to_native(),
+            entry.bufr_reference_value.to_native(),
+            entry.bufr_datawidth_bits.to_native(),
+        )?;
+    }
+    code.push_str("]);\n");
+    Ok(entries.len())
+}
+
+fn table_d(input: &Path, ident: &str, code: &mut String) -> Result<usize> {
+    let table: BUFRTableD = BUFRTableD::load_from_disk(input)?;
+    let entries: Vec<_> = table.iter_sorted().collect();
+
+    code.push_str("use librbufr::core::FXY;\n");
+    code.push_str("use librbufr::core::statics::{StaticDEntry, StaticTable};\n\n");
+    writeln!(
+        code,
+        "pub static {}: StaticTable<StaticDEntry> = StaticTable::new(&[",
+        ident
+    )?;
+    for entry in &entries {
+        let chain: Vec<String> = entry.fxy_chain.iter().map(fxy).collect();
+        writeln!(
+            code,
+            "    StaticDEntry {{ fxy: {}, title: {:?}, chain: &[{}] }},",
+            fxy(&entry.fxy),
+            entry.title_en.as_deref(),
+            chain.join(", "),
+        )?;
+    }
+    code.push_str("]);\n");
+    Ok(entries.len())
+}
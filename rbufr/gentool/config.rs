@@ -28,6 +28,10 @@ pub struct FieldMapping {
     /// Optional capture group index for center
     pub center_group: Option<usize>,
 
+    /// Optional capture group index for master table number
+    #[serde(default)]
+    pub master_table_group: Option<usize>,
+
     /// Optional capture group index for language
     pub language_group: Option<usize>,
 
@@ -90,6 +94,13 @@ impl TableFilePattern for ConfigurablePattern {
             None
         };
 
+        // Extract master table number
+        let master_table = if let Some(idx) = self.mapping.master_table_group {
+            caps.get(idx).and_then(|m| m.as_str().parse().ok())
+        } else {
+            None
+        };
+
         // Extract language
         let language = if let Some(idx) = self.mapping.language_group {
             caps.get(idx).map(|m| m.as_str().to_string())
@@ -102,6 +113,7 @@ impl TableFilePattern for ConfigurablePattern {
             version,
             subcenter,
             center,
+            master_table,
             language,
             is_local: self.mapping.is_local,
             filename: filename.to_string(),
@@ -150,6 +162,7 @@ impl ScanConfig {
                         version_group: Some(2),
                         subcenter_group: None,
                         center_group: None,
+                        master_table_group: None,
                         language_group: None,
                         is_local: true,
                     },
@@ -163,6 +176,7 @@ impl ScanConfig {
                         version_group: Some(1),
                         subcenter_group: None,
                         center_group: None,
+                        master_table_group: None,
                         language_group: None,
                         is_local: true,
                     },
@@ -210,6 +224,7 @@ mod tests {
                 version_group: Some(2),
                 subcenter_group: None,
                 center_group: None,
+                master_table_group: None,
                 language_group: None,
                 is_local: true,
             },
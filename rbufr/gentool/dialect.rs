@@ -0,0 +1,198 @@
+use anyhow::{Context, Result};
+use csv::{Reader, ReaderBuilder};
+use encoding_rs::{Encoding, UTF_8, WINDOWS_1252};
+use std::io::Cursor;
+use std::path::Path;
+
+/// Delimiters tried when sniffing, in order of preference on a tie
+const DELIMITERS: [u8; 4] = [b',', b';', b'\t', b'|'];
+/// Lines looked at when sniffing
+const SAMPLE_LINES: usize = 20;
+
+/// How a CSV file is written, as sniffed from its first lines
+#[derive(Debug, Clone, Copy)]
+pub struct Dialect {
+    pub delimiter: u8,
+    pub quote: u8,
+    pub encoding: &'static Encoding,
+}
+
+impl Dialect {
+    /// Sniff the dialect of decoded `text`, read from a file in `encoding`
+    pub fn sniff(text: &str, encoding: &'static Encoding) -> Self {
+        let lines: Vec<&str> = text
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .take(SAMPLE_LINES)
+            .collect();
+
+        let quote = if count_quotes(&lines, b'\'') > count_quotes(&lines, b'"') {
+            b'\''
+        } else {
+            b'"'
+        };
+
+        // The delimiter splitting most lines into the same number of
+        // fields wins; the most frequent one if none is consistent
+        let mut best = (false, 0, DELIMITERS[0]);
+        for delimiter in DELIMITERS {
+            let counts: Vec<usize> = lines
+                .iter()
+                .map(|line| count_unquoted(line, delimiter, quote))
+                .collect();
+            let total: usize = counts.iter().sum();
+            if total == 0 {
+                continue;
+            }
+            let consistent = counts.windows(2).all(|pair| pair[0] == pair[1]);
+            if (consistent, total) > (best.0, best.1) {
+                best = (consistent, total, delimiter);
+            }
+        }
+
+        Dialect {
+            delimiter: best.2,
+            quote,
+            encoding,
+        }
+    }
+
+    pub fn report(&self, path: &Path) {
+        println!(
+            "Reading {} as {} with delimiter {:?}, quote {:?}",
+            path.display(),
+            self.encoding.name(),
+            self.delimiter as char,
+            self.quote as char
+        );
+    }
+}
+
+/// Read a text file to UTF-8
+///
+/// A byte order mark picks the encoding and is dropped; otherwise the file
+/// is read as UTF-8, or in `fallback` when it isn't valid UTF-8.
+pub fn decode(path: &Path, fallback: &'static Encoding) -> Result<(&'static Encoding, String)> {
+    let bytes =
+        std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let (encoding, bytes) = match Encoding::for_bom(&bytes) {
+        Some((encoding, bom_len)) => (encoding, &bytes[bom_len..]),
+        None if std::str::from_utf8(&bytes).is_ok() => (UTF_8, &bytes[..]),
+        None => (fallback, &bytes[..]),
+    };
+    Ok((
+        encoding,
+        encoding.decode_without_bom_handling(bytes).0.into_owned(),
+    ))
+}
+
+/// Open a CSV file whatever its delimiter, quoting or encoding
+///
+/// The file is decoded as by `decode`, falling back to Windows-1252, and
+/// the dialect is then sniffed from the decoded text.
+pub fn open(path: &Path, has_headers: bool) -> Result<(Dialect, Reader<Cursor<String>>)> {
+    let (encoding, text) = decode(path, WINDOWS_1252)?;
+
+    let dialect = Dialect::sniff(&text, encoding);
+    let reader = ReaderBuilder::new()
+        .has_headers(has_headers)
+        .delimiter(dialect.delimiter)
+        .quote(dialect.quote)
+        .flexible(true) // Allow variable number of fields
+        .from_reader(Cursor::new(text));
+    Ok((dialect, reader))
+}
+
+/// Occurrences of `delimiter` outside quoted fields
+fn count_unquoted(line: &str, delimiter: u8, quote: u8) -> usize {
+    let mut quoted = false;
+    let mut count = 0;
+    for b in line.bytes() {
+        if b == quote {
+            quoted = !quoted;
+        } else if b == delimiter && !quoted {
+            count += 1;
+        }
+    }
+    count
+}
+
+/// Occurrences of `quote` opening a field
+fn count_quotes(lines: &[&str], quote: u8) -> usize {
+    lines
+        .iter()
+        .map(|line| {
+            let bytes = line.as_bytes();
+            (0..bytes.len())
+                .filter(|&i| bytes[i] == quote && (i == 0 || DELIMITERS.contains(&bytes[i - 1])))
+                .count()
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use csv::StringRecord;
+
+    /// Write `bytes` to a file of its own and open it
+    fn open_bytes(name: &str, bytes: &[u8]) -> (Dialect, Vec<StringRecord>) {
+        let path =
+            std::env::temp_dir().join(format!("rbufrgen_dialect_{}_{}", std::process::id(), name));
+        std::fs::write(&path, bytes).unwrap();
+        let (dialect, mut reader) = open(&path, false).unwrap();
+        let records = reader.records().map(|record| record.unwrap()).collect();
+        std::fs::remove_file(&path).unwrap();
+        (dialect, records)
+    }
+
+    #[test]
+    fn test_sniff_delimiter() {
+        let comma = Dialect::sniff("F,X,Y\n0,01,001\n0,01,002\n", UTF_8);
+        assert_eq!(comma.delimiter, b',');
+        assert_eq!(comma.quote, b'"');
+
+        // Semicolons split every line the same way, commas don't
+        let semicolon = Dialect::sniff(
+            "FXY;Name\n001001;WMO block number\n001002;Station, number\n",
+            UTF_8,
+        );
+        assert_eq!(semicolon.delimiter, b';');
+    }
+
+    #[test]
+    fn test_sniff_quoted_delimiter() {
+        // The commas inside quotes are not counted
+        let text =
+            "\"FXY\";\"Name\"\n\"001001\";\"Block, number\"\n\"001002\";\"Station, id, number\"\n";
+        let dialect = Dialect::sniff(text, UTF_8);
+        assert_eq!(dialect.delimiter, b';');
+        assert_eq!(dialect.quote, b'"');
+
+        let single = Dialect::sniff("'FXY','Name'\n'001001','Block; number'\n", UTF_8);
+        assert_eq!(single.delimiter, b',');
+        assert_eq!(single.quote, b'\'');
+    }
+
+    #[test]
+    fn test_open_bom() {
+        let (dialect, records) = open_bytes("bom", b"\xEF\xBB\xBFFXY;Name\n001001;Block\n");
+        assert_eq!(dialect.encoding, UTF_8);
+        assert_eq!(dialect.delimiter, b';');
+        // The BOM is not part of the first field
+        assert_eq!(&records[0][0], "FXY");
+        assert_eq!(&records[1][1], "Block");
+    }
+
+    #[test]
+    fn test_open_windows_1252() {
+        // 0xB0 is a degree sign in Windows-1252 and invalid UTF-8
+        let (dialect, records) = open_bytes("cp1252", b"FXY,Unit\n012101,\xB0C\n");
+        assert_eq!(dialect.encoding, WINDOWS_1252);
+        assert_eq!(&records[1][1], "\u{b0}C");
+
+        let (dialect, records) = open_bytes("utf8", "FXY,Unit\n012101,\u{b0}C\n".as_bytes());
+        assert_eq!(dialect.encoding, UTF_8);
+        assert_eq!(&records[1][1], "\u{b0}C");
+    }
+}
@@ -70,6 +70,10 @@ impl EntryLoader for BTableLoader {
             note_en: None,
             note_ids: None,
             status: None,
+            bufr_unit_original: None,
+            crex_unit: None,
+            crex_scale: None,
+            crex_datawidth_char: None,
         };
 
         Ok(Some(entry))
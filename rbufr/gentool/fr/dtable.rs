@@ -15,6 +15,7 @@ pub struct FRDTableLoader {
 impl EntryLoader for FRDTableLoader {
     type Output = DTableEntry;
     type TableType = DTable;
+    const CHAINED: bool = true;
 
     fn process_entry(&mut self, raw: StringRecord) -> anyhow::Result<Option<Self::Output>> {
         // Skip empty lines
@@ -1,4 +1,5 @@
-use csv::{ReaderBuilder, StringRecord};
+use crate::dialect;
+use csv::StringRecord;
 use librbufr::core::{
     TableConverter,
     tables::{TableEntryFull, TableTypeTrait},
@@ -19,22 +20,28 @@ impl<C: EntryLoader> TableLoader<C> {
         &self,
         path: P,
         loader: &mut C,
-    ) -> anyhow::Result<Vec<C::Output>> {
+    ) -> anyhow::Result<Vec<(usize, C::Output)>> {
         let path = path.as_ref();
         let mut entries = vec![];
-        let mut rdr = ReaderBuilder::new()
-            .has_headers(false)
-            .delimiter(b';')
-            .flexible(true)
-            .from_path(path)?;
+        let (dialect, mut rdr) = dialect::open(path, false)?;
+        dialect.report(path);
 
         let mut line_num = 1;
+        let mut start_line = None;
         for result in rdr.records() {
             line_num += 1;
             match result {
                 Ok(record) => match loader.process_entry(record) {
+                    Ok(Some(processed_entry)) if C::CHAINED => {
+                        entries.push((start_line.unwrap_or(line_num), processed_entry));
+                        start_line = Some(line_num);
+                    }
                     Ok(Some(processed_entry)) => {
-                        entries.push(processed_entry);
+                        entries.push((line_num, processed_entry));
+                        start_line = None;
+                    }
+                    Ok(None) => {
+                        start_line.get_or_insert(line_num);
                     }
                     Err(e) => {
                         eprintln!(
@@ -44,8 +51,6 @@ impl<C: EntryLoader> TableLoader<C> {
                             e
                         );
                     }
-
-                    _ => {}
                 },
                 Err(e) => {
                     eprintln!(
@@ -59,7 +64,7 @@ impl<C: EntryLoader> TableLoader<C> {
         }
 
         if let Some(processed_entry) = loader.finish()? {
-            entries.push(processed_entry);
+            entries.push((start_line.unwrap_or(line_num), processed_entry));
         }
 
         Ok(entries)
@@ -70,6 +75,10 @@ pub trait EntryLoader: Default {
     type Output: TableEntryFull;
     type TableType: TableTypeTrait;
 
+    /// Whether an entry spans several lines and is returned once the line
+    /// after its last one is processed, as Table D sequences are
+    const CHAINED: bool = false;
+
     fn process_entry(&mut self, raw: StringRecord) -> anyhow::Result<Option<Self::Output>>;
     fn finish(&mut self) -> anyhow::Result<Option<Self::Output>> {
         Ok(None)
@@ -84,6 +93,17 @@ impl<T: EntryLoader> TableConverter for TableLoader<T> {
         &self,
         path: P,
     ) -> anyhow::Result<Vec<Self::OutputEntry>> {
+        Ok(self
+            .convert_with_lines(path)?
+            .into_iter()
+            .map(|(_, entry)| entry)
+            .collect())
+    }
+
+    fn convert_with_lines<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+    ) -> anyhow::Result<Vec<(usize, Self::OutputEntry)>> {
         let mut loader = T::default();
         self.load_table(path, &mut loader)
     }
@@ -1,17 +1,24 @@
 ///
+mod bench;
+mod codegen;
+mod dialect;
 mod fr;
+mod jma;
+mod metoffice;
 mod opera;
+mod text;
 mod wmo;
 ///
 use anyhow::{Context, Result, anyhow};
 use clap::{Parser, Subcommand};
-#[cfg(feature = "opera")]
-use librbufr::core::{BUFRTableMPH, tables::BitMap};
 use librbufr::core::{
-    TableType,
+    BUFRKey, FXY, TableType, archive,
     pattern::{TableKind, TableScanner},
     prelude::{BUFRTableB, BUFRTableD},
+    tables::{BTable, DTable},
 };
+#[cfg(feature = "opera")]
+use librbufr::core::{BUFRTableMPH, tables::BitMap};
 mod config;
 use crate::config::ScanConfig;
 use std::path::{Path, PathBuf};
@@ -44,9 +51,19 @@ enum Commands {
         #[arg(short, long)]
         config: Option<PathBuf>,
 
-        /// Loader type: "auto" (try all), "wmo" (WMO only), "fr" (French only)
+        /// Loader type: "auto" (try all), or only "wmo", "fr", "jma" or "metoffice"
         #[arg(short, long, default_value = "auto")]
         loader: String,
+
+        /// Master table number of the WMO tables scanned, when their file
+        /// names don't carry one (e.g. 10 for oceanography)
+        #[arg(short, long)]
+        master_table: Option<u32>,
+
+        /// Language of the WMO CSVs to convert, and of the names stored
+        /// (e.g. "en", "fr", "es", "ru")
+        #[arg(long, default_value = wmo::DEFAULT_LANGUAGE)]
+        language: String,
     },
     /// Convert a single BUFR table file
     Convert {
@@ -62,9 +79,31 @@ enum Commands {
         #[arg(short, long)]
         table_type: String,
 
-        /// Loader type: "auto" (try all), "wmo" (WMO only), "fr" (French only)
+        /// Loader type: "auto" (try all), or only "wmo", "fr", "jma" or "metoffice"
         #[arg(short, long, default_value = "auto")]
         loader: String,
+
+        /// Language of the names stored from a WMO CSV (e.g. "en", "fr")
+        #[arg(long, default_value = wmo::DEFAULT_LANGUAGE)]
+        language: String,
+    },
+    /// Pack the master table versions in a directory into one archive
+    Pack {
+        /// Directory containing BUFR_Table{B|D}_{version}.bufrtbl files
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Output archive (`.bufrarc`)
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Table type: "d" for Table D, "b" for Table B
+        #[arg(short, long)]
+        table_type: String,
+
+        /// Master table number of the tables packed (e.g. 10 for oceanography)
+        #[arg(short, long, default_value_t = 0)]
+        master_table: u8,
     },
     /// Print a BUFR table in formatted output
     Print {
@@ -79,6 +118,86 @@ enum Commands {
         /// Maximum number of entries to print (optional)
         #[arg(short, long)]
         limit: Option<usize>,
+
+        /// Only entries of this class (the X of FXY), e.g. 12
+        #[arg(long)]
+        class: Option<i32>,
+
+        /// Only the entry with this descriptor, e.g. 012101
+        #[arg(long)]
+        fxy: Option<String>,
+
+        /// Only entries whose name (Table D: title) contains this, ignoring case
+        #[arg(long)]
+        name: Option<String>,
+
+        /// Only entries with this status, e.g. operational
+        #[arg(long)]
+        status: Option<String>,
+    },
+    /// Time loading and lookups of a .bufrtbl against a HashMap of its entries
+    Bench {
+        /// Path to .bufrtbl file (without extension)
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Table type: "d" for Table D, "b" for Table B
+        #[arg(short, long)]
+        table_type: String,
+
+        /// Times the table is loaded and each entry looked up
+        #[arg(short, long, default_value_t = 100)]
+        rounds: usize,
+    },
+    /// Time decoding the messages of a BUFR file and count the heap
+    /// allocations of each decode
+    BenchDecode {
+        /// Path to the BUFR file
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Times every message is decoded
+        #[arg(short, long, default_value_t = 100)]
+        rounds: usize,
+    },
+    /// Write a .bufrtbl as a Rust source file of static entries, for
+    /// binaries that carry their tables and decode with `to_table`
+    Codegen {
+        /// Path to .bufrtbl file (without extension)
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Output .rs file
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Table type: "d" for Table D, "b" for Table B
+        #[arg(short, long)]
+        table_type: String,
+
+        /// Name of the generated static (default: TABLE_B or TABLE_D)
+        #[arg(long)]
+        ident: Option<String>,
+    },
+    /// Print the Section 3 descriptor tree of the messages in a BUFR file
+    Descriptors {
+        /// Input BUFR file
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Only print the message at this index (optional)
+        #[arg(short, long)]
+        message: Option<usize>,
+    },
+    /// Dump the messages of a BUFR file as JSON, laid out like `bufr_dump -j`
+    Json {
+        /// Input BUFR file
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Flat layout with ranked keys (`-jf`) instead of nested subsets
+        #[arg(short, long)]
+        flat: bool,
     },
     /// Generate example configuration file
     GenConfig {
@@ -120,23 +239,77 @@ fn main() -> Result<()> {
             table_type,
             config,
             loader,
+            master_table,
+            language,
         } => {
-            scan_and_convert(&input, &output, &table_type, config.as_deref(), &loader)?;
+            scan_and_convert(
+                &input,
+                &output,
+                &table_type,
+                config.as_deref(),
+                &loader,
+                master_table,
+                &language,
+            )?;
         }
         Commands::Convert {
             input,
             output,
             table_type,
             loader,
+            language,
+        } => {
+            convert_single_file(&input, &output, &table_type, &loader, &language)?;
+        }
+        Commands::Pack {
+            input,
+            output,
+            table_type,
+            master_table,
         } => {
-            convert_single_file(&input, &output, &table_type, &loader)?;
+            pack_archive(&input, &output, &table_type, master_table)?;
         }
         Commands::Print {
             input,
             table_type,
             limit,
+            class,
+            fxy,
+            name,
+            status,
+        } => {
+            let filter = PrintFilter {
+                class,
+                fxy: fxy.as_deref().map(FXY::from_fxxyyy).transpose()?,
+                name: name.map(|name| name.to_lowercase()),
+                status,
+            };
+            print_table(&input, &table_type, limit, &filter)?;
+        }
+        Commands::Bench {
+            input,
+            table_type,
+            rounds,
+        } => match table_type.to_lowercase().as_str() {
+            "b" => bench::bench_table::<BTable>(&input, rounds)?,
+            "d" => bench::bench_table::<DTable>(&input, rounds)?,
+            _ => anyhow::bail!("Invalid table type: {}. Use 'd' or 'b'", table_type),
+        },
+        Commands::BenchDecode { input, rounds } => bench::bench_decode(&input, rounds)?,
+        Commands::Codegen {
+            input,
+            output,
+            table_type,
+            ident,
         } => {
-            print_table(&input, &table_type, limit)?;
+            let ident = ident.unwrap_or_else(|| format!("TABLE_{}", table_type.to_uppercase()));
+            codegen::generate(&input, &output, &table_type, &ident)?;
+        }
+        Commands::Descriptors { input, message } => {
+            print_descriptor_tree(&input, message)?;
+        }
+        Commands::Json { input, flat } => {
+            dump_json(&input, flat)?;
         }
         Commands::GenConfig { output } => {
             generate_config_file(&output)?;
@@ -160,6 +333,8 @@ fn scan_and_convert(
     table_type: &str,
     config_path: Option<&Path>,
     loader_type: &str,
+    master_table: Option<u32>,
+    language: &str,
 ) -> Result<()> {
     // Create output directory if it doesn't exist
     std::fs::create_dir_all(output_dir).context("Failed to create output directory")?;
@@ -210,17 +385,47 @@ fn scan_and_convert(
         .scan_directory(input_dir, kind_filter)
         .context("Failed to scan directory")?;
 
+    // WMO tables come in several languages; keep the one asked for
+    let language = language.to_lowercase();
+    let languages: std::collections::BTreeSet<&str> = files
+        .iter()
+        .filter_map(|(_, metadata)| metadata.language.as_deref())
+        .collect();
+    if !languages.is_empty() && !languages.contains(language.as_str()) {
+        let languages: Vec<&str> = languages.into_iter().collect();
+        anyhow::bail!(
+            "No WMO tables in language '{}'; found: {}",
+            language,
+            languages.join(", ")
+        );
+    }
+    let files: Vec<_> = files
+        .into_iter()
+        .filter(|(_, metadata)| {
+            metadata
+                .language
+                .as_deref()
+                .is_none_or(|file_language| file_language == language)
+        })
+        .collect();
+
     println!("Found {} matching files", files.len());
     println!();
 
     let mut processed_count = 0;
     let mut error_count = 0;
+    // Files read into a table built from another, such as Met Office local
+    // sequences, aren't converted again
+    let mut built = std::collections::HashSet::new();
 
     // Group files by table kind for organized output
     let mut table_b_files = Vec::new();
     let mut table_d_files = Vec::new();
 
-    for (path, metadata) in files {
+    for (path, mut metadata) in files {
+        if !metadata.is_local && metadata.master_table.is_none() {
+            metadata.master_table = master_table;
+        }
         match metadata.kind {
             TableKind::B => table_b_files.push((path, metadata)),
             TableKind::D => table_d_files.push((path, metadata)),
@@ -234,6 +439,15 @@ fn scan_and_convert(
             let output_name = metadata.output_name();
             let output_path = output_dir.join(&output_name);
 
+            if built.contains(&output_name) {
+                println!(
+                    "  Skipping {} (already in {})",
+                    path.file_name().unwrap().to_str().unwrap(),
+                    output_name
+                );
+                continue;
+            }
+
             let file_type = if metadata.is_local { "local" } else { "WMO" };
             print!(
                 "  Converting {} ({}) ... ",
@@ -241,10 +455,11 @@ fn scan_and_convert(
                 file_type
             );
 
-            match convert_table_d(&path, &output_path, loader_type) {
+            match convert_table_d(&path, &output_path, loader_type, &language) {
                 Ok(_) => {
                     println!("OK -> {}", output_name);
                     processed_count += 1;
+                    built.insert(output_name);
                 }
                 Err(e) => {
                     println!("ERROR: {}", e);
@@ -262,6 +477,15 @@ fn scan_and_convert(
             let output_name = metadata.output_name();
             let output_path = output_dir.join(&output_name);
 
+            if built.contains(&output_name) {
+                println!(
+                    "  Skipping {} (already in {})",
+                    path.file_name().unwrap().to_str().unwrap(),
+                    output_name
+                );
+                continue;
+            }
+
             let file_type = if metadata.is_local { "local" } else { "WMO" };
             print!(
                 "  Converting {} ({}) ... ",
@@ -269,10 +493,11 @@ fn scan_and_convert(
                 file_type
             );
 
-            match convert_table_b(&path, &output_path, loader_type) {
+            match convert_table_b(&path, &output_path, loader_type, &language) {
                 Ok(_) => {
                     println!("OK -> {}", output_name);
                     processed_count += 1;
+                    built.insert(output_name);
                 }
                 Err(e) => {
                     eprintln!("ERROR: {}", e);
@@ -299,6 +524,7 @@ fn convert_single_file(
     output_path: &Path,
     table_type: &str,
     loader_type: &str,
+    language: &str,
 ) -> Result<()> {
     println!(
         "Converting {} to {}",
@@ -308,8 +534,8 @@ fn convert_single_file(
     println!("Loader type: {}", loader_type);
 
     match table_type.to_lowercase().as_str() {
-        "d" => convert_table_d(input_path, output_path, loader_type)?,
-        "b" => convert_table_b(input_path, output_path, loader_type)?,
+        "d" => convert_table_d(input_path, output_path, loader_type, language)?,
+        "b" => convert_table_b(input_path, output_path, loader_type, language)?,
         _ => anyhow::bail!("Invalid table type: {}. Use 'd' or 'b'", table_type),
     }
 
@@ -317,17 +543,19 @@ fn convert_single_file(
     Ok(())
 }
 
-type BuildFn = fn(&Path, &Path) -> Result<()>;
+/// Build from an input and output path, in a language (WMO only)
+type BuildFn = fn(&Path, &Path, &str) -> Result<()>;
 
 fn run_with_fallbacks(
     kind: TableType,
     input_path: &Path,
     output_path: &Path,
+    language: &str,
     attempts: &[(&str, BuildFn)],
 ) -> Result<()> {
     let mut errors = Vec::new();
     for (label, build_fn) in attempts {
-        match build_fn(input_path, output_path) {
+        match build_fn(input_path, output_path, language) {
             Ok(()) => return Ok(()),
             Err(err) => errors.push(format!("{label} failed: {err:#}")),
         }
@@ -340,93 +568,295 @@ fn run_with_fallbacks(
     ))
 }
 
-fn build_wmo_d(input_path: &Path, output_path: &Path) -> Result<()> {
-    let loader = wmo::WMODTableLoader::default();
-    BUFRTableD::build_from_csv(loader, input_path, output_path).map(|_| ())
+fn build_wmo_d(input_path: &Path, output_path: &Path, language: &str) -> Result<()> {
+    let loader = wmo::WMODTableLoader::with_language(language);
+    BUFRTableD::build_from_csv(loader, input_path, output_path)?;
+    Ok(())
 }
 
-fn build_fr_d(input_path: &Path, output_path: &Path) -> Result<()> {
+fn build_fr_d(input_path: &Path, output_path: &Path, _: &str) -> Result<()> {
     let loader = fr::FRDTableLoader::default();
-    BUFRTableD::build_from_csv(loader, input_path, output_path).map(|_| ())
+    BUFRTableD::build_from_csv(loader, input_path, output_path)?;
+    Ok(())
 }
 
-fn convert_table_d(input_path: &Path, output_path: &Path, loader_type: &str) -> Result<()> {
+fn build_jma_d(input_path: &Path, output_path: &Path, _: &str) -> Result<()> {
+    let loader = jma::JMADTableLoader::default();
+    BUFRTableD::build_from_csv(loader, input_path, output_path)?;
+    Ok(())
+}
+
+fn build_metoffice_d(input_path: &Path, output_path: &Path, _: &str) -> Result<()> {
+    let loader = metoffice::MetOfficeDTableLoader::default();
+    BUFRTableD::build_from_csv(loader, input_path, output_path)?;
+    Ok(())
+}
+
+fn convert_table_d(
+    input_path: &Path,
+    output_path: &Path,
+    loader_type: &str,
+    language: &str,
+) -> Result<()> {
     match loader_type.to_lowercase().as_str() {
         "wmo" => {
             // WMO only
-            build_wmo_d(input_path, output_path)
+            build_wmo_d(input_path, output_path, language)
         }
         "fr" => {
             // French only
-            build_fr_d(input_path, output_path)
+            build_fr_d(input_path, output_path, language)
+        }
+        "jma" => {
+            // JMA only
+            build_jma_d(input_path, output_path, language)
+        }
+        "metoffice" => {
+            // Met Office only
+            build_metoffice_d(input_path, output_path, language)
         }
         "auto" => {
             // Try all loaders
             const ATTEMPTS: &[(&str, BuildFn)] = &[
                 ("WMO Table D loader", build_wmo_d),
                 ("FR Table D loader", build_fr_d),
+                ("JMA Table D loader", build_jma_d),
+                ("Met Office Table D loader", build_metoffice_d),
             ];
-            run_with_fallbacks(TableType::D, input_path, output_path, ATTEMPTS)
+            run_with_fallbacks(TableType::D, input_path, output_path, language, ATTEMPTS)
         }
         _ => anyhow::bail!(
-            "Invalid loader type: {}. Use 'auto', 'wmo', or 'fr'",
+            "Invalid loader type: {}. Use 'auto', 'wmo', 'fr', 'jma', or 'metoffice'",
             loader_type
         ),
     }
 }
 
-fn build_wmo_b(input_path: &Path, output_path: &Path) -> Result<()> {
-    let loader = wmo::WMOBTableLoader::default();
-    BUFRTableB::build_from_csv(loader, input_path, output_path).map(|_| ())
+fn build_wmo_b(input_path: &Path, output_path: &Path, language: &str) -> Result<()> {
+    let loader = wmo::WMOBTableLoader::with_language(language);
+    BUFRTableB::build_from_csv(loader, input_path, output_path)?;
+    Ok(())
 }
 
-fn build_fr_b(input_path: &Path, output_path: &Path) -> Result<()> {
+fn build_fr_b(input_path: &Path, output_path: &Path, _: &str) -> Result<()> {
     let loader = fr::FRBTableLoader::default();
-    BUFRTableB::build_from_csv(loader, input_path, output_path).map(|_| ())
+    BUFRTableB::build_from_csv(loader, input_path, output_path)?;
+    Ok(())
+}
+
+fn build_jma_b(input_path: &Path, output_path: &Path, _: &str) -> Result<()> {
+    let loader = jma::JMABTableLoader::default();
+    BUFRTableB::build_from_csv(loader, input_path, output_path)?;
+    Ok(())
+}
+
+fn build_metoffice_b(input_path: &Path, output_path: &Path, _: &str) -> Result<()> {
+    let loader = metoffice::MetOfficeBTableLoader::default();
+    BUFRTableB::build_from_csv(loader, input_path, output_path)?;
+    Ok(())
 }
 
-fn convert_table_b(input_path: &Path, output_path: &Path, loader_type: &str) -> Result<()> {
+fn convert_table_b(
+    input_path: &Path,
+    output_path: &Path,
+    loader_type: &str,
+    language: &str,
+) -> Result<()> {
     match loader_type.to_lowercase().as_str() {
         "wmo" => {
             // WMO only
-            build_wmo_b(input_path, output_path)
+            build_wmo_b(input_path, output_path, language)
         }
         "fr" => {
             // French only
-            build_fr_b(input_path, output_path)
+            build_fr_b(input_path, output_path, language)
+        }
+        "jma" => {
+            // JMA only
+            build_jma_b(input_path, output_path, language)
+        }
+        "metoffice" => {
+            // Met Office only
+            build_metoffice_b(input_path, output_path, language)
         }
         "auto" => {
             // Try all loaders
             const ATTEMPTS: &[(&str, BuildFn)] = &[
                 ("WMO Table B loader", build_wmo_b),
                 ("FR Table B loader", build_fr_b),
+                ("JMA Table B loader", build_jma_b),
+                ("Met Office Table B loader", build_metoffice_b),
             ];
-            run_with_fallbacks(TableType::B, input_path, output_path, ATTEMPTS)
+            run_with_fallbacks(TableType::B, input_path, output_path, language, ATTEMPTS)
         }
         _ => anyhow::bail!(
-            "Invalid loader type: {}. Use 'auto', 'wmo', or 'fr'",
+            "Invalid loader type: {}. Use 'auto', 'wmo', 'fr', 'jma', or 'metoffice'",
             loader_type
         ),
     }
 }
 
-fn print_table(input_path: &Path, table_type: &str, limit: Option<usize>) -> Result<()> {
+fn pack_archive(
+    input_dir: &Path,
+    output_path: &Path,
+    table_type: &str,
+    master_table: u8,
+) -> Result<()> {
+    let kind = match table_type.to_lowercase().as_str() {
+        "b" => "B",
+        "d" => "D",
+        _ => anyhow::bail!("Invalid table type: {}. Use 'd' or 'b'", table_type),
+    };
+    // Named as `MasterTable` looks them up
+    let prefix = match master_table {
+        0 => format!("BUFR_Table{}_", kind),
+        number => format!("BUFR_Table{}_mt{}_", kind, number),
+    };
+
+    let mut tables = Vec::new();
+    for entry in std::fs::read_dir(input_dir)
+        .with_context(|| format!("Failed to read {}", input_dir.display()))?
+    {
+        let path = entry?.path();
+        let version = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_prefix(&prefix))
+            .and_then(|rest| rest.strip_suffix(".bufrtbl"))
+            .and_then(|version| version.parse::<u8>().ok());
+        if let Some(version) = version {
+            tables.push((version, path));
+        }
+    }
+    if tables.is_empty() {
+        anyhow::bail!("No {}*.bufrtbl files in {}", prefix, input_dir.display());
+    }
+    tables.sort_by_key(|(version, _)| *version);
+
+    let mut output_path = output_path.to_path_buf();
+    output_path.set_extension(archive::ARCHIVE_EXTENSION);
+    let inputs: Vec<(u8, &Path)> = tables
+        .iter()
+        .map(|(version, path)| (*version, path.as_path()))
+        .collect();
+    archive::pack(&inputs, &output_path)?;
+
+    let versions: Vec<String> = tables.iter().map(|(v, _)| v.to_string()).collect();
+    println!(
+        "Packed versions {} into {}",
+        versions.join(", "),
+        output_path.display()
+    );
+
+    Ok(())
+}
+
+/// Which entries `print` shows; unset filters let everything through
+struct PrintFilter {
+    class: Option<i32>,
+    fxy: Option<FXY>,
+    /// Lowercased
+    name: Option<String>,
+    status: Option<String>,
+}
+
+impl PrintFilter {
+    fn is_empty(&self) -> bool {
+        self.class.is_none() && self.fxy.is_none() && self.name.is_none() && self.status.is_none()
+    }
+
+    fn matches(&self, fxy: &impl BUFRKey, name: &str, status: Option<&str>) -> bool {
+        self.class.is_none_or(|class| fxy.x() == class)
+            && self.fxy.is_none_or(|wanted| wanted == *fxy)
+            && self
+                .name
+                .as_ref()
+                .is_none_or(|wanted| name.to_lowercase().contains(wanted.as_str()))
+            && self.status.as_ref().is_none_or(|wanted| {
+                status.is_some_and(|status| status.trim().eq_ignore_ascii_case(wanted))
+            })
+    }
+}
+
+fn print_table(
+    input_path: &Path,
+    table_type: &str,
+    limit: Option<usize>,
+    filter: &PrintFilter,
+) -> Result<()> {
     match table_type.to_lowercase().as_str() {
-        "d" => print_table_d(input_path, limit)?,
-        "b" => print_table_b(input_path, limit)?,
+        "d" => print_table_d(input_path, limit, filter)?,
+        "b" => print_table_b(input_path, limit, filter)?,
         _ => anyhow::bail!("Invalid table type: {}. Use 'd' or 'b'", table_type),
     }
 
     Ok(())
 }
 
-fn print_table_d(input_path: &Path, limit: Option<usize>) -> Result<()> {
+fn print_descriptor_tree(input_path: &Path, message: Option<usize>) -> Result<()> {
+    let bytes = std::fs::read(input_path)
+        .with_context(|| format!("Failed to read {}", input_path.display()))?;
+    let file = librbufr::parse(&bytes).context("Failed to parse BUFR file")?;
+
+    let indices = match message {
+        Some(index) if index >= file.message_count() => anyhow::bail!(
+            "Message index {} out of range, file has {} messages",
+            index,
+            file.message_count()
+        ),
+        Some(index) => index..index + 1,
+        None => 0..file.message_count(),
+    };
+
+    for index in indices {
+        let msg = file.message_at(index).unwrap();
+        let decoder = librbufr::Decoder::from_message(msg)
+            .with_context(|| format!("Failed to load tables for message {}", index))?;
+        let tree = msg.descriptor_tree(&decoder)?;
+
+        println!("Message {}", index);
+        println!("{}", "=".repeat(80));
+        print!("{}", tree);
+        println!();
+    }
+
+    Ok(())
+}
+
+fn dump_json(input_path: &Path, flat: bool) -> Result<()> {
+    let bytes = std::fs::read(input_path)
+        .with_context(|| format!("Failed to read {}", input_path.display()))?;
+    let file = librbufr::parse(&bytes).context("Failed to parse BUFR file")?;
+    let style = if flat {
+        librbufr::dump::JsonStyle::Flat
+    } else {
+        librbufr::dump::JsonStyle::Structure
+    };
+    println!("{}", librbufr::dump::to_json(&file, style)?);
+    Ok(())
+}
+
+fn print_table_d(input_path: &Path, limit: Option<usize>, filter: &PrintFilter) -> Result<()> {
     println!("Loading Table D from: {}", input_path.display());
 
     let table: BUFRTableD = BUFRTableD::load_from_disk(input_path)?;
-    let entries = table.get_all_entries();
-
-    println!("\nTable D Entries (Total: {})", entries.len());
+    println!("\n{}", table.metadata()?);
+    let mut entries: Vec<_> = table.iter_sorted().collect();
+    let total = entries.len();
+    entries.retain(|entry| {
+        let title = entry.title_en.as_deref().unwrap_or("");
+        filter.matches(&entry.fxy, title, entry.status.as_deref())
+    });
+
+    if filter.is_empty() {
+        println!("\nTable D Entries (Total: {})", total);
+    } else {
+        println!(
+            "\nTable D Entries (Total: {}, matching: {})",
+            total,
+            entries.len()
+        );
+    }
     println!("{}", "=".repeat(140));
     println!(
         "{:<7} | {:<50} | {:<12} | {}",
@@ -453,13 +883,26 @@ fn print_table_d(input_path: &Path, limit: Option<usize>) -> Result<()> {
     Ok(())
 }
 
-fn print_table_b(input_path: &Path, limit: Option<usize>) -> Result<()> {
+fn print_table_b(input_path: &Path, limit: Option<usize>, filter: &PrintFilter) -> Result<()> {
     println!("Loading Table B from: {}", input_path.display());
 
     let table: BUFRTableB = BUFRTableB::load_from_disk(input_path)?;
-    let entries = table.get_all_entries();
-
-    println!("\nTable B Entries (Total: {})", entries.len());
+    println!("\n{}", table.metadata()?);
+    let mut entries: Vec<_> = table.iter_sorted().collect();
+    let total = entries.len();
+    entries.retain(|entry| {
+        filter.matches(&entry.fxy, &entry.element_name_en, entry.status.as_deref())
+    });
+
+    if filter.is_empty() {
+        println!("\nTable B Entries (Total: {})", total);
+    } else {
+        println!(
+            "\nTable B Entries (Total: {}, matching: {})",
+            total,
+            entries.len()
+        );
+    }
     println!("{}", "=".repeat(120));
     println!(
         "{:<7} | {:<40} | {:<15} | {:<5} | {:<8} | {:<8} | {}",
@@ -0,0 +1,57 @@
+use super::column;
+use crate::text::EntryLoader;
+use encoding_rs::Encoding;
+use librbufr::core::{
+    FXY,
+    tables::{BTable, BTableEntry},
+};
+
+/// Table B lines, by column:
+///
+/// ```text
+///  1..7     FXXYYY
+///  8..72    element name
+///  73..97   unit
+///  97..101  scale
+///  101..114 reference value
+///  114..118 data width (bits)
+/// ```
+#[derive(Default)]
+pub struct BTableTextLoader;
+
+impl EntryLoader for BTableTextLoader {
+    type Output = BTableEntry;
+    type TableType = BTable;
+    const FALLBACK: &'static Encoding = super::FALLBACK;
+    const COMMENTS: &'static [char] = super::COMMENTS;
+
+    fn process_line(&mut self, line: &str) -> anyhow::Result<Option<Self::Output>> {
+        let fxy = FXY::from_fxxyyy(column(line, 1, 7))?;
+        let element_name = column(line, 8, 72).to_string();
+        let number = |start: usize, end: usize, field: &str| {
+            let text = column(line, start, end);
+            text.parse::<i64>()
+                .map_err(|_| anyhow::anyhow!("Invalid {}: {:?}", field, text))
+        };
+
+        let entry = BTableEntry {
+            fxy,
+            // The text tables give no class names
+            class_name_en: String::new(),
+            element_name_en: element_name,
+            bufr_unit: column(line, 73, 97).to_string(),
+            bufr_scale: number(97, 101, "scale")? as i32,
+            bufr_reference_value: number(101, 114, "reference value")? as i32,
+            bufr_datawidth_bits: number(114, 118, "data width")? as u32,
+            note_en: None,
+            note_ids: None,
+            status: None,
+            bufr_unit_original: None,
+            crex_unit: None,
+            crex_scale: None,
+            crex_datawidth_char: None,
+        };
+
+        Ok(Some(entry))
+    }
+}
@@ -0,0 +1,88 @@
+use super::column;
+use crate::text::EntryLoader;
+use encoding_rs::Encoding;
+use librbufr::core::{
+    FXY,
+    tables::{DTable, DTableEntry},
+};
+
+/// Table D lines, by column:
+///
+/// ```text
+///  1..7   FXXYYY of the sequence, blank on continuation lines
+///  7..10  number of elements, on the first line only
+///  11..17 FXXYYY of the element
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DTableTextLoader {
+    current_chain: Option<DTableEntry>,
+    /// Elements the current sequence declares
+    expected: usize,
+}
+
+/// Hand back a finished sequence, checking it has every element it declares
+fn check_finished(
+    finished: Option<DTableEntry>,
+    expected: usize,
+) -> anyhow::Result<Option<DTableEntry>> {
+    if let Some(ref entry) = finished
+        && entry.fxy_chain.len() != expected
+    {
+        anyhow::bail!(
+            "Sequence {:?} declares {} elements but lists {}",
+            entry.fxy,
+            expected,
+            entry.fxy_chain.len()
+        );
+    }
+    Ok(finished)
+}
+
+impl EntryLoader for DTableTextLoader {
+    type Output = DTableEntry;
+    type TableType = DTable;
+    const CHAINED: bool = true;
+    const FALLBACK: &'static Encoding = super::FALLBACK;
+    const COMMENTS: &'static [char] = super::COMMENTS;
+
+    fn process_line(&mut self, line: &str) -> anyhow::Result<Option<Self::Output>> {
+        let element = FXY::from_fxxyyy(column(line, 11, 17))?;
+
+        let sequence = column(line, 1, 7);
+        if sequence.is_empty() {
+            // Continuation line - add to current chain
+            let chain = self
+                .current_chain
+                .as_mut()
+                .ok_or_else(|| anyhow::anyhow!("Continuation line without a sequence header"))?;
+            chain.fxy_chain.push(element);
+            return Ok(None);
+        }
+
+        let count = column(line, 7, 10);
+        let expected = count
+            .parse()
+            .map_err(|_| anyhow::anyhow!("Invalid element count: {:?}", count))?;
+
+        let entry = DTableEntry {
+            fxy: FXY::from_fxxyyy(sequence)?,
+            fxy_chain: vec![element],
+            category: None,
+            category_of_sequences_en: None,
+            title_en: None,
+            subtitle_en: None,
+            note_en: None,
+            note_ids: None,
+            status: None,
+        };
+
+        // Start the new sequence even if the finished one is rejected
+        let finished = self.current_chain.replace(entry);
+        let declared = std::mem::replace(&mut self.expected, expected);
+        check_finished(finished, declared)
+    }
+
+    fn finish(&mut self) -> anyhow::Result<Option<Self::Output>> {
+        check_finished(self.current_chain.take(), self.expected)
+    }
+}
@@ -0,0 +1,92 @@
+//! JMA local tables: fixed-width text, one entry or sequence element per
+//! line, with comments that may be in Japanese (Shift-JIS)
+pub mod btable;
+pub mod dtable;
+use crate::text::TableLoader;
+use encoding_rs::{Encoding, SHIFT_JIS};
+
+pub type JMADTableLoader = TableLoader<dtable::DTableTextLoader>;
+pub type JMABTableLoader = TableLoader<btable::BTableTextLoader>;
+
+/// Comments may be in Japanese
+const FALLBACK: &Encoding = SHIFT_JIS;
+/// Lines starting with `#` or `!` are comments
+const COMMENTS: &[char] = &['#', '!'];
+
+/// The text of columns `start..end`, trimmed; lines may end early
+fn column(line: &str, start: usize, end: usize) -> &str {
+    let chars = line.char_indices().map(|(index, _)| index);
+    let mut bounds = chars.chain(std::iter::once(line.len()));
+    let from = bounds.by_ref().nth(start).unwrap_or(line.len());
+    let to = bounds.nth(end - start - 1).unwrap_or(line.len());
+    line[from..to].trim()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::text::EntryLoader;
+    use librbufr::core::{
+        FXY, TableConverter,
+        tables::{BTableEntry, DTableEntry},
+    };
+
+    /// A Table B line in JMA's columns
+    fn b_line(fxy: &str, name: &str, unit: &str, scale: i32, reference: i32, width: u32) -> String {
+        format!(
+            " {:<6} {:<64} {:<24}{:>4}{:>13}{:>4}\n",
+            fxy, name, unit, scale, reference, width
+        )
+    }
+
+    /// Convert `bytes` written to a file of their own
+    fn convert<C: EntryLoader>(name: &str, bytes: &[u8]) -> Vec<(usize, C::Output)> {
+        let path =
+            std::env::temp_dir().join(format!("rbufrgen_jma_{}_{}", std::process::id(), name));
+        std::fs::write(&path, bytes).unwrap();
+        let entries = TableLoader::<C>::default()
+            .convert_with_lines(&path)
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+        entries
+    }
+
+    #[test]
+    fn test_jma_table_b() {
+        // A Shift-JIS comment ("気象庁"), then two entries
+        let mut bytes = b"! \x8B\x43\x8F\xDB\x92\xA1\n".to_vec();
+        bytes.extend(b_line("001192", "JMA STATION IDENTIFIER", "CCITT IA5", 0, 0, 64).bytes());
+        bytes.extend(b_line("012192", "RADAR ECHO TOP TEMPERATURE", "K", 1, -2000, 12).bytes());
+        let entries: Vec<(usize, BTableEntry)> = convert::<btable::BTableTextLoader>("b", &bytes);
+
+        assert_eq!(entries.len(), 2);
+        let (line, entry) = &entries[1];
+        assert_eq!(*line, 3);
+        assert_eq!(entry.fxy, FXY::new(0, 12, 192));
+        assert_eq!(entry.element_name_en, "RADAR ECHO TOP TEMPERATURE");
+        assert!(entry.class_name_en.is_empty());
+        assert_eq!(entry.bufr_unit, "K");
+        assert_eq!(entry.bufr_scale, 1);
+        assert_eq!(entry.bufr_reference_value, -2000);
+        assert_eq!(entry.bufr_datawidth_bits, 12);
+        assert_eq!(entries[0].1.bufr_unit, "CCITT IA5");
+    }
+
+    #[test]
+    fn test_jma_table_d() {
+        let text = "# Local sequences\n 301192  3 001001\n           001002\n           001192\n 302192  1 012192\n";
+        let entries: Vec<(usize, DTableEntry)> =
+            convert::<dtable::DTableTextLoader>("d", text.as_bytes());
+
+        assert_eq!(entries.len(), 2);
+        let (line, entry) = &entries[0];
+        assert_eq!(*line, 2);
+        assert_eq!(entry.fxy, FXY::new(3, 1, 192));
+        assert_eq!(
+            entry.fxy_chain,
+            vec![FXY::new(0, 1, 1), FXY::new(0, 1, 2), FXY::new(0, 1, 192)]
+        );
+        assert_eq!(entries[1].0, 5);
+        assert_eq!(entries[1].1.fxy_chain, vec![FXY::new(0, 12, 192)]);
+    }
+}
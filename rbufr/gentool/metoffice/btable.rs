@@ -0,0 +1,90 @@
+use crate::text::EntryLoader;
+use encoding_rs::Encoding;
+use librbufr::core::{
+    FXY,
+    tables::{BTable, BTableEntry},
+};
+
+/// Table B entries, over two lines:
+///
+/// ```text
+/// 012101 TEMPERATURE/AIR TEMPERATURE
+///        K                          2        0   16
+/// ```
+///
+/// The first holds the descriptor and element name; the second the unit,
+/// then scale, reference value and data width as its last three words.
+#[derive(Default)]
+pub struct BTableTextLoader {
+    /// Descriptor and name of an entry waiting for its second line
+    pending: Option<(FXY, String)>,
+}
+
+impl EntryLoader for BTableTextLoader {
+    type Output = BTableEntry;
+    type TableType = BTable;
+    const FALLBACK: &'static Encoding = super::FALLBACK;
+    const COMMENTS: &'static [char] = super::COMMENTS;
+
+    fn process_line(&mut self, line: &str) -> anyhow::Result<Option<Self::Output>> {
+        let mut words = line.split_whitespace();
+        let first = words.next().unwrap_or_default();
+
+        if !line.starts_with(char::is_whitespace) {
+            let fxy = FXY::from_fxxyyy(first)?;
+            if let Some((pending, _)) = self
+                .pending
+                .replace((fxy, words.collect::<Vec<_>>().join(" ")))
+            {
+                anyhow::bail!("Entry {:?} has no unit line", pending);
+            }
+            return Ok(None);
+        }
+
+        let (fxy, element_name) = self
+            .pending
+            .take()
+            .ok_or_else(|| anyhow::anyhow!("Unit line without a descriptor line"))?;
+
+        let words: Vec<&str> = line.split_whitespace().collect();
+        if words.len() < 4 {
+            anyhow::bail!(
+                "Expected unit, scale, reference value and width for {:?}",
+                fxy
+            );
+        }
+        let (unit, numbers) = words.split_at(words.len() - 3);
+        let number = |index: usize, field: &str| {
+            numbers[index]
+                .parse::<i64>()
+                .map_err(|_| anyhow::anyhow!("Invalid {}: {:?}", field, numbers[index]))
+        };
+
+        let entry = BTableEntry {
+            fxy,
+            // MetDB extracts give no class names
+            class_name_en: String::new(),
+            element_name_en: element_name,
+            bufr_unit: unit.join(" "),
+            bufr_scale: number(0, "scale")? as i32,
+            bufr_reference_value: number(1, "reference value")? as i32,
+            bufr_datawidth_bits: number(2, "data width")? as u32,
+            note_en: None,
+            note_ids: None,
+            status: None,
+            bufr_unit_original: None,
+            crex_unit: None,
+            crex_scale: None,
+            crex_datawidth_char: None,
+        };
+
+        Ok(Some(entry))
+    }
+
+    fn finish(&mut self) -> anyhow::Result<Option<Self::Output>> {
+        match self.pending.take() {
+            Some((fxy, _)) => anyhow::bail!("Entry {:?} has no unit line", fxy),
+            None => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use crate::text::EntryLoader;
+use encoding_rs::Encoding;
+use librbufr::core::{
+    FXY,
+    tables::{DTable, DTableEntry},
+};
+use std::path::{Path, PathBuf};
+
+/// File name prefixes of Table D and of the local sequences of the same
+/// version, in the order their sequences are read
+const SOURCES: [&str; 2] = ["MetDB_TableD_", "MetDB_LocalSeq_"];
+
+/// Table D sequences, and local sequence files, which hold one or more
+/// sequences written the same way and are read with the Table D of their
+/// version:
+///
+/// ```text
+/// 300002 001001 001002
+///        001003 001004 END
+/// ```
+///
+/// A line starting in the first column opens a sequence with its
+/// descriptor; its remaining descriptors, and those of the indented lines
+/// after it, are the elements. Other words, such as a closing `END` or a
+/// trailing title, are ignored.
+#[derive(Debug, Clone, Default)]
+pub struct DTableTextLoader {
+    current_chain: Option<DTableEntry>,
+}
+
+impl EntryLoader for DTableTextLoader {
+    type Output = DTableEntry;
+    type TableType = DTable;
+    const CHAINED: bool = true;
+    const FALLBACK: &'static Encoding = super::FALLBACK;
+    const COMMENTS: &'static [char] = super::COMMENTS;
+
+    fn process_line(&mut self, line: &str) -> anyhow::Result<Option<Self::Output>> {
+        let mut words = line.split_whitespace();
+
+        if line.starts_with(char::is_whitespace) {
+            // Continuation line - add to current chain
+            let chain = self
+                .current_chain
+                .as_mut()
+                .ok_or_else(|| anyhow::anyhow!("Continuation line without a sequence header"))?;
+            chain
+                .fxy_chain
+                .extend(words.filter_map(|word| FXY::from_fxxyyy(word).ok()));
+            return Ok(None);
+        }
+
+        let first = words.next().unwrap_or_default();
+        let fxy = FXY::from_fxxyyy(first)?;
+        let entry = DTableEntry {
+            fxy,
+            fxy_chain: words
+                .filter_map(|word| FXY::from_fxxyyy(word).ok())
+                .collect(),
+            category: None,
+            category_of_sequences_en: None,
+            title_en: None,
+            subtitle_en: None,
+            note_en: None,
+            note_ids: None,
+            status: None,
+        };
+
+        Ok(self.current_chain.replace(entry))
+    }
+
+    fn finish(&mut self) -> anyhow::Result<Option<Self::Output>> {
+        Ok(self.current_chain.take())
+    }
+
+    /// `MetDB_TableD_{version}.txt` and `MetDB_LocalSeq_{version}.txt`,
+    /// whichever of the two `path` is and the other when it exists
+    fn sources(path: &Path) -> Vec<PathBuf> {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy())
+            .unwrap_or_default();
+        let Some(own) = SOURCES.iter().position(|prefix| {
+            name.get(..prefix.len())
+                .is_some_and(|start| start.eq_ignore_ascii_case(prefix))
+        }) else {
+            return vec![path.to_path_buf()];
+        };
+        let version = &name[SOURCES[own].len()..];
+
+        SOURCES
+            .iter()
+            .enumerate()
+            .filter_map(|(index, prefix)| {
+                if index == own {
+                    return Some(path.to_path_buf());
+                }
+                let other = path.with_file_name(format!("{}{}", prefix, version));
+                other.exists().then_some(other)
+            })
+            .collect()
+    }
+}
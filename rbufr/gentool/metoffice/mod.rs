@@ -0,0 +1,98 @@
+//! Met Office tables, as in MetDB extracts: free-format text with entries
+//! split over lines, and local sequences written like Table D and built
+//! into the same table
+pub mod btable;
+pub mod dtable;
+use crate::text::TableLoader;
+use encoding_rs::{Encoding, WINDOWS_1252};
+
+pub type MetOfficeDTableLoader = TableLoader<dtable::DTableTextLoader>;
+pub type MetOfficeBTableLoader = TableLoader<btable::BTableTextLoader>;
+
+const FALLBACK: &Encoding = WINDOWS_1252;
+/// Lines starting with `*` or `#` are comments
+const COMMENTS: &[char] = &['*', '#'];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use librbufr::core::{FXY, TableConverter};
+    use std::path::PathBuf;
+
+    /// A directory of its own holding `files`
+    fn fixture(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "rbufrgen_metoffice_{}_{}",
+            std::process::id(),
+            name
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        for (file, text) in files {
+            std::fs::write(dir.join(file), text).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_metoffice_table_b() {
+        let dir = fixture(
+            "b",
+            &[(
+                "MetDB_TableB_13.txt",
+                "* MetDB Table B\n001001 WMO BLOCK NUMBER\n       NUMERIC  0  0  7\n\
+                 012101 TEMPERATURE/AIR TEMPERATURE\n       K  2  0  16\n",
+            )],
+        );
+        let entries = MetOfficeBTableLoader::default()
+            .convert_with_lines(dir.join("MetDB_TableB_13.txt"))
+            .unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(entries.len(), 2);
+        let (line, entry) = &entries[1];
+        assert_eq!(*line, 4);
+        assert_eq!(entry.fxy, FXY::new(0, 12, 101));
+        assert_eq!(entry.element_name_en, "TEMPERATURE/AIR TEMPERATURE");
+        assert!(entry.class_name_en.is_empty());
+        assert_eq!(entry.bufr_unit, "K");
+        assert_eq!(
+            (
+                entry.bufr_scale,
+                entry.bufr_reference_value,
+                entry.bufr_datawidth_bits
+            ),
+            (2, 0, 16)
+        );
+    }
+
+    #[test]
+    fn test_metoffice_local_sequences() {
+        let dir = fixture(
+            "d",
+            &[
+                (
+                    "MetDB_TableD_13.txt",
+                    "300002 001001 001002\n       012101 END\n",
+                ),
+                ("MetDB_LocalSeq_13.txt", "* Local\n301001 001001\n"),
+            ],
+        );
+        let loader = MetOfficeDTableLoader::default();
+        // Either file builds the whole table, Table D first
+        for file in ["MetDB_TableD_13.txt", "MetDB_LocalSeq_13.txt"] {
+            let entries = loader.convert(dir.join(file)).unwrap();
+            let sequences: Vec<FXY> = entries.iter().map(|entry| entry.fxy).collect();
+            assert_eq!(sequences, vec![FXY::new(3, 0, 2), FXY::new(3, 1, 1)]);
+            assert_eq!(
+                entries[0].fxy_chain,
+                vec![FXY::new(0, 1, 1), FXY::new(0, 1, 2), FXY::new(0, 12, 101)]
+            );
+        }
+
+        // Local sequences alone are a table of their own
+        std::fs::remove_file(dir.join("MetDB_TableD_13.txt")).unwrap();
+        let entries = loader.convert(dir.join("MetDB_LocalSeq_13.txt")).unwrap();
+        assert_eq!(entries.len(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
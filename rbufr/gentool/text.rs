@@ -0,0 +1,184 @@
+//! Tables published as plain text rather than CSV, read line by line: JMA's
+//! fixed-width tables and the Met Office's MetDB extracts
+use crate::dialect;
+use encoding_rs::Encoding;
+use librbufr::core::{
+    TableConverter,
+    tables::{TableEntryFull, TableTypeTrait},
+};
+use std::path::{Path, PathBuf};
+
+#[derive(Default)]
+pub struct TableLoader<C: EntryLoader> {
+    _marker: std::marker::PhantomData<C>,
+}
+
+impl<C: EntryLoader> TableLoader<C> {
+    /// Entries of every file `C::sources` gives for `path`, in order, with
+    /// the line of its file each starts on
+    pub fn load_table<P: AsRef<Path>>(
+        &self,
+        path: P,
+        loader: &mut C,
+    ) -> anyhow::Result<Vec<(usize, C::Output)>> {
+        let mut entries = vec![];
+        for source in C::sources(path.as_ref()) {
+            load_file(&source, loader, &mut entries)?;
+        }
+        Ok(entries)
+    }
+}
+
+fn load_file<C: EntryLoader>(
+    path: &Path,
+    loader: &mut C,
+    entries: &mut Vec<(usize, C::Output)>,
+) -> anyhow::Result<()> {
+    let (_, text) = dialect::decode(path, C::FALLBACK)?;
+
+    let mut start_line = None;
+    let mut line_num = 0;
+    for line in text.lines() {
+        line_num += 1;
+        if is_comment(line, C::COMMENTS) {
+            continue;
+        }
+        match loader.process_line(line) {
+            Ok(Some(processed_entry)) if C::CHAINED => {
+                entries.push((start_line.unwrap_or(line_num), processed_entry));
+                start_line = Some(line_num);
+            }
+            Ok(Some(processed_entry)) => {
+                // Table B entries end on the line that completes them
+                entries.push((start_line.take().unwrap_or(line_num), processed_entry));
+            }
+            Ok(None) => {
+                start_line.get_or_insert(line_num);
+            }
+            Err(e) => {
+                eprintln!(
+                    "Warning: Skipping line {} in {}: {}",
+                    line_num,
+                    path.display(),
+                    e
+                );
+            }
+        }
+    }
+
+    if let Some(processed_entry) = loader.finish()? {
+        entries.push((start_line.unwrap_or(line_num), processed_entry));
+    }
+    Ok(())
+}
+
+pub trait EntryLoader: Default {
+    type Output: TableEntryFull;
+    type TableType: TableTypeTrait;
+
+    /// Whether an entry spans several lines and is returned once the line
+    /// after its last one is processed, as Table D sequences are
+    const CHAINED: bool = false;
+
+    /// Encoding of files that have no byte order mark and aren't UTF-8
+    const FALLBACK: &'static Encoding;
+
+    /// Characters starting a comment line
+    const COMMENTS: &'static [char];
+
+    fn process_line(&mut self, line: &str) -> anyhow::Result<Option<Self::Output>>;
+
+    fn finish(&mut self) -> anyhow::Result<Option<Self::Output>> {
+        Ok(None)
+    }
+
+    /// Files the table converted from `path` is read from
+    fn sources(path: &Path) -> Vec<PathBuf> {
+        vec![path.to_path_buf()]
+    }
+}
+
+impl<T: EntryLoader> TableConverter for TableLoader<T> {
+    type OutputEntry = T::Output;
+    type TableType = T::TableType;
+
+    fn convert<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<Vec<Self::OutputEntry>> {
+        Ok(self
+            .convert_with_lines(path)?
+            .into_iter()
+            .map(|(_, entry)| entry)
+            .collect())
+    }
+
+    fn convert_with_lines<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> anyhow::Result<Vec<(usize, Self::OutputEntry)>> {
+        let mut loader = T::default();
+        self.load_table(path, &mut loader)
+    }
+}
+
+/// Blank lines and lines starting with one of `comments`
+fn is_comment(line: &str, comments: &[char]) -> bool {
+    let line = line.trim();
+    line.is_empty() || line.starts_with(comments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use librbufr::core::{
+        FXY,
+        tables::{DTable, DTableEntry},
+    };
+
+    /// Sequences of one line each, `FXXYYY FXXYYY...`
+    #[derive(Default)]
+    struct Sequences;
+
+    impl EntryLoader for Sequences {
+        type Output = DTableEntry;
+        type TableType = DTable;
+        const FALLBACK: &'static Encoding = encoding_rs::WINDOWS_1252;
+        const COMMENTS: &'static [char] = &['#'];
+
+        fn process_line(&mut self, line: &str) -> anyhow::Result<Option<DTableEntry>> {
+            let mut words = line.split_whitespace().map(FXY::from_fxxyyy);
+            Ok(Some(DTableEntry {
+                fxy: words.next().unwrap()?,
+                fxy_chain: words.collect::<anyhow::Result<_>>()?,
+                category: None,
+                category_of_sequences_en: None,
+                title_en: None,
+                subtitle_en: None,
+                note_en: None,
+                note_ids: None,
+                status: None,
+            }))
+        }
+    }
+
+    #[test]
+    fn test_load_table() {
+        let path = std::env::temp_dir().join(format!("rbufrgen_text_{}.txt", std::process::id()));
+        std::fs::write(
+            &path,
+            "# Sequences\n\n300002 001001 001002\n300003 0010X1\n  # indented comment\n300004 012101\n",
+        )
+        .unwrap();
+        let entries = TableLoader::<Sequences>::default()
+            .convert_with_lines(&path)
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        // The invalid line is skipped, comments and blank lines don't count
+        let lines: Vec<usize> = entries.iter().map(|(line, _)| *line).collect();
+        assert_eq!(lines, vec![3, 6]);
+        assert_eq!(
+            entries[0].1.fxy_chain,
+            vec![FXY::new(0, 1, 1), FXY::new(0, 1, 2)]
+        );
+        assert_eq!(entries[1].1.fxy, FXY::new(3, 0, 4));
+    }
+}
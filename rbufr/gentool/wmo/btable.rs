@@ -58,6 +58,10 @@ impl EntryLoader for BTableCsvLoader {
             note_en: raw.note_en,
             note_ids: raw.note_ids,
             status: raw.status,
+            bufr_unit_original: None,
+            crex_unit: raw.crex_unit,
+            crex_scale: raw.crex_scale,
+            crex_datawidth_char: raw.crex_datawidth_char,
         };
 
         Ok(Some(entry))
@@ -39,6 +39,7 @@ impl EntryLoader for DTableCsvLoader {
     type RawEntry = RawDTableEntry;
     type Output = DTableEntry;
     type TableType = DTable;
+    const CHAINED: bool = true;
 
     fn process_entry(&mut self, raw: Self::RawEntry) -> anyhow::Result<Option<Self::Output>> {
         // Process the raw entry as needed
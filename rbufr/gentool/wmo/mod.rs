@@ -1,6 +1,8 @@
 pub mod btable;
 pub mod dtable;
-use csv::ReaderBuilder;
+use crate::dialect;
+use anyhow::Context;
+use csv::StringRecord;
 use librbufr::core::{
     TableConverter,
     tables::{TableEntryFull, TableTypeTrait},
@@ -10,32 +12,61 @@ use std::fmt::Debug;
 pub type WMODTableLoader = TableLoader<dtable::DTableCsvLoader>;
 pub type WMOBTableLoader = TableLoader<btable::BTableCsvLoader>;
 
-#[derive(Default)]
+/// Language of the CSVs converted unless another is asked for
+pub const DEFAULT_LANGUAGE: &str = "en";
+
 pub struct TableLoader<C: EntryLoader> {
+    /// Suffix of the columns names and titles are read from, e.g. "fr" for
+    /// `ElementName_fr`
+    language: String,
     _marker: std::marker::PhantomData<C>,
 }
 
+impl<C: EntryLoader> Default for TableLoader<C> {
+    fn default() -> Self {
+        Self::with_language(DEFAULT_LANGUAGE)
+    }
+}
+
 impl<C: EntryLoader> TableLoader<C> {
+    pub fn with_language(language: &str) -> Self {
+        TableLoader {
+            language: language.to_lowercase(),
+            _marker: std::marker::PhantomData,
+        }
+    }
+
     pub fn load_table<P: AsRef<std::path::Path>>(
         &self,
         path: P,
         loader: &mut C,
-    ) -> anyhow::Result<Vec<C::Output>> {
+    ) -> anyhow::Result<Vec<(usize, C::Output)>> {
         let mut entries = vec![];
-        let mut rdr = ReaderBuilder::new()
-            .has_headers(true)
-            .delimiter(b',')
-            .flexible(true) // Allow variable number of fields
-            .from_path(path.as_ref())?;
+        let (dialect, mut rdr) = dialect::open(path.as_ref(), true)?;
+        dialect.report(path.as_ref());
+        let headers = localized_headers(rdr.headers()?, &self.language)
+            .with_context(|| format!("Reading {}", path.as_ref().display()))?;
+        rdr.set_headers(headers);
 
         let mut line_num = 1; // Start at 1 for header
+        let mut start_line = None;
         for result in rdr.deserialize() {
             line_num += 1;
             match result {
                 Ok(record) => {
                     let record: C::RawEntry = record;
-                    if let Some(processed_entry) = loader.process_entry(record)? {
-                        entries.push(processed_entry);
+                    match loader.process_entry(record)? {
+                        Some(processed_entry) if C::CHAINED => {
+                            entries.push((start_line.unwrap_or(line_num), processed_entry));
+                            start_line = Some(line_num);
+                        }
+                        Some(processed_entry) => {
+                            entries.push((line_num, processed_entry));
+                            start_line = None;
+                        }
+                        None => {
+                            start_line.get_or_insert(line_num);
+                        }
                     }
                 }
                 Err(e) => {
@@ -51,7 +82,7 @@ impl<C: EntryLoader> TableLoader<C> {
         }
 
         if let Some(processed_entry) = loader.finish()? {
-            entries.push(processed_entry);
+            entries.push((start_line.unwrap_or(line_num), processed_entry));
         }
         Ok(entries)
     }
@@ -62,6 +93,10 @@ pub trait EntryLoader: Default {
     type RawEntry: for<'de> serde::Deserialize<'de> + Debug;
     type TableType: TableTypeTrait;
 
+    /// Whether an entry spans several lines and is returned once the line
+    /// after its last one is processed, as Table D sequences are
+    const CHAINED: bool = false;
+
     fn process_entry(&mut self, raw: Self::RawEntry) -> anyhow::Result<Option<Self::Output>>;
 
     fn finish(&mut self) -> anyhow::Result<Option<Self::Output>> {
@@ -77,7 +112,44 @@ impl<T: EntryLoader> TableConverter for TableLoader<T> {
         &self,
         path: P,
     ) -> anyhow::Result<Vec<Self::OutputEntry>> {
+        Ok(self
+            .convert_with_lines(path)?
+            .into_iter()
+            .map(|(_, entry)| entry)
+            .collect())
+    }
+
+    fn convert_with_lines<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+    ) -> anyhow::Result<Vec<(usize, Self::OutputEntry)>> {
         let mut loader = T::default();
         self.load_table(path, &mut loader)
     }
 }
+
+/// `headers` with the columns of `language` renamed to the `_en` names the
+/// raw entries deserialize from, and the English ones moved aside
+fn localized_headers(headers: &StringRecord, language: &str) -> anyhow::Result<StringRecord> {
+    let suffix = format!("_{}", language);
+    if !headers.iter().any(|header| header.ends_with(&suffix)) {
+        let columns: Vec<&str> = headers.iter().collect();
+        anyhow::bail!(
+            "No '{}' columns; the file has: {}",
+            language,
+            columns.join(", ")
+        );
+    }
+    if language == DEFAULT_LANGUAGE {
+        return Ok(headers.clone());
+    }
+
+    Ok(headers
+        .iter()
+        .map(|header| match header.strip_suffix(&suffix) {
+            Some(stem) => format!("{}_{}", stem, DEFAULT_LANGUAGE),
+            None if header.ends_with("_en") => format!("{}_original", header),
+            None => header.to_string(),
+        })
+        .collect())
+}
@@ -1,20 +1,33 @@
+use chrono::NaiveDateTime;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{BufWriter, Write};
 use std::ops::Deref;
+use std::path::{Path, PathBuf};
 
 use crate::core::BUFRTableMPH;
 #[cfg(feature = "opera")]
 use crate::core::prelude::BUFRTableBitMap;
+use crate::core::prelude::BUFRTableD;
+use crate::core::table_a;
 use crate::core::tables::TableTypeTrait;
 
+use crate::decode::DecodeIter;
+use crate::decoder::{Decoder, DescriptorTree, ExpandedDescriptor};
 use crate::errors::Result;
+use crate::parser::MessageHeader;
 #[cfg(feature = "opera")]
 #[allow(unused)]
 use crate::structs::GENCENTER;
-use crate::structs::versions::BUFRMessage;
+use crate::structs::versions::{BUFRMessage, MessageVersion};
 use crate::tables::*;
+use crate::templates::{self, Template};
 
 #[derive(Clone)]
 pub struct MessageBlock {
     message: BUFRMessage,
+    raw: Vec<u8>,
 }
 
 impl std::fmt::Display for MessageBlock {
@@ -32,25 +45,93 @@ impl Deref for MessageBlock {
 }
 
 impl MessageBlock {
-    pub fn new(message: BUFRMessage) -> Self {
-        MessageBlock { message }
+    pub fn new(message: BUFRMessage, raw: Vec<u8>) -> Self {
+        MessageBlock { message, raw }
     }
 
+    /// The original bytes of the message, exactly as read
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.raw
+    }
+
+    /// The identification fields of Sections 0 and 1
+    pub fn header(&self) -> MessageHeader {
+        let mut bytes = [0u8; MessageHeader::MAX_LEN];
+        let len = self.raw.len().min(bytes.len());
+        bytes[..len].copy_from_slice(&self.raw[..len]);
+        MessageHeader::from_bytes(&bytes)
+    }
+
+    /// Re-serialize the message from its parsed sections
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.message.to_bytes()
+    }
+
+    /// The typical date and time of the data, from Section 1
+    ///
+    /// Two-digit years of editions 0 to 3 are placed in 1950-2049.
+    pub fn obs_time(&self) -> Option<NaiveDateTime> {
+        self.message.obs_time()
+    }
+
+    /// The Table A name of the message's data category, `None` for a
+    /// reserved category
+    pub fn category_name(&self) -> Option<&'static str> {
+        table_a::category_name(self.header().data_category)
+    }
+
+    /// Section 3 expanded against the tables of `tables`, without reading
+    /// the data; see `Decoder::expand`
+    pub fn expanded_descriptors(&self, tables: &Decoder) -> Result<Vec<ExpandedDescriptor>> {
+        tables.expand(&self.message.descriptors()?)
+    }
+
+    /// Section 3 as a tree named from the tables of `tables`; see
+    /// `Decoder::descriptor_tree`
+    pub fn descriptor_tree(&self, tables: &Decoder) -> Result<DescriptorTree> {
+        Ok(tables.descriptor_tree(&self.message.descriptors()?))
+    }
+
+    /// The well-known WMO template this message carries, if any
+    ///
+    /// Only Section 3 and the master sequence table are consulted; the data
+    /// section is not decoded.
+    pub fn template(&self) -> Option<&'static Template> {
+        let descriptors = self.message.descriptors().ok()?;
+        let (table_d, _): (BUFRTableD, _) = self
+            .load_first_validable_table(
+                self.message.master_table(),
+                self.message.master_table_version(),
+                None,
+            )
+            .ok()?;
+        templates::detect(&descriptors, &table_d)
+    }
+
+    /// The newest version at or below `table_version` of master table
+    /// `number`, with the version it was found for; looked for under
+    /// `base`, or the global tables directory when `None`
     pub(crate) fn load_first_validable_table<E: TableTypeTrait>(
         &self,
+        number: u8,
         table_version: u8,
-    ) -> Result<BUFRTableMPH<E>> {
+        base: Option<&Path>,
+    ) -> Result<(BUFRTableMPH<E>, u8)> {
         (0..=table_version)
             .rev()
             .find_map(|version| {
                 TableLoader
-                    .load_table(MasterTable::new(version))
+                    .load_table_in(base, MasterTable::with_number(number, version))
                     .ok()
                     .inspect(|_| {
                         if version != table_version {
-                            eprintln!("Falling back to Master Table version {}", version);
+                            eprintln!(
+                                "Falling back to Master Table {} version {}",
+                                number, version
+                            );
                         }
                     })
+                    .map(|table| (table, version))
             })
             .ok_or(crate::errors::Error::TableNotFoundEmpty)
     }
@@ -62,13 +143,12 @@ impl MessageBlock {
         center: u16,
         local_version: u8,
         master_version: u8,
+        base: Option<&Path>,
     ) -> Result<BUFRTableBitMap> {
-        TableLoader.load_table(BitmapTable::new(
-            center,
-            subcenter,
-            local_version,
-            master_version,
-        ))
+        TableLoader.load_table_in(
+            base,
+            BitmapTable::new(center, subcenter, local_version, master_version),
+        )
     }
 }
 
@@ -83,8 +163,8 @@ impl BUFRFile {
         }
     }
 
-    pub(crate) fn push_message(&mut self, message: BUFRMessage) {
-        self.messages.push(MessageBlock::new(message));
+    pub(crate) fn push_message(&mut self, message: BUFRMessage, raw: Vec<u8>) {
+        self.messages.push(MessageBlock::new(message, raw));
     }
 
     pub fn message_count(&self) -> usize {
@@ -98,4 +178,78 @@ impl BUFRFile {
     pub fn messages(&self) -> &[MessageBlock] {
         &self.messages
     }
+
+    /// Decode the messages one at a time, with default options
+    ///
+    /// Decoders are built from each message's Section 1 and shared between
+    /// messages asking for the same tables. A message that fails to decode
+    /// comes out as an error; iteration carries on after it.
+    pub fn decode_iter(&self) -> DecodeIter<'_> {
+        DecodeIter::new(&self.messages)
+    }
+
+    /// Decode every message across rayon's thread pool, with default options
+    ///
+    /// Tables are loaded once per set and shared between threads; results
+    /// come back in the order of the messages.
+    #[cfg(feature = "rayon")]
+    pub fn decode_all_parallel(&self) -> Vec<Result<crate::decoder::BUFRParsed<'static>>> {
+        crate::decode::decode_parallel(&self.messages)
+    }
+
+    /// Write every message, byte for byte, to `writer`
+    pub fn write<W: Write>(&self, writer: W) -> Result<()> {
+        let mut writer = BufWriter::new(writer);
+        for message in &self.messages {
+            writer.write_all(&message.raw)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Write every message to the file at `path`, truncating it if it exists
+    pub fn write_to<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        self.write(File::create(path)?)
+    }
+
+    /// Group messages by `key` and write each group to `{dir}/{key}.bufr`
+    ///
+    /// Messages keep their original order within a group. Returns the paths
+    /// of the written files, sorted by key.
+    pub fn split_by<P, K, F>(&self, dir: P, mut key: F) -> Result<Vec<PathBuf>>
+    where
+        P: AsRef<Path>,
+        K: Display,
+        F: FnMut(&MessageBlock) -> K,
+    {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+
+        let mut groups: BTreeMap<String, BUFRFile> = BTreeMap::new();
+        for message in &self.messages {
+            groups
+                .entry(key(message).to_string())
+                .or_insert_with(BUFRFile::new)
+                .messages
+                .push(message.clone());
+        }
+
+        let mut paths = Vec::with_capacity(groups.len());
+        for (key, group) in groups {
+            let path = dir.join(format!("{}.bufr", key));
+            group.write_to(&path)?;
+            paths.push(path);
+        }
+
+        Ok(paths)
+    }
+
+    /// Concatenate the messages of several files into one, preserving order
+    pub fn merge<I: IntoIterator<Item = BUFRFile>>(files: I) -> BUFRFile {
+        let mut merged = BUFRFile::new();
+        for file in files {
+            merged.messages.extend(file.messages);
+        }
+        merged
+    }
 }
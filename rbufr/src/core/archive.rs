@@ -0,0 +1,106 @@
+//! Several versions of one table in a single file
+//!
+//! An archive holds the `.bufrtbl` images of each version end to end,
+//! behind a version directory:
+//!
+//! - the magic `BUFRARC1`, then the number of versions as a little-endian
+//!   `u32`
+//! - per version: the version byte, then the offset and length of its image
+//!   as little-endian `u64`s
+//! - the images, each starting on a 16-byte boundary so they can be read in
+//!   place from a memory map
+use anyhow::{Context, bail};
+use std::io::Write;
+use std::ops::Range;
+use std::path::Path;
+
+pub const ARCHIVE_EXTENSION: &str = "bufrarc";
+
+const MAGIC: &[u8; 8] = b"BUFRARC1";
+const ENTRY_LEN: usize = 17;
+const ALIGN: usize = 16;
+
+/// Pack the `.bufrtbl` files of `tables`, keyed by version, into an archive
+/// at `output`
+pub fn pack<P: AsRef<Path>>(tables: &[(u8, &Path)], output: P) -> anyhow::Result<()> {
+    let mut versions: Vec<u8> = tables.iter().map(|(version, _)| *version).collect();
+    versions.sort_unstable();
+    versions.dedup();
+    if versions.len() != tables.len() {
+        bail!("Each version can only be packed once");
+    }
+
+    let mut images = Vec::with_capacity(tables.len());
+    for (version, path) in tables {
+        let bytes =
+            std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        images.push((*version, bytes));
+    }
+    images.sort_by_key(|(version, _)| *version);
+
+    let header_len = MAGIC.len() + 4 + images.len() * ENTRY_LEN;
+    let mut directory = Vec::with_capacity(header_len);
+    directory.extend_from_slice(MAGIC);
+    directory.extend_from_slice(&(images.len() as u32).to_le_bytes());
+
+    let mut body = Vec::new();
+    for (version, image) in &images {
+        let offset = (header_len + body.len()).next_multiple_of(ALIGN);
+        body.resize(offset - header_len, 0);
+        body.extend_from_slice(image);
+
+        directory.push(*version);
+        directory.extend_from_slice(&(offset as u64).to_le_bytes());
+        directory.extend_from_slice(&(image.len() as u64).to_le_bytes());
+    }
+
+    let mut file = std::fs::File::create(output.as_ref())?;
+    file.write_all(&directory)?;
+    file.write_all(&body)?;
+    Ok(())
+}
+
+/// The versions held by the archive at `path`, ascending
+pub fn versions<P: AsRef<Path>>(path: P) -> anyhow::Result<Vec<u8>> {
+    let bytes = std::fs::read(path)?;
+    Ok(directory(&bytes)?
+        .into_iter()
+        .map(|(version, _)| version)
+        .collect())
+}
+
+/// Where the image of `version` lies in the archive `bytes`
+pub(crate) fn find(bytes: &[u8], version: u8) -> anyhow::Result<Range<usize>> {
+    directory(bytes)?
+        .into_iter()
+        .find_map(|(v, range)| (v == version).then_some(range))
+        .with_context(|| format!("Version {} is not in the archive", version))
+}
+
+fn directory(bytes: &[u8]) -> anyhow::Result<Vec<(u8, Range<usize>)>> {
+    let Some(rest) = bytes.strip_prefix(MAGIC) else {
+        bail!("Not a table archive");
+    };
+    let count = u32::from_le_bytes(
+        rest.get(..4)
+            .context("Truncated archive header")?
+            .try_into()?,
+    );
+
+    let entries = rest
+        .get(4..4 + count as usize * ENTRY_LEN)
+        .context("Truncated archive directory")?;
+    entries
+        .chunks_exact(ENTRY_LEN)
+        .map(|entry| {
+            let offset = u64::from_le_bytes(entry[1..9].try_into()?) as usize;
+            let len = u64::from_le_bytes(entry[9..17].try_into()?) as usize;
+            if !offset.is_multiple_of(ALIGN)
+                || offset.checked_add(len).is_none_or(|end| end > bytes.len())
+            {
+                bail!("Archive entry for version {} is out of range", entry[0]);
+            }
+            Ok((entry[0], offset..offset + len))
+        })
+        .collect()
+}
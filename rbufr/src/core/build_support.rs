@@ -0,0 +1,115 @@
+//! Embedding tables in downstream crates, from their build scripts
+//!
+//! ```ignore
+//! // build.rs
+//! fn main() {
+//!     let out_dir = std::env::var("OUT_DIR").unwrap();
+//!     librbufr::core::build_support::EmbedTables::new("tables")
+//!         .master(16)
+//!         .local(85, None, 20)
+//!         .write(out_dir)
+//!         .unwrap();
+//! }
+//!
+//! // main.rs
+//! include!(concat!(env!("OUT_DIR"), "/rbufr_tables.rs"));
+//!
+//! fn main() {
+//!     register();
+//!     // decoders now find the embedded tables without any table files
+//! }
+//! ```
+use anyhow::{Context, bail};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use super::TableType;
+use crate::tables::{LocalTable, MasterTable, TableTrait};
+
+/// Name of the module `EmbedTables::write` generates under `OUT_DIR`
+pub const MODULE_NAME: &str = "rbufr_tables.rs";
+
+/// The table files to embed, picked from a tables directory
+#[derive(Debug, Clone)]
+pub struct EmbedTables {
+    source: PathBuf,
+    /// Names relative to `source`, as `TableTrait::file_name` gives them
+    names: Vec<String>,
+}
+
+impl EmbedTables {
+    /// Pick from `source`, laid out like the bundled `tables` directory
+    pub fn new(source: impl Into<PathBuf>) -> Self {
+        EmbedTables {
+            source: source.into(),
+            names: vec![],
+        }
+    }
+
+    /// Table B and D of master table 0, `version`
+    pub fn master(self, version: u8) -> Self {
+        self.table(MasterTable::new(version))
+    }
+
+    /// Table B and D of master table `number`, `version`
+    pub fn master_table(self, number: u8, version: u8) -> Self {
+        self.table(MasterTable::with_number(number, version))
+    }
+
+    /// Local Table B and D; see `LocalTable::new`
+    pub fn local(self, centre: u16, subcentre: Option<u16>, version: u8) -> Self {
+        self.table(LocalTable::new(centre, subcentre, version))
+    }
+
+    fn table(mut self, table: impl TableTrait) -> Self {
+        for table_type in [TableType::B, TableType::D] {
+            let name = table.file_name(table_type);
+            if !self.names.contains(&name) {
+                self.names.push(name);
+            }
+        }
+        self
+    }
+
+    /// Copy the tables under `out_dir/rbufr_tables` and generate
+    /// `out_dir/rbufr_tables.rs`, returning its path
+    ///
+    /// The module holds `TABLES`, a `&[EmbeddedTable]`, and `register()`,
+    /// which hands them to `tables::register_embedded`. Cargo is told to
+    /// rerun the build script when a source table changes.
+    pub fn write(&self, out_dir: impl AsRef<Path>) -> anyhow::Result<PathBuf> {
+        let out_dir = out_dir.as_ref();
+        if self.names.is_empty() {
+            bail!("No tables selected");
+        }
+
+        let mut module =
+            String::from("// Generated by librbufr::core::build_support. Do not edit.\n\n");
+        module.push_str("pub static TABLES: &[librbufr::tables::EmbeddedTable] = &[\n");
+        for name in &self.names {
+            let source = self.source.join(name);
+            let copy = out_dir.join("rbufr_tables").join(name);
+            if let Some(parent) = copy.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::copy(&source, &copy)
+                .with_context(|| format!("Failed to copy {}", source.display()))?;
+            println!("cargo:rerun-if-changed={}", source.display());
+
+            writeln!(
+                module,
+                "    librbufr::tables::EmbeddedTable {{ name: {:?}, bytes: include_bytes!({:?}) }},",
+                name,
+                copy.display().to_string()
+            )?;
+        }
+        module.push_str("];\n\n");
+        module.push_str("/// Make the embedded tables available to every decoder\n");
+        module
+            .push_str("pub fn register() {\n    librbufr::tables::register_embedded(TABLES);\n}\n");
+
+        let path = out_dir.join(MODULE_NAME);
+        std::fs::write(&path, module)?;
+        Ok(path)
+    }
+}
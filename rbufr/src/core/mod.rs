@@ -1,4 +1,9 @@
+pub mod archive;
+pub mod build_support;
 pub mod prelude;
+pub mod statics;
+pub mod table_a;
+pub mod table_c;
 pub mod tables;
 use anyhow::Context;
 use memmap2::Mmap;
@@ -6,12 +11,15 @@ use ph::fmph::GOFunction;
 use rkyv::api::high::HighValidator;
 use rkyv::bytecheck::CheckBytes;
 use rkyv::rancor::Error;
+use rkyv::util::AlignedVec;
 use rkyv::{Archive, Deserialize, Serialize};
+use rustc_hash::FxHashMap;
 use serde::{Deserialize as SerdeDeserialize, Serialize as SerdeSerialize};
 use std::fmt::Debug;
 use std::io::{Cursor, Write};
+use std::ops::Range;
 use std::path::Path;
-use tables::{TableEntryFull, TableTypeTrait};
+use tables::{TableEntry, TableEntryFull, TableTypeTrait};
 pub mod pattern;
 
 pub trait TableConverter {
@@ -19,17 +27,168 @@ pub trait TableConverter {
     type TableType: TableTypeTrait;
     fn convert<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<Vec<Self::OutputEntry>>;
 
+    /// Like `convert`, with the source line each entry starts on. Converters
+    /// that don't track lines number the entries instead.
+    fn convert_with_lines<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> anyhow::Result<Vec<(usize, Self::OutputEntry)>> {
+        Ok(self
+            .convert(path)?
+            .into_iter()
+            .enumerate()
+            .map(|(index, entry)| (index + 1, entry))
+            .collect())
+    }
+
     fn table_type(&self) -> TableType {
         Self::TableType::TABLE_TYPE
     }
+
+    /// Name recorded in the tables built with this converter
+    fn name(&self) -> String {
+        std::any::type_name::<Self>().to_string()
+    }
+
+    /// Table version `path` declares, read from its file name
+    fn declared_version(&self, path: &Path) -> Option<u32> {
+        let file_name = path.file_name()?.to_str()?;
+        pattern::TableScanner::new()
+            .match_filename(file_name)?
+            .version
+    }
+}
+
+/// Drop repeated descriptors from converted entries, keyed by source line
+///
+/// A descriptor defined more than once with identical contents keeps its
+/// first definition. One defined with different contents is a conflict: all
+/// of them are reported, with their lines, and nothing is returned.
+pub fn dedupe_entries<E: TableEntry>(entries: Vec<(usize, E)>) -> anyhow::Result<Vec<E>> {
+    let mut seen: FxHashMap<FXY, (usize, serde_json::Value)> = FxHashMap::default();
+    let mut conflicts: Vec<(FXY, Vec<usize>)> = vec![];
+    let mut kept = Vec::with_capacity(entries.len());
+
+    for (line, entry) in entries {
+        let fxy = entry.fxy();
+        let value = serde_json::to_value(&entry)?;
+        match seen.get(&fxy) {
+            None => {
+                seen.insert(fxy, (line, value));
+                kept.push(entry);
+            }
+            Some((_, first)) if *first == value => {}
+            Some((first_line, _)) => match conflicts.iter_mut().find(|(key, _)| *key == fxy) {
+                Some((_, lines)) => lines.push(line),
+                None => conflicts.push((fxy, vec![*first_line, line])),
+            },
+        }
+    }
+
+    if !conflicts.is_empty() {
+        let report: Vec<String> = conflicts
+            .iter()
+            .map(|(fxy, lines)| {
+                let lines: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+                format!(
+                    "{}-{:02}-{:03} (lines {})",
+                    fxy.f,
+                    fxy.x,
+                    fxy.y,
+                    lines.join(", ")
+                )
+            })
+            .collect();
+        anyhow::bail!(
+            "{} conflicting descriptor(s): {}",
+            conflicts.len(),
+            report.join("; ")
+        );
+    }
+
+    Ok(kept)
+}
+
+/// Where a table's bytes live
+enum TableBytes {
+    Mapped(Mmap),
+    /// Copied in, aligned for in-place access
+    Owned(AlignedVec),
+}
+
+impl std::ops::Deref for TableBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            TableBytes::Mapped(mmap) => mmap,
+            TableBytes::Owned(bytes) => bytes,
+        }
+    }
 }
 
 struct BufrTableMph<T: TableEntryFull> {
     mphf: GOFunction,
-    mmap: Mmap,
+    mmap: TableBytes,
+    /// The table's image within `mmap`, all of it unless read from an archive
+    range: Range<usize>,
     _marker: std::marker::PhantomData<T>,
 }
 
+/// How a .bufrtbl file was built, recorded inside it
+#[derive(Archive, Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Default)]
+#[rkyv(compare(PartialEq))]
+pub struct BuildMetadata {
+    /// File name of the table it was converted from
+    pub source: Option<String>,
+    /// The `TableConverter` that read the source
+    pub converter: String,
+    /// Version of rbufr that built it
+    pub converter_version: String,
+    pub entry_count: u64,
+    /// Seconds since the Unix epoch, or `SOURCE_DATE_EPOCH` when set
+    pub built_at: u64,
+    /// Table version the source declared
+    pub wmo_version: Option<u32>,
+}
+
+impl std::fmt::Display for BuildMetadata {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let built_at = chrono::DateTime::from_timestamp(self.built_at as i64, 0)
+            .map(|time| time.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+            .unwrap_or_else(|| self.built_at.to_string());
+        writeln!(
+            f,
+            "Source:      {}",
+            self.source.as_deref().unwrap_or("N/A")
+        )?;
+        writeln!(
+            f,
+            "Converter:   {} (rbufr {})",
+            self.converter, self.converter_version
+        )?;
+        writeln!(f, "Entries:     {}", self.entry_count)?;
+        writeln!(f, "Built:       {}", built_at)?;
+        match self.wmo_version {
+            Some(version) => write!(f, "Version:     {}", version),
+            None => write!(f, "Version:     N/A"),
+        }
+    }
+}
+
+/// When a table is built; `SOURCE_DATE_EPOCH` fixes it for reproducible builds
+fn build_timestamp() -> u64 {
+    std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|epoch| epoch.parse().ok())
+        .unwrap_or_else(|| {
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_secs())
+                .unwrap_or(0)
+        })
+}
+
 #[derive(Archive, Deserialize, Serialize, PartialEq)]
 #[rkyv(compare(PartialEq))]
 struct BUFRTF<T>
@@ -38,13 +197,16 @@ where
 {
     pub function_header: Vec<u8>,
     pub entries: Vec<T>,
+    /// Indices into `entries`, in descriptor order
+    pub sorted: Vec<u32>,
+    pub metadata: BuildMetadata,
 }
 
 impl<T> BUFRTF<T>
 where
     T: TableEntryFull,
 {
-    fn new(entries: Vec<T>) -> std::io::Result<Self> {
+    fn new(entries: Vec<T>, metadata: BuildMetadata) -> std::io::Result<Self> {
         let keys: Vec<FXY> = entries.iter().map(|e| e.fxy()).collect();
         let mphf = GOFunction::from_slice(&keys);
         let mut sorted_entries: Vec<(usize, T)> = entries
@@ -57,9 +219,18 @@ where
         mphf.write(&mut mphf_bytes)
             .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, format!("{:?}", e)))?;
 
+        let entries: Vec<T> = sorted_entries.into_iter().map(|(_, e)| e).collect();
+        let mut sorted: Vec<u32> = (0..entries.len() as u32).collect();
+        sorted.sort_by_key(|&index| {
+            let fxy = entries[index as usize].fxy();
+            (fxy.f, fxy.x, fxy.y)
+        });
+
         Ok(Self {
             function_header: mphf_bytes,
-            entries: sorted_entries.into_iter().map(|(_, e)| e).collect(),
+            entries,
+            sorted,
+            metadata,
         })
     }
 
@@ -82,21 +253,56 @@ where
         path
     }
 
-    fn build<P: AsRef<Path>>(entries: Vec<T>, output_path: P) -> anyhow::Result<Self> {
+    fn build<P: AsRef<Path>>(
+        entries: Vec<T>,
+        metadata: BuildMetadata,
+        output_path: P,
+    ) -> anyhow::Result<Self> {
         let output_path = Self::bufrtbl_path(output_path);
-        let bufrtf = BUFRTF::new(entries)?;
+        let bufrtf = BUFRTF::new(entries, metadata)?;
         bufrtf.write_to_disk(&output_path)?;
 
         Self::load(output_path)
     }
 
+    /// Build in memory, without writing a .bufrtbl file
+    fn build_in_memory(entries: Vec<T>, metadata: BuildMetadata) -> anyhow::Result<Self> {
+        let bytes = rkyv::to_bytes::<Error>(&BUFRTF::new(entries, metadata)?)?;
+        let range = 0..bytes.len();
+
+        Self::from_mmap(TableBytes::Owned(bytes), range)
+    }
+
     fn load<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
         let path = Self::bufrtbl_path(path);
 
         let merged_file = std::fs::File::open(&path)?;
         let mmap = unsafe { Mmap::map(&merged_file)? };
+        let range = 0..mmap.len();
+
+        Self::from_mmap(TableBytes::Mapped(mmap), range)
+    }
+
+    /// Load from the bytes of a .bufrtbl file, copied so they are aligned
+    fn load_bytes(bytes: &[u8]) -> anyhow::Result<Self> {
+        let mut owned = AlignedVec::new();
+        owned.extend_from_slice(bytes);
+        let range = 0..owned.len();
+
+        Self::from_mmap(TableBytes::Owned(owned), range)
+    }
+
+    /// Load `version` from the archive at `path`
+    fn load_archived<P: AsRef<Path>>(path: P, version: u8) -> anyhow::Result<Self> {
+        let archive_file = std::fs::File::open(path.as_ref())?;
+        let mmap = unsafe { Mmap::map(&archive_file)? };
+        let range = archive::find(&mmap, version)?;
 
-        let archived = rkyv::access::<ArchivedBUFRTF<T>, Error>(&mmap)?;
+        Self::from_mmap(TableBytes::Mapped(mmap), range)
+    }
+
+    fn from_mmap(mmap: TableBytes, range: Range<usize>) -> anyhow::Result<Self> {
+        let archived = rkyv::access::<ArchivedBUFRTF<T>, Error>(&mmap[range.clone()])?;
         let function_reader = &archived.function_header[..];
 
         let mut cursor = Cursor::new(function_reader);
@@ -104,6 +310,7 @@ where
         Ok(Self {
             mphf: GOFunction::read(&mut cursor)?,
             mmap,
+            range,
             _marker: std::marker::PhantomData,
         })
     }
@@ -111,14 +318,37 @@ where
     /// 获取拥有的版本
     fn get<K: BUFRKey>(&self, fxy: &K) -> Option<&<T as Archive>::Archived> {
         let hash = self.mphf.get(&fxy)? as usize;
-        self.archived().ok()?.entries.get(hash)
+        let entry = self.archived().ok()?.entries.get(hash)?;
+        // Keys outside the table hash to some other entry
+        (fxy == T::archived_fxy(entry)).then_some(entry)
+    }
+
+    fn metadata(&self) -> anyhow::Result<BuildMetadata> {
+        let metadata = rkyv::deserialize::<BuildMetadata, Error>(&self.archived()?.metadata)?;
+        Ok(metadata)
     }
 
     fn archived(&self) -> anyhow::Result<&ArchivedBUFRTF<T>> {
-        let archived = rkyv::access::<ArchivedBUFRTF<T>, Error>(&self.mmap)?;
+        let archived = rkyv::access::<ArchivedBUFRTF<T>, Error>(&self.mmap[self.range.clone()])?;
         Ok(archived)
     }
 
+    fn len(&self) -> usize {
+        self.archived()
+            .map(|archived| archived.entries.len())
+            .unwrap_or(0)
+    }
+
+    /// Entries in descriptor order
+    fn iter_sorted(&self) -> impl Iterator<Item = &<T as Archive>::Archived> {
+        self.archived().ok().into_iter().flat_map(|archived| {
+            archived
+                .sorted
+                .iter()
+                .filter_map(|index| archived.entries.get(index.to_native() as usize))
+        })
+    }
+
     /// 获取所有条目
     fn get_all(&self) -> Vec<&<T as Archive>::Archived> {
         if let Ok(archived) = self.archived() {
@@ -153,7 +383,7 @@ pub struct FXY {
 }
 
 impl FXY {
-    pub fn new(f: i32, x: i32, y: i32) -> Self {
+    pub const fn new(f: i32, x: i32, y: i32) -> Self {
         FXY { f, x, y }
     }
     pub fn from_str(fxy_str: &str) -> anyhow::Result<Self> {
@@ -175,6 +405,22 @@ impl FXY {
         Ok(FXY { f, x, y })
     }
 
+    /// A descriptor written FXXYYY, as in the WMO tables
+    pub fn from_fxxyyy(fxy: &str) -> anyhow::Result<Self> {
+        let digits = |range: std::ops::Range<usize>| -> anyhow::Result<i32> {
+            fxy.get(range)
+                .filter(|part| part.bytes().all(|b| b.is_ascii_digit()))
+                .and_then(|part| part.parse().ok())
+                .ok_or_else(|| {
+                    anyhow::anyhow!("Invalid descriptor: {}. Use FXXYYY, e.g. 012101", fxy)
+                })
+        };
+        if fxy.len() != 6 {
+            anyhow::bail!("Invalid descriptor: {}. Use FXXYYY, e.g. 012101", fxy);
+        }
+        Ok(FXY::new(digits(0..1)?, digits(1..3)?, digits(3..6)?))
+    }
+
     /// Convert FXY to u32 for use as hash key
     /// Format: F (2 bits) | X (6 bits) | Y (8 bits) = 16 bits total
     pub fn to_u32(&self) -> u32 {
@@ -200,24 +446,91 @@ where
         L: TableConverter<TableType = T>,
         <T::EntryType as Archive>::Archived: for<'a> CheckBytes<HighValidator<'a, Error>>,
     {
-        let entries = loader.convert(path)?;
-        let bhm = BufrTableMph::<T::EntryType>::build(entries, output_path)?;
+        let mut entries = loader.convert_with_lines(path.as_ref())?;
+        for (_, entry) in &mut entries {
+            entry.normalize();
+        }
+        let entries = dedupe_entries(entries)?;
+        // Nothing is written for a file the loader didn't understand
+        if entries.is_empty() {
+            anyhow::bail!("No entries converted from {}", path.as_ref().display());
+        }
+        let metadata = BuildMetadata {
+            source: path
+                .as_ref()
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned()),
+            converter: loader.name(),
+            converter_version: env!("CARGO_PKG_VERSION").to_string(),
+            entry_count: entries.len() as u64,
+            built_at: build_timestamp(),
+            wmo_version: loader.declared_version(path.as_ref()),
+        };
+        let bhm = BufrTableMph::<T::EntryType>::build(entries, metadata, output_path)?;
 
         Ok(BUFRTableMPH { inner: bhm })
     }
 
+    /// A table of `entries` held in memory, as compiled-in tables are
+    /// loaded
+    pub fn from_entries(
+        entries: Vec<T::EntryType>,
+        metadata: BuildMetadata,
+    ) -> anyhow::Result<Self> {
+        let bhm = BufrTableMph::<T::EntryType>::build_in_memory(entries, metadata)?;
+
+        Ok(BUFRTableMPH { inner: bhm })
+    }
+
+    /// How the table was built
+    pub fn metadata(&self) -> anyhow::Result<BuildMetadata> {
+        self.inner.metadata()
+    }
+
     pub fn get_all_entries(&self) -> Vec<&<T::EntryType as Archive>::Archived> {
         self.inner.get_all()
     }
 
+    /// Entries ordered by F, X and Y, from an index kept in the table
+    pub fn iter_sorted(&self) -> impl Iterator<Item = &<T::EntryType as Archive>::Archived> {
+        self.inner.iter_sorted()
+    }
+
     pub fn load_from_disk<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
         let bhm = BufrTableMph::<T::EntryType>::load(path)?;
         Ok(BUFRTableMPH { inner: bhm })
     }
 
+    /// Load from the contents of a .bufrtbl file, such as one embedded with
+    /// `include_bytes!`
+    pub fn load_from_bytes(bytes: &[u8]) -> anyhow::Result<Self> {
+        let bhm = BufrTableMph::<T::EntryType>::load_bytes(bytes)?;
+        Ok(BUFRTableMPH { inner: bhm })
+    }
+
+    /// Load `version` from an archive made by `archive::pack`
+    pub fn load_from_archive<P: AsRef<Path>>(path: P, version: u8) -> anyhow::Result<Self> {
+        let bhm = BufrTableMph::<T::EntryType>::load_archived(path, version)?;
+        Ok(BUFRTableMPH { inner: bhm })
+    }
+
+    /// The entry of `fxy`, `None` if it isn't in the table
     pub fn lookup<K: BUFRKey>(&self, fxy: &K) -> Option<&<T::EntryType as Archive>::Archived> {
         self.inner.get(fxy)
     }
+
+    pub fn contains_key<K: BUFRKey>(&self, fxy: &K) -> bool {
+        self.lookup(fxy).is_some()
+    }
+
+    /// Number of entries
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
 }
 
 pub trait BUFRKey: Debug + Eq + std::hash::Hash + PartialEq<FXY> + PartialEq<ArchivedFXY> {
@@ -29,6 +29,8 @@ pub struct TableMetadata {
     pub subcenter: Option<u32>,
     /// Originating center ID (for local tables)
     pub center: Option<u32>,
+    /// Master table number (e.g., 10 for oceanography); `None` for table 0
+    pub master_table: Option<u32>,
     /// Language code (e.g., "en")
     pub language: Option<String>,
     /// Whether this is a local table
@@ -44,23 +46,41 @@ impl TableMetadata {
     /// - WMO standard tables: BUFR_TableB_{version} or BUFR_TableD_{version}
     ///   Example: BUFR_TableB_14
     ///
-    /// - Local tables with subcenter: BUFR_TableB_{subcenter}_{version}
-    ///   Example: BUFR_TableB_1_14 (subcenter 1, version 14)
+    /// - Local tables of a sub-centre: BUFR_TableB_{center}_{subcenter}_{version}
+    ///   Example: BUFR_TableB_7_4_14 (center 7, subcenter 4, version 14)
+    ///
+    /// - Local tables of a whole centre: BUFR_TableB_{center}_{version}
+    ///   Example: BUFR_TableB_85_14 (center 85, version 14)
+    ///
+    /// - Tables of another master table: BUFR_TableB_mt{master_table}_{version}
+    ///   Example: BUFR_TableB_mt10_14 (master table 10, version 14)
+    ///
+    /// These are the names `LocalTable` looks up when decoding.
     pub fn output_name(&self) -> String {
         let kind = match self.kind {
             TableKind::B => "TableB",
             TableKind::D => "TableD",
         };
-
-        if self.is_local && self.subcenter.is_some() {
-            // Format: BUFR_Table{B|D}_{subcenter}_{version}
-            let subcenter = self.subcenter.unwrap();
-            let version = self.version.unwrap_or(0);
-            format!("BUFR_{}_{}_{}", kind, subcenter, version)
-        } else {
+        let version = self.version.unwrap_or(0);
+
+        match (
+            self.is_local,
+            self.center,
+            self.subcenter,
+            self.master_table,
+        ) {
+            // Format: BUFR_Table{B|D}_{center}_{subcenter}_{version}
+            (true, Some(center), Some(subcenter), _) => {
+                format!("BUFR_{}_{}_{}_{}", kind, center, subcenter, version)
+            }
+            // Format: BUFR_Table{B|D}_{center}_{version}
+            (true, Some(center), None, _) => format!("BUFR_{}_{}_{}", kind, center, version),
+            // Format: BUFR_Table{B|D}_mt{master_table}_{version}
+            (false, _, _, Some(number)) if number != 0 => {
+                format!("BUFR_{}_mt{}_{}", kind, number, version)
+            }
             // Format: BUFR_Table{B|D}_{version}
-            let version = self.version.unwrap_or(0);
-            format!("BUFR_{}_{}", kind, version)
+            _ => format!("BUFR_{}_{}", kind, version),
         }
     }
 }
@@ -120,6 +140,7 @@ impl TableFilePattern for WMOPattern {
             version: Some(version),
             subcenter: None,
             center: None,
+            master_table: None,
             language: Some(language),
             is_local: false,
             filename: filename.to_string(),
@@ -169,14 +190,21 @@ impl TableFilePattern for LocalPattern {
             _ => return None,
         };
 
-        let subcenter = caps[2].parse().ok()?;
+        // The number is the originating centre; numbers past 255 are the
+        // `subcenter * 256 + center` key some distributions name files by
+        let key: u32 = caps[2].parse().ok()?;
+        let (center, subcenter) = match key {
+            0..=255 => (key, None),
+            _ => (key % 256, Some(key / 256)),
+        };
         let version = caps[3].parse().ok()?;
 
         Some(TableMetadata {
             kind,
             version: Some(version),
-            subcenter: Some(subcenter),
-            center: None,
+            subcenter,
+            center: Some(center),
+            master_table: None,
             language: None,
             is_local: true,
             filename: filename.to_string(),
@@ -188,7 +216,7 @@ impl TableFilePattern for LocalPattern {
     }
 
     fn description(&self) -> &str {
-        "Local tables (localtab[bd]_subcenter_version.csv)"
+        "Local tables (localtab[bd]_center_version.csv)"
     }
 }
 
@@ -222,6 +250,7 @@ impl TableFilePattern for OldMasterPattern {
             version: Some(version),
             subcenter: None,
             center: None,
+            master_table: None,
             is_local: false,
             language: None,
             filename: filename.to_string(),
@@ -237,6 +266,133 @@ impl TableFilePattern for OldMasterPattern {
     }
 }
 
+/// Originating centre of the Japan Meteorological Agency
+pub const JMA_CENTER: u32 = 34;
+
+/// JMA local table pattern, in the fixed-width text layout JMA publishes
+/// Examples:
+/// - JMA_LocalTableB_13.txt (centre 34, version 13)
+/// - JMA_LocalTableD_sc1_13.txt (centre 34, sub-centre 1, version 13)
+#[derive(Debug)]
+pub struct JMAPattern {
+    regex: Regex,
+}
+
+impl Default for JMAPattern {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JMAPattern {
+    pub fn new() -> Self {
+        // Pattern: JMA_LocalTable(B|D)(_sc\d+)?_(\d+)\.txt
+        let regex = Regex::new(r"(?i)^JMA_LocalTable([BD])(?:_sc(\d+))?_(\d+)\.txt$")
+            .expect("Invalid regex");
+
+        Self { regex }
+    }
+}
+
+impl TableFilePattern for JMAPattern {
+    fn matches(&self, filename: &str) -> Option<TableMetadata> {
+        let caps = self.regex.captures(filename)?;
+
+        let kind = match caps[1].to_uppercase().as_str() {
+            "B" => TableKind::B,
+            "D" => TableKind::D,
+            _ => return None,
+        };
+
+        let subcenter = match caps.get(2) {
+            Some(subcenter) => Some(subcenter.as_str().parse().ok()?),
+            None => None,
+        };
+        let version = caps[3].parse().ok()?;
+
+        Some(TableMetadata {
+            kind,
+            version: Some(version),
+            subcenter,
+            center: Some(JMA_CENTER),
+            master_table: None,
+            language: None,
+            is_local: true,
+            filename: filename.to_string(),
+        })
+    }
+
+    fn glob_pattern(&self) -> &str {
+        "JMA_LocalTable[BD]*.txt"
+    }
+
+    fn description(&self) -> &str {
+        "JMA local tables (JMA_LocalTable[BD][_sc{subcenter}]_{version}.txt)"
+    }
+}
+
+/// Originating centre of the UK Met Office (Exeter)
+pub const MET_OFFICE_CENTER: u32 = 74;
+
+/// Met Office tables from MetDB extracts, and their local sequence files,
+/// which are built into the local Table D of the same version
+/// Examples:
+/// - MetDB_TableB_13.txt (centre 74, version 13)
+/// - MetDB_LocalSeq_13.txt (centre 74, version 13, Table D)
+#[derive(Debug)]
+pub struct MetOfficePattern {
+    regex: Regex,
+}
+
+impl Default for MetOfficePattern {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MetOfficePattern {
+    pub fn new() -> Self {
+        // Pattern: MetDB_(TableB|TableD|LocalSeq)_(\d+)\.txt
+        let regex =
+            Regex::new(r"(?i)^MetDB_(TableB|TableD|LocalSeq)_(\d+)\.txt$").expect("Invalid regex");
+
+        Self { regex }
+    }
+}
+
+impl TableFilePattern for MetOfficePattern {
+    fn matches(&self, filename: &str) -> Option<TableMetadata> {
+        let caps = self.regex.captures(filename)?;
+
+        let kind = match caps[1].to_lowercase().as_str() {
+            "tableb" => TableKind::B,
+            "tabled" | "localseq" => TableKind::D,
+            _ => return None,
+        };
+
+        let version = caps[2].parse().ok()?;
+
+        Some(TableMetadata {
+            kind,
+            version: Some(version),
+            subcenter: None,
+            center: Some(MET_OFFICE_CENTER),
+            master_table: None,
+            language: None,
+            is_local: true,
+            filename: filename.to_string(),
+        })
+    }
+
+    fn glob_pattern(&self) -> &str {
+        "MetDB_*.txt"
+    }
+
+    fn description(&self) -> &str {
+        "Met Office tables (MetDB_Table[BD]_{version}.txt, MetDB_LocalSeq_{version}.txt)"
+    }
+}
+
 /// Custom pattern with flexible center/subcenter
 /// Examples:
 /// - center_7_subcenter_85_tableb_v20.csv
@@ -283,6 +439,7 @@ impl TableFilePattern for CustomPattern {
             version: Some(version),
             subcenter: Some(subcenter),
             center: Some(center),
+            master_table: None,
             language: None,
             is_local: true,
             filename: filename.to_string(),
@@ -317,6 +474,8 @@ impl TableScanner {
                 Box::new(OldMasterPattern::new()),
                 Box::new(LocalPattern::new()),
                 Box::new(CustomPattern::new()),
+                Box::new(JMAPattern::new()),
+                Box::new(MetOfficePattern::new()),
             ],
         }
     }
@@ -424,16 +583,22 @@ mod tests {
         // Valid local patterns
         let meta = pattern.matches("localtabb_85_20.csv").unwrap();
         assert_eq!(meta.kind, TableKind::B);
-        assert_eq!(meta.subcenter, Some(85));
+        assert_eq!(meta.center, Some(85));
+        assert_eq!(meta.subcenter, None);
         assert_eq!(meta.version, Some(20));
         assert!(meta.is_local);
 
         let meta = pattern.matches("localtabd_100_5.csv").unwrap();
         assert_eq!(meta.kind, TableKind::D);
-        assert_eq!(meta.subcenter, Some(100));
+        assert_eq!(meta.center, Some(100));
         assert_eq!(meta.version, Some(5));
         assert!(meta.is_local);
 
+        // Combined subcenter * 256 + center key
+        let meta = pattern.matches("localtabb_65279_4.csv").unwrap();
+        assert_eq!(meta.center, Some(255));
+        assert_eq!(meta.subcenter, Some(254));
+
         // Invalid patterns
         assert!(pattern.matches("local_table_85_20.csv").is_none());
         assert!(pattern.matches("localtabb_85.csv").is_none());
@@ -460,6 +625,44 @@ mod tests {
         assert_eq!(meta.version, Some(10));
     }
 
+    #[test]
+    fn test_jma_pattern() {
+        let pattern = JMAPattern::new();
+
+        let meta = pattern.matches("JMA_LocalTableB_13.txt").unwrap();
+        assert_eq!(meta.kind, TableKind::B);
+        assert_eq!(meta.center, Some(34));
+        assert_eq!(meta.subcenter, None);
+        assert_eq!(meta.version, Some(13));
+        assert!(meta.is_local);
+        assert_eq!(meta.output_name(), "BUFR_TableB_34_13");
+
+        let meta = pattern.matches("JMA_LocalTableD_sc1_13.txt").unwrap();
+        assert_eq!(meta.kind, TableKind::D);
+        assert_eq!(meta.subcenter, Some(1));
+        assert_eq!(meta.output_name(), "BUFR_TableD_34_1_13");
+
+        assert!(pattern.matches("JMA_LocalTableB_13.csv").is_none());
+    }
+
+    #[test]
+    fn test_met_office_pattern() {
+        let pattern = MetOfficePattern::new();
+
+        let meta = pattern.matches("MetDB_TableB_13.txt").unwrap();
+        assert_eq!(meta.kind, TableKind::B);
+        assert_eq!(meta.center, Some(74));
+        assert_eq!(meta.version, Some(13));
+        assert!(meta.is_local);
+        assert_eq!(meta.output_name(), "BUFR_TableB_74_13");
+
+        let meta = pattern.matches("MetDB_LocalSeq_13.txt").unwrap();
+        assert_eq!(meta.kind, TableKind::D);
+        assert_eq!(meta.output_name(), "BUFR_TableD_74_13");
+
+        assert!(pattern.matches("MetDB_TableC_13.txt").is_none());
+    }
+
     #[test]
     fn test_output_name_generation() {
         // WMO table (no subcenter) - Format: BUFR_TableB_{version}
@@ -468,6 +671,7 @@ mod tests {
             version: Some(14),
             subcenter: None,
             center: None,
+            master_table: None,
             language: Some("en".to_string()),
             is_local: false,
             filename: "BUFRCREX_TableB_en_14.csv".to_string(),
@@ -480,30 +684,46 @@ mod tests {
             version: Some(40),
             subcenter: None,
             center: None,
+            master_table: None,
             language: Some("en".to_string()),
             is_local: false,
             filename: "BUFR_TableD_en_40.csv".to_string(),
         };
         assert_eq!(meta.output_name(), "BUFR_TableD_40");
 
-        // Local table with subcenter - Format: BUFR_TableB_{subcenter}_{version}
+        // Oceanographic master table - Format: BUFR_TableB_mt{master_table}_{version}
         let meta = TableMetadata {
             kind: TableKind::B,
             version: Some(14),
-            subcenter: Some(1),
+            subcenter: None,
             center: None,
+            master_table: Some(10),
+            language: Some("en".to_string()),
+            is_local: false,
+            filename: "BUFRCREX_TableB_en_14.csv".to_string(),
+        };
+        assert_eq!(meta.output_name(), "BUFR_TableB_mt10_14");
+
+        // Local table of a sub-centre - Format: BUFR_TableB_{center}_{subcenter}_{version}
+        let meta = TableMetadata {
+            kind: TableKind::B,
+            version: Some(14),
+            subcenter: Some(4),
+            center: Some(7),
+            master_table: None,
             language: None,
             is_local: true,
-            filename: "localtabb_1_14.csv".to_string(),
+            filename: "test_c7_sc4_tableb_v14.csv".to_string(),
         };
-        assert_eq!(meta.output_name(), "BUFR_TableB_1_14");
+        assert_eq!(meta.output_name(), "BUFR_TableB_7_4_14");
 
-        // Local table with larger subcenter number
+        // Local table of a whole centre - Format: BUFR_TableB_{center}_{version}
         let meta = TableMetadata {
             kind: TableKind::B,
             version: Some(20),
-            subcenter: Some(85),
-            center: None,
+            subcenter: None,
+            center: Some(85),
+            master_table: None,
             language: None,
             is_local: true,
             filename: "localtabb_85_20.csv".to_string(),
@@ -0,0 +1,141 @@
+//! Tables compiled into the binary, as emitted by `gen-ctl codegen`
+//!
+//! Entries are kept sorted by descriptor and found by binary search, so
+//! looking one up needs no file, memory map or allocation. Decoding needs
+//! the tables `Decoder::new` takes: `to_table` builds one from the entries,
+//! allocating its archive and hash function in memory.
+use super::prelude::{BUFRTableB, BUFRTableD};
+use super::tables::{BTableEntry, DTableEntry};
+use super::{BUFRKey, BuildMetadata, FXY};
+
+/// A Table B entry of a compiled-in table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StaticBEntry {
+    pub fxy: FXY,
+    pub element_name: &'static str,
+    pub unit: &'static str,
+    pub scale: i32,
+    pub reference: i32,
+    pub width_bits: u32,
+}
+
+/// A Table D entry of a compiled-in table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StaticDEntry {
+    pub fxy: FXY,
+    pub title: Option<&'static str>,
+    pub chain: &'static [FXY],
+}
+
+pub trait StaticEntry {
+    fn fxy(&self) -> FXY;
+}
+
+impl StaticEntry for StaticBEntry {
+    fn fxy(&self) -> FXY {
+        self.fxy
+    }
+}
+
+impl StaticEntry for StaticDEntry {
+    fn fxy(&self) -> FXY {
+        self.fxy
+    }
+}
+
+/// Entries sorted by descriptor
+#[derive(Debug, Clone, Copy)]
+pub struct StaticTable<E: 'static> {
+    entries: &'static [E],
+}
+
+impl<E: StaticEntry> StaticTable<E> {
+    /// `entries` must be sorted by F, then X, then Y, as `gen-ctl codegen`
+    /// writes them
+    pub const fn new(entries: &'static [E]) -> Self {
+        StaticTable { entries }
+    }
+
+    pub fn lookup<K: BUFRKey>(&self, fxy: &K) -> Option<&'static E> {
+        let key = (fxy.f(), fxy.x(), fxy.y());
+        let entries = self.entries;
+        entries
+            .binary_search_by_key(&key, |entry| {
+                let fxy = entry.fxy();
+                (fxy.f, fxy.x, fxy.y)
+            })
+            .ok()
+            .map(|index| &entries[index])
+    }
+
+    pub fn entries(&self) -> &'static [E] {
+        self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// How a table compiled in with `gen-ctl codegen` was built
+fn static_metadata(entry_count: usize) -> BuildMetadata {
+    BuildMetadata {
+        converter: "gen-ctl codegen".to_string(),
+        converter_version: env!("CARGO_PKG_VERSION").to_string(),
+        entry_count: entry_count as u64,
+        ..BuildMetadata::default()
+    }
+}
+
+impl StaticTable<StaticBEntry> {
+    /// The Table B to decode with
+    pub fn to_table(&self) -> anyhow::Result<BUFRTableB> {
+        let entries = self
+            .entries
+            .iter()
+            .map(|entry| BTableEntry {
+                fxy: entry.fxy,
+                class_name_en: String::new(),
+                element_name_en: entry.element_name.to_string(),
+                bufr_unit: entry.unit.to_string(),
+                bufr_scale: entry.scale,
+                bufr_reference_value: entry.reference,
+                bufr_datawidth_bits: entry.width_bits,
+                note_en: None,
+                note_ids: None,
+                status: None,
+                bufr_unit_original: None,
+                crex_unit: None,
+                crex_scale: None,
+                crex_datawidth_char: None,
+            })
+            .collect();
+        BUFRTableB::from_entries(entries, static_metadata(self.len()))
+    }
+}
+
+impl StaticTable<StaticDEntry> {
+    /// The Table D to decode with
+    pub fn to_table(&self) -> anyhow::Result<BUFRTableD> {
+        let entries = self
+            .entries
+            .iter()
+            .map(|entry| DTableEntry {
+                fxy: entry.fxy,
+                fxy_chain: entry.chain.to_vec(),
+                category: None,
+                category_of_sequences_en: None,
+                title_en: entry.title.map(str::to_string),
+                subtitle_en: None,
+                note_en: None,
+                note_ids: None,
+                status: None,
+            })
+            .collect();
+        BUFRTableD::from_entries(entries, static_metadata(self.len()))
+    }
+}
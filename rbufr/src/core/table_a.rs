@@ -0,0 +1,46 @@
+//! Table A: the data category of Section 1
+
+/// Every data category of Table A with its name; 240 to 254 are left for
+/// experimental use
+pub const TABLE_A: &[(u8, &str)] = &[
+    (0, "Surface data - land"),
+    (1, "Surface data - sea"),
+    (2, "Vertical soundings (other than satellite)"),
+    (3, "Vertical soundings (satellite)"),
+    (4, "Single level upper-air data (other than satellite)"),
+    (5, "Single level upper-air data (satellite)"),
+    (6, "Radar data"),
+    (7, "Synoptic features"),
+    (8, "Physical/chemical constituents"),
+    (9, "Dispersal and transport"),
+    (10, "Radiological data"),
+    (11, "BUFR tables, complete replacement or update"),
+    (12, "Surface data (satellite)"),
+    (13, "Forecasts"),
+    (14, "Warnings"),
+    (20, "Status information"),
+    (21, "Radiances (satellite measured)"),
+    (22, "Radar (satellite) but not altimeter and scatterometer"),
+    (23, "Lidar (satellite)"),
+    (24, "Scatterometry (satellite)"),
+    (25, "Altimetry (satellite)"),
+    (26, "Spectrometry (satellite)"),
+    (27, "Gravity measurement (satellite)"),
+    (28, "Precision orbit (satellite)"),
+    (29, "Space environment (satellite)"),
+    (30, "Calibration datasets (satellite)"),
+    (31, "Oceanographic data"),
+    (101, "Image data (satellite)"),
+    (255, "Indicator for local use, with sub-category"),
+];
+
+/// The name of data category `category`, `None` for reserved ones
+pub fn category_name(category: u8) -> Option<&'static str> {
+    match category {
+        240..=254 => Some("For experimental use"),
+        _ => TABLE_A
+            .iter()
+            .find(|(c, _)| *c == category)
+            .map(|(_, name)| *name),
+    }
+}
@@ -0,0 +1,140 @@
+//! Table C: the data description operators (F = 2)
+use super::BUFRKey;
+
+use OperatorY::{Any, NonZero, Zero, ZeroOr255};
+
+/// Y values an operator may be used with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperatorY {
+    /// Any Y; for most, 0 cancels the operator
+    Any,
+    /// Any Y but 0, for operators with nothing to cancel
+    NonZero,
+    /// Only Y = 0
+    Zero,
+    /// Y = 0, or Y = 255 for a marker or cancellation
+    ZeroOr255,
+}
+
+/// One Table C entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OperatorInfo {
+    pub x: i32,
+    pub name: &'static str,
+    /// Whether the operator makes bits be read from the data section that
+    /// no Table B element accounts for: characters, associated fields, new
+    /// reference values, or the values behind a 255 marker
+    pub consumes_bits: bool,
+    /// Whether Y = 255 cancels the operator, rather than being a marker or
+    /// an ordinary Y
+    pub y255_cancels: bool,
+    pub y: OperatorY,
+}
+
+impl OperatorInfo {
+    const fn new(
+        x: i32,
+        name: &'static str,
+        consumes_bits: bool,
+        y255_cancels: bool,
+        y: OperatorY,
+    ) -> Self {
+        OperatorInfo {
+            x,
+            name,
+            consumes_bits,
+            y255_cancels,
+            y,
+        }
+    }
+
+    /// Whether `y` is a valid Y for this operator
+    pub fn accepts(&self, y: i32) -> bool {
+        match self.y {
+            OperatorY::Any => (0..=255).contains(&y),
+            OperatorY::NonZero => (1..=255).contains(&y),
+            OperatorY::Zero => y == 0,
+            OperatorY::ZeroOr255 => y == 0 || y == 255,
+        }
+    }
+}
+
+/// Every operator of Table C, by X
+pub const TABLE_C: &[OperatorInfo] = &[
+    OperatorInfo::new(1, "Change data width", false, false, Any),
+    OperatorInfo::new(2, "Change scale", false, false, Any),
+    // 2-03-255 ends the definition of new reference values
+    OperatorInfo::new(3, "Change reference values", true, false, Any),
+    OperatorInfo::new(4, "Add associated field", true, false, Any),
+    OperatorInfo::new(5, "Signify character", true, false, NonZero),
+    OperatorInfo::new(6, "Signify data width", false, false, Any),
+    OperatorInfo::new(
+        7,
+        "Increase scale, reference value and data width",
+        false,
+        false,
+        Any,
+    ),
+    OperatorInfo::new(8, "Change width of CCITT IA5 field", false, false, Any),
+    OperatorInfo::new(9, "IEEE floating point representation", false, false, Any),
+    OperatorInfo::new(21, "Data not present", false, false, Any),
+    OperatorInfo::new(22, "Quality information follows", false, false, Zero),
+    OperatorInfo::new(23, "Substituted values operator", true, false, ZeroOr255),
+    OperatorInfo::new(
+        24,
+        "First-order statistical values follow",
+        true,
+        false,
+        ZeroOr255,
+    ),
+    OperatorInfo::new(
+        25,
+        "Difference statistical values follow",
+        true,
+        false,
+        ZeroOr255,
+    ),
+    OperatorInfo::new(
+        32,
+        "Replaced/retained values follow",
+        true,
+        false,
+        ZeroOr255,
+    ),
+    OperatorInfo::new(35, "Cancel backward data reference", false, false, Zero),
+    OperatorInfo::new(36, "Define data present bit-map", false, false, Zero),
+    OperatorInfo::new(
+        37,
+        "Use defined data present bit-map",
+        false,
+        true,
+        ZeroOr255,
+    ),
+    OperatorInfo::new(41, "Define event", false, true, ZeroOr255),
+    OperatorInfo::new(42, "Define conditioning event", false, true, ZeroOr255),
+    OperatorInfo::new(
+        43,
+        "Categorical forecast values follow",
+        false,
+        true,
+        ZeroOr255,
+    ),
+];
+
+/// The Table C entry of operator 2-`x`-YYY
+pub fn lookup(x: i32) -> Option<&'static OperatorInfo> {
+    TABLE_C.iter().find(|op| op.x == x)
+}
+
+/// The entry of `operator`, or why it is not a valid operator
+pub fn check<K: BUFRKey>(operator: &K) -> Result<&'static OperatorInfo, String> {
+    let (x, y) = (operator.x(), operator.y());
+    let info = lookup(x).ok_or_else(|| format!("Unknown operator 2-{:02}-{:03}", x, y))?;
+    if !info.accepts(y) {
+        return Err(format!(
+            "Operator 2-{:02}-{:03} ({}) does not take Y = {}",
+            x, y, info.name, y
+        ));
+    }
+    Ok(info)
+}
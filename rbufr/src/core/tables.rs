@@ -1,4 +1,4 @@
-use super::FXY;
+use super::{ArchivedFXY, FXY};
 use rkyv::Archive;
 use rkyv::api::high::{HighDeserializer, HighSerializer, HighValidator};
 use rkyv::bytecheck::CheckBytes;
@@ -47,6 +47,12 @@ pub trait TableEntry:
     >
 {
     fn fxy(&self) -> FXY;
+
+    /// Descriptor of the entry as stored in a table
+    fn archived_fxy(archived: &<Self as Archive>::Archived) -> &ArchivedFXY;
+
+    /// Bring the entry to canonical form before it is written to a table
+    fn normalize(&mut self) {}
 }
 
 pub trait TableEntryFull: TableEntry {
@@ -83,6 +89,47 @@ pub struct BTableEntry {
     pub note_en: Option<String>,
     pub note_ids: Option<String>,
     pub status: Option<String>,
+    /// The unit as the source table spelled it, when `bufr_unit` is its
+    /// canonical form
+    #[serde(default)]
+    pub bufr_unit_original: Option<String>,
+    #[serde(default)]
+    pub crex_unit: Option<String>,
+    #[serde(default)]
+    pub crex_scale: Option<i32>,
+    #[serde(default)]
+    pub crex_datawidth_char: Option<u32>,
+}
+
+/// Canonical unit of code table elements
+pub const UNIT_CODE_TABLE: &str = "Code table";
+/// Canonical unit of flag table elements
+pub const UNIT_FLAG_TABLE: &str = "Flag table";
+/// Canonical unit of character elements
+pub const UNIT_CCITT_IA5: &str = "CCITT IA5";
+
+/// The canonical spelling of `unit`, as WMO writes it: "CODE TABLE",
+/// "code-table" and "Code tables" all become "Code table", "degree" and
+/// "DEG" become "deg", and so on. Units without a canonical form are only
+/// trimmed.
+pub fn normalize_unit(unit: &str) -> String {
+    let key = unit
+        .split(|c: char| c.is_whitespace() || c == '-' || c == '_')
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase();
+
+    match key.as_str() {
+        "code table" | "code tables" | "codetable" => UNIT_CODE_TABLE,
+        "flag table" | "flag tables" | "flagtable" => UNIT_FLAG_TABLE,
+        "ccitt ia5" | "ccittia5" | "character" => UNIT_CCITT_IA5,
+        "numeric" => "Numeric",
+        "deg" | "degree" | "degrees" => "deg",
+        "degree true" | "degrees true" | "deg true" => "Degree true",
+        _ => unit.trim(),
+    }
+    .to_string()
 }
 
 impl BTableEntry {
@@ -125,6 +172,22 @@ impl BTableEntry {
     pub fn status(&self) -> Option<&str> {
         self.status.as_deref()
     }
+
+    pub fn bufr_unit_original(&self) -> Option<&str> {
+        self.bufr_unit_original.as_deref()
+    }
+
+    pub fn crex_unit(&self) -> Option<&str> {
+        self.crex_unit.as_deref()
+    }
+
+    pub fn crex_scale(&self) -> Option<i32> {
+        self.crex_scale
+    }
+
+    pub fn crex_datawidth_char(&self) -> Option<u32> {
+        self.crex_datawidth_char
+    }
 }
 
 impl Display for BTableEntry {
@@ -332,16 +395,36 @@ impl TableEntry for BitMapEntry {
     fn fxy(&self) -> FXY {
         self.fxy
     }
+
+    fn archived_fxy(archived: &<Self as Archive>::Archived) -> &ArchivedFXY {
+        &archived.fxy
+    }
 }
 
 impl TableEntry for DTableEntry {
     fn fxy(&self) -> FXY {
         self.fxy
     }
+
+    fn archived_fxy(archived: &<Self as Archive>::Archived) -> &ArchivedFXY {
+        &archived.fxy
+    }
 }
 
 impl TableEntry for BTableEntry {
     fn fxy(&self) -> FXY {
         self.fxy
     }
+
+    fn archived_fxy(archived: &<Self as Archive>::Archived) -> &ArchivedFXY {
+        &archived.fxy
+    }
+
+    fn normalize(&mut self) {
+        let unit = normalize_unit(&self.bufr_unit);
+        if unit != self.bufr_unit {
+            let original = std::mem::replace(&mut self.bufr_unit, unit);
+            self.bufr_unit_original.get_or_insert(original);
+        }
+    }
 }
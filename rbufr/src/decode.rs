@@ -0,0 +1,106 @@
+//! Parse and decode in one call, with default decoder options
+use std::collections::hash_map::Entry;
+use std::path::Path;
+
+use rustc_hash::FxHashMap;
+
+use crate::block::{BUFRFile, MessageBlock};
+use crate::decoder::{BUFRParsed, Decoder};
+use crate::errors::Result;
+use crate::parser::{parse, parse_with_filter};
+use crate::structs::versions::{MessageVersion, TableInfo};
+
+/// Decode every message of the file at `path`, gzipped or not
+///
+/// Decoders are built from each message's Section 1 and shared between
+/// messages asking for the same tables. The first message that fails to
+/// decode fails the call.
+pub fn decode_file<P: AsRef<Path>>(path: P) -> Result<Vec<BUFRParsed<'static>>> {
+    decode_all(&parse_with_filter(path, |_| true)?)
+}
+
+/// Like `decode_file`, for a file already in memory
+pub fn decode_bytes(data: &[u8]) -> Result<Vec<BUFRParsed<'static>>> {
+    decode_all(&parse(data)?)
+}
+
+fn decode_all(file: &BUFRFile) -> Result<Vec<BUFRParsed<'static>>> {
+    file.decode_iter().collect()
+}
+
+/// Messages of a `BUFRFile`, decoded one at a time as they are iterated;
+/// see `BUFRFile::decode_iter`
+pub struct DecodeIter<'f> {
+    messages: std::slice::Iter<'f, MessageBlock>,
+    decoders: Decoders,
+}
+
+impl<'f> DecodeIter<'f> {
+    pub(crate) fn new(messages: &'f [MessageBlock]) -> Self {
+        DecodeIter {
+            messages: messages.iter(),
+            decoders: Decoders::default(),
+        }
+    }
+}
+
+impl Iterator for DecodeIter<'_> {
+    type Item = Result<BUFRParsed<'static>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let message = self.messages.next()?;
+        Some(
+            self.decoders
+                .get(message)
+                .and_then(|decoder| decoder.decode(message).map(BUFRParsed::into_static)),
+        )
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.messages.size_hint()
+    }
+}
+
+/// Decode `messages` across rayon's thread pool, in their original order
+#[cfg(feature = "rayon")]
+pub(crate) fn decode_parallel(messages: &[MessageBlock]) -> Vec<Result<BUFRParsed<'static>>> {
+    use rayon::prelude::*;
+
+    // Tables are loaded up front, once per set, then shared by every thread
+    let mut decoders = Decoders::default();
+    let built: Vec<Result<()>> = messages
+        .iter()
+        .map(|message| decoders.get(message).map(|_| ()))
+        .collect();
+
+    messages
+        .par_iter()
+        .zip(built)
+        .map(|(message, built)| {
+            built?;
+            let decoder = &decoders.decoders[&Decoders::key(message)];
+            Ok(decoder.decode(message)?.into_static())
+        })
+        .collect()
+}
+
+/// Default decoders, one per edition and set of tables, built as messages
+/// asking for them come
+#[derive(Default)]
+pub struct Decoders {
+    decoders: FxHashMap<(u8, TableInfo), Decoder>,
+}
+
+impl Decoders {
+    fn key(message: &MessageBlock) -> (u8, TableInfo) {
+        (message.version(), message.table_info())
+    }
+
+    /// The decoder for `message`, built the first time its tables are seen
+    pub fn get(&mut self, message: &MessageBlock) -> Result<&Decoder> {
+        Ok(match self.decoders.entry(Self::key(message)) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(Decoder::from_message(message)?),
+        })
+    }
+}
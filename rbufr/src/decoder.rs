@@ -1,19 +1,75 @@
+mod associated;
+mod builder;
+mod compressed;
+mod descriptor_tree;
+mod expand;
+mod missing;
+mod provenance;
+mod quality;
+mod storage;
+mod trace;
+mod tree;
+mod validate;
+mod visitor;
+
+pub use associated::{ASSOCIATED_FIELD_SIGNIFICANCE, associated_field_name};
+pub use builder::{DecoderBuilder, MissingPolicy, Mode, Output, Units};
+pub use descriptor_tree::{DescriptorNode, DescriptorTree};
+pub use expand::{ExpandedDescriptor, ExpandedKind};
+pub use missing::{MISS_VAL, MISS_VAL_F32, MissingMask, MissingValues, is_missing};
+pub use provenance::{Provenance, TableSource};
+pub use quality::{QualityKind, QualityLink};
+pub use trace::TraceEvent;
+pub use tree::{DecodedTree, Node};
+pub use validate::Validation;
+pub use visitor::DecodeVisitor;
+
 #[cfg(feature = "opera")]
 use crate::core::tables::ArchivedBitMapEntry;
 use crate::core::{
     ArchivedFXY, BUFRKey, FXY,
     prelude::{BUFRTableB, BUFRTableBitMap, BUFRTableD},
-    tables::{ArchivedBTableEntry, ArchivedDTableEntry},
+    table_c,
+    tables::{
+        ArchivedBTableEntry, ArchivedDTableEntry, UNIT_CCITT_IA5, UNIT_CODE_TABLE, UNIT_FLAG_TABLE,
+    },
 };
 use crate::{
     block::MessageBlock,
     errors::{Error, Result},
+    keys::eccodes_key,
     structs::versions::MessageVersion,
-    tables::{LocalTable, TableLoader},
 };
-use std::{borrow::Cow, fmt::Display, ops::Deref};
+use builder::Preferred;
+use compressed::{Compressed, compressed_subsets};
+use regex::Regex;
+use rustc_hash::FxHashMap;
+use std::{
+    borrow::Cow,
+    fmt::Display,
+    ops::Deref,
+    ops::Range,
+    sync::{Arc, RwLock},
+};
+use storage::{Entry, Stored};
+use tree::TreeBuilder;
+
+/// Widest field stored as `f32`; its 24-bit mantissa holds any such raw value exactly
+const F32_MAX_WIDTH: u32 = 24;
 
-const MISS_VAL: f64 = 99999.999999;
+/// Name of the records 2-05-YYY character literals are decoded to
+pub const SIGNIFY_CHARACTER_NAME: &str = "ASSOCIATED SIGNIFY CHARACTER";
+
+/// Name of the marker records of delayed replications with a count of 0
+pub const EMPTY_REPLICATION_NAME: &str = "EMPTY REPLICATION";
+
+/// Names of the `BUFRData::Group` records of `Decoder::with_group_markers`
+pub const GROUP_BEGIN_NAME: &str = "REPLICATION BEGIN";
+pub const GROUP_END_NAME: &str = "REPLICATION END";
+
+/// Name of the placeholder records of elements in no Table B, skipped
+/// thanks to a 2-06-YYY width
+pub const UNKNOWN_ELEMENT_NAME: &str = "UNKNOWN LOCAL ELEMENT";
 
 pub struct Decoder {
     #[allow(unused)]
@@ -26,6 +82,23 @@ pub struct Decoder {
     // opera
     #[cfg(feature = "opera")]
     opera_bitmap_table: Option<BUFRTableBitMap>,
+    // store compiled arrays as f32 where the field width allows
+    f32_arrays: bool,
+    // mark where each replication iteration begins and ends in flat output
+    group_markers: bool,
+    // drop the padding after CCITT IA5 values
+    trim_text: bool,
+    // subsets located outside are dropped by decode_subsets
+    bbox: Option<BoundingBox>,
+    mode: Mode,
+    missing: MissingPolicy,
+    missing_values: MissingValues,
+    units: Units,
+    output: Output,
+    subsets: Option<Range<usize>>,
+    provenance: Provenance,
+    // compiled replication layouts, shared by every message this decoder sees
+    layouts: RwLock<FxHashMap<LayoutKey, Option<Arc<CompiledLayout>>>>,
 }
 
 struct Cache<'a> {
@@ -68,28 +141,22 @@ impl<'a> Cache<'a> {
 
     #[inline]
     fn lookup_local_b_descriptor<K: BUFRKey>(&self, fxy: &K) -> Option<&'a ArchivedBTableEntry> {
-        self.local_b
-            .as_ref()
-            .and_then(|t| t.lookup(fxy))
-            .filter(|e| &e.fxy == fxy)
+        self.local_b.as_ref().and_then(|t| t.lookup(fxy))
     }
 
     #[inline]
     fn lookup_master_b_descriptor<K: BUFRKey>(&self, fxy: &K) -> Option<&'a ArchivedBTableEntry> {
-        self.master_b.lookup(fxy).filter(|e| &e.fxy == fxy)
+        self.master_b.lookup(fxy)
     }
 
     #[inline]
     fn lookup_master_d_descriptor<K: BUFRKey>(&self, fxy: &K) -> Option<&'a ArchivedDTableEntry> {
-        self.master_d.lookup(fxy).filter(|e| &e.fxy == fxy)
+        self.master_d.lookup(fxy)
     }
 
     #[inline]
     fn lookup_local_d_descriptor<K: BUFRKey>(&self, fxy: &K) -> Option<&'a ArchivedDTableEntry> {
-        self.local_d
-            .as_ref()
-            .and_then(|t| t.lookup(fxy))
-            .filter(|e| &e.fxy == fxy)
+        self.local_d.as_ref().and_then(|t| t.lookup(fxy))
     }
 
     #[inline(always)]
@@ -109,47 +176,186 @@ struct State {
     local_data_width: Option<i32>,
     // Temporary storage
     temp_operator: Option<i32>,
+    // Associated fields (2-04), innermost last, and their 0-31-021 significance
+    associated_widths: Vec<i32>,
+    associated_significance: Option<u32>,
+    // Data present bit-maps and what they link
+    back_reference: quality::BackReference,
+    // Subsets of compressed data, each element read once for all of them
+    compressed: Option<usize>,
+    scratch: compressed::Scratch,
 }
 
 /// Pre-compiled metadata for one field in the array body
 #[derive(Debug, Clone)]
-struct FieldSpec<'a> {
-    /// Original FXY for debugging/output
+struct FieldSpec {
+    /// Original FXY, also used to look up name and unit on output
     fxy: FXY,
-    /// Name from Table B
-    name: &'a str,
-    /// Unit from Table B
-    unit: &'a str,
     /// Effective bit width (after operators applied)
     width_bits: u32,
     /// Effective scale (after operators applied)
     scale: i32,
     /// Effective reference value (after operators applied)
     reference: i32,
+    /// `10^-scale`, precomputed for the hot loop
+    factor: f64,
     /// Missing value for this field (all bits set for this width)
     missing_value: u64,
+    /// Byte width of a CCITT IA5 field, `None` for numeric fields
+    string_bytes: Option<usize>,
+}
+
+impl FieldSpec {
+    fn encoding(&self) -> Encoding {
+        Encoding {
+            scale: self.scale,
+            reference: self.reference,
+            width_bits: self.width_bits,
+            raw: None,
+        }
+    }
+
+    /// Scaled value, `None` when missing
+    #[inline(always)]
+    fn to_value(&self, raw_value: u64) -> Option<f64> {
+        // Check for missing value (skip 0-31-YYY delayed replication counts)
+        if raw_value == self.missing_value && !(self.fxy.f == 0 && self.fxy.x == 31) {
+            None
+        } else {
+            // Apply scale and reference
+            Some(((raw_value as f64) + (self.reference as f64)) * self.factor)
+        }
+    }
+}
+
+/// Bytes a CCITT IA5 element takes, `str_width` if 2-08-YYY set one
+fn string_bytes(str_width: Option<usize>, e: &ArchivedBTableEntry) -> usize {
+    str_width.unwrap_or((e.bufr_datawidth_bits.to_native() as usize).div_ceil(8))
+}
+
+/// The missing value of a `width`-bit field, every bit set; `width` is at
+/// most 64
+fn all_ones(width: u32) -> u64 {
+    u64::MAX.checked_shr(64 - width).unwrap_or(0)
+}
+
+/// Hand the values of one element across the subsets of compressed data
+/// to `values`: numbers as an array, CCITT IA5 fields as a repeat
+fn push_compressed<'a, C: Container<'a>>(
+    values: &mut C,
+    compressed: Compressed<'_, 'a>,
+    fxy: FXY,
+    encoding: Encoding,
+    e: &'a ArchivedBTableEntry,
+) {
+    let name = e.element_name_en.as_str();
+    let unit = e.bufr_unit.as_str();
+    match compressed {
+        Compressed::Numbers(numbers, missing) => {
+            values.push_array(numbers, missing, fxy, encoding, name, unit)
+        }
+        Compressed::Texts(texts) => values.push_repeat(texts, fxy, encoding, name, unit),
+    }
+}
+
+/// A delayed replication count of compressed data, the same in every subset
+fn compressed_count(
+    state: &State,
+    data: &mut BitInput,
+    e: &ArchivedBTableEntry,
+    subsets: usize,
+) -> Result<usize> {
+    let mut raw = vec![];
+    compressed::take_raw(data, state.datawidth(e), subsets, true, &mut raw)?;
+    match raw.first().copied().flatten() {
+        Some(count) if raw.iter().all(|r| *r == Some(count)) => Ok(count as usize),
+        Some(_) => Err(data.error("Delayed replication counts differ between subsets")),
+        None => Ok(0),
+    }
+}
+
+/// How a value was packed in the data section
+#[derive(Debug, Clone, Copy)]
+struct Encoding {
+    scale: i32,
+    reference: i32,
+    width_bits: u32,
+    /// Unscaled integer, only kept for single numeric values
+    raw: Option<u64>,
+}
+
+/// Per-field output of a compiled array
+enum Column<'a> {
+    F64(Vec<f64>, MissingMask<'static>),
+    F32(Vec<f32>, MissingMask<'static>),
+    Strings(Vec<Value<'a>>),
+}
+
+impl Column<'_> {
+    #[inline(always)]
+    fn push(&mut self, value: Option<f64>) {
+        match self {
+            Column::F64(values, missing) => {
+                values.push(value.unwrap_or(MISS_VAL));
+                missing.push(value.is_none());
+            }
+            Column::F32(values, missing) => {
+                values.push(value.map_or(MISS_VAL_F32, |v| v as f32));
+                missing.push(value.is_none());
+            }
+            Column::Strings(_) => unreachable!("numeric value pushed to a string field"),
+        }
+    }
 }
 
 /// Compiled layout for one array repetition
 #[derive(Debug, Clone)]
-struct CompiledLayout<'a> {
-    fields: Vec<FieldSpec<'a>>,
-    #[allow(unused)]
+struct CompiledLayout {
+    fields: Vec<FieldSpec>,
     bits_per_element: usize,
+    /// Operator state after the body, carried over to the descriptors that follow
+    end_state: CompilerState,
 }
 
-#[derive(Debug)]
+/// Cache key for a compiled layout: the replication body and the operator
+/// state it was compiled under
+#[derive(PartialEq, Eq, Hash)]
+struct LayoutKey {
+    body: Vec<FXY>,
+    state: CompilerState,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 struct CompilerState {
     common_scale: Option<i32>,
     common_ref_value: Option<i32>,
     common_data_width: Option<i32>,
     temp_operator: Option<i32>,
-    #[allow(unused)]
     common_str_width: Option<usize>,
     local_data_width: Option<i32>,
 }
 
 impl State {
+    fn compiler_state(&self) -> CompilerState {
+        CompilerState {
+            common_scale: self.common_scale,
+            common_ref_value: self.common_ref_value,
+            common_data_width: self.common_data_width,
+            temp_operator: self.temp_operator,
+            common_str_width: self.common_str_width,
+            local_data_width: self.local_data_width,
+        }
+    }
+
+    fn apply_compiler_state(&mut self, state: &CompilerState) {
+        self.common_scale = state.common_scale;
+        self.common_ref_value = state.common_ref_value;
+        self.common_data_width = state.common_data_width;
+        self.temp_operator = state.temp_operator;
+        self.common_str_width = state.common_str_width;
+        self.local_data_width = state.local_data_width;
+    }
+
     fn new() -> Self {
         Self {
             common_scale: None,
@@ -158,16 +364,79 @@ impl State {
             common_str_width: None,
             local_data_width: None,
             temp_operator: None,
+            associated_widths: vec![],
+            associated_significance: None,
+            back_reference: quality::BackReference::default(),
+            compressed: None,
+            scratch: compressed::Scratch::default(),
+        }
+    }
+
+    /// Take in an operator (F = 2) that only changes how later elements are
+    /// read; 2-05-YYY, which carries data of its own, is left to the caller
+    fn apply_operator(&mut self, x: i32, y: i32) {
+        match x {
+            1 => match y {
+                0 => {
+                    self.common_data_width = None;
+                }
+                _ => {
+                    self.common_data_width = Some(y);
+                }
+            },
+            2 => match y {
+                0 => {
+                    self.common_scale = None;
+                }
+                _ => {
+                    self.common_scale = Some(y);
+                }
+            },
+            3 => match y {
+                0 => {
+                    self.common_ref_value = None;
+                }
+                _ => {
+                    self.common_ref_value = Some(y);
+                }
+            },
+            4 => match y {
+                0 => {
+                    self.associated_widths.pop();
+                    if self.associated_widths.is_empty() {
+                        self.associated_significance = None;
+                    }
+                }
+                _ => {
+                    self.associated_widths.push(y);
+                }
+            },
+            6 => {
+                let localized_width = y;
+                self.local_data_width = Some(localized_width);
+            }
+            7 => {
+                self.temp_operator = Some(y);
+            }
+            8 => match y {
+                0 => {
+                    self.common_str_width = None;
+                }
+                _ => {
+                    self.common_str_width = Some(y as usize);
+                }
+            },
+            22 | 23 | 24 | 25 | 32 | 35 | 36 | 37 => {
+                self.back_reference.operator(x, y);
+            }
+            _ => {}
         }
     }
 
     #[inline(always)]
     fn no_change(&self, e: &ArchivedBTableEntry) -> bool {
         let unit = e.bufr_unit.as_str();
-        let is_flag_or_code = matches!(
-            unit,
-            "flag table" | "flag-table" | "code table" | "code-table"
-        );
+        let is_flag_or_code = matches!(unit, UNIT_FLAG_TABLE | UNIT_CODE_TABLE);
         let delay_repeat_count = e.fxy.f.to_native() == 0 && e.fxy.x.to_native() == 31;
 
         is_flag_or_code || delay_repeat_count
@@ -184,17 +453,15 @@ impl State {
         } else {
             self.common_data_width
                 .map(|c| {
-                    let (v, _) = e
-                        .bufr_datawidth_bits
+                    e.bufr_datawidth_bits
                         .to_native()
-                        .overflowing_add_signed(c - 128);
-                    v
+                        .saturating_add_signed(c - 128)
                 })
                 .unwrap_or(e.bufr_datawidth_bits.to_native())
         };
 
         if let Some(op) = self.temp_operator {
-            v + (10 * op) as u32
+            v.saturating_add_signed(op.saturating_mul(10))
         } else {
             v
         }
@@ -206,15 +473,12 @@ impl State {
             e.bufr_scale.to_native()
         } else {
             self.common_scale
-                .map(|c| {
-                    let (v, _) = e.bufr_scale.to_native().overflowing_add(128 - c);
-                    v
-                })
+                .map(|c| e.bufr_scale.to_native().saturating_add(128 - c))
                 .unwrap_or(e.bufr_scale.to_native())
         };
 
         if let Some(op) = self.temp_operator {
-            e.bufr_scale.to_native() + op
+            e.bufr_scale.to_native().saturating_add(op)
         } else {
             v
         }
@@ -233,58 +497,10 @@ impl State {
 }
 
 impl Decoder {
+    /// Decoder with the tables `message` asks for and default options;
+    /// shorthand for `Decoder::builder().message(message).build()`
     pub fn from_message(message: &MessageBlock) -> Result<Self> {
-        let table_info = message.table_info();
-        let master_table_version = table_info.master_table_version;
-
-        let master_b: BUFRTableB = message.load_first_validable_table(master_table_version)?;
-        let master_d: BUFRTableD = message.load_first_validable_table(master_table_version)?;
-
-        let local_table_version = table_info.local_table_version as u32;
-
-        let local_tables = if local_table_version > 0 {
-            let local_b: BUFRTableB = TableLoader.load_table(LocalTable::new(
-                Some(table_info.subcenter_id * 256 + table_info.center_id),
-                table_info.local_table_version,
-            ))?;
-
-            let local_d: BUFRTableD = TableLoader.load_table(LocalTable::new(
-                Some(table_info.subcenter_id * 256 + table_info.center_id),
-                table_info.local_table_version,
-            ))?;
-
-            Some((local_b, local_d))
-        } else {
-            None
-        };
-
-        let (local_b, local_d) = if let Some((b, d)) = local_tables {
-            (Some(b), Some(d))
-        } else {
-            (None, None)
-        };
-
-        #[cfg(feature = "opera")]
-        let opera_bitmap_table = message
-            .load_opera_bitmap_table(
-                table_info.center_id,
-                table_info.subcenter_id,
-                table_info.local_table_version,
-                master_table_version,
-            )
-            .ok();
-
-        let decoder = Self::new(
-            message.version(),
-            master_b,
-            master_d,
-            local_b,
-            local_d,
-            #[cfg(feature = "opera")]
-            opera_bitmap_table,
-        );
-
-        Ok(decoder)
+        Self::builder().message(message).build()
     }
 
     pub fn new(
@@ -296,6 +512,11 @@ impl Decoder {
 
         #[cfg(feature = "opera")] _opera_bitmap_table: Option<BUFRTableBitMap>,
     ) -> Self {
+        let provenance = Provenance {
+            local_b: local_b.as_ref().map(|_| TableSource::default()),
+            local_d: local_d.as_ref().map(|_| TableSource::default()),
+            ..Provenance::default()
+        };
         Decoder {
             bufr_edition: edition,
             master_b,
@@ -304,19 +525,330 @@ impl Decoder {
             local_d,
             #[cfg(feature = "opera")]
             opera_bitmap_table: _opera_bitmap_table,
+            layouts: RwLock::new(FxHashMap::default()),
+            f32_arrays: false,
+            group_markers: false,
+            trim_text: false,
+            bbox: None,
+            mode: Mode::default(),
+            missing: MissingPolicy::default(),
+            missing_values: MissingValues::default(),
+            units: Units::default(),
+            output: Output::default(),
+            subsets: None,
+            provenance,
         }
     }
 
-    pub fn decode<'a, V: MessageVersion>(
-        &'a mut self,
-        message: &impl Deref<Target = V>,
+    /// Produce `BUFRData::ArrayF32` instead of `BUFRData::Array` for compiled
+    /// arrays whose fields are at most 24 bits wide, halving their memory
+    ///
+    /// Missing values in those arrays are `MISS_VAL_F32`.
+    pub fn with_f32_arrays(mut self, enabled: bool) -> Self {
+        self.f32_arrays = enabled;
+        self
+    }
+
+    /// Put `BUFRData::Group` records around every replication iteration in
+    /// `decode`'s output, so its structure can be rebuilt from the flat list
+    ///
+    /// A replication decoded as whole arrays gets a single pair around them,
+    /// from iteration 0 to its last.
+    pub fn with_group_markers(mut self, enabled: bool) -> Self {
+        self.group_markers = enabled;
+        self
+    }
+
+    /// Drop trailing spaces and NULs from CCITT IA5 values, such as
+    /// space-padded station identifiers
+    pub fn with_trimmed_text(mut self, enabled: bool) -> Self {
+        self.trim_text = enabled;
+        self
+    }
+
+    /// Drop subsets located outside `bbox` from everything decoded
+    ///
+    /// A subset's position is its first latitude (0-05-001 or 0-05-002) and
+    /// longitude (0-06-001 or 0-06-002). Once it falls outside, the rest of
+    /// the subset is walked without collecting values, and the decode
+    /// methods returning a single subset return nothing. Compressed data is
+    /// decoded whole, then the values of the subsets outside are dropped
+    /// from every element. Subsets without a position are kept.
+    pub fn with_bbox(mut self, bbox: BoundingBox) -> Self {
+        self.bbox = Some(bbox);
+        self
+    }
+
+    /// Table B name and unit of `fxy`, local entries first
+    pub(crate) fn element_info(&self, fxy: FXY) -> Option<(String, String)> {
+        let cache = Cache::new(
+            &self.master_b,
+            &self.master_d,
+            self.local_b.as_ref(),
+            self.local_d.as_ref(),
+        );
+        cache
+            .lookup_b_descriptor(&fxy)
+            .map(|e| (e.element_name_en.to_string(), e.bufr_unit.to_string()))
+    }
+
+    /// Decode into one record per element
+    ///
+    /// Elements of compressed data become one record each, holding their
+    /// value in every subset: an array of numbers or a repeat of strings.
+    pub fn decode<'a, V: MessageVersion + 'a>(
+        &'a self,
+        message: &'a impl Deref<Target = V>,
     ) -> Result<BUFRParsed<'a>> {
         let data_block = message.data_block()?;
         let descriptors = message.descriptors()?;
 
         let mut data_input = BitInput::new(data_block);
+        self.skip_to_selected(message, &descriptors, &mut data_input)?;
         let mut record = BUFRParsed::new();
+        record.compressed = message.is_compressed();
+        let mut clip = Clip::new(self.subset_bbox(message), record);
+        let subsets = compressed_subsets(message);
+        let result = self.walk(&descriptors, &mut data_input, &mut clip, subsets);
+
+        let mut record = self.recover(result, clip.finish().unwrap_or_else(BUFRParsed::new))?;
+        if let (Some(bbox), Some(subsets)) = (self.bbox, subsets) {
+            record.clip(bbox, subsets);
+        }
+        self.missing_values.apply_all(&mut record);
+        Ok(record)
+    }
+
+    /// Decode into records nested by Table D sequence
+    pub fn decode_tree<'a, V: MessageVersion + 'a>(
+        &'a self,
+        message: &'a impl Deref<Target = V>,
+    ) -> Result<DecodedTree<'a>> {
+        let data_block = message.data_block()?;
+        let descriptors = message.descriptors()?;
+
+        let mut data_input = BitInput::new(data_block);
+        self.skip_to_selected(message, &descriptors, &mut data_input)?;
+        let mut clip = Clip::new(self.subset_bbox(message), TreeBuilder::new());
+        let subsets = compressed_subsets(message);
+        let result = self.walk(&descriptors, &mut data_input, &mut clip, subsets);
+
+        let mut tree = self.recover(result, clip.finish().unwrap_or_else(TreeBuilder::new))?;
+        if let (Some(bbox), Some(subsets)) = (self.bbox, subsets) {
+            tree.parsed.clip(bbox, subsets);
+        }
+        self.missing_values.apply_all(&mut tree.parsed);
+        Ok(tree.finish())
+    }
+
+    /// Decode into the shape chosen with `DecoderBuilder::output`
+    pub fn decode_output<'a, V: MessageVersion + 'a>(
+        &'a self,
+        message: &'a impl Deref<Target = V>,
+    ) -> Result<Decoded<'a>> {
+        match self.output {
+            Output::Flat => self.decode(message).map(Decoded::Flat),
+            Output::Tree => self.decode_tree(message).map(Decoded::Tree),
+            Output::Values => self.decode_values(message).map(Decoded::Values),
+        }
+    }
+
+    /// Decode into a flat `(FXY, value)` stream, skipping record bookkeeping
+    ///
+    /// Values come in bitstream order, except that replications decoded
+    /// through a compiled layout are emitted field by field.
+    pub fn decode_values<'a, V: MessageVersion + 'a>(
+        &'a self,
+        message: &'a impl Deref<Target = V>,
+    ) -> Result<Vec<(FXY, Value<'a>)>> {
+        if self.bbox.is_some() && message.is_compressed() {
+            // Positions are only known once every element has been read
+            return self.decode(message).map(BUFRParsed::into_values);
+        }
+
+        let data_block = message.data_block()?;
+        let descriptors = message.descriptors()?;
+
+        let mut data_input = BitInput::new(data_block);
+        self.skip_to_selected(message, &descriptors, &mut data_input)?;
+        let mut clip = Clip::new(self.bbox, Vec::new());
+        let subsets = compressed_subsets(message);
+        let result = self.walk(&descriptors, &mut data_input, &mut clip, subsets);
+
+        self.recover(result, clip.finish().unwrap_or_default())
+    }
+
+    /// Decode only the subset at `index` of an uncompressed multi-subset message
+    ///
+    /// Earlier subsets are walked without being collected, and replications
+    /// that compile to a fixed layout are skipped by their total bit width.
+    pub fn decode_subset<'a, V: MessageVersion + 'a>(
+        &'a self,
+        message: &'a impl Deref<Target = V>,
+        index: usize,
+    ) -> Result<BUFRParsed<'a>> {
+        let subsets = message.subsets_count() as usize;
+        if index >= subsets {
+            return Err(Error::ParseError(format!(
+                "Subset index {} out of range, message has {} subsets",
+                index, subsets
+            )));
+        }
+        if message.is_compressed() {
+            return Err(Error::ParseError(
+                "Selecting a subset of compressed data is not supported".to_string(),
+            ));
+        }
+
+        let data_block = message.data_block()?;
+        let descriptors = message.descriptors()?;
+
+        let mut data_input = BitInput::new(data_block);
+        for _ in 0..index {
+            self.decode_descriptors(&descriptors, &mut data_input, &mut Discard, None)?;
+        }
+
+        let mut clip = Clip::new(self.bbox, BUFRParsed::new());
+        let result = self.walk(&descriptors, &mut data_input, &mut clip, None);
+
+        let mut record = self.recover(result, clip.finish().unwrap_or_else(BUFRParsed::new))?;
+        self.missing_values.apply_all(&mut record);
+        Ok(record)
+    }
+
+    /// Decode every subset of an uncompressed message, one record set each
+    ///
+    /// Only the subsets selected with `DecoderBuilder::subsets` are returned.
+    pub fn decode_subsets<'a, V: MessageVersion + 'a>(
+        &'a self,
+        message: &'a impl Deref<Target = V>,
+    ) -> Result<Vec<BUFRParsed<'a>>> {
+        if message.is_compressed() {
+            return Err(Error::ParseError(
+                "Splitting compressed data into subsets is not supported".to_string(),
+            ));
+        }
+
+        let data_block = message.data_block()?;
+        let descriptors = message.descriptors()?;
+
+        let this: &'a Self = self;
+        let selected = this
+            .subsets
+            .clone()
+            .unwrap_or(0..message.subsets_count() as usize);
+        let mut data_input = BitInput::new(data_block);
+        let mut subsets = Vec::with_capacity(selected.len());
+        for index in 0..(message.subsets_count() as usize).min(selected.end) {
+            if index < selected.start {
+                this.decode_descriptors(&descriptors, &mut data_input, &mut Discard, None)?;
+                continue;
+            }
+            let mut clip = Clip::new(this.bbox, BUFRParsed::new());
+            let result = this.walk(&descriptors, &mut data_input, &mut clip, None);
+            subsets.extend(clip.finish().map(|mut subset| {
+                this.missing_values.apply_all(&mut subset);
+                subset
+            }));
+            if let Err(e) = result {
+                return this.recover(Err(e), subsets);
+            }
+        }
+
+        Ok(subsets)
+    }
+
+    /// The box subsets are clipped to as they are walked; compressed data
+    /// is clipped once decoded, see `BUFRParsed::clip`
+    fn subset_bbox<V: MessageVersion>(
+        &self,
+        message: &impl Deref<Target = V>,
+    ) -> Option<BoundingBox> {
+        self.bbox.filter(|_| !message.is_compressed())
+    }
+
+    /// Walk past the subsets before the first selected one
+    fn skip_to_selected<'a, V: MessageVersion>(
+        &'a self,
+        message: &impl Deref<Target = V>,
+        descriptors: &[FXY],
+        data_input: &mut BitInput<'a>,
+    ) -> Result<()> {
+        let Some(first) = self.subsets.as_ref().map(|range| range.start) else {
+            return Ok(());
+        };
+        if first == 0 {
+            return Ok(());
+        }
+        let subsets = message.subsets_count() as usize;
+        if first >= subsets {
+            return Err(Error::ParseError(format!(
+                "Subset index {} out of range, message has {} subsets",
+                first, subsets
+            )));
+        }
+        if message.is_compressed() {
+            return Err(Error::ParseError(
+                "Selecting a subset of compressed data is not supported".to_string(),
+            ));
+        }
+        for _ in 0..first {
+            self.decode_descriptors(descriptors, data_input, &mut Discard, None)?;
+        }
+        Ok(())
+    }
+
+    /// `decode_descriptors` with the missing-value policy and unit preference applied
+    fn walk<'a, C: Container<'a>>(
+        &'a self,
+        descriptors: &[FXY],
+        data_input: &mut BitInput<'a>,
+        record: &mut C,
+        compressed: Option<usize>,
+    ) -> Result<()> {
+        if self.missing == MissingPolicy::Keep && self.units == Units::Bufr {
+            return self.decode_descriptors(descriptors, data_input, record, compressed);
+        }
+        let mut preferred = Preferred {
+            inner: record,
+            missing: self.missing,
+            units: self.units,
+        };
+        self.decode_descriptors(descriptors, data_input, &mut preferred, compressed)
+    }
+
+    /// In lenient mode, keep what was decoded before an error
+    fn recover<T>(&self, result: Result<()>, output: T) -> Result<T> {
+        match result {
+            Err(_) if self.mode == Mode::Lenient => Ok(output),
+            Err(e) => Err(e),
+            Ok(()) => Ok(output),
+        }
+    }
+
+    /// Walk `descriptors` once: one subset, or every subset of compressed
+    /// data with `compressed` set to their number; parse errors carry the
+    /// bit they were raised at
+    fn decode_descriptors<'a, C: Container<'a>>(
+        &'a self,
+        descriptors: &[FXY],
+        data_input: &mut BitInput<'a>,
+        record: &mut C,
+        compressed: Option<usize>,
+    ) -> Result<()> {
+        self.decode_frames(descriptors, data_input, record, compressed)
+            .map_err(|e| data_input.locate(e))
+    }
+
+    fn decode_frames<'a, C: Container<'a>>(
+        &'a self,
+        descriptors: &[FXY],
+        data_input: &mut BitInput<'a>,
+        record: &mut C,
+        compressed: Option<usize>,
+    ) -> Result<()> {
         let mut state = State::new();
+        state.compressed = compressed;
         let mut cache = Cache::new(
             &self.master_b,
             &self.master_d,
@@ -326,11 +858,14 @@ impl Decoder {
 
         let mut stack: Vec<Frame> = vec![];
         stack.push(Frame::Slice {
-            descs: Descs::Raw(&descriptors),
+            descs: Descs::Raw(descriptors),
             idx: 0,
         });
 
         while let Some(frame) = stack.pop() {
+            if record.stopped() {
+                break;
+            }
             match frame {
                 Frame::Slice { descs, idx } => {
                     if idx >= descs.len() {
@@ -340,27 +875,15 @@ impl Decoder {
                         Descs::Raw(raw) => {
                             let des = &raw[idx];
                             self.parse_slice(
-                                des,
-                                idx,
-                                &mut record,
-                                descs,
-                                &mut stack,
-                                &mut cache,
-                                &mut state,
-                                &mut data_input,
+                                des, idx, record, descs, &mut stack, &mut cache, &mut state,
+                                data_input,
                             )?;
                         }
                         Descs::Archived(archived) => {
                             let des = &archived[idx];
                             self.parse_slice(
-                                des,
-                                idx,
-                                &mut record,
-                                descs,
-                                &mut stack,
-                                &mut cache,
-                                &mut state,
-                                &mut data_input,
+                                des, idx, record, descs, &mut stack, &mut cache, &mut state,
+                                data_input,
                             )?;
                         }
                     }
@@ -370,31 +893,53 @@ impl Decoder {
                     descs,
                     times,
                     current,
+                    group,
                 } => {
-                    self.parse_repeating(times, current, descs, &mut stack)?;
+                    self.parse_repeating(times, current, group, descs, record, &mut stack)?;
                 }
 
+                Frame::EndGroup {
+                    fxy,
+                    iteration,
+                    count,
+                } => record.end_group(fxy, iteration, count),
+
                 Frame::CompiledArray { layout, times } => {
-                    self.parse_compiled_array(&layout, times, &mut data_input, &mut record)?;
+                    let first = record.record_count();
+                    self.parse_compiled_array(&layout, times, data_input, record, &mut cache)?;
+                    let first = (record.record_count() > first).then_some(first);
+                    state.back_reference.array(layout, times, first);
+                }
+
+                Frame::EndSequence => record.end_sequence(),
+
+                #[cfg(feature = "opera")]
+                Frame::OperaBitmap { fxy, depth, start } => {
+                    let name = cache
+                        .get_d(&fxy)
+                        .and_then(|seq| seq.title_en.as_deref())
+                        .unwrap_or("OPERA bitmap");
+                    record.end_bitmap(fxy, depth, start, name);
                 }
             }
+            record.at_bit(data_input.position());
         }
 
-        Ok(record)
+        Ok(())
     }
 
     #[inline]
-    fn parse_slice<'k, 'c, 'i, 's, K: BUFRKey>(
+    fn parse_slice<'k, 'c, 's, C: Container<'c>, K: BUFRKey>(
         &self,
         des: &K,
         idx: usize,
-        values: &mut BUFRParsed<'c>,
+        values: &mut C,
         descs: Descs<'k>,
         // Stack
-        stack: &mut Vec<Frame<'k, 'c>>,
+        stack: &mut Vec<Frame<'k>>,
         cache: &mut Cache<'c>,
         state: &mut State,
-        data: &mut BitInput<'i>,
+        data: &mut BitInput<'c>,
     ) -> Result<()>
     where
         'c: 'k,
@@ -403,11 +948,41 @@ impl Decoder {
             0 => {
                 // Element descriptor - parse data
                 if let Some(e) = cache.get_b(des) {
-                    let value = self.evalute(state, data, &e)?;
-                    values.push(value, e.element_name_en.as_str(), e.bufr_unit.as_str());
+                    self.read_associated_field(state, values, des, data)?;
+                    let fxy = FXY::new(des.f(), des.x(), des.y());
+                    let record = values.record_count();
+                    let number = if let Some(subsets) = state.compressed {
+                        let (compressed, encoding) =
+                            state.read_compressed(data, e, subsets, self.trim_text)?;
+                        let number = compressed.first();
+                        push_compressed(values, compressed, fxy, encoding, e);
+                        number
+                    } else {
+                        let (value, encoding) = self.evalute_encoded(state, data, &e)?;
+                        let number = value.as_f64();
+                        values.push(
+                            value,
+                            fxy,
+                            Some(encoding),
+                            e.element_name_en.as_str(),
+                            e.bufr_unit.as_str(),
+                        );
+                        number
+                    };
+                    if associated::is_significance(des) {
+                        state.associated_significance = number.map(|v| v as u32);
+                    }
+                    let record = (values.record_count() > record).then_some(record);
+                    state.back_reference.element(fxy, number, record, values);
                     state.temp_operator = None;
                     state.local_data_width = None;
 
+                    stack.push(Frame::Slice {
+                        descs,
+                        idx: idx + 1,
+                    });
+                } else if state.local_data_width.is_some() {
+                    self.skip_unknown_element(state, values, des, data)?;
                     stack.push(Frame::Slice {
                         descs,
                         idx: idx + 1,
@@ -427,12 +1002,20 @@ impl Decoder {
                 if delay_repeat {
                     let count = match descs {
                         Descs::Raw(raw) => {
-                            let count_des = &raw[idx + 1];
+                            let count_des = raw.get(idx + 1).ok_or_else(|| {
+                                Error::ParseError(
+                                    "Delayed replication without a count descriptor".to_string(),
+                                )
+                            })?;
                             self.parse_usize(state, cache, count_des, data)?
                         }
 
                         Descs::Archived(archived) => {
-                            let count_des = &archived[idx + 1];
+                            let count_des = archived.get(idx + 1).ok_or_else(|| {
+                                Error::ParseError(
+                                    "Delayed replication without a count descriptor".to_string(),
+                                )
+                            })?;
                             self.parse_usize(state, cache, count_des, data)?
                         }
                     };
@@ -450,23 +1033,52 @@ impl Decoder {
                     )));
                 }
 
+                values.begin_replication(FXY::new(des.f(), des.x(), des.y()), y);
+
+                // An optional section left out, such as no cloud layers: an
+                // empty group, then on with what follows it
+                if y == 0 {
+                    values.empty_replication(FXY::new(des.f(), des.x(), des.y()));
+                    stack.push(Frame::Slice {
+                        descs,
+                        idx: body_end,
+                    });
+                    return Ok(());
+                }
+
                 let compiled_layout = match descs {
+                    _ if !values.compiles_arrays() || state.compressed.is_some() => None,
                     Descs::Raw(raw) => {
                         let body = &raw[body_start..body_end];
-                        self.try_compile_array_layout(body, y, cache)?
+                        self.try_compile_array_layout(body, y, state, cache)?
                     }
                     Descs::Archived(archived) => {
                         let body = &archived[body_start..body_end];
-                        self.try_compile_array_layout(body, y, cache)?
+                        self.try_compile_array_layout(body, y, state, cache)?
                     }
                 };
 
+                if let Some(layout) = &compiled_layout {
+                    state.apply_compiler_state(&layout.end_state);
+                }
+
                 stack.push(Frame::Slice {
                     descs,
                     idx: body_end,
                 });
 
+                let group = self
+                    .group_markers
+                    .then(|| FXY::new(des.f(), des.x(), des.y()));
                 let frame = if let Some(layout) = compiled_layout {
+                    if let Some(fxy) = group {
+                        values.begin_group(fxy, 0, y);
+                        stack.push(Frame::EndGroup {
+                            fxy,
+                            iteration: y - 1,
+                            count: y,
+                        });
+                    }
                     Frame::CompiledArray { layout, times: y }
                 } else {
                     // Fallback to normal interpretation
@@ -475,11 +1087,13 @@ impl Decoder {
                             descs: Descs::Raw(&raw[body_start..body_end]),
                             times: y,
                             current: 0,
+                            group,
                         },
                         Descs::Archived(archived) => Frame::Repeat {
                             descs: Descs::Archived(&archived[body_start..body_end]),
                             times: y,
                             current: 0,
+                            group,
                         },
                     }
                 };
@@ -487,7 +1101,8 @@ impl Decoder {
                 stack.push(frame);
             }
             2 => {
-                self.deal_with_operator(state, values, des, data)?;
+                values.operator(FXY::new(des.f(), des.x(), des.y()));
+                self.deal_with_operator(state, values, des, cache, data)?;
                 stack.push(Frame::Slice {
                     descs,
                     idx: idx + 1,
@@ -499,17 +1114,30 @@ impl Decoder {
 
                 if let Some(seq) = cache.get_d(des) {
                     let fxy_chain = seq.fxy_chain.as_slice();
-                    #[cfg(feature = "opera")]
-                    if opera_dw.is_some() {
-                        // TODO
-                        unimplemented!("");
-                    }
 
                     stack.push(Frame::Slice {
                         descs,
                         idx: idx + 1,
                     });
 
+                    if values.keeps_structure() {
+                        values.begin_sequence(
+                            FXY::new(des.f(), des.x(), des.y()),
+                            seq.title_en.as_deref().unwrap_or(""),
+                        );
+                        stack.push(Frame::EndSequence);
+                    }
+
+                    // Popped once the whole sequence has been decoded
+                    #[cfg(feature = "opera")]
+                    if let Some(depth) = opera_dw {
+                        stack.push(Frame::OperaBitmap {
+                            fxy: FXY::new(des.f(), des.x(), des.y()),
+                            depth,
+                            start: values.bitmap_start(),
+                        });
+                    }
+
                     stack.push(Frame::Slice {
                         descs: Descs::Archived(fxy_chain),
                         idx: 0,
@@ -533,23 +1161,40 @@ impl Decoder {
     }
 
     #[inline]
-    fn _parse_slice<'c, 'i, 's, K: BUFRKey>(
+    fn _parse_slice<'c, 's, K: BUFRKey>(
         &self,
         des: &K,
         values: &mut BUFRParsed<'c>,
         // Stack
         cache: &mut Cache<'c>,
         state: &mut State,
-        data: &mut BitInput<'i>,
+        data: &mut BitInput<'c>,
     ) -> Result<()> {
         match des.f() {
             0 => {
                 if let Some(e) = cache.get_b(des) {
-                    let value = self.evalute(state, data, &e)?;
-                    values.push(value, e.element_name_en.as_str(), e.bufr_unit.as_str());
+                    self.read_associated_field(state, values, des, data)?;
+                    let (value, encoding) = self.evalute_encoded(state, data, &e)?;
+                    let number = value.as_f64();
+                    if associated::is_significance(des) {
+                        state.associated_significance = number.map(|v| v as u32);
+                    }
+                    let fxy = FXY::new(des.f(), des.x(), des.y());
+                    let record = values.record_count();
+                    values.push(
+                        value,
+                        fxy,
+                        Some(encoding),
+                        e.element_name_en.as_str(),
+                        e.bufr_unit.as_str(),
+                    );
+                    let record = (values.record_count() > record).then_some(record);
+                    state.back_reference.element(fxy, number, record, values);
 
                     state.temp_operator = None;
                     state.local_data_width = None;
+                } else if state.local_data_width.is_some() {
+                    self.skip_unknown_element(state, values, des, data)?;
                 } else {
                     return Err(Error::ParseError(format!(
                         "Descriptor {:?} not found in Table B",
@@ -558,7 +1203,7 @@ impl Decoder {
                 }
             }
             2 => {
-                self.deal_with_operator(state, values, des, data)?;
+                self.deal_with_operator(state, values, des, cache, data)?;
             }
             _ => {
                 return Err(Error::ParseError(format!(
@@ -571,14 +1216,16 @@ impl Decoder {
         Ok(())
     }
 
-    fn parse_repeating<'k, 'c, 'i, 's>(
+    fn parse_repeating<'k, 'c, 'i, 's, C: Container<'c>>(
         &self,
         times: usize,
         current: usize,
+        group: Option<FXY>,
         //
         descs: Descs<'k>,
+        values: &mut C,
         // Stack
-        stack: &mut Vec<Frame<'k, '_>>,
+        stack: &mut Vec<Frame<'k>>,
     ) -> Result<()>
     where
         'c: 'k,
@@ -590,8 +1237,17 @@ impl Decoder {
             descs,
             times,
             current: current + 1,
+            group,
         });
 
+        if let Some(fxy) = group {
+            values.begin_group(fxy, current, times);
+            stack.push(Frame::EndGroup {
+                fxy,
+                iteration: current,
+                count: times,
+            });
+        }
         stack.push(Frame::Slice { descs, idx: 0 });
 
         Ok(())
@@ -607,6 +1263,9 @@ impl Decoder {
         match des.f() {
             0 => {
                 if let Some(e) = cache.get_b(des) {
+                    if let Some(subsets) = state.compressed {
+                        return compressed_count(state, data, e, subsets);
+                    }
                     let value = self.evalute(state, data, &e)?;
 
                     if let Some(v) = value.as_f64() {
@@ -634,121 +1293,252 @@ impl Decoder {
         state: &State,
         data: &mut BitInput<'a>,
         e: &ArchivedBTableEntry,
-    ) -> Result<Value> {
+    ) -> Result<Value<'a>> {
+        self.evalute_encoded(state, data, e).map(|(value, _)| value)
+    }
+
+    /// Like `evalute`, also returning how the value was packed
+    #[inline(always)]
+    fn evalute_encoded<'a>(
+        &self,
+        state: &State,
+        data: &mut BitInput<'a>,
+        e: &ArchivedBTableEntry,
+    ) -> Result<(Value<'a>, Encoding)> {
         match e.bufr_unit.as_str() {
-            "CCITT IA5" => {
-                let total_bytes = state
-                    .common_str_width
-                    .unwrap_or(((e.bufr_datawidth_bits.to_native() as usize) + 7) / 8);
-                let s = data.take_string(total_bytes as usize)?;
-                return Ok(Value::String(s));
+            UNIT_CCITT_IA5 => {
+                let total_bytes = string_bytes(state.common_str_width, e);
+                let s = data.take_text(total_bytes, self.trim_text)?;
+                let encoding = Encoding {
+                    scale: 0,
+                    reference: 0,
+                    width_bits: (total_bytes * 8) as u32,
+                    raw: None,
+                };
+                return Ok((s, encoding));
             }
             _ => {
                 let datawidth = state.datawidth(e);
-                let scale = state.scale(e) as f64;
-                let reference_value = state.reference_value(e) as f64;
+                let scale = state.scale(e);
+                let reference_value = state.reference_value(e);
                 let value = data.get_arbitary_bits(datawidth as usize)?;
-                let mv = (1 << datawidth) - 1;
-                if value == mv && e.fxy.x != 31 {
-                    return Ok(Value::Missing);
+                let encoding = Encoding {
+                    scale,
+                    reference: reference_value,
+                    width_bits: datawidth,
+                    raw: Some(value),
+                };
+                if value == all_ones(datawidth) && e.fxy.x != 31 {
+                    return Ok((Value::Missing, encoding));
                 }
-                let result = ((value as f64) + reference_value) * 10.0f64.powi(-scale as i32);
-                return Ok(Value::Number(result));
+                let result = ((value as f64) + reference_value as f64) * 10.0f64.powi(-scale);
+                return Ok((Value::Number(result), encoding));
             }
         }
     }
 
-    fn try_compile_array_layout<'a, K: BUFRKey>(
+    fn try_compile_array_layout<K: BUFRKey>(
         &self,
         body: &[K],
         repeat_count: usize,
-        cache: &mut Cache<'a>,
-    ) -> Result<Option<CompiledLayout<'a>>> {
+        state: &State,
+        cache: &mut Cache,
+    ) -> Result<Option<Arc<CompiledLayout>>> {
         // Early rejection: too small
         if repeat_count < 16 {
             return Ok(None);
         }
 
-        let mut compiler_state = CompilerState {
-            common_scale: None,
-            common_ref_value: None,
-            common_data_width: None,
-            temp_operator: None,
-            common_str_width: None,
-            local_data_width: None,
+        // One-shot operators pending before the replication only apply to its
+        // first repetition, which a per-repetition layout can't express
+        if state.temp_operator.is_some() || state.local_data_width.is_some() {
+            return Ok(None);
+        }
+
+        // Associated fields come before every element, outside any layout
+        if !state.associated_widths.is_empty() {
+            return Ok(None);
+        }
+
+        // Bit-maps and the values they link are followed element by element
+        if state.back_reference.busy() {
+            return Ok(None);
+        }
+
+        let key = LayoutKey {
+            body: body.iter().map(|d| FXY::new(d.f(), d.x(), d.y())).collect(),
+            state: state.compiler_state(),
         };
 
+        if let Some(layout) = self.layouts.read().unwrap().get(&key) {
+            return Ok(layout.clone());
+        }
+
+        let layout = self
+            .compile_array_layout(body, key.state.clone(), cache)?
+            .map(Arc::new);
+        self.layouts.write().unwrap().insert(key, layout.clone());
+
+        Ok(layout)
+    }
+
+    fn compile_array_layout<K: BUFRKey>(
+        &self,
+        body: &[K],
+        mut compiler_state: CompilerState,
+        cache: &mut Cache,
+    ) -> Result<Option<CompiledLayout>> {
+        let start_state = compiler_state.clone();
         let mut fields = Vec::with_capacity(body.len());
         let mut total_bits = 0usize;
 
-        for desc in body {
+        let mut idx = 0;
+        while idx < body.len() {
+            let desc = &body[idx];
             match desc.f() {
-                0 => {
-                    // Element descriptor - compile field spec
-                    let entry = cache.get_b(desc).ok_or_else(|| {
-                        Error::ParseError(format!("Missing Table B entry for {:?}", desc))
-                    })?;
-
-                    // Reject strings
-                    if entry.bufr_unit.as_str() == "CCITT IA5" {
+                1 => {
+                    // Inline one level of fixed-count replication
+                    let x = desc.x() as usize;
+                    let y = desc.y() as usize;
+                    let inner_end = idx + 1 + x;
+
+                    // Delayed replication needs the data to know its count
+                    if y == 0 || inner_end > body.len() {
                         return Ok(None);
                     }
 
-                    // Compute effective parameters
-                    let width = self.compute_effective_width(&compiler_state, entry);
-                    let scale = self.compute_effective_scale(&compiler_state, entry);
-                    let reference = self.compute_effective_reference(&compiler_state, entry);
-                    let missing = if width == 64 {
-                        u64::MAX
-                    } else {
-                        (1u64 << width) - 1
-                    };
-
-                    fields.push(FieldSpec {
-                        fxy: FXY::new(desc.f(), desc.x(), desc.y()),
-                        name: entry.element_name_en.as_str(),
-                        unit: entry.bufr_unit.as_str(),
-                        width_bits: width,
-                        scale,
-                        reference,
-                        missing_value: missing,
-                    });
-
-                    total_bits += width as usize;
-
-                    // Clear one-time operators after use
-                    // 2-07 and 2-06 apply only to the next element
-                    compiler_state.temp_operator = None;
-                    compiler_state.local_data_width = None;
-                }
-
-                2 => {
-                    if !self.apply_operator_to_compiler(&mut compiler_state, desc)? {
+                    let inner = &body[idx + 1..inner_end];
+                    if inner.iter().any(|d| matches!(d.f(), 1 | 3)) {
                         return Ok(None);
                     }
+
+                    for _ in 0..y {
+                        for inner_desc in inner {
+                            if !self.compile_field(
+                                inner_desc,
+                                &mut compiler_state,
+                                &mut fields,
+                                &mut total_bits,
+                                cache,
+                            )? {
+                                return Ok(None);
+                            }
+                        }
+                    }
+
+                    idx = inner_end;
                 }
 
-                1 | 3 => {
-                    // Nested replication or sequence - reject
+                3 => {
+                    // Nested sequence - reject
                     return Ok(None);
                 }
 
                 _ => {
-                    return Err(Error::ParseError(format!("Invalid F value: {}", desc.f())));
+                    if !self.compile_field(
+                        desc,
+                        &mut compiler_state,
+                        &mut fields,
+                        &mut total_bits,
+                        cache,
+                    )? {
+                        return Ok(None);
+                    }
+                    idx += 1;
                 }
             }
         }
 
-        if compiler_state.temp_operator.is_some() {
+        // A body that leaves operators in effect reads its next repetition
+        // differently from this one
+        if compiler_state != start_state {
             return Ok(None);
         }
 
         Ok(Some(CompiledLayout {
             fields,
             bits_per_element: total_bits,
+            end_state: compiler_state,
         }))
     }
 
+    /// Compile a single element or operator descriptor into `fields`
+    ///
+    /// Returns `false` when the descriptor can't be expressed in a fixed layout.
+    fn compile_field<K: BUFRKey>(
+        &self,
+        desc: &K,
+        compiler_state: &mut CompilerState,
+        fields: &mut Vec<FieldSpec>,
+        total_bits: &mut usize,
+        cache: &mut Cache,
+    ) -> Result<bool> {
+        match desc.f() {
+            0 => {
+                // Element descriptor - compile field spec
+                let Some(entry) = cache.get_b(desc) else {
+                    // Skipped with a 2-06 width by the interpreter
+                    if compiler_state.local_data_width.is_some() {
+                        return Ok(false);
+                    }
+                    return Err(Error::ParseError(format!(
+                        "Missing Table B entry for {:?}",
+                        desc
+                    )));
+                };
+
+                // Strings are read as whole bytes, unaffected by 2-01/2-02/2-03
+                if entry.bufr_unit.as_str() == UNIT_CCITT_IA5 {
+                    let nbytes = string_bytes(compiler_state.common_str_width, entry);
+                    fields.push(FieldSpec {
+                        fxy: FXY::new(desc.f(), desc.x(), desc.y()),
+                        width_bits: (nbytes * 8) as u32,
+                        scale: 0,
+                        reference: 0,
+                        factor: 1.0,
+                        missing_value: 0,
+                        string_bytes: Some(nbytes),
+                    });
+                    *total_bits += nbytes * 8;
+                    return Ok(true);
+                }
+
+                // Compute effective parameters
+                let width = self.compute_effective_width(compiler_state, entry);
+                // Left to the interpreter to report
+                if width > 64 {
+                    return Ok(false);
+                }
+                let scale = self.compute_effective_scale(compiler_state, entry);
+                let reference = self.compute_effective_reference(compiler_state, entry);
+                let missing = all_ones(width);
+
+                fields.push(FieldSpec {
+                    fxy: FXY::new(desc.f(), desc.x(), desc.y()),
+                    width_bits: width,
+                    scale,
+                    reference,
+                    factor: 10.0f64.powi(-scale),
+                    missing_value: missing,
+                    string_bytes: None,
+                });
+
+                *total_bits += width as usize;
+
+                // Clear one-time operators after use
+                // 2-07 and 2-06 apply only to the next element
+                compiler_state.temp_operator = None;
+                compiler_state.local_data_width = None;
+
+                Ok(true)
+            }
+
+            2 => self.apply_operator_to_compiler(compiler_state, desc),
+
+            _ => Err(Error::ParseError(format!("Invalid F value: {}", desc.f()))),
+        }
+    }
+
     fn apply_operator_to_compiler<K: BUFRKey>(
         &self,
         state: &mut CompilerState,
@@ -757,6 +1547,12 @@ impl Decoder {
         let x = operator.x();
         let y = operator.y() as i32;
 
+        // Operators reading data of their own, such as 2-05-YYY characters
+        // or associated fields, have no fixed place in a layout
+        if table_c::lookup(x).is_some_and(|op| op.consumes_bits) {
+            return Ok(false);
+        }
+
         match x {
             1 => {
                 // 2-01-YYY: data width change
@@ -768,15 +1564,6 @@ impl Decoder {
                 state.common_scale = if y == 0 { None } else { Some(y) };
                 Ok(true)
             }
-            3 => {
-                // 2-03-YYY: reference value change
-                state.common_ref_value = if y == 0 { None } else { Some(y) };
-                Ok(true)
-            }
-            5 => {
-                // 2-05-YYY: string literal - consumes bits, reject
-                Ok(false)
-            }
             6 => {
                 // 2-06-YYY: localized data width - affects only next element
                 state.local_data_width = Some(y);
@@ -788,8 +1575,9 @@ impl Decoder {
                 Ok(true)
             }
             8 => {
-                // 2-08-YYY: character width - reject (affects strings)
-                Ok(false)
+                // 2-08-YYY: character width, in bytes
+                state.common_str_width = if y == 0 { None } else { Some(y as usize) };
+                Ok(true)
             }
             _ => {
                 // Unknown/unsupported operator - allow but ignore
@@ -805,10 +1593,7 @@ impl Decoder {
         }
 
         let unit = e.bufr_unit.as_str();
-        let is_flag_or_code = matches!(
-            unit,
-            "flag table" | "flag-table" | "code table" | "code-table"
-        );
+        let is_flag_or_code = matches!(unit, UNIT_FLAG_TABLE | UNIT_CODE_TABLE);
         let delay_repeat_count = e.fxy.f.to_native() == 0 && e.fxy.x.to_native() == 31;
         let no_change = is_flag_or_code || delay_repeat_count;
 
@@ -818,18 +1603,16 @@ impl Decoder {
             state
                 .common_data_width
                 .map(|c| {
-                    let (v, _) = e
-                        .bufr_datawidth_bits
+                    e.bufr_datawidth_bits
                         .to_native()
-                        .overflowing_add_signed(c - 128);
-                    v
+                        .saturating_add_signed(c - 128)
                 })
                 .unwrap_or(e.bufr_datawidth_bits.to_native())
         };
 
         // 2-07-YYY: increase width by 10*Y bits
         if let Some(op) = state.temp_operator {
-            base_width + (10 * op) as u32
+            base_width.saturating_add_signed(op.saturating_mul(10))
         } else {
             base_width
         }
@@ -838,10 +1621,7 @@ impl Decoder {
     #[inline]
     fn compute_effective_scale(&self, state: &CompilerState, e: &ArchivedBTableEntry) -> i32 {
         let unit = e.bufr_unit.as_str();
-        let is_flag_or_code = matches!(
-            unit,
-            "flag table" | "flag-table" | "code table" | "code-table"
-        );
+        let is_flag_or_code = matches!(unit, UNIT_FLAG_TABLE | UNIT_CODE_TABLE);
         let delay_repeat_count = e.fxy.f.to_native() == 0 && e.fxy.x.to_native() == 31;
         let no_change = is_flag_or_code || delay_repeat_count;
 
@@ -850,15 +1630,12 @@ impl Decoder {
         } else {
             state
                 .common_scale
-                .map(|c| {
-                    let (v, _) = e.bufr_scale.to_native().overflowing_add(128 - c);
-                    v
-                })
+                .map(|c| e.bufr_scale.to_native().saturating_add(128 - c))
                 .unwrap_or(e.bufr_scale.to_native())
         };
 
         if let Some(op) = state.temp_operator {
-            base_scale + op
+            base_scale.saturating_add(op)
         } else {
             base_scale
         }
@@ -876,103 +1653,323 @@ impl Decoder {
     }
 
     /// Fast path: decode array using pre-compiled layout
-    fn parse_compiled_array<'a>(
+    fn parse_compiled_array<'a, C: Container<'a>>(
         &self,
-        layout: &CompiledLayout<'a>,
+        layout: &CompiledLayout,
         repeat_count: usize,
-        data: &mut BitInput,
-        values: &mut BUFRParsed<'a>,
+        data: &mut BitInput<'a>,
+        values: &mut C,
+        cache: &mut Cache<'a>,
     ) -> Result<()> {
-        let mut total_values = vec![vec![]; layout.fields.len()];
-        // For each repetition
-        for _ in 0..repeat_count {
-            // For each field in the layout
-            for (i, field_spec) in layout.fields.iter().enumerate() {
-                let raw_value = data.get_arbitary_bits(field_spec.width_bits as usize)?;
-
-                // Check for missing value (skip 0-31-YYY delayed replication counts)
-                let value = if raw_value == field_spec.missing_value
-                    && !(field_spec.fxy.f == 0 && field_spec.fxy.x == 31)
-                {
-                    MISS_VAL
+        // Checked before anything is allocated for a count read from the data
+        let bits = layout
+            .bits_per_element
+            .checked_mul(repeat_count)
+            .filter(|&bits| bits <= data.remaining_bits())
+            .ok_or_else(|| data.error("Not enough data for replication"))?;
+        if !values.keeps_values() {
+            return data.skip_bits(bits);
+        }
+
+        let nfields = layout.fields.len();
+        let mut columns: Vec<Column> = layout
+            .fields
+            .iter()
+            .map(|f| {
+                if f.string_bytes.is_some() {
+                    Column::Strings(vec![])
+                } else if self.f32_arrays && f.width_bits <= F32_MAX_WIDTH {
+                    Column::F32(
+                        Vec::with_capacity(repeat_count),
+                        MissingMask::with_capacity(repeat_count),
+                    )
                 } else {
-                    // Apply scale and reference
-                    let scaled = ((raw_value as f64) + (field_spec.reference as f64))
-                        * 10.0f64.powi(-field_spec.scale);
-                    scaled
-                };
+                    Column::F64(
+                        Vec::with_capacity(repeat_count),
+                        MissingMask::with_capacity(repeat_count),
+                    )
+                }
+            })
+            .collect();
+
+        // Numeric fields sharing one width form a single run over the whole
+        // replication, so they can be unpacked a word at a time
+        let uniform_width = layout.fields.first().and_then(|first| {
+            layout
+                .fields
+                .iter()
+                .all(|f| f.string_bytes.is_none() && f.width_bits == first.width_bits)
+                .then_some(first.width_bits as usize)
+        });
 
-                total_values[i].push(value);
+        if let Some(nbits) = uniform_width {
+            let mut i = 0;
+            data.unpack_same_width(nbits, repeat_count.saturating_mul(nfields), |raw| {
+                columns[i].push(layout.fields[i].to_value(raw));
+                i += 1;
+                if i == nfields {
+                    i = 0;
+                }
+            })?;
+        } else {
+            // For each repetition
+            for _ in 0..repeat_count {
+                // For each field in the layout
+                for (column, field_spec) in columns.iter_mut().zip(layout.fields.iter()) {
+                    if let (Some(nbytes), Column::Strings(strings)) =
+                        (field_spec.string_bytes, &mut *column)
+                    {
+                        strings.push(data.take_text(nbytes, self.trim_text)?);
+                        continue;
+                    }
+
+                    let raw_value = data.get_arbitary_bits(field_spec.width_bits as usize)?;
+                    column.push(field_spec.to_value(raw_value));
+                }
             }
         }
 
-        for (v, field) in total_values.into_iter().zip(layout.fields.iter()) {
-            let mut array = values.start_array(0);
-            array.set_values(v);
-            array.finish(Some(field.name), Some(field.unit));
+        for (column, field) in columns.into_iter().zip(layout.fields.iter()) {
+            let entry = cache.get_b(&field.fxy).ok_or_else(|| {
+                Error::ParseError(format!("Missing Table B entry for {:?}", field.fxy))
+            })?;
+            let name = entry.element_name_en.as_str();
+            let unit = entry.bufr_unit.as_str();
+            let encoding = field.encoding();
+            match column {
+                Column::F64(mut v, missing) => {
+                    values.push_array(&mut v, &missing, field.fxy, encoding, name, unit)
+                }
+                Column::F32(mut v, missing) => {
+                    values.push_array_f32(&mut v, &missing, field.fxy, encoding, name, unit)
+                }
+                Column::Strings(v) => values.push_repeat(v, field.fxy, encoding, name, unit),
+            }
         }
 
         Ok(())
     }
 
-    fn deal_with_operator<'s, 'a, C: Container<'s>, K: BUFRKey>(
+    fn deal_with_operator<'s, C: Container<'s>, K: BUFRKey>(
         &self,
         state: &mut State,
         values: &mut C,
         operator: &K,
-        data: &mut BitInput<'a>,
+        cache: &mut Cache<'s>,
+        data: &mut BitInput<'s>,
     ) -> Result<()> {
         let x = operator.x();
         let y = operator.y();
+        table_c::check(operator).map_err(Error::ParseError)?;
+
+        if let (5, Some(subsets)) = (x, state.compressed) {
+            let texts = compressed::take_texts(data, y as usize, subsets, self.trim_text)?;
+            let encoding = Encoding {
+                scale: 0,
+                reference: 0,
+                width_bits: y as u32 * 8,
+                raw: None,
+            };
+            values.push_repeat(
+                texts,
+                FXY::new(operator.f(), x, y),
+                encoding,
+                SIGNIFY_CHARACTER_NAME,
+                UNIT_CCITT_IA5,
+            );
+        } else if x == 5 {
+            let string = data.take_text(y as usize, self.trim_text)?;
+            values.push(
+                string,
+                FXY::new(operator.f(), x, y),
+                None,
+                SIGNIFY_CHARACTER_NAME,
+                UNIT_CCITT_IA5,
+            );
+        } else if quality::is_marker(operator) {
+            self.read_marker(state, values, x, cache, data)?;
+        } else {
+            state.apply_operator(x, y);
+        }
 
-        match x {
-            1 => match y {
-                0 => {
-                    state.common_data_width = None;
-                }
-                _ => {
-                    state.common_data_width = Some(y);
-                }
-            },
-            2 => match y {
-                0 => {
-                    state.common_scale = None;
-                }
-                _ => {
-                    state.common_scale = Some(y);
-                }
-            },
-            3 => match y {
-                0 => {
-                    state.common_ref_value = None;
-                }
-                _ => {
-                    state.common_ref_value = Some(y);
-                }
-            },
-            5 => {
-                let string = data.take_string(y as usize)?;
-                values.push(Value::String(string), "", "CAITT IA5");
-            }
+        Ok(())
+    }
 
-            6 => {
-                let localized_width = y;
-                state.local_data_width = Some(localized_width);
+    /// Read the value a 2-`x`-255 marker stands for, packed like the element
+    /// the bit-map links it to
+    fn read_marker<'s, C: Container<'s>>(
+        &self,
+        state: &mut State,
+        values: &mut C,
+        x: i32,
+        cache: &mut Cache<'s>,
+        data: &mut BitInput<'s>,
+    ) -> Result<()> {
+        let (target, kind) = state.back_reference.next_target(x).ok_or_else(|| {
+            Error::ParseError(format!(
+                "2-{:02}-255 has no element left in the data present bit-map",
+                x
+            ))
+        })?;
+        let e = cache.get_b(&target.fxy).ok_or_else(|| {
+            Error::ParseError(format!("Missing Table B entry for {:?}", target.fxy))
+        })?;
+
+        let encoding = if kind == QualityKind::DifferenceStatistics {
+            // One bit wider than the element, with reference -2^width
+            let width = state.datawidth(e).saturating_add(1);
+            let reference = 1i64
+                .checked_shl(width - 1)
+                .and_then(|r| i32::try_from(-r).ok())
+                .ok_or_else(|| {
+                    Error::ParseError(format!(
+                        "Difference statistical value of {} bits is too wide",
+                        width
+                    ))
+                })?;
+            Some(Encoding {
+                scale: state.scale(e),
+                reference,
+                width_bits: width,
+                raw: None,
+            })
+        } else {
+            None
+        };
+
+        let record = values.record_count();
+        match (encoding, state.compressed) {
+            (Some(encoding), Some(subsets)) => {
+                let (numbers, missing) = state.scratch.read(data, &encoding, subsets, false)?;
+                let compressed = Compressed::Numbers(numbers, missing);
+                push_compressed(values, compressed, target.fxy, encoding, e);
             }
-            7 => {
-                state.temp_operator = Some(y);
+            (Some(mut encoding), None) => {
+                let width = encoding.width_bits;
+                let raw = data.get_arbitary_bits(width as usize)?;
+                let value = if raw == all_ones(width) {
+                    Value::Missing
+                } else {
+                    Value::Number(
+                        (raw as f64 + encoding.reference as f64) * 10.0f64.powi(-encoding.scale),
+                    )
+                };
+                encoding.raw = Some(raw);
+                values.push(
+                    value,
+                    target.fxy,
+                    Some(encoding),
+                    e.element_name_en.as_str(),
+                    e.bufr_unit.as_str(),
+                );
             }
-            8 => match y {
-                0 => {
-                    state.common_str_width = None;
-                }
-                _ => {
-                    state.common_str_width = Some(y as usize);
-                }
-            },
-            _ => {}
+            (None, Some(subsets)) => {
+                let (compressed, encoding) =
+                    state.read_compressed(data, e, subsets, self.trim_text)?;
+                push_compressed(values, compressed, target.fxy, encoding, e);
+            }
+            (None, None) => {
+                let (value, encoding) = self.evalute_encoded(state, data, e)?;
+                values.push(
+                    value,
+                    target.fxy,
+                    Some(encoding),
+                    e.element_name_en.as_str(),
+                    e.bufr_unit.as_str(),
+                );
+            }
+        }
+        let record = (values.record_count() > record).then_some(record);
+        state.back_reference.link(target, record, kind, values);
+        Ok(())
+    }
+
+    /// Read the associated fields in effect before element `des`, as one
+    /// record named after the latest 0-31-021 significance
+    fn read_associated_field<'s, C: Container<'s>, K: BUFRKey>(
+        &self,
+        state: &mut State,
+        values: &mut C,
+        des: &K,
+        data: &mut BitInput<'_>,
+    ) -> Result<()> {
+        let width: i32 = state.associated_widths.iter().sum();
+        if width == 0 || !associated::takes_associated_field(des) {
+            return Ok(());
+        }
+        if width > 64 {
+            return Err(Error::ParseError(format!(
+                "Associated fields of {} bits are not supported",
+                width
+            )));
         }
 
+        let fxy = FXY::new(2, 4, width);
+        let name = associated_field_name(state.associated_significance);
+        if let Some(subsets) = state.compressed {
+            let encoding = Encoding {
+                scale: 0,
+                reference: 0,
+                width_bits: width as u32,
+                raw: None,
+            };
+            let (numbers, missing) = state.scratch.read(data, &encoding, subsets, false)?;
+            values.push_array(numbers, missing, fxy, encoding, name, "");
+            return Ok(());
+        }
+
+        let raw = data.get_arbitary_bits(width as usize)?;
+        values.push(
+            Value::Number(raw as f64),
+            fxy,
+            Some(Encoding {
+                scale: 0,
+                reference: 0,
+                width_bits: width as u32,
+                raw: Some(raw),
+            }),
+            name,
+            "",
+        );
+        Ok(())
+    }
+
+    /// Read past an element found in no Table B, as 2-06-YYY allows: the
+    /// width it declared is skipped and a missing placeholder recorded
+    fn skip_unknown_element<'s, C: Container<'s>, K: BUFRKey>(
+        &self,
+        state: &mut State,
+        values: &mut C,
+        des: &K,
+        data: &mut BitInput<'_>,
+    ) -> Result<()> {
+        self.read_associated_field(state, values, des, data)?;
+        let width = state.local_data_width.take().unwrap_or(0);
+        let fxy = FXY::new(des.f(), des.x(), des.y());
+        let record = values.record_count();
+        let encoding = Encoding {
+            scale: 0,
+            reference: 0,
+            width_bits: width as u32,
+            raw: None,
+        };
+        if let Some(subsets) = state.compressed {
+            state.scratch.read(data, &encoding, subsets, false)?;
+            let missing = vec![Value::Missing; subsets];
+            values.push_repeat(missing, fxy, encoding, UNKNOWN_ELEMENT_NAME, "");
+        } else {
+            data.skip_bits(width as usize)?;
+            values.push(
+                Value::Missing,
+                fxy,
+                Some(encoding),
+                UNKNOWN_ELEMENT_NAME,
+                "",
+            );
+        }
+        let record = (values.record_count() > record).then_some(record);
+        state.back_reference.element(fxy, None, record, values);
+        state.temp_operator = None;
         Ok(())
     }
 
@@ -1007,28 +2004,33 @@ impl Decoder {
 }
 
 #[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
-pub enum Value {
+pub enum Value<'a> {
     Number(f64),
     Missing,
-    String(String),
+    /// A CCITT IA5 field, borrowed from Section 4 when it is byte-aligned
+    String(Cow<'a, str>),
+    /// A CCITT IA5 field that isn't valid UTF-8, such as an opaque binary
+    /// payload, borrowed like `String`
+    Bytes(Cow<'a, [u8]>),
 }
 
-impl std::fmt::Display for Value {
+impl std::fmt::Display for Value<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Value::Number(v) => write!(f, "{}", v),
             Value::String(v) => write!(f, "{}", v),
+            Value::Bytes(v) => write!(f, "{}", hex(v)),
             Value::Missing => write!(f, "MISSING"),
         }
     }
 }
 
-impl Value {
+impl Value<'_> {
     pub fn as_f64(&self) -> Option<f64> {
         match self {
             Value::Number(v) => Some(*v),
             Value::Missing => Some(MISS_VAL),
-            Value::String(_) => None,
+            Value::String(_) | Value::Bytes(_) => None,
         }
     }
 
@@ -1036,13 +2038,14 @@ impl Value {
         match self {
             Value::String(v) => Some(v),
             Value::Number(_) => None,
-            Value::Missing => None,
+            Value::Missing | Value::Bytes(_) => None,
         }
     }
 
     pub fn as_bytes(&self) -> Option<Vec<u8>> {
         match self {
             Value::String(_) => None,
+            Value::Bytes(b) => Some(b.to_vec()),
             Value::Number(n) => Some(n.to_le_bytes().to_vec()),
             Value::Missing => None,
         }
@@ -1051,50 +2054,210 @@ impl Value {
     pub fn is_missing(&self) -> bool {
         matches!(self, Value::Missing)
     }
+
+    /// This value with its string copied out of the message
+    pub fn into_owned(self) -> Value<'static> {
+        match self {
+            Value::Number(v) => Value::Number(v),
+            Value::Missing => Value::Missing,
+            Value::String(s) => Value::String(Cow::Owned(s.into_owned())),
+            Value::Bytes(b) => Value::Bytes(Cow::Owned(b.into_owned())),
+        }
+    }
+
+    /// This value borrowing its string from `self` rather than copying it
+    pub(crate) fn borrowed(&self) -> Value<'_> {
+        match self {
+            Value::Number(v) => Value::Number(*v),
+            Value::Missing => Value::Missing,
+            Value::String(s) => Value::String(Cow::Borrowed(s)),
+            Value::Bytes(b) => Value::Bytes(Cow::Borrowed(b)),
+        }
+    }
+}
+
+/// `bytes` as `0x` and lowercase hex digits
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    let mut s = String::with_capacity(2 + bytes.len() * 2);
+    s.push_str("0x");
+    for b in bytes {
+        s.push_str(&format!("{:02x}", b));
+    }
+    s
 }
 
+/// `s` without the spaces and NULs CCITT IA5 fields are padded with
+fn trim_padding(s: &str) -> &str {
+    s.trim_end_matches([' ', '\0'])
+}
+
+/// Bits of Section 4 before its data: the length and a reserved octet
+const SECTION4_HEADER_BITS: usize = 32;
+
+/// Where `BitInput::error` puts the position in its messages
+const AT_BIT: &str = " at bit";
+
+/// A position of a `BitInput`, to go back to with `BitInput::restore`
 #[derive(Debug, Clone, Copy)]
-pub struct BitInput<'a>(&'a [u8], usize);
+pub struct Checkpoint<'a>(&'a [u8], usize);
+
+/// A big-endian bit reader; the remaining bytes, the bit offset into the
+/// first of them, and the whole input
+#[derive(Debug, Clone, Copy)]
+pub struct BitInput<'a>(&'a [u8], usize, &'a [u8]);
 
 impl<'a> BitInput<'a> {
     pub fn new(input: &[u8]) -> BitInput<'_> {
-        BitInput(input, 0)
+        BitInput(input, 0, input)
     }
 
     pub fn pointer(&self) -> usize {
         self.1
     }
 
+    /// A parse error at the current position, in bits from the start of
+    /// Section 4
+    fn error(&self, message: impl std::fmt::Display) -> Error {
+        Error::ParseError(format!(
+            "{}{} {} of Section 4",
+            message,
+            AT_BIT,
+            SECTION4_HEADER_BITS + self.position()
+        ))
+    }
+
+    /// `error` at the current position, unless it is not a parse error or
+    /// already has one
+    fn locate(&self, error: Error) -> Error {
+        match error {
+            Error::ParseError(message) if !message.contains(AT_BIT) => self.error(message),
+            error => error,
+        }
+    }
+
+    /// Bits left to read
+    pub fn remaining_bits(&self) -> usize {
+        self.0.len() * 8 - self.1
+    }
+
+    /// Bits read since the start of the input
+    pub fn position(&self) -> usize {
+        (self.2.len() - self.0.len()) * 8 + self.1
+    }
+
+    /// Move to bit `bitpos` of the input, backwards or forwards
+    pub fn seek_to(&mut self, bitpos: usize) -> Result<()> {
+        let nbytes = bitpos / 8;
+        if nbytes > self.2.len() || (nbytes == self.2.len() && !bitpos.is_multiple_of(8)) {
+            return Err(Error::ParseError(format!(
+                "Bit position {} is past the end of the data",
+                bitpos
+            )));
+        }
+        self.0 = &self.2[nbytes..];
+        self.1 = bitpos % 8;
+        Ok(())
+    }
+
+    /// The current position, cheap to take and to `restore`
+    pub fn checkpoint(&self) -> Checkpoint<'a> {
+        Checkpoint(self.0, self.1)
+    }
+
+    /// Go back, or forward, to a checkpoint taken from this input
+    pub fn restore(&mut self, checkpoint: Checkpoint<'a>) {
+        self.0 = checkpoint.0;
+        self.1 = checkpoint.1;
+    }
+
+    /// A UTF-8 string, borrowed from the input when it is byte-aligned
+    #[inline]
+    pub fn take_string(&mut self, nbytes: usize) -> Result<Cow<'a, str>> {
+        let start = *self;
+        let invalid = || start.error("Invalid UTF-8 string");
+        Ok(match self.take_bytes(nbytes)? {
+            Cow::Borrowed(bytes) => {
+                Cow::Borrowed(std::str::from_utf8(bytes).map_err(|_| invalid())?)
+            }
+            Cow::Owned(bytes) => Cow::Owned(String::from_utf8(bytes).map_err(|_| invalid())?),
+        })
+    }
+
+    /// Like `take_string`, without trailing spaces and NULs
+    pub fn take_string_trimmed(&mut self, nbytes: usize) -> Result<Cow<'a, str>> {
+        Ok(match self.take_string(nbytes)? {
+            Cow::Borrowed(s) => Cow::Borrowed(trim_padding(s)),
+            Cow::Owned(mut s) => {
+                s.truncate(trim_padding(&s).len());
+                Cow::Owned(s)
+            }
+        })
+    }
+
+    /// A CCITT IA5 field: `Value::String`, or `Value::Bytes` when its bytes
+    /// aren't valid UTF-8; `trim` drops trailing spaces and NULs of strings
+    #[inline]
+    pub fn take_text(&mut self, nbytes: usize, trim: bool) -> Result<Value<'a>> {
+        Ok(match self.take_bytes(nbytes)? {
+            Cow::Borrowed(bytes) => match std::str::from_utf8(bytes) {
+                Ok(s) if trim => Value::String(Cow::Borrowed(trim_padding(s))),
+                Ok(s) => Value::String(Cow::Borrowed(s)),
+                Err(_) => Value::Bytes(Cow::Borrowed(bytes)),
+            },
+            Cow::Owned(bytes) => match String::from_utf8(bytes) {
+                Ok(mut s) => {
+                    if trim {
+                        s.truncate(trim_padding(&s).len());
+                    }
+                    Value::String(Cow::Owned(s))
+                }
+                Err(e) => Value::Bytes(Cow::Owned(e.into_bytes())),
+            },
+        })
+    }
+
+    /// `nbytes` whole octets, borrowed from the input when it is
+    /// byte-aligned
     #[inline]
-    pub fn take_string(&mut self, nbytes: usize) -> Result<String> {
+    pub fn take_bytes(&mut self, nbytes: usize) -> Result<Cow<'a, [u8]>> {
         if nbytes == 0 {
-            return Ok(String::new());
+            return Ok(Cow::Borrowed(&[]));
         }
 
         // Fast path: byte-aligned string reads
         if self.1 == 0 {
             if self.0.len() < nbytes {
-                return Err(Error::ParseError("Not enough data for string".to_string()));
+                return Err(self.error("Not enough data for string"));
             }
-            let s = String::from_utf8(self.0[..nbytes].to_vec())
-                .map_err(|_| Error::ParseError("Invalid UTF-8 string".to_string()))?;
-            self.0 = &self.0[nbytes..];
-            self.1 = 0;
-            return Ok(s);
+            let (bytes, rest) = self.0.split_at(nbytes);
+            self.0 = rest;
+            return Ok(Cow::Borrowed(bytes));
         }
 
         // Slow path: unaligned reads
+        if self.0.len() <= nbytes {
+            return Err(self.error("Not enough data for string"));
+        }
         let mut chars = Vec::with_capacity(nbytes);
-        // let mut remaining_input = self;
 
         for _ in 0..nbytes {
             let byte_value = self.get_arbitary_bits(8)?;
             chars.push(byte_value as u8);
         }
 
-        let s = String::from_utf8(chars)
-            .map_err(|_| Error::ParseError("Invalid UTF-8 string".to_string()))?;
-        Ok(s)
+        Ok(Cow::Owned(chars))
+    }
+
+    /// Advance the pointer by `nbits` without reading
+    pub fn skip_bits(&mut self, nbits: usize) -> Result<()> {
+        let position = self.1 + nbits;
+        let nbytes = position / 8;
+        if nbytes > self.0.len() || (nbytes == self.0.len() && !position.is_multiple_of(8)) {
+            return Err(self.error("Not enough data"));
+        }
+        self.0 = &self.0[nbytes..];
+        self.1 = position % 8;
+        Ok(())
     }
 
     #[inline]
@@ -1102,6 +2265,9 @@ impl<'a> BitInput<'a> {
         if nbits == 0 {
             return Ok(0);
         }
+        if nbits > 64 {
+            return Err(self.error("Cannot read more than 64 bits"));
+        }
 
         // Fast path: byte-aligned reads for common bit widths
         if self.1 == 0 {
@@ -1112,25 +2278,88 @@ impl<'a> BitInput<'a> {
         self.get_arbitary_bits_unaligned(nbits)
     }
 
-    /// Batch read multiple values with the same bit width
-    /// Optimized for arrays of numeric data
+    /// Unpack `count` values of `nbits` each, feeding them to `sink` in order
+    ///
+    /// The stream is loaded one 64-bit big-endian word at a time and every
+    /// value that fits in the word is extracted before the next load.
     #[inline]
-    pub fn get_batch_same_width(&mut self, nbits: usize, count: usize) -> Result<Vec<u64>> {
+    pub fn unpack_same_width<F: FnMut(u64)>(
+        &mut self,
+        nbits: usize,
+        count: usize,
+        mut sink: F,
+    ) -> Result<()> {
         if count == 0 {
-            return Ok(Vec::new());
+            return Ok(());
         }
 
-        let mut result = Vec::with_capacity(count);
+        // A word loaded at a bit offset of up to 7 holds at least 57 bits
+        if nbits == 0 || nbits > 56 {
+            for _ in 0..count {
+                sink(self.get_arbitary_bits(nbits)?);
+            }
+            return Ok(());
+        }
 
-        // Fast path: byte-aligned and byte-multiple bit widths
-        if self.1 == 0 && nbits % 8 == 0 {
+        if nbits
+            .checked_mul(count)
+            .is_none_or(|bits| bits > self.remaining_bits())
+        {
+            return Err(self.error("Not enough data for batch read"));
+        }
+        let data = self.0;
+
+        let mut pos = self.1;
+        let mut remaining = count;
+        while remaining > 0 {
+            let byte = pos / 8;
+            let word = if byte + 8 <= data.len() {
+                u64::from_be_bytes(data[byte..byte + 8].try_into().unwrap())
+            } else {
+                let mut buf = [0u8; 8];
+                buf[..data.len() - byte].copy_from_slice(&data[byte..]);
+                u64::from_be_bytes(buf)
+            };
+
+            let mut word = word << (pos % 8);
+            let mut available = 64 - pos % 8;
+            while available >= nbits && remaining > 0 {
+                sink(word >> (64 - nbits));
+                word <<= nbits;
+                available -= nbits;
+                pos += nbits;
+                remaining -= 1;
+            }
+        }
+
+        self.0 = &data[pos / 8..];
+        self.1 = pos % 8;
+        Ok(())
+    }
+
+    /// Batch read multiple values with the same bit width
+    /// Optimized for arrays of numeric data
+    #[inline]
+    pub fn get_batch_same_width(&mut self, nbits: usize, count: usize) -> Result<Vec<u64>> {
+        if count == 0 {
+            return Ok(Vec::new());
+        }
+        if nbits
+            .checked_mul(count)
+            .is_none_or(|bits| bits > self.remaining_bits())
+        {
+            return Err(self.error("Not enough data for batch read"));
+        }
+
+        let mut result = Vec::with_capacity(count);
+
+        // Fast path: byte-aligned and byte-multiple bit widths
+        if self.1 == 0 && nbits % 8 == 0 {
             let bytes_per_item = nbits / 8;
             let total_bytes = bytes_per_item * count;
 
             if self.0.len() < total_bytes {
-                return Err(Error::ParseError(
-                    "Not enough data for batch read".to_string(),
-                ));
+                return Err(self.error("Not enough data for batch read"));
             }
 
             match nbits {
@@ -1192,10 +2421,8 @@ impl<'a> BitInput<'a> {
             return Ok(result);
         }
 
-        // Non-aligned or non-byte-multiple: fall back to individual reads
-        for _ in 0..count {
-            result.push(self.get_arbitary_bits(nbits)?);
-        }
+        // Non-aligned or non-byte-multiple: unpack word by word
+        self.unpack_same_width(nbits, count, |v| result.push(v))?;
 
         Ok(result)
     }
@@ -1209,7 +2436,7 @@ impl<'a> BitInput<'a> {
         match nbits {
             8 => {
                 if byte_data.is_empty() {
-                    return Err(Error::ParseError("Not enough data".to_string()));
+                    return Err(self.error("Not enough data"));
                 }
                 self.0 = &self.0[1..];
                 self.1 = 0;
@@ -1217,7 +2444,7 @@ impl<'a> BitInput<'a> {
             }
             16 => {
                 if byte_data.len() < 2 {
-                    return Err(Error::ParseError("Not enough data".to_string()));
+                    return Err(self.error("Not enough data"));
                 }
                 let value = u16::from_be_bytes([byte_data[0], byte_data[1]]) as u64;
                 self.0 = &self.0[2..];
@@ -1226,7 +2453,7 @@ impl<'a> BitInput<'a> {
             }
             24 => {
                 if byte_data.len() < 3 {
-                    return Err(Error::ParseError("Not enough data".to_string()));
+                    return Err(self.error("Not enough data"));
                 }
                 let value = ((byte_data[0] as u64) << 16)
                     | ((byte_data[1] as u64) << 8)
@@ -1237,7 +2464,7 @@ impl<'a> BitInput<'a> {
             }
             32 => {
                 if byte_data.len() < 4 {
-                    return Err(Error::ParseError("Not enough data".to_string()));
+                    return Err(self.error("Not enough data"));
                 }
                 let value =
                     u32::from_be_bytes([byte_data[0], byte_data[1], byte_data[2], byte_data[3]])
@@ -1250,7 +2477,7 @@ impl<'a> BitInput<'a> {
                 // Generic byte-aligned path
                 let nbytes = (nbits + 7) / 8;
                 if byte_data.len() < nbytes {
-                    return Err(Error::ParseError("Not enough data".to_string()));
+                    return Err(self.error("Not enough data"));
                 }
 
                 let mut value: u64 = 0;
@@ -1286,9 +2513,7 @@ impl<'a> BitInput<'a> {
     #[inline]
     fn get_arbitary_bits_unaligned(&mut self, nbits: usize) -> Result<u64> {
         if nbits > 64 {
-            return Err(Error::ParseError(
-                "Cannot read more than 64 bits".to_string(),
-            ));
+            return Err(self.error("Cannot read more than 64 bits"));
         }
 
         let bit_offset = self.1;
@@ -1299,7 +2524,7 @@ impl<'a> BitInput<'a> {
         let bytes_needed = (total_bits_needed + 7) / 8;
 
         if self.0.len() < bytes_needed {
-            return Err(Error::ParseError("Not enough data".to_string()));
+            return Err(self.error("Not enough data"));
         }
 
         // Read up to 8 bytes into a u64 buffer for fast bit extraction
@@ -1344,100 +2569,802 @@ trait Container<'a>
 where
     Self: Sized,
 {
-    fn push(&mut self, value: Value, name: &'a str, unit: &'a str);
+    fn push(
+        &mut self,
+        value: Value<'a>,
+        fxy: FXY,
+        encoding: Option<Encoding>,
+        name: &'a str,
+        unit: &'a str,
+    );
+
+    fn push_array(
+        &mut self,
+        values: &mut [f64],
+        missing: &MissingMask,
+        fxy: FXY,
+        encoding: Encoding,
+        name: &'a str,
+        unit: &'a str,
+    );
+
+    fn push_array_f32(
+        &mut self,
+        values: &mut [f32],
+        missing: &MissingMask,
+        fxy: FXY,
+        encoding: Encoding,
+        name: &'a str,
+        unit: &'a str,
+    );
+
+    fn push_repeat(
+        &mut self,
+        values: Vec<Value<'a>>,
+        fxy: FXY,
+        encoding: Encoding,
+        name: &'a str,
+        unit: &'a str,
+    );
+
+    /// Whether pushed values are kept; compiled arrays are skipped by width when not
+    fn keeps_values(&self) -> bool {
+        true
+    }
+
+    /// Whether `begin_sequence` and `end_sequence` are wanted; each costs a
+    /// stack frame per sequence, so only containers that nest ask for them
+    fn keeps_structure(&self) -> bool {
+        false
+    }
+
+    fn begin_sequence(&mut self, _fxy: FXY, _name: &'a str) {}
+
+    fn end_sequence(&mut self) {}
+
+    /// Whether fixed-layout replications may arrive as whole arrays through
+    /// `push_array`; when not, every element goes through `push`
+    fn compiles_arrays(&self) -> bool {
+        true
+    }
+
+    /// Whether decoding should end here, checked after every step
+    fn stopped(&self) -> bool {
+        false
+    }
+
+    /// Bits of the data section read so far, given after every step
+    fn at_bit(&mut self, _position: usize) {}
+
+    fn begin_replication(&mut self, _fxy: FXY, _times: usize) {}
+
+    /// A delayed replication read with a count of 0
+    fn empty_replication(&mut self, _fxy: FXY) {}
+
+    /// Iteration `iteration` of `count` of replication `fxy` begins; only
+    /// with `Decoder::with_group_markers`
+    fn begin_group(&mut self, _fxy: FXY, _iteration: usize, _count: usize) {}
+
+    /// Iteration `iteration` of `count` of replication `fxy` ends
+    fn end_group(&mut self, _fxy: FXY, _iteration: usize, _count: usize) {}
+
+    fn operator(&mut self, _fxy: FXY) {}
+
+    /// Records kept so far, the index of the next one pushed; containers
+    /// that keep no records stay at 0
+    fn record_count(&self) -> usize {
+        0
+    }
+
+    /// Note quality information linked to an element by a bit-map
+    fn link_quality(&mut self, _link: QualityLink) {}
+
+    /// Marker handed back to `end_bitmap` when an OPERA bitmap sequence ends
+    #[cfg(feature = "opera")]
+    fn bitmap_start(&self) -> usize {
+        0
+    }
+
+    /// Replace the values pushed since `start` with the assembled pixels
+    #[cfg(feature = "opera")]
+    fn end_bitmap(&mut self, _fxy: FXY, _depth: u8, _start: usize, _name: &'a str) {}
 }
 
 impl<'a> Container<'a> for BUFRParsed<'a> {
-    fn push(&mut self, value: Value, name: &'a str, unit: &'a str) {
-        self.push(value, name, unit);
+    fn push(
+        &mut self,
+        value: Value<'a>,
+        fxy: FXY,
+        encoding: Option<Encoding>,
+        name: &'a str,
+        unit: &'a str,
+    ) {
+        self.push(value, fxy, encoding, name, unit);
+    }
+
+    fn push_array(
+        &mut self,
+        values: &mut [f64],
+        missing: &MissingMask,
+        fxy: FXY,
+        encoding: Encoding,
+        name: &'a str,
+        unit: &'a str,
+    ) {
+        let (values, missing) = self.store_numbers(values, missing);
+        self.records.push(Entry::new(
+            fxy,
+            Some(encoding),
+            Some(name),
+            Stored::Array { values, missing },
+            Some(unit),
+        ));
+    }
+
+    fn push_array_f32(
+        &mut self,
+        values: &mut [f32],
+        missing: &MissingMask,
+        fxy: FXY,
+        encoding: Encoding,
+        name: &'a str,
+        unit: &'a str,
+    ) {
+        let (values, missing) = self.store_floats(values, missing);
+        self.records.push(Entry::new(
+            fxy,
+            Some(encoding),
+            Some(name),
+            Stored::ArrayF32 { values, missing },
+            Some(unit),
+        ));
+    }
+
+    fn push_repeat(
+        &mut self,
+        values: Vec<Value<'a>>,
+        fxy: FXY,
+        encoding: Encoding,
+        name: &'a str,
+        unit: &'a str,
+    ) {
+        let values = self.store_values(values);
+        self.records.push(Entry::new(
+            fxy,
+            Some(encoding),
+            Some(name),
+            Stored::Repeat(values),
+            Some(unit),
+        ));
+    }
+
+    fn empty_replication(&mut self, fxy: FXY) {
+        let at = self.values.len();
+        self.records.push(Entry::new(
+            fxy,
+            None,
+            Some(EMPTY_REPLICATION_NAME),
+            Stored::Repeat(at..at),
+            None,
+        ));
+    }
+
+    fn begin_group(&mut self, fxy: FXY, iteration: usize, count: usize) {
+        self.push_group(fxy, true, iteration, count);
+    }
+
+    fn end_group(&mut self, fxy: FXY, iteration: usize, count: usize) {
+        self.push_group(fxy, false, iteration, count);
+    }
+
+    fn record_count(&self) -> usize {
+        self.records.len()
+    }
+
+    fn link_quality(&mut self, link: QualityLink) {
+        self.quality.push(link);
+    }
+
+    #[cfg(feature = "opera")]
+    fn bitmap_start(&self) -> usize {
+        self.records.len()
+    }
+
+    #[cfg(feature = "opera")]
+    fn end_bitmap(&mut self, fxy: FXY, depth: u8, start: usize, name: &'a str) {
+        use crate::opera::OperaBitmapParser;
+
+        if start > self.records.len() {
+            return;
+        }
+        let (pixels, missing) = {
+            let mut parser = OperaBitmapParser::new(depth);
+            for entry in &self.records[start..] {
+                let record = self.view(entry);
+                parser.push_data(record.fxy, &record.values);
+            }
+            parser.pixels()
+        };
+        self.truncate(start);
+
+        // Grid size from the projection sequence decoded ahead of the image
+        let last = |fxy: FXY| {
+            self.records
+                .iter()
+                .rev()
+                .find(|r| r.fxy == fxy)
+                .and_then(|r| match &r.data {
+                    Stored::Single(Value::Number(n)) => Some(*n as usize),
+                    _ => None,
+                })
+        };
+        let grid = match (last(FXY::new(0, 30, 22)), last(FXY::new(0, 30, 21))) {
+            (Some(rows), Some(cols)) if rows * cols == pixels.len() => Some((rows, cols)),
+            _ => None,
+        };
+        let (values, missing) = self.store_numbers(&pixels, &missing);
+        let data = match grid {
+            Some((rows, cols)) => Stored::Array2 {
+                rows,
+                cols,
+                values,
+                missing,
+            },
+            None => Stored::Array { values, missing },
+        };
+
+        let encoding = Encoding {
+            scale: 0,
+            reference: 0,
+            width_bits: depth as u32 * 8,
+            raw: None,
+        };
+        self.records
+            .push(Entry::new(fxy, Some(encoding), Some(name), data, None));
     }
 }
 
-#[derive(Clone)]
-pub struct BUFRParsed<'a> {
-    records: Vec<BUFRRecord<'a>>,
+/// Flat `(FXY, value)` stream without names, units or records
+impl<'a> Container<'a> for Vec<(FXY, Value<'a>)> {
+    fn push(
+        &mut self,
+        value: Value<'a>,
+        fxy: FXY,
+        _encoding: Option<Encoding>,
+        _name: &'a str,
+        _unit: &'a str,
+    ) {
+        self.push((fxy, value));
+    }
+
+    fn push_array(
+        &mut self,
+        values: &mut [f64],
+        missing: &MissingMask,
+        fxy: FXY,
+        _encoding: Encoding,
+        _name: &'a str,
+        _unit: &'a str,
+    ) {
+        self.extend(values.iter().enumerate().map(|(i, &v)| {
+            if missing.get(i) {
+                (fxy, Value::Missing)
+            } else {
+                (fxy, Value::Number(v))
+            }
+        }));
+    }
+
+    fn push_array_f32(
+        &mut self,
+        values: &mut [f32],
+        missing: &MissingMask,
+        fxy: FXY,
+        _encoding: Encoding,
+        _name: &'a str,
+        _unit: &'a str,
+    ) {
+        self.extend(values.iter().enumerate().map(|(i, &v)| {
+            if missing.get(i) {
+                (fxy, Value::Missing)
+            } else {
+                (fxy, Value::Number(v as f64))
+            }
+        }));
+    }
+
+    fn push_repeat(
+        &mut self,
+        values: Vec<Value<'a>>,
+        fxy: FXY,
+        _encoding: Encoding,
+        _name: &'a str,
+        _unit: &'a str,
+    ) {
+        self.extend(values.into_iter().map(|v| (fxy, v)));
+    }
 }
 
-impl<'a> BUFRParsed<'a> {
-    pub fn new() -> Self {
-        Self { records: vec![] }
+/// Latitude/longitude window in degrees, edges included
+///
+/// A box with `min_lon > max_lon` crosses the antimeridian.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    pub min_lat: f64,
+    pub max_lat: f64,
+    pub min_lon: f64,
+    pub max_lon: f64,
+}
+
+impl BoundingBox {
+    pub fn new(min_lat: f64, max_lat: f64, min_lon: f64, max_lon: f64) -> Self {
+        BoundingBox {
+            min_lat,
+            max_lat,
+            min_lon,
+            max_lon,
+        }
     }
 
-    fn push(&mut self, value: Value, element_name: &'a str, unit: &'a str) {
-        self.records.push(BUFRRecord {
-            name: Some(Cow::Borrowed(element_name)),
-            values: BUFRData::Single(value),
-            unit: Some(Cow::Borrowed(unit)),
-        });
+    pub fn contains(&self, lat: f64, lon: f64) -> bool {
+        let lon_inside = if self.min_lon <= self.max_lon {
+            (self.min_lon..=self.max_lon).contains(&lon)
+        } else {
+            lon >= self.min_lon || lon <= self.max_lon
+        };
+        (self.min_lat..=self.max_lat).contains(&lat) && lon_inside
     }
+}
 
-    fn start_array<'s>(&'s mut self, time: usize) -> Array<'a, 's> {
-        Array {
-            parsed: self,
-            values: Vec::with_capacity(time),
+const LATITUDES: [FXY; 2] = [FXY::new(0, 5, 1), FXY::new(0, 5, 2)];
+const LONGITUDES: [FXY; 2] = [FXY::new(0, 6, 1), FXY::new(0, 6, 2)];
+
+/// Passes one subset on to `inner` until its position turns out to be
+/// outside `bbox`, then walks the rest like `Discard`
+struct Clip<C> {
+    bbox: Option<BoundingBox>,
+    inner: C,
+    lat: Option<f64>,
+    lon: Option<f64>,
+    outside: bool,
+}
+
+impl<C> Clip<C> {
+    fn new(bbox: Option<BoundingBox>, inner: C) -> Self {
+        Clip {
+            bbox,
+            inner,
+            lat: None,
+            lon: None,
+            outside: false,
         }
     }
 
-    pub fn into_owned(&self) -> BUFRParsed<'static> {
-        BUFRParsed {
-            records: self.records.iter().map(|r| r.into_owned()).collect(),
+    /// What `inner` collected, `None` for a subset outside the box
+    fn finish(self) -> Option<C> {
+        (!self.outside).then_some(self.inner)
+    }
+
+    fn locate(&mut self, value: &Value, fxy: FXY) {
+        let Some(bbox) = self.bbox else {
+            return;
+        };
+        let Value::Number(n) = *value else {
+            return;
+        };
+        if self.lat.is_none() && LATITUDES.contains(&fxy) {
+            self.lat = Some(n);
+        } else if self.lon.is_none() && LONGITUDES.contains(&fxy) {
+            self.lon = Some(n);
+        } else {
+            return;
+        }
+
+        if let (Some(lat), Some(lon)) = (self.lat, self.lon) {
+            self.outside = !bbox.contains(lat, lon);
         }
     }
 }
 
-struct Array<'a, 's> {
-    parsed: &'s mut BUFRParsed<'a>,
-    values: Vec<f64>,
+impl<'a, C: Container<'a>> Container<'a> for Clip<C> {
+    fn push(
+        &mut self,
+        value: Value<'a>,
+        fxy: FXY,
+        encoding: Option<Encoding>,
+        name: &'a str,
+        unit: &'a str,
+    ) {
+        if self.outside {
+            return;
+        }
+        self.locate(&value, fxy);
+        if !self.outside {
+            self.inner.push(value, fxy, encoding, name, unit);
+        }
+    }
+
+    fn push_array(
+        &mut self,
+        values: &mut [f64],
+        missing: &MissingMask,
+        fxy: FXY,
+        encoding: Encoding,
+        name: &'a str,
+        unit: &'a str,
+    ) {
+        if !self.outside {
+            self.inner
+                .push_array(values, missing, fxy, encoding, name, unit);
+        }
+    }
+
+    fn push_array_f32(
+        &mut self,
+        values: &mut [f32],
+        missing: &MissingMask,
+        fxy: FXY,
+        encoding: Encoding,
+        name: &'a str,
+        unit: &'a str,
+    ) {
+        if !self.outside {
+            self.inner
+                .push_array_f32(values, missing, fxy, encoding, name, unit);
+        }
+    }
+
+    fn push_repeat(
+        &mut self,
+        values: Vec<Value<'a>>,
+        fxy: FXY,
+        encoding: Encoding,
+        name: &'a str,
+        unit: &'a str,
+    ) {
+        if !self.outside {
+            self.inner.push_repeat(values, fxy, encoding, name, unit);
+        }
+    }
+
+    fn keeps_values(&self) -> bool {
+        !self.outside && self.inner.keeps_values()
+    }
+
+    fn keeps_structure(&self) -> bool {
+        self.inner.keeps_structure()
+    }
+
+    fn begin_sequence(&mut self, fxy: FXY, name: &'a str) {
+        if !self.outside {
+            self.inner.begin_sequence(fxy, name);
+        }
+    }
+
+    fn end_sequence(&mut self) {
+        if !self.outside {
+            self.inner.end_sequence();
+        }
+    }
+
+    fn compiles_arrays(&self) -> bool {
+        self.inner.compiles_arrays()
+    }
+
+    fn stopped(&self) -> bool {
+        self.inner.stopped()
+    }
+
+    fn at_bit(&mut self, position: usize) {
+        self.inner.at_bit(position);
+    }
+
+    fn begin_replication(&mut self, fxy: FXY, times: usize) {
+        if !self.outside {
+            self.inner.begin_replication(fxy, times);
+        }
+    }
+
+    fn empty_replication(&mut self, fxy: FXY) {
+        if !self.outside {
+            self.inner.empty_replication(fxy);
+        }
+    }
+
+    fn begin_group(&mut self, fxy: FXY, iteration: usize, count: usize) {
+        if !self.outside {
+            self.inner.begin_group(fxy, iteration, count);
+        }
+    }
+
+    fn end_group(&mut self, fxy: FXY, iteration: usize, count: usize) {
+        if !self.outside {
+            self.inner.end_group(fxy, iteration, count);
+        }
+    }
+
+    fn operator(&mut self, fxy: FXY) {
+        if !self.outside {
+            self.inner.operator(fxy);
+        }
+    }
+
+    fn record_count(&self) -> usize {
+        self.inner.record_count()
+    }
+
+    fn link_quality(&mut self, link: QualityLink) {
+        if !self.outside {
+            self.inner.link_quality(link);
+        }
+    }
+
+    #[cfg(feature = "opera")]
+    fn bitmap_start(&self) -> usize {
+        self.inner.bitmap_start()
+    }
+
+    #[cfg(feature = "opera")]
+    fn end_bitmap(&mut self, fxy: FXY, depth: u8, start: usize, name: &'a str) {
+        if !self.outside {
+            self.inner.end_bitmap(fxy, depth, start, name);
+        }
+    }
 }
 
-impl<'a> Array<'a, '_> {
-    fn set_values(&mut self, values: Vec<f64>) {
-        self.values = values;
+/// Walks the bitstream without collecting anything, used to skip subsets
+struct Discard;
+
+impl<'a> Container<'a> for Discard {
+    fn push(&mut self, _: Value<'a>, _: FXY, _: Option<Encoding>, _: &'a str, _: &'a str) {}
+
+    fn push_array(
+        &mut self,
+        _: &mut [f64],
+        _: &MissingMask,
+        _: FXY,
+        _: Encoding,
+        _: &'a str,
+        _: &'a str,
+    ) {
+    }
+
+    fn push_array_f32(
+        &mut self,
+        _: &mut [f32],
+        _: &MissingMask,
+        _: FXY,
+        _: Encoding,
+        _: &'a str,
+        _: &'a str,
+    ) {
+    }
+
+    fn push_repeat(&mut self, _: Vec<Value<'a>>, _: FXY, _: Encoding, _: &'a str, _: &'a str) {}
+
+    fn keeps_values(&self) -> bool {
+        false
     }
+}
 
-    fn finish(self, name: Option<&'a str>, unit: Option<&'a str>) {
-        let recording = BUFRRecord {
-            name: name.map(|n| Cow::Borrowed(n)),
-            values: BUFRData::Array(self.values),
-            unit: unit.map(|u| Cow::Borrowed(u)),
+#[derive(Clone, serde::Deserialize, serde::Serialize)]
+pub struct BUFRParsed<'a> {
+    records: Vec<Entry<'a>>,
+    /// Values of `Array` and `Array2` records, one after the other
+    numbers: Vec<f64>,
+    /// Values of `ArrayF32` records
+    floats: Vec<f32>,
+    /// Values of `Repeat` records
+    values: Vec<Value<'a>>,
+    /// Missing masks of every array, each starting on a word of its own
+    missing: Vec<u64>,
+    #[serde(default)]
+    quality: Vec<QualityLink>,
+    /// Decoded from compressed data, each record holding one element for
+    /// every subset
+    #[serde(default)]
+    compressed: bool,
+}
+
+impl<'a> BUFRParsed<'a> {
+    pub fn new() -> Self {
+        Self {
+            records: vec![],
+            numbers: vec![],
+            floats: vec![],
+            values: vec![],
+            missing: vec![],
+            quality: vec![],
+            compressed: false,
+        }
+    }
+
+    fn push(
+        &mut self,
+        value: Value<'a>,
+        fxy: FXY,
+        encoding: Option<Encoding>,
+        element_name: &'a str,
+        unit: &'a str,
+    ) {
+        self.records.push(Entry::new(
+            fxy,
+            encoding,
+            Some(element_name),
+            Stored::Single(value),
+            Some(unit),
+        ));
+    }
+
+    fn push_group(&mut self, fxy: FXY, begin: bool, iteration: usize, count: usize) {
+        let name = if begin {
+            GROUP_BEGIN_NAME
+        } else {
+            GROUP_END_NAME
         };
-        self.parsed.records.push(recording);
+        self.records.push(Entry::new(
+            fxy,
+            None,
+            Some(name),
+            Stored::Group {
+                begin,
+                iteration,
+                count,
+            },
+            None,
+        ));
     }
 }
 
-#[derive(Debug, Clone)]
-pub enum BUFRData {
-    Repeat(Vec<Value>),
-    Single(Value),
-    Array(Vec<f64>),
+/// Result of `Decoder::decode_output`
+pub enum Decoded<'a> {
+    Flat(BUFRParsed<'a>),
+    Tree(DecodedTree<'a>),
+    Values(Vec<(FXY, Value<'a>)>),
+}
+
+/// Values of a record, borrowed from the buffers of its `BUFRParsed`
+/// unless `into_owned` copied them
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub enum BUFRData<'a> {
+    Repeat(Cow<'a, [Value<'a>]>),
+    Single(Value<'a>),
+    /// `values` holds the `MissingValues` fill where `missing` is set
+    Array {
+        values: Cow<'a, [f64]>,
+        missing: MissingMask<'a>,
+    },
+    ArrayF32 {
+        values: Cow<'a, [f32]>,
+        missing: MissingMask<'a>,
+    },
+    /// Row-major raster
+    Array2 {
+        rows: usize,
+        cols: usize,
+        values: Cow<'a, [f64]>,
+        missing: MissingMask<'a>,
+    },
+    /// Where iteration `iteration` of `count` of the replication in the
+    /// record's `fxy` begins or ends; see `Decoder::with_group_markers`
+    Group {
+        begin: bool,
+        iteration: usize,
+        count: usize,
+    },
+}
+
+impl BUFRData<'_> {
+    /// The value at `index`, a single value being at 0; `None` past the end
+    /// and for group markers
+    pub fn get(&self, index: usize) -> Option<Value<'_>> {
+        let number = |value: f64, missing: bool| {
+            if missing {
+                Value::Missing
+            } else {
+                Value::Number(value)
+            }
+        };
+        match self {
+            BUFRData::Single(value) => (index == 0).then(|| value.borrowed()),
+            BUFRData::Repeat(values) => values.get(index).map(Value::borrowed),
+            BUFRData::Array { values, missing }
+            | BUFRData::Array2 {
+                values, missing, ..
+            } => Some(number(*values.get(index)?, missing.get(index))),
+            BUFRData::ArrayF32 { values, missing } => {
+                Some(number(f64::from(*values.get(index)?), missing.get(index)))
+            }
+            BUFRData::Group { .. } => None,
+        }
+    }
+
+    /// These values with their arrays and strings copied out of the
+    /// buffers and the message they borrow
+    pub fn into_owned(self) -> BUFRData<'static> {
+        match self {
+            BUFRData::Repeat(values) => BUFRData::Repeat(Cow::Owned(
+                values
+                    .into_owned()
+                    .into_iter()
+                    .map(Value::into_owned)
+                    .collect(),
+            )),
+            BUFRData::Single(value) => BUFRData::Single(value.into_owned()),
+            BUFRData::Array { values, missing } => BUFRData::Array {
+                values: Cow::Owned(values.into_owned()),
+                missing: missing.into_owned(),
+            },
+            BUFRData::ArrayF32 { values, missing } => BUFRData::ArrayF32 {
+                values: Cow::Owned(values.into_owned()),
+                missing: missing.into_owned(),
+            },
+            BUFRData::Array2 {
+                rows,
+                cols,
+                values,
+                missing,
+            } => BUFRData::Array2 {
+                rows,
+                cols,
+                values: Cow::Owned(values.into_owned()),
+                missing: missing.into_owned(),
+            },
+            BUFRData::Group {
+                begin,
+                iteration,
+                count,
+            } => BUFRData::Group {
+                begin,
+                iteration,
+                count,
+            },
+        }
+    }
 }
 
-#[derive(Clone)]
+#[derive(Clone, serde::Deserialize, serde::Serialize)]
 pub struct BUFRRecord<'a> {
+    /// Descriptor the values were decoded from
+    pub fxy: FXY,
     // pub name: Option<&'a str>,
     pub name: Option<Cow<'a, str>>,
-    pub values: BUFRData,
+    pub values: BUFRData<'a>,
     pub unit: Option<Cow<'a, str>>,
+    /// Effective scale after operators, `None` for operator output such as 2-05
+    pub scale: Option<i32>,
+    /// Effective reference value after operators
+    pub reference: Option<i32>,
+    /// Effective data width in bits after operators
+    pub width_bits: Option<u32>,
+    /// Unscaled integer as read from the data section, only for single numeric values
+    pub raw: Option<u64>,
 }
 
 impl BUFRRecord<'_> {
     pub fn into_owned(&self) -> BUFRRecord<'static> {
         BUFRRecord {
+            fxy: self.fxy,
+            scale: self.scale,
+            reference: self.reference,
+            width_bits: self.width_bits,
+            raw: self.raw,
             name: self.name.as_ref().map(|s| Cow::Owned(s.to_string())),
-            values: match &self.values {
-                BUFRData::Single(v) => BUFRData::Single(v.clone()),
-                BUFRData::Repeat(vs) => BUFRData::Repeat(vs.clone()),
-                BUFRData::Array(a) => BUFRData::Array(a.clone()),
-            },
+            values: self.values.clone().into_owned(),
             unit: self.unit.as_ref().map(|s| Cow::Owned(s.to_string())),
         }
     }
+
+    /// The ecCodes key of the record's element, `None` without a name
+    pub fn eccodes_key(&self) -> Option<String> {
+        self.name.as_deref().map(|name| eccodes_key(self.fxy, name))
+    }
 }
 
 impl Display for BUFRRecord<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let is_print_unit = match self.unit.as_ref().map(|s| &**s) {
-            Some("CAITT IA5" | "code table" | "code-table" | "flag table" | "flag-table") => false,
+            Some(UNIT_CCITT_IA5 | UNIT_CODE_TABLE | UNIT_FLAG_TABLE) => false,
             None => false,
             _ => true,
         };
@@ -1460,6 +3387,7 @@ impl Display for BUFRRecord<'_> {
                 match v {
                     Value::Missing => write!(f, "MISSING")?,
                     Value::String(s) => write!(f, "\"{}\"", s)?,
+                    Value::Bytes(b) => write!(f, "{}", hex(b))?,
                     Value::Number(n) => {
                         if is_print_unit {
                             write!(f, "{:>12.6} {}", n, self.unit.as_ref().unwrap())?;
@@ -1472,8 +3400,36 @@ impl Display for BUFRRecord<'_> {
             BUFRData::Repeat(vs) => {
                 self.format_sequence(f, name, vs, is_print_unit, width)?;
             }
-            BUFRData::Array(a) => {
-                self.format_array(f, name, a, is_print_unit, width)?;
+            BUFRData::Array { values, missing } => {
+                self.format_array(f, name, values, missing, is_print_unit, width)?;
+            }
+            BUFRData::ArrayF32 { values, missing } => {
+                let values: Vec<f64> = values.iter().map(|&v| v as f64).collect();
+                self.format_array(f, name, &values, missing, is_print_unit, width)?;
+            }
+            BUFRData::Array2 {
+                rows,
+                cols,
+                values,
+                missing,
+            } => {
+                self.format_array(f, name, values, missing, is_print_unit, width)?;
+                write!(f, " ({}x{})", rows, cols)?;
+            }
+            BUFRData::Group {
+                iteration, count, ..
+            } => {
+                write!(
+                    f,
+                    "{:<width$} : {}-{:02}-{:03} {}/{}",
+                    name,
+                    self.fxy.f,
+                    self.fxy.x,
+                    self.fxy.y,
+                    iteration + 1,
+                    count,
+                    width = width
+                )?;
             }
         }
 
@@ -1545,11 +3501,17 @@ impl BUFRRecord<'_> {
         f: &mut std::fmt::Formatter<'_>,
         name: &str,
         values: &[f64],
+        missing: &MissingMask,
         is_print_unit: bool,
         width: usize,
     ) -> std::fmt::Result {
-        let missing_count = values.iter().filter(|&&v| v == MISS_VAL).count();
-        let valid_values: Vec<f64> = values.iter().copied().filter(|&v| v != MISS_VAL).collect();
+        let missing_count = missing.count();
+        let valid_values: Vec<f64> = values
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| !missing.get(i))
+            .map(|(_, &v)| v)
+            .collect();
 
         if width > 0 {
             write!(f, "{:<width$} : ", name, width = width)?;
@@ -1590,7 +3552,7 @@ impl BUFRRecord<'_> {
                     if i > 0 {
                         write!(f, ", ")?;
                     }
-                    if *v == MISS_VAL {
+                    if missing.get(i) {
                         write!(f, "MISSING")?;
                     } else {
                         write!(f, "{:.3}", v)?;
@@ -1603,18 +3565,19 @@ impl BUFRRecord<'_> {
                     if i > 0 {
                         write!(f, ", ")?;
                     }
-                    if *v == MISS_VAL {
+                    if missing.get(i) {
                         write!(f, "MISSING")?;
                     } else {
                         write!(f, "{:.3}", v)?;
                     }
                 }
                 write!(f, " ... ")?;
-                for (i, v) in values.iter().skip(values.len() - 2).enumerate() {
+                let tail = values.len() - 2;
+                for (i, v) in values.iter().skip(tail).enumerate() {
                     if i > 0 {
                         write!(f, ", ")?;
                     }
-                    if *v == MISS_VAL {
+                    if missing.get(tail + i) {
                         write!(f, "MISSING")?;
                     } else {
                         write!(f, "{:.3}", v)?;
@@ -1636,6 +3599,7 @@ impl BUFRRecord<'_> {
         match value {
             Value::Missing => write!(f, "MISSING"),
             Value::String(s) => write!(f, "\"{}\"", s),
+            Value::Bytes(b) => write!(f, "{}", hex(b)),
             Value::Number(n) => {
                 if is_print_unit {
                     write!(f, "{:.3}", n)
@@ -1661,7 +3625,7 @@ impl Display for BUFRParsed<'_> {
             .unwrap_or(0)
             .min(50); // 限制最大宽度
 
-        for record in &self.records {
+        for record in self {
             writeln!(f, "{:<max_name_len$}", record, max_name_len = max_name_len)?;
         }
 
@@ -1669,13 +3633,113 @@ impl Display for BUFRParsed<'_> {
     }
 }
 
-impl BUFRParsed<'_> {
+impl<'a> BUFRParsed<'a> {
     pub fn record_count(&self) -> usize {
         self.records.len()
     }
 
-    pub fn records(&self) -> &[BUFRRecord<'_>] {
-        &self.records
+    /// Every record, in decode order
+    pub fn records(&self) -> Records<'_, 'a> {
+        Records {
+            parsed: self,
+            inner: self.records.iter(),
+        }
+    }
+
+    pub fn iter(&self) -> Records<'_, 'a> {
+        self.records()
+    }
+
+    /// First record whose element name is exactly `name`
+    pub fn get(&self, name: &str) -> Option<BUFRRecord<'_>> {
+        self.iter().named(name).next()
+    }
+
+    /// Every record whose element name is exactly `name`, in decode order
+    pub fn get_all(&self, name: &str) -> Vec<BUFRRecord<'_>> {
+        self.iter().named(name).collect()
+    }
+
+    /// The `rank`-th occurrence of the element named `name`, counting from
+    /// 1 in decode order as ecCodes' `#rank#key` does: the record holding
+    /// it and its index among the record's values
+    ///
+    /// Every value of a replication decoded as an array is an occurrence
+    /// of its own; in compressed data a record, which holds the element
+    /// for every subset, is one occurrence.
+    pub fn get_ranked(&self, name: &str, rank: usize) -> Option<(BUFRRecord<'_>, usize)> {
+        let mut rank = rank.checked_sub(1)?;
+        for record in self.iter().named(name) {
+            let span = self.rank_span(&record);
+            if rank < span {
+                return Some((record, rank));
+            }
+            rank -= span;
+        }
+        None
+    }
+
+    /// How many occurrences of the element named `name` there are, the
+    /// highest rank `get_ranked` accepts
+    pub fn ranks(&self, name: &str) -> usize {
+        self.iter()
+            .named(name)
+            .map(|record| self.rank_span(&record))
+            .sum()
+    }
+
+    /// `#rank#name` of every occurrence of every named element, in decode
+    /// order
+    pub fn ranked_names(&self) -> Vec<String> {
+        let mut ranks: FxHashMap<&str, usize> = FxHashMap::default();
+        let mut names = vec![];
+        for entry in &self.records {
+            let Some(name) = entry.name.as_deref() else {
+                continue;
+            };
+            let span = self.rank_span(&self.view(entry));
+            let rank = ranks.entry(name).or_insert(0);
+            for _ in 0..span {
+                *rank += 1;
+                names.push(format!("#{}#{}", rank, name));
+            }
+        }
+        names
+    }
+
+    /// Occurrences `record` stands for, in `get_ranked`'s counting
+    fn rank_span(&self, record: &BUFRRecord<'_>) -> usize {
+        match &record.values {
+            BUFRData::Group { .. } => 0,
+            _ if self.compressed => 1,
+            BUFRData::Single(_) => 1,
+            BUFRData::Repeat(values) => values.len(),
+            BUFRData::Array { values, .. } | BUFRData::Array2 { values, .. } => values.len(),
+            BUFRData::ArrayF32 { values, .. } => values.len(),
+        }
+    }
+
+    /// First record whose ecCodes key is `key`, e.g. `airTemperature`; see
+    /// `keys::eccodes_key`
+    pub fn get_key(&self, key: &str) -> Option<BUFRRecord<'_>> {
+        self.iter().keyed(key).next()
+    }
+
+    /// Every record whose ecCodes key is `key`, in decode order
+    pub fn get_all_key(&self, key: &str) -> Vec<BUFRRecord<'_>> {
+        self.iter().keyed(key).collect()
+    }
+
+    /// Every record decoded from the element descriptor `fxy`
+    pub fn get_by_fxy(&self, fxy: FXY) -> Vec<BUFRRecord<'_>> {
+        self.iter().with_fxy(fxy).collect()
+    }
+
+    /// Every record whose element name matches `pattern`
+    pub fn select(&self, pattern: &Regex) -> Vec<BUFRRecord<'_>> {
+        self.iter()
+            .filter(|r| r.name.as_deref().is_some_and(|n| pattern.is_match(n)))
+            .collect()
     }
 
     pub fn display_compact(&self) -> CompactDisplay<'_> {
@@ -1687,11 +3751,78 @@ impl BUFRParsed<'_> {
     }
 }
 
+/// Iterator over the records of a `BUFRParsed`, in decode order
+pub struct Records<'r, 'a> {
+    parsed: &'r BUFRParsed<'a>,
+    inner: std::slice::Iter<'r, Entry<'a>>,
+}
+
+impl<'r> Iterator for Records<'r, '_> {
+    type Item = BUFRRecord<'r>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|entry| self.parsed.view(entry))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for Records<'_, '_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|entry| self.parsed.view(entry))
+    }
+}
+
+impl ExactSizeIterator for Records<'_, '_> {}
+
+impl<'r> Records<'r, '_> {
+    /// Records whose element name is exactly `name`
+    pub fn named(self, name: &str) -> impl Iterator<Item = BUFRRecord<'r>> {
+        self.filter(move |r| r.name.as_deref() == Some(name))
+    }
+
+    /// Records whose ecCodes key is `key`
+    pub fn keyed(self, key: &str) -> impl Iterator<Item = BUFRRecord<'r>> {
+        self.filter(move |r| r.eccodes_key().as_deref() == Some(key))
+    }
+
+    /// Records decoded from the descriptor `fxy`
+    pub fn with_fxy(self, fxy: FXY) -> impl Iterator<Item = BUFRRecord<'r>> {
+        self.filter(move |r| r.fxy == fxy)
+    }
+
+    /// Records holding a single value
+    pub fn singles(self) -> impl Iterator<Item = BUFRRecord<'r>> {
+        self.filter(|r| matches!(r.values, BUFRData::Single(_)))
+    }
+
+    /// Records holding an array: compiled `f64` or `f32`, or a raster
+    pub fn arrays(self) -> impl Iterator<Item = BUFRRecord<'r>> {
+        self.filter(|r| {
+            matches!(
+                r.values,
+                BUFRData::Array { .. } | BUFRData::ArrayF32 { .. } | BUFRData::Array2 { .. }
+            )
+        })
+    }
+}
+
+impl<'r, 'a> IntoIterator for &'r BUFRParsed<'a> {
+    type Item = BUFRRecord<'r>;
+    type IntoIter = Records<'r, 'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.records()
+    }
+}
+
 pub struct CompactDisplay<'a>(&'a BUFRParsed<'a>);
 
 impl Display for CompactDisplay<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for record in &self.0.records {
+        for record in self.0 {
             writeln!(f, "{}", record)?;
         }
         Ok(())
@@ -1706,24 +3837,16 @@ impl Display for DetailedDisplay<'_> {
         writeln!(f)?;
 
         let total_records = self.0.records.len();
-        let single_count = self
-            .0
-            .records
-            .iter()
-            .filter(|r| matches!(r.values, BUFRData::Single(_)))
-            .count();
-        let array_count = self
-            .0
-            .records
-            .iter()
-            .filter(|r| matches!(r.values, BUFRData::Array(_)))
-            .count();
-        let repeat_count = self
-            .0
-            .records
-            .iter()
-            .filter(|r| matches!(r.values, BUFRData::Repeat(_)))
-            .count();
+        let count =
+            |stored: fn(&Stored) -> bool| self.0.records.iter().filter(|r| stored(&r.data)).count();
+        let single_count = count(|d| matches!(d, Stored::Single(_)));
+        let array_count = count(|d| {
+            matches!(
+                d,
+                Stored::Array { .. } | Stored::ArrayF32 { .. } | Stored::Array2 { .. }
+            )
+        });
+        let repeat_count = count(|d| matches!(d, Stored::Repeat(_)));
 
         writeln!(f, "Statistics:")?;
         writeln!(f, "  Total records:     {}", total_records)?;
@@ -1742,7 +3865,7 @@ impl Display for DetailedDisplay<'_> {
             .unwrap_or(0)
             .min(50);
 
-        for (idx, record) in self.0.records.iter().enumerate() {
+        for (idx, record) in self.0.iter().enumerate() {
             writeln!(
                 f,
                 "Record {}: {:<max_name_len$}",
@@ -1756,7 +3879,7 @@ impl Display for DetailedDisplay<'_> {
     }
 }
 
-enum Frame<'v, 'a> {
+enum Frame<'v> {
     Slice {
         descs: Descs<'v>,
         idx: usize,
@@ -1765,11 +3888,28 @@ enum Frame<'v, 'a> {
         descs: Descs<'v>,
         times: usize,
         current: usize,
+        /// The replication, when its iterations are marked
+        group: Option<FXY>,
+    },
+    /// End of one iteration of a marked replication
+    EndGroup {
+        fxy: FXY,
+        iteration: usize,
+        count: usize,
     },
     CompiledArray {
-        layout: CompiledLayout<'a>,
+        layout: Arc<CompiledLayout>,
         times: usize,
     },
+    /// End of a Table D sequence, for containers that keep structure
+    EndSequence,
+    /// End of an OPERA bitmap sequence whose values start at `start`
+    #[cfg(feature = "opera")]
+    OperaBitmap {
+        fxy: FXY,
+        depth: u8,
+        start: usize,
+    },
 }
 
 #[derive(Clone, Copy)]
@@ -0,0 +1,31 @@
+use crate::core::{BUFRKey, FXY};
+
+/// 0-31-021, the significance of the associated fields that follow
+pub const ASSOCIATED_FIELD_SIGNIFICANCE: FXY = FXY::new(0, 31, 21);
+
+/// Whether `fxy` is 0-31-021
+pub(super) fn is_significance<K: BUFRKey>(fxy: &K) -> bool {
+    fxy.f() == 0 && fxy.x() == 31 && fxy.y() == 21
+}
+
+/// Whether an element gets the associated fields in effect; class 31
+/// elements, such as 0-31-021 itself, never do
+pub(super) fn takes_associated_field<K: BUFRKey>(fxy: &K) -> bool {
+    fxy.x() != 31
+}
+
+/// Name of the records associated fields are decoded to, labelled with
+/// their significance from code table 0-31-021
+pub fn associated_field_name(significance: Option<u32>) -> &'static str {
+    match significance {
+        Some(1) => "ASSOCIATED FIELD (1 = 1-BIT INDICATOR OF QUALITY)",
+        Some(2) => "ASSOCIATED FIELD (2 = 2-BIT INDICATOR OF QUALITY)",
+        Some(6) => "ASSOCIATED FIELD (6 = 4-BIT INDICATOR OF QUALITY CONTROL CLASS)",
+        Some(7) => "ASSOCIATED FIELD (7 = PERCENTAGE CONFIDENCE)",
+        Some(8) => "ASSOCIATED FIELD (8 = SUSPECTED VALUE INDICATOR)",
+        Some(9) => "ASSOCIATED FIELD (9 = STATUS OF ASCAT DATA)",
+        Some(21) => "ASSOCIATED FIELD (21 = 1-BIT INDICATOR OF CORRECTION)",
+        Some(22..=62) => "ASSOCIATED FIELD (LOCAL SIGNIFICANCE)",
+        _ => "ASSOCIATED FIELD",
+    }
+}
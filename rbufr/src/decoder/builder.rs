@@ -0,0 +1,561 @@
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "opera")]
+use crate::core::prelude::BUFRTableBitMap;
+use crate::core::{
+    FXY,
+    prelude::{BUFRTableB, BUFRTableD},
+};
+#[cfg(feature = "opera")]
+use crate::tables::BitmapTable;
+use crate::{
+    block::MessageBlock,
+    errors::{Error, Result},
+    structs::versions::MessageVersion,
+    tables::{LocalTable, MasterTable, TableLoader, TableTrait, TableType},
+};
+
+use super::{
+    BoundingBox, Container, Decoder, Encoding, MissingMask, MissingValues, Provenance, QualityLink,
+    TableSource, Value,
+};
+
+/// Table B and Table D of one source
+type Tables = (BUFRTableB, BUFRTableD);
+
+/// Preferred unit and the function taking a value into it
+type Conversion = (&'static str, fn(f64) -> f64);
+
+/// What a decode call does when the data cannot be decoded to the end
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Mode {
+    /// Fail with the error
+    #[default]
+    Strict,
+    /// Return what was decoded before the error
+    Lenient,
+}
+
+/// What happens to single values that are missing in the data
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MissingPolicy {
+    /// Keep them as `Value::Missing`
+    #[default]
+    Keep,
+    /// Leave them out of the output
+    Skip,
+}
+
+/// Units numeric values are reported in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Units {
+    /// The Table B unit, as encoded
+    #[default]
+    Bufr,
+    /// Temperatures in degrees Celsius (`C`) instead of kelvin and pressures
+    /// in hectopascal (`hPa`) instead of pascal; other units are kept.
+    /// Temperature differences, standard deviations and accuracies stay in
+    /// kelvin
+    Meteorological,
+}
+
+/// Class 12 elements in kelvin that are not absolute temperatures
+const TEMPERATURE_DIFFERENCES: [FXY; 12] = [
+    FXY::new(0, 12, 49),
+    FXY::new(0, 12, 51),
+    FXY::new(0, 12, 65),
+    FXY::new(0, 12, 80),
+    FXY::new(0, 12, 81),
+    FXY::new(0, 12, 82),
+    FXY::new(0, 12, 151),
+    FXY::new(0, 12, 158),
+    FXY::new(0, 12, 159),
+    FXY::new(0, 12, 166),
+    FXY::new(0, 12, 167),
+    FXY::new(0, 12, 168),
+];
+
+/// Absolute temperatures outside class 12
+const WATER_TEMPERATURES: [FXY; 7] = [
+    FXY::new(0, 13, 82),
+    FXY::new(0, 22, 41),
+    FXY::new(0, 22, 42),
+    FXY::new(0, 22, 43),
+    FXY::new(0, 22, 45),
+    FXY::new(0, 22, 49),
+    FXY::new(0, 22, 141),
+];
+
+fn is_absolute_temperature(fxy: FXY) -> bool {
+    (fxy.f == 0 && fxy.x == 12 && !TEMPERATURE_DIFFERENCES.contains(&fxy))
+        || WATER_TEMPERATURES.contains(&fxy)
+}
+
+impl Units {
+    /// Preferred unit and conversion for an element, `None` to keep it
+    fn convert(self, fxy: FXY, unit: &str) -> Option<Conversion> {
+        match (self, unit) {
+            (Units::Meteorological, "K") if is_absolute_temperature(fxy) => {
+                Some(("C", |v| v - 273.15))
+            }
+            (Units::Meteorological, "Pa") => Some(("hPa", |v| v / 100.0)),
+            _ => None,
+        }
+    }
+}
+
+/// Shape of the result of `Decoder::decode_output`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Output {
+    /// One record per element, as `Decoder::decode`
+    #[default]
+    Flat,
+    /// Records nested by Table D sequence, as `Decoder::decode_tree`
+    Tree,
+    /// Bare `(FXY, value)` pairs, as `Decoder::decode_values`
+    Values,
+}
+
+/// Configures a `Decoder`; see `Decoder::builder`
+///
+/// Tables not given explicitly are loaded for the message set with
+/// `message`, the same way `Decoder::from_message` does.
+pub struct DecoderBuilder<'m> {
+    message: Option<&'m MessageBlock>,
+    edition: Option<u8>,
+    master: Option<Tables>,
+    local: Option<Tables>,
+    #[cfg(feature = "opera")]
+    opera_bitmap_table: Option<BUFRTableBitMap>,
+    mode: Mode,
+    missing: MissingPolicy,
+    missing_values: MissingValues,
+    units: Units,
+    output: Output,
+    subsets: Option<Range<usize>>,
+    f32_arrays: bool,
+    group_markers: bool,
+    trim_text: bool,
+    bbox: Option<BoundingBox>,
+    tables_path: Option<PathBuf>,
+}
+
+impl Decoder {
+    pub fn builder<'m>() -> DecoderBuilder<'m> {
+        DecoderBuilder {
+            message: None,
+            edition: None,
+            master: None,
+            local: None,
+            #[cfg(feature = "opera")]
+            opera_bitmap_table: None,
+            mode: Mode::default(),
+            missing: MissingPolicy::default(),
+            missing_values: MissingValues::default(),
+            units: Units::default(),
+            output: Output::default(),
+            subsets: None,
+            f32_arrays: false,
+            group_markers: false,
+            trim_text: false,
+            bbox: None,
+            tables_path: None,
+        }
+    }
+}
+
+impl<'m> DecoderBuilder<'m> {
+    /// Load the tables `message` asks for in Section 1
+    pub fn message(mut self, message: &'m MessageBlock) -> Self {
+        self.message = Some(message);
+        self
+    }
+
+    pub fn edition(mut self, edition: u8) -> Self {
+        self.edition = Some(edition);
+        self
+    }
+
+    /// Load tables from `path` instead of the global tables directory
+    pub fn tables_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.tables_path = Some(path.into());
+        self
+    }
+
+    /// Use these master tables instead of loading them
+    pub fn tables(mut self, table_b: BUFRTableB, table_d: BUFRTableD) -> Self {
+        self.master = Some((table_b, table_d));
+        self
+    }
+
+    /// Use these local tables instead of loading them
+    pub fn local_tables(mut self, table_b: BUFRTableB, table_d: BUFRTableD) -> Self {
+        self.local = Some((table_b, table_d));
+        self
+    }
+
+    #[cfg(feature = "opera")]
+    pub fn opera_bitmap_table(mut self, table: BUFRTableBitMap) -> Self {
+        self.opera_bitmap_table = Some(table);
+        self
+    }
+
+    pub fn mode(mut self, mode: Mode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn missing(mut self, missing: MissingPolicy) -> Self {
+        self.missing = missing;
+        self
+    }
+
+    /// How missing elements of arrays are represented
+    pub fn missing_values(mut self, missing_values: MissingValues) -> Self {
+        self.missing_values = missing_values;
+        self
+    }
+
+    pub fn units(mut self, units: Units) -> Self {
+        self.units = units;
+        self
+    }
+
+    pub fn output(mut self, output: Output) -> Self {
+        self.output = output;
+        self
+    }
+
+    /// Decode only the subsets in `range` of uncompressed messages
+    ///
+    /// `decode_subsets` returns those subsets; `decode`, `decode_values`
+    /// and `decode_tree` decode the first of them.
+    pub fn subsets(mut self, range: Range<usize>) -> Self {
+        self.subsets = Some(range);
+        self
+    }
+
+    /// See `Decoder::with_f32_arrays`
+    pub fn f32_arrays(mut self, enabled: bool) -> Self {
+        self.f32_arrays = enabled;
+        self
+    }
+
+    /// See `Decoder::with_group_markers`
+    pub fn group_markers(mut self, enabled: bool) -> Self {
+        self.group_markers = enabled;
+        self
+    }
+
+    /// See `Decoder::with_trimmed_text`
+    pub fn trim_text(mut self, enabled: bool) -> Self {
+        self.trim_text = enabled;
+        self
+    }
+
+    /// See `Decoder::with_bbox`
+    pub fn bbox(mut self, bbox: BoundingBox) -> Self {
+        self.bbox = Some(bbox);
+        self
+    }
+
+    pub fn build(self) -> Result<Decoder> {
+        let message = self.message;
+        let base = self.tables_path.as_deref();
+        let mut provenance = Provenance::default();
+
+        let (master_b, master_d) = match (self.master, message) {
+            (Some(tables), _) => tables,
+            (None, Some(message)) => {
+                let table_info = message.table_info();
+                let (number, version) = (table_info.master_table, table_info.master_table_version);
+                let (master_b, version_b) =
+                    message.load_first_validable_table(number, version, base)?;
+                let (master_d, version_d) =
+                    message.load_first_validable_table(number, version, base)?;
+                provenance.master_table = Some(number);
+                provenance.requested_master_version = Some(version);
+                provenance.master_b = TableSource::file(
+                    version_b,
+                    MasterTable::with_number(number, version_b).source_in(base, TableType::B),
+                );
+                provenance.master_d = TableSource::file(
+                    version_d,
+                    MasterTable::with_number(number, version_d).source_in(base, TableType::D),
+                );
+                (master_b, master_d)
+            }
+            (None, None) => return Err(Error::TableNotFoundEmpty),
+        };
+
+        let local = match (self.local, message) {
+            (Some(tables), _) => {
+                provenance.local_b = Some(TableSource::default());
+                provenance.local_d = Some(TableSource::default());
+                Some(tables)
+            }
+            (None, Some(message)) => match load_local_tables(message, base)? {
+                Some((table, tables)) => {
+                    let version = message.table_info().local_table_version;
+                    provenance.local_b = Some(TableSource::file(
+                        version,
+                        table.file_path_in(base, TableType::B),
+                    ));
+                    provenance.local_d = Some(TableSource::file(
+                        version,
+                        table.file_path_in(base, TableType::D),
+                    ));
+                    Some(tables)
+                }
+                None => None,
+            },
+            (None, None) => None,
+        };
+        let (local_b, local_d) = match local {
+            Some((b, d)) => (Some(b), Some(d)),
+            None => (None, None),
+        };
+
+        #[cfg(feature = "opera")]
+        let opera_bitmap_table = self.opera_bitmap_table.or_else(|| {
+            let table_info = message?.table_info();
+            let table = message?
+                .load_opera_bitmap_table(
+                    table_info.subcenter_id,
+                    table_info.center_id,
+                    table_info.local_table_version,
+                    table_info.master_table_version,
+                    base,
+                )
+                .ok()?;
+            provenance.opera_bitmap = Some(
+                BitmapTable::new(
+                    table_info.center_id,
+                    table_info.subcenter_id,
+                    table_info.local_table_version,
+                    table_info.master_table_version,
+                )
+                .file_path_in(base, TableType::BitMap),
+            );
+            Some(table)
+        });
+
+        let edition = self
+            .edition
+            .or(message.map(|message| message.version()))
+            .unwrap_or(4);
+
+        let mut decoder = Decoder::new(
+            edition,
+            master_b,
+            master_d,
+            local_b,
+            local_d,
+            #[cfg(feature = "opera")]
+            opera_bitmap_table,
+        );
+        decoder.mode = self.mode;
+        decoder.missing = self.missing;
+        decoder.missing_values = self.missing_values;
+        decoder.units = self.units;
+        decoder.output = self.output;
+        decoder.subsets = self.subsets;
+        decoder.f32_arrays = self.f32_arrays;
+        decoder.group_markers = self.group_markers;
+        decoder.trim_text = self.trim_text;
+        decoder.bbox = self.bbox;
+        decoder.provenance = provenance;
+
+        Ok(decoder)
+    }
+}
+
+/// Local tables of `message`, with the table they were found as
+///
+/// Tried in `LocalTable::search_order`: the originating sub-centre's own
+/// tables, then those of the centre. A message using no local tables, or
+/// one whose tables are not installed, gets none; a table file that exists
+/// but does not load is an error.
+fn load_local_tables(
+    message: &MessageBlock,
+    base: Option<&Path>,
+) -> Result<Option<(LocalTable, Tables)>> {
+    let table_info = message.table_info();
+    if table_info.local_table_version == 0 {
+        return Ok(None);
+    }
+
+    let candidates = LocalTable::search_order(
+        table_info.center_id,
+        table_info.subcenter_id,
+        table_info.local_table_version,
+    );
+    let Some(table) = candidates
+        .into_iter()
+        .find(|table| table.file_path_in(base, TableType::B).exists())
+    else {
+        return Ok(None);
+    };
+
+    let local_b: BUFRTableB = TableLoader.load_table_in(base, table)?;
+    let local_d: BUFRTableD = TableLoader.load_table_in(base, table)?;
+
+    Ok(Some((table, (local_b, local_d))))
+}
+
+/// Applies the missing-value policy and unit preference on the way to `inner`
+pub(super) struct Preferred<'r, C> {
+    pub(super) inner: &'r mut C,
+    pub(super) missing: MissingPolicy,
+    pub(super) units: Units,
+}
+
+impl<'a, C: Container<'a>> Container<'a> for Preferred<'_, C> {
+    fn push(
+        &mut self,
+        value: Value<'a>,
+        fxy: FXY,
+        encoding: Option<Encoding>,
+        name: &'a str,
+        unit: &'a str,
+    ) {
+        if self.missing == MissingPolicy::Skip && value.is_missing() {
+            return;
+        }
+        match (self.units.convert(fxy, unit), value) {
+            (Some((unit, convert)), Value::Number(n)) => {
+                self.inner
+                    .push(Value::Number(convert(n)), fxy, encoding, name, unit)
+            }
+            (_, value) => self.inner.push(value, fxy, encoding, name, unit),
+        }
+    }
+
+    fn push_array(
+        &mut self,
+        values: &mut [f64],
+        missing: &MissingMask,
+        fxy: FXY,
+        encoding: Encoding,
+        name: &'a str,
+        mut unit: &'a str,
+    ) {
+        if let Some((preferred, convert)) = self.units.convert(fxy, unit) {
+            values
+                .iter_mut()
+                .zip(missing.iter())
+                .filter(|(_, missing)| !missing)
+                .for_each(|(v, _)| *v = convert(*v));
+            unit = preferred;
+        }
+        self.inner
+            .push_array(values, missing, fxy, encoding, name, unit);
+    }
+
+    fn push_array_f32(
+        &mut self,
+        values: &mut [f32],
+        missing: &MissingMask,
+        fxy: FXY,
+        encoding: Encoding,
+        name: &'a str,
+        mut unit: &'a str,
+    ) {
+        if let Some((preferred, convert)) = self.units.convert(fxy, unit) {
+            values
+                .iter_mut()
+                .zip(missing.iter())
+                .filter(|(_, missing)| !missing)
+                .for_each(|(v, _)| *v = convert(*v as f64) as f32);
+            unit = preferred;
+        }
+        self.inner
+            .push_array_f32(values, missing, fxy, encoding, name, unit);
+    }
+
+    fn push_repeat(
+        &mut self,
+        mut values: Vec<Value<'a>>,
+        fxy: FXY,
+        encoding: Encoding,
+        name: &'a str,
+        mut unit: &'a str,
+    ) {
+        if let Some((preferred, convert)) = self.units.convert(fxy, unit) {
+            for value in values.iter_mut() {
+                if let Value::Number(n) = value {
+                    *n = convert(*n);
+                }
+            }
+            unit = preferred;
+        }
+        self.inner.push_repeat(values, fxy, encoding, name, unit);
+    }
+
+    fn keeps_values(&self) -> bool {
+        self.inner.keeps_values()
+    }
+
+    fn keeps_structure(&self) -> bool {
+        self.inner.keeps_structure()
+    }
+
+    fn begin_sequence(&mut self, fxy: FXY, name: &'a str) {
+        self.inner.begin_sequence(fxy, name);
+    }
+
+    fn end_sequence(&mut self) {
+        self.inner.end_sequence();
+    }
+
+    fn compiles_arrays(&self) -> bool {
+        self.inner.compiles_arrays()
+    }
+
+    fn stopped(&self) -> bool {
+        self.inner.stopped()
+    }
+
+    fn at_bit(&mut self, position: usize) {
+        self.inner.at_bit(position);
+    }
+
+    fn begin_replication(&mut self, fxy: FXY, times: usize) {
+        self.inner.begin_replication(fxy, times);
+    }
+
+    fn empty_replication(&mut self, fxy: FXY) {
+        self.inner.empty_replication(fxy);
+    }
+
+    fn begin_group(&mut self, fxy: FXY, iteration: usize, count: usize) {
+        self.inner.begin_group(fxy, iteration, count);
+    }
+
+    fn end_group(&mut self, fxy: FXY, iteration: usize, count: usize) {
+        self.inner.end_group(fxy, iteration, count);
+    }
+
+    fn record_count(&self) -> usize {
+        self.inner.record_count()
+    }
+
+    fn link_quality(&mut self, link: QualityLink) {
+        self.inner.link_quality(link);
+    }
+
+    fn operator(&mut self, fxy: FXY) {
+        self.inner.operator(fxy);
+    }
+
+    #[cfg(feature = "opera")]
+    fn bitmap_start(&self) -> usize {
+        self.inner.bitmap_start()
+    }
+
+    #[cfg(feature = "opera")]
+    fn end_bitmap(&mut self, fxy: FXY, depth: u8, start: usize, name: &'a str) {
+        self.inner.end_bitmap(fxy, depth, start, name);
+    }
+}
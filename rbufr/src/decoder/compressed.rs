@@ -0,0 +1,156 @@
+//! Compressed data (Section 3 flag 0x40): every element is stored once for
+//! all subsets, as a reference R0 of the element's width, a 6-bit width
+//! NBINC and one NBINC-bit increment per subset. CCITT IA5 elements store
+//! R0 as zero octets, then the number of octets of each string as NBINC and
+//! the strings themselves; an NBINC of 0 means every subset holds R0.
+
+use std::ops::Deref;
+
+use crate::core::tables::ArchivedBTableEntry;
+use crate::errors::Result;
+use crate::structs::versions::MessageVersion;
+
+use super::{BitInput, Encoding, MISS_VAL, MissingMask, State, Value, all_ones};
+
+/// Bits of the NBINC that follows each R0
+const NBINC_BITS: usize = 6;
+
+/// Subsets of `message` when its data is compressed, `None` otherwise
+pub(super) fn compressed_subsets<V: MessageVersion>(
+    message: &impl Deref<Target = V>,
+) -> Option<usize> {
+    message
+        .is_compressed()
+        .then(|| message.subsets_count() as usize)
+}
+
+/// Raw values of a `width`-bit element in every subset, `None` where
+/// missing, into `raw`; delayed replication counts (`counts`) are never
+/// missing
+pub(super) fn take_raw(
+    data: &mut BitInput,
+    width: u32,
+    subsets: usize,
+    counts: bool,
+    raw: &mut Vec<Option<u64>>,
+) -> Result<()> {
+    raw.clear();
+    let reference = data.get_arbitary_bits(width as usize)?;
+    let nbinc = data.get_arbitary_bits(NBINC_BITS)? as u32;
+    if nbinc == 0 {
+        let value = (counts || reference != all_ones(width)).then_some(reference);
+        raw.resize(subsets, value);
+        return Ok(());
+    }
+    if nbinc > 64 {
+        return Err(data.error(format!("Increments of {} bits", nbinc)));
+    }
+
+    raw.reserve(subsets);
+    for _ in 0..subsets {
+        let increment = data.get_arbitary_bits(nbinc as usize)?;
+        raw.push(
+            (counts || increment != all_ones(nbinc)).then(|| reference.wrapping_add(increment)),
+        );
+    }
+    Ok(())
+}
+
+/// CCITT IA5 values of `nbytes` octets in every subset
+pub(super) fn take_texts<'a>(
+    data: &mut BitInput<'a>,
+    nbytes: usize,
+    subsets: usize,
+    trim: bool,
+) -> Result<Vec<Value<'a>>> {
+    let reference = data.take_text(nbytes, trim)?;
+    let nbinc = data.get_arbitary_bits(NBINC_BITS)? as usize;
+    if nbinc == 0 {
+        return Ok(vec![reference; subsets]);
+    }
+    (0..subsets).map(|_| data.take_text(nbinc, trim)).collect()
+}
+
+/// Buffers an element is read into for every subset, kept from one
+/// element to the next so that reading one allocates nothing
+#[derive(Default)]
+pub(super) struct Scratch {
+    raw: Vec<Option<u64>>,
+    values: Vec<f64>,
+    missing: MissingMask<'static>,
+}
+
+impl Scratch {
+    /// Scaled values of an element in every subset and their missing mask
+    pub(super) fn read(
+        &mut self,
+        data: &mut BitInput,
+        encoding: &Encoding,
+        subsets: usize,
+        counts: bool,
+    ) -> Result<(&mut [f64], &MissingMask<'static>)> {
+        take_raw(data, encoding.width_bits, subsets, counts, &mut self.raw)?;
+        let factor = 10.0f64.powi(-encoding.scale);
+        self.values.clear();
+        self.missing.clear();
+        for raw in &self.raw {
+            self.values.push(raw.map_or(MISS_VAL, |raw| {
+                (raw as f64 + encoding.reference as f64) * factor
+            }));
+            self.missing.push(raw.is_none());
+        }
+        Ok((&mut self.values, &self.missing))
+    }
+}
+
+/// An element read for every subset, numbers borrowed from a `Scratch`
+pub(super) enum Compressed<'s, 'a> {
+    Numbers(&'s mut [f64], &'s MissingMask<'static>),
+    Texts(Vec<Value<'a>>),
+}
+
+impl Compressed<'_, '_> {
+    /// The value of the first subset, as numbers flags and counts are
+    /// taken from
+    pub(super) fn first(&self) -> Option<f64> {
+        match self {
+            Compressed::Numbers(values, missing) => {
+                (!values.is_empty() && !missing.get(0)).then(|| values[0])
+            }
+            Compressed::Texts(_) => None,
+        }
+    }
+}
+
+impl State {
+    /// Element `e` in every subset, read as `evalute_encoded` reads it once
+    pub(super) fn read_compressed<'a>(
+        &mut self,
+        data: &mut BitInput<'a>,
+        e: &ArchivedBTableEntry,
+        subsets: usize,
+        trim: bool,
+    ) -> Result<(Compressed<'_, 'a>, Encoding)> {
+        if e.bufr_unit.as_str() == super::UNIT_CCITT_IA5 {
+            let nbytes = super::string_bytes(self.common_str_width, e);
+            let texts = take_texts(data, nbytes, subsets, trim)?;
+            let encoding = Encoding {
+                scale: 0,
+                reference: 0,
+                width_bits: (nbytes * 8) as u32,
+                raw: None,
+            };
+            return Ok((Compressed::Texts(texts), encoding));
+        }
+
+        let encoding = Encoding {
+            scale: self.scale(e),
+            reference: self.reference_value(e),
+            width_bits: self.datawidth(e),
+            raw: None,
+        };
+        let counts = e.fxy.x == 31;
+        let (values, missing) = self.scratch.read(data, &encoding, subsets, counts)?;
+        Ok((Compressed::Numbers(values, missing), encoding))
+    }
+}
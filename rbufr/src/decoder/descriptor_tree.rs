@@ -0,0 +1,204 @@
+use std::fmt::Display;
+
+use crate::core::{BUFRKey, FXY, table_c};
+
+use super::{Cache, Decoder};
+
+/// The Section 3 descriptor hierarchy, named from Table B and Table D
+///
+/// Built without reading the data, and without failing on descriptors the
+/// tables lack: those are kept with no name, so a message that won't decode
+/// can still be inspected.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DescriptorTree {
+    pub nodes: Vec<DescriptorNode>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DescriptorNode {
+    Element {
+        fxy: FXY,
+        /// `None` when Table B has no entry
+        name: Option<String>,
+        unit: Option<String>,
+        width_bits: Option<u32>,
+    },
+    Sequence {
+        fxy: FXY,
+        /// `None` when Table D has no entry, `children` then being empty;
+        /// empty when the entry has no title
+        title: Option<String>,
+        children: Vec<DescriptorNode>,
+    },
+    /// The body is listed once; `count` is the delayed replication factor
+    Replication {
+        fxy: FXY,
+        times: Option<usize>,
+        count: Option<Box<DescriptorNode>>,
+        children: Vec<DescriptorNode>,
+    },
+    Operator {
+        fxy: FXY,
+    },
+}
+
+impl Decoder {
+    /// The hierarchy of `descriptors`, as named by this decoder's tables
+    pub fn descriptor_tree(&self, descriptors: &[FXY]) -> DescriptorTree {
+        let mut cache = Cache::new(
+            &self.master_b,
+            &self.master_d,
+            self.local_b.as_ref(),
+            self.local_d.as_ref(),
+        );
+        DescriptorTree {
+            nodes: build(descriptors, &mut cache),
+        }
+    }
+}
+
+fn build<K: BUFRKey>(descs: &[K], cache: &mut Cache<'_>) -> Vec<DescriptorNode> {
+    let mut nodes = vec![];
+    let mut idx = 0;
+    while idx < descs.len() {
+        let des = &descs[idx];
+        let fxy = FXY::new(des.f(), des.x(), des.y());
+        idx += 1;
+        let node = match des.f() {
+            1 => {
+                let delayed = des.y() == 0;
+                let count = if delayed && idx < descs.len() {
+                    idx += 1;
+                    build(&descs[idx - 1..idx], cache).pop().map(Box::new)
+                } else {
+                    None
+                };
+                // A short list keeps what there is rather than failing
+                let body_end = (idx + des.x() as usize).min(descs.len());
+                let children = build(&descs[idx..body_end], cache);
+                idx = body_end;
+                DescriptorNode::Replication {
+                    fxy,
+                    times: (!delayed).then_some(des.y() as usize),
+                    count,
+                    children,
+                }
+            }
+            2 => DescriptorNode::Operator { fxy },
+            3 => match cache.get_d(des) {
+                Some(seq) => DescriptorNode::Sequence {
+                    fxy,
+                    title: Some(seq.title_en.as_deref().unwrap_or("").to_string()),
+                    children: build(seq.fxy_chain.as_slice(), cache),
+                },
+                None => DescriptorNode::Sequence {
+                    fxy,
+                    title: None,
+                    children: vec![],
+                },
+            },
+            _ => {
+                let entry = cache.get_b(des);
+                DescriptorNode::Element {
+                    fxy,
+                    name: entry.map(|e| e.element_name_en.to_string()),
+                    unit: entry.map(|e| e.bufr_unit.to_string()),
+                    width_bits: entry.map(|e| e.bufr_datawidth_bits.to_native()),
+                }
+            }
+        };
+        nodes.push(node);
+    }
+    nodes
+}
+
+/// What an operator does, for display
+fn operator_name(fxy: FXY) -> &'static str {
+    table_c::lookup(fxy.x).map_or("Operator", |op| op.name)
+}
+
+impl Display for DescriptorTree {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fn write_nodes(
+            f: &mut std::fmt::Formatter<'_>,
+            nodes: &[DescriptorNode],
+            depth: usize,
+        ) -> std::fmt::Result {
+            for node in nodes {
+                write_node(f, node, depth)?;
+            }
+            Ok(())
+        }
+
+        fn write_fxy(f: &mut std::fmt::Formatter<'_>, fxy: &FXY, depth: usize) -> std::fmt::Result {
+            write!(
+                f,
+                "{:indent$}{}-{:02}-{:03}",
+                "",
+                fxy.f,
+                fxy.x,
+                fxy.y,
+                indent = depth * 2
+            )
+        }
+
+        fn write_node(
+            f: &mut std::fmt::Formatter<'_>,
+            node: &DescriptorNode,
+            depth: usize,
+        ) -> std::fmt::Result {
+            match node {
+                DescriptorNode::Element {
+                    fxy,
+                    name,
+                    unit,
+                    width_bits,
+                } => {
+                    write_fxy(f, fxy, depth)?;
+                    match (name, unit, width_bits) {
+                        (Some(name), Some(unit), Some(width)) => {
+                            writeln!(f, " {} [{}, {} bits]", name.trim(), unit, width)
+                        }
+                        _ => writeln!(f, " <not in Table B>"),
+                    }
+                }
+                DescriptorNode::Sequence {
+                    fxy,
+                    title,
+                    children,
+                } => {
+                    write_fxy(f, fxy, depth)?;
+                    match title.as_deref() {
+                        Some("") => writeln!(f)?,
+                        Some(title) => writeln!(f, " {}", title)?,
+                        None => writeln!(f, " <not in Table D>")?,
+                    }
+                    write_nodes(f, children, depth + 1)
+                }
+                DescriptorNode::Replication {
+                    fxy,
+                    times,
+                    count,
+                    children,
+                } => {
+                    write_fxy(f, fxy, depth)?;
+                    write!(f, " Replicate {} ", fxy.x)?;
+                    match times {
+                        Some(times) => writeln!(f, "descriptors {} times", times)?,
+                        None => writeln!(f, "descriptors, delayed")?,
+                    }
+                    if let Some(count) = count {
+                        write_node(f, count, depth + 1)?;
+                    }
+                    write_nodes(f, children, depth + 1)
+                }
+                DescriptorNode::Operator { fxy } => {
+                    write_fxy(f, fxy, depth)?;
+                    writeln!(f, " {}", operator_name(*fxy))
+                }
+            }
+        }
+
+        write_nodes(f, &self.nodes, 0)
+    }
+}
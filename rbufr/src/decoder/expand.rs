@@ -0,0 +1,211 @@
+use crate::core::{BUFRKey, FXY, table_c, tables::UNIT_CCITT_IA5};
+use crate::errors::{Error, Result};
+
+use super::associated::{associated_field_name, takes_associated_field};
+use super::{Cache, Decoder, State, UNKNOWN_ELEMENT_NAME, string_bytes};
+
+/// What an `ExpandedDescriptor` stands for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpandedKind {
+    /// A Table B element, the characters a 2-05-YYY operator inserts, or
+    /// the associated fields 2-04-YYY puts before an element
+    Element,
+    /// A replication of the next `descriptors` descriptors of its list;
+    /// `times` is `None` for delayed replication, whose count is only in the
+    /// data
+    Replication {
+        descriptors: usize,
+        times: Option<usize>,
+    },
+    /// An operator that changes how later elements are read
+    Operator,
+}
+
+/// One entry of a descriptor list expanded without the data
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpandedDescriptor {
+    pub fxy: FXY,
+    pub kind: ExpandedKind,
+    /// Table B element name, or Table C operator name; empty for
+    /// replications
+    pub name: String,
+    pub unit: String,
+    /// Bits in Section 4, after operators; 0 for entries taking none
+    pub width_bits: u32,
+    pub scale: i32,
+    pub reference: i32,
+    /// Delayed replications the entry sits in; such entries appear once here
+    /// but as many times in the data as the counts say
+    pub delayed_depth: usize,
+}
+
+impl ExpandedDescriptor {
+    fn marker(fxy: FXY, kind: ExpandedKind, delayed_depth: usize) -> Self {
+        ExpandedDescriptor {
+            fxy,
+            kind,
+            name: String::new(),
+            unit: String::new(),
+            width_bits: 0,
+            scale: 0,
+            reference: 0,
+            delayed_depth,
+        }
+    }
+}
+
+impl Decoder {
+    /// Expand `descriptors` against this decoder's tables, as one subset
+    /// would be read
+    ///
+    /// Sequences are replaced by their contents and fixed replications are
+    /// unrolled. The body of a delayed replication is listed once, after the
+    /// replication and its count descriptor, with `delayed_depth` raised.
+    /// Widths, scales and references take the operators before them into
+    /// account.
+    pub fn expand(&self, descriptors: &[FXY]) -> Result<Vec<ExpandedDescriptor>> {
+        let mut cache = Cache::new(
+            &self.master_b,
+            &self.master_d,
+            self.local_b.as_ref(),
+            self.local_d.as_ref(),
+        );
+        let mut state = State::new();
+        let mut out = vec![];
+        expand_slice(descriptors, &mut cache, &mut state, 0, &mut out)?;
+        Ok(out)
+    }
+}
+
+fn expand_slice<K: BUFRKey>(
+    descs: &[K],
+    cache: &mut Cache<'_>,
+    state: &mut State,
+    delayed_depth: usize,
+    out: &mut Vec<ExpandedDescriptor>,
+) -> Result<()> {
+    let mut idx = 0;
+    while idx < descs.len() {
+        let des = &descs[idx];
+        let fxy = FXY::new(des.f(), des.x(), des.y());
+        idx += 1;
+        match des.f() {
+            0 => {
+                let e = cache.get_b(des);
+                if e.is_none() && state.local_data_width.is_none() {
+                    return Err(Error::ParseError(format!(
+                        "Descriptor {:?} not found in Table B",
+                        des
+                    )));
+                }
+                let associated_bits: i32 = state.associated_widths.iter().sum();
+                if associated_bits > 0 && takes_associated_field(des) {
+                    out.push(ExpandedDescriptor {
+                        fxy: FXY::new(2, 4, associated_bits),
+                        kind: ExpandedKind::Element,
+                        name: associated_field_name(None).to_string(),
+                        unit: String::new(),
+                        width_bits: associated_bits as u32,
+                        scale: 0,
+                        reference: 0,
+                        delayed_depth,
+                    });
+                }
+                match e {
+                    Some(e) => {
+                        let width_bits = match e.bufr_unit.as_str() {
+                            UNIT_CCITT_IA5 => string_bytes(state.common_str_width, e) as u32 * 8,
+                            _ => state.datawidth(e),
+                        };
+                        out.push(ExpandedDescriptor {
+                            fxy,
+                            kind: ExpandedKind::Element,
+                            name: e.element_name_en.to_string(),
+                            unit: e.bufr_unit.to_string(),
+                            width_bits,
+                            scale: state.scale(e),
+                            reference: state.reference_value(e),
+                            delayed_depth,
+                        });
+                    }
+                    // Found in no table, but 2-06-YYY gave its width
+                    None => out.push(ExpandedDescriptor {
+                        name: UNKNOWN_ELEMENT_NAME.to_string(),
+                        width_bits: state.local_data_width.unwrap_or(0) as u32,
+                        ..ExpandedDescriptor::marker(fxy, ExpandedKind::Element, delayed_depth)
+                    }),
+                }
+                state.temp_operator = None;
+                state.local_data_width = None;
+            }
+            1 => {
+                let x = des.x() as usize;
+                let delayed = des.y() == 0;
+                let body_start = if delayed { idx + 1 } else { idx };
+                let body_end = body_start + x;
+                if body_end > descs.len() {
+                    return Err(Error::ParseError(format!(
+                        "Not enough descriptors to repeat: requested {}, available {}",
+                        x,
+                        descs.len().saturating_sub(body_start)
+                    )));
+                }
+
+                let times = (!delayed).then_some(des.y() as usize);
+                let kind = ExpandedKind::Replication {
+                    descriptors: x,
+                    times,
+                };
+                out.push(ExpandedDescriptor::marker(fxy, kind, delayed_depth));
+
+                let body = &descs[body_start..body_end];
+                if delayed {
+                    expand_slice(&descs[idx..body_start], cache, state, delayed_depth, out)?;
+                    expand_slice(body, cache, state, delayed_depth + 1, out)?;
+                } else {
+                    for _ in 0..des.y() {
+                        expand_slice(body, cache, state, delayed_depth, out)?;
+                    }
+                }
+                idx = body_end;
+            }
+            2 => {
+                let operator = table_c::check(des).map_err(Error::ParseError)?;
+                if des.x() == 5 {
+                    out.push(ExpandedDescriptor {
+                        fxy,
+                        kind: ExpandedKind::Element,
+                        name: operator.name.to_string(),
+                        unit: UNIT_CCITT_IA5.to_string(),
+                        width_bits: des.y() as u32 * 8,
+                        scale: 0,
+                        reference: 0,
+                        delayed_depth,
+                    });
+                } else {
+                    state.apply_operator(des.x(), des.y());
+                    out.push(ExpandedDescriptor {
+                        name: operator.name.to_string(),
+                        ..ExpandedDescriptor::marker(fxy, ExpandedKind::Operator, delayed_depth)
+                    });
+                }
+            }
+            3 => {
+                let seq = cache.get_d(des).ok_or_else(|| {
+                    Error::ParseError(format!(
+                        "Sequence descriptor {:?} not found in Table D",
+                        des
+                    ))
+                })?;
+                expand_slice(seq.fxy_chain.as_slice(), cache, state, delayed_depth, out)?;
+            }
+            f => {
+                return Err(Error::ParseError(format!(
+                    "Invalid descriptor F value: {}",
+                    f
+                )));
+            }
+        }
+    }
+    Ok(())
+}
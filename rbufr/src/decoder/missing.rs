@@ -0,0 +1,200 @@
+use std::borrow::Cow;
+
+use super::{BUFRParsed, storage::Stored};
+
+/// Default fill for missing elements of `BUFRData::Array` and `BUFRData::Array2`
+pub const MISS_VAL: f64 = 99999.999999;
+/// Default fill for missing elements of `BUFRData::ArrayF32`, `MISS_VAL` rounded to `f32`
+pub const MISS_VAL_F32: f32 = MISS_VAL as f32;
+
+/// Whether a lone number stands for a missing value: `MISS_VAL` or NaN
+///
+/// Arrays carry a `MissingMask`; prefer it over comparing their values.
+#[inline]
+pub fn is_missing(value: f64) -> bool {
+    value == MISS_VAL || value.is_nan()
+}
+
+/// One bit per array element, set where the element is missing
+///
+/// The masks of a `BUFRParsed` borrow their words from one buffer shared by
+/// all of its arrays.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct MissingMask<'a> {
+    words: Cow<'a, [u64]>,
+    len: usize,
+}
+
+impl<'a> MissingMask<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        MissingMask {
+            words: Cow::Owned(Vec::with_capacity(capacity.div_ceil(64))),
+            len: 0,
+        }
+    }
+
+    /// A mask of `len` elements, none missing
+    pub fn none(len: usize) -> Self {
+        MissingMask {
+            words: Cow::Owned(vec![0; len.div_ceil(64)]),
+            len,
+        }
+    }
+
+    /// A mask of `len` elements over `words`, which holds
+    /// `len.div_ceil(64)` of them
+    pub(super) fn borrowed(words: &'a [u64], len: usize) -> Self {
+        debug_assert_eq!(words.len(), len.div_ceil(64));
+        MissingMask {
+            words: Cow::Borrowed(words),
+            len,
+        }
+    }
+
+    /// Bits of the mask, 64 elements a word from the lowest bit up
+    pub(super) fn words(&self) -> &[u64] {
+        &self.words
+    }
+
+    #[inline]
+    pub fn push(&mut self, missing: bool) {
+        let bit = self.len % 64;
+        let words = self.words.to_mut();
+        if bit == 0 {
+            words.push(0);
+        }
+        if missing {
+            *words.last_mut().unwrap() |= 1 << bit;
+        }
+        self.len += 1;
+    }
+
+    /// Remove every element, keeping the capacity of an owned mask
+    pub fn clear(&mut self) {
+        match &mut self.words {
+            Cow::Owned(words) => words.clear(),
+            words => *words = Cow::Owned(vec![]),
+        }
+        self.len = 0;
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Whether element `index` is missing; `false` past the end
+    #[inline]
+    pub fn get(&self, index: usize) -> bool {
+        index < self.len && self.words[index / 64] & (1 << (index % 64)) != 0
+    }
+
+    /// Number of missing elements
+    pub fn count(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn any(&self) -> bool {
+        self.words.iter().any(|w| *w != 0)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        (0..self.len).map(|i| self.get(i))
+    }
+
+    /// This mask with its words copied out of the buffer it borrows
+    pub fn into_owned(self) -> MissingMask<'static> {
+        MissingMask {
+            words: Cow::Owned(self.words.into_owned()),
+            len: self.len,
+        }
+    }
+}
+
+impl FromIterator<bool> for MissingMask<'_> {
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        let iter = iter.into_iter();
+        let mut mask = MissingMask::with_capacity(iter.size_hint().0);
+        for missing in iter {
+            mask.push(missing);
+        }
+        mask
+    }
+}
+
+/// What fills the missing elements of decoded arrays
+///
+/// Single and replicated values are always `Value::Missing`, and arrays
+/// always carry a `MissingMask`; this only picks the placeholder stored in
+/// `Array`, `ArrayF32` and `Array2` values.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MissingValues {
+    /// Fill with this value, `MISS_VAL` by default (`MISS_VAL_F32` in `f32`
+    /// arrays)
+    Sentinel(f64),
+    /// Fill with NaN
+    Nan,
+}
+
+impl Default for MissingValues {
+    fn default() -> Self {
+        MissingValues::Sentinel(MISS_VAL)
+    }
+}
+
+impl MissingValues {
+    fn is_default(self) -> bool {
+        self == MissingValues::Sentinel(MISS_VAL)
+    }
+
+    fn fill(self) -> f64 {
+        match self {
+            MissingValues::Sentinel(value) => value,
+            MissingValues::Nan => f64::NAN,
+        }
+    }
+
+    /// Refill the missing elements of the arrays of `parsed`, decoded as
+    /// `MISS_VAL`
+    pub(super) fn apply_all(self, parsed: &mut BUFRParsed<'_>) {
+        if self.is_default() {
+            return;
+        }
+        let fill = self.fill();
+        for record in &parsed.records {
+            match &record.data {
+                Stored::Array { values, missing }
+                | Stored::Array2 {
+                    values, missing, ..
+                } => {
+                    let words = &parsed.missing[*missing..][..values.len().div_ceil(64)];
+                    fill_missing(&mut parsed.numbers[values.clone()], words, fill);
+                }
+                Stored::ArrayF32 { values, missing } => {
+                    let words = &parsed.missing[*missing..][..values.len().div_ceil(64)];
+                    fill_missing(&mut parsed.floats[values.clone()], words, fill as f32);
+                }
+                Stored::Single(_) | Stored::Repeat(_) | Stored::Group { .. } => {}
+            }
+        }
+    }
+}
+
+fn fill_missing<T: Copy>(values: &mut [T], words: &[u64], fill: T) {
+    let missing = MissingMask::borrowed(words, values.len());
+    if !missing.any() {
+        return;
+    }
+    for (i, value) in values.iter_mut().enumerate() {
+        if missing.get(i) {
+            *value = fill;
+        }
+    }
+}
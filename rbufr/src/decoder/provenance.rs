@@ -0,0 +1,115 @@
+use std::fmt::Display;
+use std::path::PathBuf;
+
+use super::Decoder;
+
+/// Where one table came from
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TableSource {
+    /// Version the table was loaded for; `None` when it was handed to
+    /// `DecoderBuilder` directly
+    pub version: Option<u8>,
+    /// File the table was read from; `None` when handed over directly
+    pub path: Option<PathBuf>,
+}
+
+impl TableSource {
+    pub(super) fn file(version: u8, path: PathBuf) -> Self {
+        TableSource {
+            version: Some(version),
+            path: Some(path),
+        }
+    }
+}
+
+/// Which tables a `Decoder` decodes with; see `Decoder::provenance`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Provenance {
+    /// Master table number Section 1 asked for, when tables were loaded for
+    /// a message
+    pub master_table: Option<u8>,
+    /// Master table version Section 1 asked for, when tables were loaded for
+    /// a message
+    pub requested_master_version: Option<u8>,
+    pub master_b: TableSource,
+    pub master_d: TableSource,
+    /// `None` when no local tables were loaded
+    pub local_b: Option<TableSource>,
+    pub local_d: Option<TableSource>,
+    /// OPERA bitmap table file, when one was loaded
+    #[cfg(feature = "opera")]
+    pub opera_bitmap: Option<PathBuf>,
+}
+
+impl Provenance {
+    /// Whether an older master version stood in for the one requested
+    pub fn master_fallback(&self) -> bool {
+        let requested = self.requested_master_version;
+        [self.master_b.version, self.master_d.version]
+            .into_iter()
+            .any(|version| requested.is_some() && version.is_some() && version != requested)
+    }
+
+    pub fn has_local_tables(&self) -> bool {
+        self.local_b.is_some() || self.local_d.is_some()
+    }
+}
+
+impl Display for Provenance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fn write_source(
+            f: &mut std::fmt::Formatter<'_>,
+            label: &str,
+            source: &TableSource,
+        ) -> std::fmt::Result {
+            write!(f, "{:<15}: ", label)?;
+            match source.version {
+                Some(version) => write!(f, "v{}", version)?,
+                None => write!(f, "given")?,
+            }
+            if let Some(path) = &source.path {
+                write!(f, " {}", path.display())?;
+            }
+            writeln!(f)
+        }
+
+        if let Some(number) = self.master_table.filter(|&number| number != 0) {
+            writeln!(f, "{:<15}: {}", "Master table", number)?;
+        }
+        if let Some(requested) = self.requested_master_version {
+            write!(f, "{:<15}: v{}", "Requested", requested)?;
+            if self.master_fallback() {
+                write!(f, " (fallback used)")?;
+            }
+            writeln!(f)?;
+        }
+        write_source(f, "Master Table B", &self.master_b)?;
+        write_source(f, "Master Table D", &self.master_d)?;
+        match (&self.local_b, &self.local_d) {
+            (None, None) => writeln!(f, "{:<15}: none", "Local tables")?,
+            (local_b, local_d) => {
+                if let Some(local_b) = local_b {
+                    write_source(f, "Local Table B", local_b)?;
+                }
+                if let Some(local_d) = local_d {
+                    write_source(f, "Local Table D", local_d)?;
+                }
+            }
+        }
+        #[cfg(feature = "opera")]
+        if let Some(path) = &self.opera_bitmap {
+            writeln!(f, "{:<15}: {}", "OPERA bitmap", path.display())?;
+        }
+        Ok(())
+    }
+}
+
+impl Decoder {
+    /// The tables this decoder was built with and where they came from
+    ///
+    /// Decoders made with `Decoder::new` know nothing of their tables'
+    /// origin and report every source as given.
+    pub fn provenance(&self) -> &Provenance {
+        &self.provenance
+    }
+}
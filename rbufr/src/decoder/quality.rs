@@ -0,0 +1,297 @@
+//! Data present bit-maps and the quality information they link back to
+//! elements (2-22-YYY to 2-37-YYY)
+//!
+//! A bit-map refers to the data elements read before its operator, the
+//! first bit to the first element of the subset, or the first after the
+//! last 2-35-000. Class 31 elements, the bit-maps and replication counts
+//! themselves, are never referred to.
+use std::sync::Arc;
+
+use crate::core::{BUFRKey, FXY};
+
+use super::{BUFRParsed, BUFRRecord, CompiledLayout, Container};
+
+/// What a record linked back to an element holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum QualityKind {
+    /// A class 33 element following 2-22-000
+    Quality,
+    /// A 2-23-255 substituted value
+    Substituted,
+    /// A 2-24-255 first-order statistical value
+    FirstOrderStatistics,
+    /// A 2-25-255 difference statistical value
+    DifferenceStatistics,
+    /// A 2-32-255 replaced or retained value
+    Replaced,
+}
+
+impl QualityKind {
+    fn of_operator(x: i32) -> Option<Self> {
+        match x {
+            22 => Some(QualityKind::Quality),
+            23 => Some(QualityKind::Substituted),
+            24 => Some(QualityKind::FirstOrderStatistics),
+            25 => Some(QualityKind::DifferenceStatistics),
+            32 => Some(QualityKind::Replaced),
+            _ => None,
+        }
+    }
+}
+
+/// Record `quality` holds quality information on value `offset` of record
+/// `element`; `offset` is 0 unless `element` is an array
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct QualityLink {
+    pub element: usize,
+    pub offset: usize,
+    pub quality: usize,
+    pub kind: QualityKind,
+}
+
+/// A data element a bit-map may refer to; `record` is `None` when the
+/// container didn't keep it
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Slot {
+    pub(super) fxy: FXY,
+    record: Option<usize>,
+    offset: usize,
+}
+
+/// Elements read one at a time, or as a whole compiled array
+enum Run {
+    Single(Slot),
+    Array {
+        first_record: Option<usize>,
+        layout: Arc<CompiledLayout>,
+        times: usize,
+    },
+}
+
+/// Whether `fxy` may be referred to by a bit-map
+fn referable(fxy: &FXY) -> bool {
+    fxy.f == 0 && fxy.x != 31
+}
+
+/// Whether `fxy` is a data present indicator, one bit of a bit-map
+fn is_indicator(fxy: &FXY) -> bool {
+    *fxy == FXY::new(0, 31, 31) || *fxy == FXY::new(0, 31, 192)
+}
+
+/// A bit-map being read: the elements before `end` it refers to
+struct Definition {
+    end: usize,
+    present: Vec<bool>,
+    /// Whether 2-36-000 asked for it to be kept for 2-37-000
+    keep: bool,
+}
+
+/// The back-reference bookkeeping of one subset
+#[derive(Default)]
+pub(super) struct BackReference {
+    runs: Vec<Run>,
+    /// Elements in `runs`
+    len: usize,
+    /// The operator whose values are being linked
+    kind: Option<QualityKind>,
+    defining: Option<Definition>,
+    /// Elements of the bit-map defined with 2-36-000
+    kept: Option<Vec<Slot>>,
+    /// Elements, in order, still waiting for their value
+    targets: std::collections::VecDeque<Slot>,
+}
+
+impl BackReference {
+    /// Whether upcoming elements must go through `element` one at a time:
+    /// a bit-map is being read or quality values are being linked
+    pub(super) fn busy(&self) -> bool {
+        self.defining.is_some() || !self.targets.is_empty()
+    }
+
+    /// Take in operator 2-`x`-`y` (Y = 255 markers excepted)
+    pub(super) fn operator(&mut self, x: i32, y: i32) {
+        self.finish_definition();
+        match (x, y) {
+            (35, 0) => *self = BackReference::default(),
+            (36, 0) => {
+                self.defining = Some(Definition {
+                    end: self.len,
+                    present: vec![],
+                    keep: true,
+                });
+            }
+            (37, 0) => self.targets = self.kept.clone().unwrap_or_default().into(),
+            (37, 255) => self.kept = None,
+            (_, 0) => {
+                if let Some(kind) = QualityKind::of_operator(x) {
+                    self.kind = Some(kind);
+                    self.targets.clear();
+                    self.defining = Some(Definition {
+                        end: self.len,
+                        present: vec![],
+                        keep: false,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Take in an element read on its own, pushed as `record` of `values`;
+    /// `number` is its value
+    pub(super) fn element<'a, C: Container<'a>>(
+        &mut self,
+        fxy: FXY,
+        number: Option<f64>,
+        record: Option<usize>,
+        values: &mut C,
+    ) {
+        if let Some(definition) = self.defining.as_mut() {
+            if is_indicator(&fxy) {
+                definition.present.push(number == Some(0.0));
+                return;
+            }
+            // Replication counts of the bit-map
+            if fxy.f == 0 && fxy.x == 31 {
+                return;
+            }
+            self.finish_definition();
+        }
+
+        if !referable(&fxy) {
+            return;
+        }
+
+        if self.kind == Some(QualityKind::Quality)
+            && fxy.x == 33
+            && let Some(target) = self.targets.pop_front()
+        {
+            self.link(target, record, QualityKind::Quality, values);
+        }
+
+        self.runs.push(Run::Single(Slot {
+            fxy,
+            record,
+            offset: 0,
+        }));
+        self.len += 1;
+    }
+
+    /// Take in `times` repetitions of `layout`, its first field pushed as
+    /// `first_record`
+    pub(super) fn array(
+        &mut self,
+        layout: Arc<CompiledLayout>,
+        times: usize,
+        first_record: Option<usize>,
+    ) {
+        let per_time = layout.fields.iter().filter(|f| referable(&f.fxy)).count();
+        self.len += per_time.saturating_mul(times);
+        self.runs.push(Run::Array {
+            first_record,
+            layout,
+            times,
+        });
+    }
+
+    /// The element the next 2-`x`-255 marker is for, also finishing the
+    /// bit-map being read
+    pub(super) fn next_target(&mut self, x: i32) -> Option<(Slot, QualityKind)> {
+        self.finish_definition();
+        let kind = QualityKind::of_operator(x)?;
+        self.targets.pop_front().map(|target| (target, kind))
+    }
+
+    /// Link `target` to record `quality` of `values`
+    pub(super) fn link<'a, C: Container<'a>>(
+        &self,
+        target: Slot,
+        quality: Option<usize>,
+        kind: QualityKind,
+        values: &mut C,
+    ) {
+        if let (Some(element), Some(quality)) = (target.record, quality) {
+            values.link_quality(QualityLink {
+                element,
+                offset: target.offset,
+                quality,
+                kind,
+            });
+        }
+    }
+
+    /// Turn a complete bit-map into the elements awaiting values
+    fn finish_definition(&mut self) {
+        let Some(definition) = self.defining.take() else {
+            return;
+        };
+        let referred = self.first(definition.end.min(definition.present.len()));
+        let present: Vec<Slot> = referred
+            .into_iter()
+            .zip(definition.present)
+            .filter_map(|(slot, present)| present.then_some(slot))
+            .collect();
+        if definition.keep {
+            self.kept = Some(present.clone());
+        }
+        if self.kind.is_some() {
+            self.targets = present.into();
+        }
+    }
+
+    /// The first `n` elements, in order
+    fn first(&self, n: usize) -> Vec<Slot> {
+        let mut slots = Vec::with_capacity(n);
+        'runs: for run in &self.runs {
+            if slots.len() == n {
+                break;
+            }
+            match run {
+                Run::Single(slot) => slots.push(*slot),
+                Run::Array {
+                    first_record,
+                    layout,
+                    times,
+                } => {
+                    for offset in 0..*times {
+                        for (field, spec) in layout.fields.iter().enumerate() {
+                            if slots.len() == n {
+                                break 'runs;
+                            }
+                            if referable(&spec.fxy) {
+                                slots.push(Slot {
+                                    fxy: spec.fxy,
+                                    record: first_record.map(|first| first + field),
+                                    offset,
+                                });
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        slots
+    }
+}
+
+impl BUFRParsed<'_> {
+    /// The quality, substituted or statistical records a bit-map links to
+    /// record `record_index`, in the order they were decoded
+    pub fn quality_for(&self, record_index: usize) -> Vec<BUFRRecord<'_>> {
+        self.quality
+            .iter()
+            .filter(|link| link.element == record_index)
+            .filter_map(|link| self.record(link.quality))
+            .collect()
+    }
+
+    /// Every link between an element and its quality information
+    pub fn quality_links(&self) -> &[QualityLink] {
+        &self.quality
+    }
+}
+
+/// Whether `fxy` is one of the markers standing for a referred element
+pub(super) fn is_marker<K: BUFRKey>(operator: &K) -> bool {
+    operator.y() == 255 && QualityKind::of_operator(operator.x()).is_some()
+}
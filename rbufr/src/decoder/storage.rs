@@ -0,0 +1,504 @@
+//! How `BUFRParsed` keeps its records: the values of every record in a few
+//! buffers shared by the whole message, each record holding the ranges of
+//! its own, and names and units borrowed from the tables. Decoding a
+//! message allocates as the buffers grow rather than once per record.
+
+use std::borrow::Cow;
+use std::ops::Range;
+
+use crate::core::FXY;
+
+use super::{
+    BUFRData, BUFRParsed, BUFRRecord, BoundingBox, Encoding, LATITUDES, LONGITUDES, MissingMask,
+    Value,
+};
+
+/// A record as `BUFRParsed` keeps it; `BUFRParsed::record` lends it out as
+/// a `BUFRRecord`
+#[derive(Clone, serde::Deserialize, serde::Serialize)]
+pub(super) struct Entry<'a> {
+    pub(super) fxy: FXY,
+    pub(super) name: Option<Cow<'a, str>>,
+    pub(super) unit: Option<Cow<'a, str>>,
+    scale: Option<i32>,
+    reference: Option<i32>,
+    width_bits: Option<u32>,
+    raw: Option<u64>,
+    pub(super) data: Stored<'a>,
+}
+
+/// Where the values of a record are, `BUFRData` with ranges of the
+/// buffers of its `BUFRParsed` in place of slices
+#[derive(Clone, serde::Deserialize, serde::Serialize)]
+pub(super) enum Stored<'a> {
+    Single(Value<'a>),
+    /// Range of `values`
+    Repeat(Range<usize>),
+    /// Range of `numbers`, with the mask from word `missing` of `missing`
+    Array {
+        values: Range<usize>,
+        missing: usize,
+    },
+    /// Range of `floats`, with the mask from word `missing` of `missing`
+    ArrayF32 {
+        values: Range<usize>,
+        missing: usize,
+    },
+    Array2 {
+        rows: usize,
+        cols: usize,
+        values: Range<usize>,
+        missing: usize,
+    },
+    Group {
+        begin: bool,
+        iteration: usize,
+        count: usize,
+    },
+}
+
+impl<'a> Entry<'a> {
+    pub(super) fn new(
+        fxy: FXY,
+        encoding: Option<Encoding>,
+        name: Option<&'a str>,
+        data: Stored<'a>,
+        unit: Option<&'a str>,
+    ) -> Self {
+        Entry {
+            fxy,
+            name: name.map(Cow::Borrowed),
+            unit: unit.map(Cow::Borrowed),
+            scale: encoding.map(|e| e.scale),
+            reference: encoding.map(|e| e.reference),
+            width_bits: encoding.map(|e| e.width_bits),
+            raw: encoding.and_then(|e| e.raw),
+            data,
+        }
+    }
+
+    /// This entry with its names copied; the ranges stay valid in buffers
+    /// moved or copied along with it
+    fn into_owned(self) -> Entry<'static> {
+        Entry {
+            fxy: self.fxy,
+            name: self.name.map(|n| Cow::Owned(n.into_owned())),
+            unit: self.unit.map(|u| Cow::Owned(u.into_owned())),
+            scale: self.scale,
+            reference: self.reference,
+            width_bits: self.width_bits,
+            raw: self.raw,
+            data: match self.data {
+                Stored::Single(value) => Stored::Single(value.into_owned()),
+                Stored::Repeat(values) => Stored::Repeat(values),
+                Stored::Array { values, missing } => Stored::Array { values, missing },
+                Stored::ArrayF32 { values, missing } => Stored::ArrayF32 { values, missing },
+                Stored::Array2 {
+                    rows,
+                    cols,
+                    values,
+                    missing,
+                } => Stored::Array2 {
+                    rows,
+                    cols,
+                    values,
+                    missing,
+                },
+                Stored::Group {
+                    begin,
+                    iteration,
+                    count,
+                } => Stored::Group {
+                    begin,
+                    iteration,
+                    count,
+                },
+            },
+        }
+    }
+}
+
+impl<'a> BUFRParsed<'a> {
+    /// Append `values` to `numbers` and their mask to `missing`, word
+    /// aligned: the range and the first word
+    pub(super) fn store_numbers(
+        &mut self,
+        values: &[f64],
+        missing: &MissingMask,
+    ) -> (Range<usize>, usize) {
+        let start = self.numbers.len();
+        self.numbers.extend_from_slice(values);
+        (start..self.numbers.len(), self.store_missing(missing))
+    }
+
+    /// Like `store_numbers`, to `floats`
+    pub(super) fn store_floats(
+        &mut self,
+        values: &[f32],
+        missing: &MissingMask,
+    ) -> (Range<usize>, usize) {
+        let start = self.floats.len();
+        self.floats.extend_from_slice(values);
+        (start..self.floats.len(), self.store_missing(missing))
+    }
+
+    fn store_missing(&mut self, missing: &MissingMask) -> usize {
+        let start = self.missing.len();
+        self.missing.extend_from_slice(missing.words());
+        start
+    }
+
+    pub(super) fn store_values(&mut self, values: Vec<Value<'a>>) -> Range<usize> {
+        let start = self.values.len();
+        self.values.extend(values);
+        start..self.values.len()
+    }
+
+    /// The record at `index`, in decode order
+    pub fn record(&self, index: usize) -> Option<BUFRRecord<'_>> {
+        self.records.get(index).map(|entry| self.view(entry))
+    }
+
+    pub(super) fn view<'p>(&'p self, entry: &'p Entry<'a>) -> BUFRRecord<'p> {
+        let mask = |missing: usize, len: usize| mask(&self.missing, missing, len);
+        let values = match &entry.data {
+            Stored::Single(value) => BUFRData::Single(value.borrowed()),
+            Stored::Repeat(values) => {
+                let values: &'p [Value<'p>] = &self.values[values.clone()];
+                BUFRData::Repeat(Cow::Borrowed(values))
+            }
+            Stored::Array { values, missing } => BUFRData::Array {
+                values: Cow::Borrowed(&self.numbers[values.clone()]),
+                missing: mask(*missing, values.len()),
+            },
+            Stored::ArrayF32 { values, missing } => BUFRData::ArrayF32 {
+                values: Cow::Borrowed(&self.floats[values.clone()]),
+                missing: mask(*missing, values.len()),
+            },
+            Stored::Array2 {
+                rows,
+                cols,
+                values,
+                missing,
+            } => BUFRData::Array2 {
+                rows: *rows,
+                cols: *cols,
+                values: Cow::Borrowed(&self.numbers[values.clone()]),
+                missing: mask(*missing, values.len()),
+            },
+            Stored::Group {
+                begin,
+                iteration,
+                count,
+            } => BUFRData::Group {
+                begin: *begin,
+                iteration: *iteration,
+                count: *count,
+            },
+        };
+        BUFRRecord {
+            fxy: entry.fxy,
+            name: entry.name.as_deref().map(Cow::Borrowed),
+            values,
+            unit: entry.unit.as_deref().map(Cow::Borrowed),
+            scale: entry.scale,
+            reference: entry.reference,
+            width_bits: entry.width_bits,
+            raw: entry.raw,
+        }
+    }
+
+    /// Drop the records from `len` on along with their values
+    #[cfg(feature = "opera")]
+    pub(super) fn truncate(&mut self, len: usize) {
+        let Some(removed) = self.records.get(len..) else {
+            return;
+        };
+        let (mut numbers, mut floats, mut values, mut missing) = (
+            self.numbers.len(),
+            self.floats.len(),
+            self.values.len(),
+            self.missing.len(),
+        );
+        for entry in removed {
+            match &entry.data {
+                Stored::Repeat(range) => values = values.min(range.start),
+                Stored::Array {
+                    values: range,
+                    missing: word,
+                }
+                | Stored::Array2 {
+                    values: range,
+                    missing: word,
+                    ..
+                } => {
+                    numbers = numbers.min(range.start);
+                    missing = missing.min(*word);
+                }
+                Stored::ArrayF32 {
+                    values: range,
+                    missing: word,
+                } => {
+                    floats = floats.min(range.start);
+                    missing = missing.min(*word);
+                }
+                Stored::Single(_) | Stored::Group { .. } => {}
+            }
+        }
+        self.records.truncate(len);
+        self.numbers.truncate(numbers);
+        self.floats.truncate(floats);
+        self.values.truncate(values);
+        self.missing.truncate(missing);
+    }
+
+    /// Drop the subsets of compressed data located outside `bbox`, from
+    /// every record holding a value per subset; see `Decoder::with_bbox`
+    pub(super) fn clip(&mut self, bbox: BoundingBox, subsets: usize) {
+        // The first latitude or longitude of a subset that isn't missing
+        let first = |fxys: &[FXY], index: usize| {
+            self.records
+                .iter()
+                .filter(|entry| fxys.contains(&entry.fxy))
+                .find_map(|entry| self.subset_number(entry, index))
+        };
+        let keep: Vec<bool> = (0..subsets)
+            .map(|i| match (first(&LATITUDES, i), first(&LONGITUDES, i)) {
+                (Some(lat), Some(lon)) => bbox.contains(lat, lon),
+                _ => true,
+            })
+            .collect();
+        if keep.iter().all(|&k| k) {
+            return;
+        }
+
+        // Values of one record, less the dropped subsets if it has one per subset
+        fn kept<T: Clone>(items: &[T], keep: &[bool]) -> Vec<T> {
+            if items.len() != keep.len() {
+                return items.to_vec();
+            }
+            items
+                .iter()
+                .zip(keep)
+                .filter(|(_, k)| **k)
+                .map(|(item, _)| item.clone())
+                .collect()
+        }
+        let kept_mask = |word: usize, len: usize| -> MissingMask<'static> {
+            kept(
+                &mask(&self.missing, word, len).iter().collect::<Vec<_>>(),
+                &keep,
+            )
+            .into_iter()
+            .collect()
+        };
+
+        let mut clipped = BUFRParsed::new();
+        clipped.records.reserve(self.records.len());
+        for entry in &self.records {
+            let data = match &entry.data {
+                Stored::Repeat(range) => {
+                    Stored::Repeat(clipped.store_values(kept(&self.values[range.clone()], &keep)))
+                }
+                Stored::Array { values, missing } => {
+                    let numbers = kept(&self.numbers[values.clone()], &keep);
+                    let missing = kept_mask(*missing, values.len());
+                    let (values, missing) = clipped.store_numbers(&numbers, &missing);
+                    Stored::Array { values, missing }
+                }
+                Stored::ArrayF32 { values, missing } => {
+                    let floats = kept(&self.floats[values.clone()], &keep);
+                    let missing = kept_mask(*missing, values.len());
+                    let (values, missing) = clipped.store_floats(&floats, &missing);
+                    Stored::ArrayF32 { values, missing }
+                }
+                Stored::Array2 {
+                    rows,
+                    cols,
+                    values,
+                    missing,
+                } => {
+                    let numbers = &self.numbers[values.clone()];
+                    let missing = mask(&self.missing, *missing, values.len());
+                    let (values, missing) = clipped.store_numbers(numbers, &missing);
+                    Stored::Array2 {
+                        rows: *rows,
+                        cols: *cols,
+                        values,
+                        missing,
+                    }
+                }
+                data => data.clone(),
+            };
+            clipped.records.push(Entry {
+                data,
+                ..entry.clone()
+            });
+        }
+        clipped.quality = std::mem::take(&mut self.quality);
+        clipped.compressed = self.compressed;
+        *self = clipped;
+    }
+
+    /// Number in subset `index` of a record of compressed data
+    fn subset_number(&self, entry: &Entry<'a>, index: usize) -> Option<f64> {
+        match &entry.data {
+            Stored::Single(Value::Number(n)) => Some(*n),
+            Stored::Repeat(range) => match self.values[range.clone()].get(index)? {
+                Value::Number(n) => Some(*n),
+                _ => None,
+            },
+            Stored::Array { values, missing } => {
+                let numbers = &self.numbers[values.clone()];
+                let missing = mask(&self.missing, *missing, values.len());
+                (index < numbers.len() && !missing.get(index)).then(|| numbers[index])
+            }
+            Stored::ArrayF32 { values, missing } => {
+                let floats = &self.floats[values.clone()];
+                let missing = mask(&self.missing, *missing, values.len());
+                (index < floats.len() && !missing.get(index)).then(|| f64::from(floats[index]))
+            }
+            _ => None,
+        }
+    }
+
+    /// The `(FXY, value)` stream `Decoder::decode_values` gives for the
+    /// same data
+    pub(super) fn into_values(self) -> Vec<(FXY, Value<'a>)> {
+        let mut out = vec![];
+        let numbers = |values: &mut Vec<_>, fxy, numbers: &[f64], missing: MissingMask| {
+            values.extend(numbers.iter().zip(missing.iter()).map(|(&n, missing)| {
+                if missing {
+                    (fxy, Value::Missing)
+                } else {
+                    (fxy, Value::Number(n))
+                }
+            }))
+        };
+        for entry in &self.records {
+            match &entry.data {
+                Stored::Single(value) => out.push((entry.fxy, value.clone())),
+                Stored::Repeat(range) => out.extend(
+                    self.values[range.clone()]
+                        .iter()
+                        .map(|value| (entry.fxy, value.clone())),
+                ),
+                Stored::Array { values, missing }
+                | Stored::Array2 {
+                    values, missing, ..
+                } => numbers(
+                    &mut out,
+                    entry.fxy,
+                    &self.numbers[values.clone()],
+                    mask(&self.missing, *missing, values.len()),
+                ),
+                Stored::ArrayF32 { values, missing } => {
+                    let floats: Vec<f64> = self.floats[values.clone()]
+                        .iter()
+                        .map(|&v| f64::from(v))
+                        .collect();
+                    numbers(
+                        &mut out,
+                        entry.fxy,
+                        &floats,
+                        mask(&self.missing, *missing, values.len()),
+                    )
+                }
+                Stored::Group { .. } => {}
+            }
+        }
+        out
+    }
+
+    /// A copy that outlives the decoder, names and units included
+    pub fn into_owned(&self) -> BUFRParsed<'static> {
+        BUFRParsed {
+            records: self
+                .records
+                .iter()
+                .map(|r| r.clone().into_owned())
+                .collect(),
+            numbers: self.numbers.clone(),
+            floats: self.floats.clone(),
+            values: self.values.iter().map(|v| v.clone().into_owned()).collect(),
+            missing: self.missing.clone(),
+            quality: self.quality.clone(),
+            compressed: self.compressed,
+        }
+    }
+
+    /// Like `into_owned`, moving the buffers instead of copying them
+    pub fn into_static(self) -> BUFRParsed<'static> {
+        BUFRParsed {
+            records: self.records.into_iter().map(Entry::into_owned).collect(),
+            numbers: self.numbers,
+            floats: self.floats,
+            values: self.values.into_iter().map(Value::into_owned).collect(),
+            missing: self.missing,
+            quality: self.quality,
+            compressed: self.compressed,
+        }
+    }
+}
+
+impl<'a> IntoIterator for BUFRParsed<'a> {
+    type Item = BUFRRecord<'a>;
+    type IntoIter = std::vec::IntoIter<BUFRRecord<'a>>;
+
+    /// Every record with its own copy of its values
+    fn into_iter(self) -> Self::IntoIter {
+        let mut records = Vec::with_capacity(self.records.len());
+        for entry in &self.records {
+            let data = match &entry.data {
+                Stored::Repeat(range) => {
+                    BUFRData::Repeat(Cow::Owned(self.values[range.clone()].to_vec()))
+                }
+                Stored::Single(value) => BUFRData::Single(value.clone()),
+                Stored::Array { values, missing } => BUFRData::Array {
+                    values: Cow::Owned(self.numbers[values.clone()].to_vec()),
+                    missing: mask(&self.missing, *missing, values.len()).into_owned(),
+                },
+                Stored::ArrayF32 { values, missing } => BUFRData::ArrayF32 {
+                    values: Cow::Owned(self.floats[values.clone()].to_vec()),
+                    missing: mask(&self.missing, *missing, values.len()).into_owned(),
+                },
+                Stored::Array2 {
+                    rows,
+                    cols,
+                    values,
+                    missing,
+                } => BUFRData::Array2 {
+                    rows: *rows,
+                    cols: *cols,
+                    values: Cow::Owned(self.numbers[values.clone()].to_vec()),
+                    missing: mask(&self.missing, *missing, values.len()).into_owned(),
+                },
+                Stored::Group {
+                    begin,
+                    iteration,
+                    count,
+                } => BUFRData::Group {
+                    begin: *begin,
+                    iteration: *iteration,
+                    count: *count,
+                },
+            };
+            records.push(BUFRRecord {
+                fxy: entry.fxy,
+                name: entry.name.clone(),
+                values: data,
+                unit: entry.unit.clone(),
+                scale: entry.scale,
+                reference: entry.reference,
+                width_bits: entry.width_bits,
+                raw: entry.raw,
+            });
+        }
+        records.into_iter()
+    }
+}
+
+/// The mask of `len` elements from word `start` of `words`
+fn mask(words: &[u64], start: usize, len: usize) -> MissingMask<'_> {
+    MissingMask::borrowed(&words[start..][..len.div_ceil(64)], len)
+}
@@ -0,0 +1,279 @@
+use std::fmt::Display;
+use std::ops::Deref;
+
+use crate::core::FXY;
+use crate::errors::Result;
+use crate::structs::versions::MessageVersion;
+
+use super::builder::Preferred;
+use super::compressed::compressed_subsets;
+use super::{BUFRParsed, BitInput, Container, Decoder, Encoding, MissingMask, QualityLink, Value};
+
+/// One descriptor processed by `Decoder::decode_traced`
+///
+/// Elements carry how they were packed and what they decoded to;
+/// replications and operators only their descriptor and offset.
+#[derive(Debug, Clone)]
+pub struct TraceEvent<'a> {
+    pub fxy: FXY,
+    /// Element name, empty for replications and operators
+    pub name: &'a str,
+    /// Effective data width in bits after operators
+    pub width_bits: Option<u32>,
+    /// Effective scale after operators
+    pub scale: Option<i32>,
+    /// Effective reference value after operators
+    pub reference: Option<i32>,
+    /// Unscaled integer as read from the data section, for numeric values
+    pub raw: Option<u64>,
+    /// The decoded value, or the repetition count of a replication
+    pub value: Option<Value<'a>>,
+    /// Bits of the data section read once this descriptor was
+    pub offset: usize,
+}
+
+impl TraceEvent<'_> {
+    fn new<'a>(
+        fxy: FXY,
+        name: &'a str,
+        encoding: Option<Encoding>,
+        value: Value<'a>,
+    ) -> TraceEvent<'a> {
+        TraceEvent {
+            fxy,
+            name,
+            width_bits: encoding.map(|e| e.width_bits),
+            scale: encoding.map(|e| e.scale),
+            reference: encoding.map(|e| e.reference),
+            raw: encoding.and_then(|e| e.raw),
+            value: Some(value),
+            offset: 0,
+        }
+    }
+
+    fn descriptor(fxy: FXY, value: Option<Value<'static>>) -> TraceEvent<'static> {
+        TraceEvent {
+            fxy,
+            name: "",
+            width_bits: None,
+            scale: None,
+            reference: None,
+            raw: None,
+            value,
+            offset: 0,
+        }
+    }
+}
+
+impl Display for TraceEvent<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:>8} {}-{:02}-{:03}",
+            self.offset, self.fxy.f, self.fxy.x, self.fxy.y
+        )?;
+        if !self.name.is_empty() {
+            write!(f, " {}", self.name)?;
+        }
+        if let Some(width) = self.width_bits {
+            write!(f, " width={}", width)?;
+        }
+        if let Some(scale) = self.scale {
+            write!(f, " scale={}", scale)?;
+        }
+        if let Some(reference) = self.reference {
+            write!(f, " reference={}", reference)?;
+        }
+        if let Some(raw) = self.raw {
+            write!(f, " raw={}", raw)?;
+        }
+        if let Some(value) = &self.value {
+            write!(f, " = {}", value)?;
+        }
+        Ok(())
+    }
+}
+
+impl Decoder {
+    /// Decode like `decode`, handing `tracer` an event for every descriptor
+    /// processed, in bitstream order
+    ///
+    /// Replications are never compiled into arrays here, so every element
+    /// is traced. In lenient mode the events of a message that fails stop
+    /// where decoding did.
+    pub fn decode_traced<'a, V: MessageVersion + 'a>(
+        &'a self,
+        message: &'a impl Deref<Target = V>,
+        tracer: impl FnMut(&TraceEvent<'_>),
+    ) -> Result<BUFRParsed<'a>> {
+        let data_block = message.data_block()?;
+        let descriptors = message.descriptors()?;
+
+        let mut data_input = BitInput::new(data_block);
+        self.skip_to_selected(message, &descriptors, &mut data_input)?;
+        let mut record = BUFRParsed::new();
+        // Outside the preferences, so that skipped missing values are
+        // traced too and values are in their BUFR units
+        let mut preferred = Preferred {
+            inner: &mut record,
+            missing: self.missing,
+            units: self.units,
+        };
+        let mut trace = Trace {
+            inner: &mut preferred,
+            tracer,
+            pending: vec![],
+        };
+        let result = self.decode_descriptors(
+            &descriptors,
+            &mut data_input,
+            &mut trace,
+            compressed_subsets(message),
+        );
+        trace.at_bit(data_input.position());
+
+        let mut record = self.recover(result, record)?;
+        self.missing_values.apply_all(&mut record);
+        Ok(record)
+    }
+}
+
+/// Forwards everything to `inner`, tracing it on the way
+struct Trace<'r, 'a, C, F> {
+    inner: &'r mut C,
+    tracer: F,
+    // events of the current step, waiting for its bit offset
+    pending: Vec<TraceEvent<'a>>,
+}
+
+impl<'a, C: Container<'a>, F: FnMut(&TraceEvent<'_>)> Container<'a> for Trace<'_, 'a, C, F> {
+    fn push(
+        &mut self,
+        value: Value<'a>,
+        fxy: FXY,
+        encoding: Option<Encoding>,
+        name: &'a str,
+        unit: &'a str,
+    ) {
+        self.pending
+            .push(TraceEvent::new(fxy, name, encoding, value.clone()));
+        self.inner.push(value, fxy, encoding, name, unit);
+    }
+
+    // Only reached if an array is pushed despite `compiles_arrays`
+    fn push_array(
+        &mut self,
+        values: &mut [f64],
+        missing: &MissingMask,
+        fxy: FXY,
+        encoding: Encoding,
+        name: &'a str,
+        unit: &'a str,
+    ) {
+        self.inner
+            .push_array(values, missing, fxy, encoding, name, unit);
+    }
+
+    fn push_array_f32(
+        &mut self,
+        values: &mut [f32],
+        missing: &MissingMask,
+        fxy: FXY,
+        encoding: Encoding,
+        name: &'a str,
+        unit: &'a str,
+    ) {
+        self.inner
+            .push_array_f32(values, missing, fxy, encoding, name, unit);
+    }
+
+    fn push_repeat(
+        &mut self,
+        values: Vec<Value<'a>>,
+        fxy: FXY,
+        encoding: Encoding,
+        name: &'a str,
+        unit: &'a str,
+    ) {
+        self.inner.push_repeat(values, fxy, encoding, name, unit);
+    }
+
+    fn keeps_values(&self) -> bool {
+        self.inner.keeps_values()
+    }
+
+    fn keeps_structure(&self) -> bool {
+        self.inner.keeps_structure()
+    }
+
+    fn begin_sequence(&mut self, fxy: FXY, name: &'a str) {
+        self.inner.begin_sequence(fxy, name);
+    }
+
+    fn end_sequence(&mut self) {
+        self.inner.end_sequence();
+    }
+
+    fn compiles_arrays(&self) -> bool {
+        false
+    }
+
+    fn stopped(&self) -> bool {
+        self.inner.stopped()
+    }
+
+    fn at_bit(&mut self, position: usize) {
+        // Events of one step were read back to back, the last ending here
+        let mut end = position;
+        for event in self.pending.iter_mut().rev() {
+            event.offset = end;
+            end = end.saturating_sub(event.width_bits.unwrap_or(0) as usize);
+        }
+        for event in self.pending.drain(..) {
+            (self.tracer)(&event);
+        }
+    }
+
+    fn begin_replication(&mut self, fxy: FXY, times: usize) {
+        self.pending.push(TraceEvent::descriptor(
+            fxy,
+            Some(Value::Number(times as f64)),
+        ));
+        self.inner.begin_replication(fxy, times);
+    }
+
+    fn empty_replication(&mut self, fxy: FXY) {
+        self.inner.empty_replication(fxy);
+    }
+
+    fn begin_group(&mut self, fxy: FXY, iteration: usize, count: usize) {
+        self.inner.begin_group(fxy, iteration, count);
+    }
+
+    fn end_group(&mut self, fxy: FXY, iteration: usize, count: usize) {
+        self.inner.end_group(fxy, iteration, count);
+    }
+
+    fn operator(&mut self, fxy: FXY) {
+        self.pending.push(TraceEvent::descriptor(fxy, None));
+        self.inner.operator(fxy);
+    }
+
+    fn record_count(&self) -> usize {
+        self.inner.record_count()
+    }
+
+    fn link_quality(&mut self, link: QualityLink) {
+        self.inner.link_quality(link);
+    }
+
+    #[cfg(feature = "opera")]
+    fn bitmap_start(&self) -> usize {
+        self.inner.bitmap_start()
+    }
+
+    #[cfg(feature = "opera")]
+    fn end_bitmap(&mut self, fxy: FXY, depth: u8, start: usize, name: &'a str) {
+        self.inner.end_bitmap(fxy, depth, start, name);
+    }
+}
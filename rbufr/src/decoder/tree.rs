@@ -0,0 +1,233 @@
+use std::borrow::Cow;
+use std::fmt::Display;
+
+use crate::core::FXY;
+
+use super::{BUFRParsed, BUFRRecord, Container, Encoding, MissingMask, Value};
+
+/// Decoded records nested by the Table D sequences they came from
+///
+/// Elements listed directly in Section 3 sit at the top level. Replicated
+/// elements stay in the sequence around the replication.
+#[derive(Clone)]
+pub struct DecodedTree<'a> {
+    pub nodes: Vec<Node<'a>>,
+}
+
+#[derive(Clone)]
+pub enum Node<'a> {
+    Record(BUFRRecord<'a>),
+    Sequence {
+        fxy: FXY,
+        name: Option<Cow<'a, str>>,
+        children: Vec<Node<'a>>,
+    },
+}
+
+impl<'a> DecodedTree<'a> {
+    /// Every record, depth first, as `Decoder::decode` would list them
+    pub fn records(&self) -> Vec<&BUFRRecord<'a>> {
+        fn walk<'r, 'a>(nodes: &'r [Node<'a>], out: &mut Vec<&'r BUFRRecord<'a>>) {
+            for node in nodes {
+                match node {
+                    Node::Record(record) => out.push(record),
+                    Node::Sequence { children, .. } => walk(children, out),
+                }
+            }
+        }
+
+        let mut out = vec![];
+        walk(&self.nodes, &mut out);
+        out
+    }
+}
+
+impl Display for DecodedTree<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fn write_nodes(
+            f: &mut std::fmt::Formatter<'_>,
+            nodes: &[Node<'_>],
+            depth: usize,
+        ) -> std::fmt::Result {
+            for node in nodes {
+                match node {
+                    Node::Record(record) => {
+                        writeln!(f, "{:indent$}{}", "", record, indent = depth * 2)?
+                    }
+                    Node::Sequence {
+                        fxy,
+                        name,
+                        children,
+                    } => {
+                        writeln!(
+                            f,
+                            "{:indent$}{}-{:02}-{:03} {}",
+                            "",
+                            fxy.f,
+                            fxy.x,
+                            fxy.y,
+                            name.as_deref().unwrap_or(""),
+                            indent = depth * 2
+                        )?;
+                        write_nodes(f, children, depth + 1)?;
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        write_nodes(f, &self.nodes, 0)
+    }
+}
+
+struct Span<'a> {
+    fxy: FXY,
+    name: &'a str,
+    depth: usize,
+    start: usize,
+    end: Option<usize>,
+}
+
+/// Collects records like `BUFRParsed` and remembers which of them each
+/// sequence covered
+pub(super) struct TreeBuilder<'a> {
+    pub(super) parsed: BUFRParsed<'a>,
+    // in the order the sequences were entered
+    spans: Vec<Span<'a>>,
+    open: Vec<usize>,
+}
+
+impl<'a> TreeBuilder<'a> {
+    pub(super) fn new() -> Self {
+        TreeBuilder {
+            parsed: BUFRParsed::new(),
+            spans: vec![],
+            open: vec![],
+        }
+    }
+
+    pub(super) fn finish(self) -> DecodedTree<'a> {
+        let len = self.parsed.records.len();
+        let mut records = self.parsed.into_iter();
+        let mut spans = self.spans.into_iter().peekable();
+
+        // Spans are in pre-order: the next one either nests at `depth` here
+        // or belongs to an enclosing sequence
+        fn build<'a>(
+            records: &mut std::vec::IntoIter<BUFRRecord<'a>>,
+            spans: &mut std::iter::Peekable<std::vec::IntoIter<Span<'a>>>,
+            position: &mut usize,
+            depth: usize,
+            end: usize,
+            len: usize,
+        ) -> Vec<Node<'a>> {
+            let mut nodes = vec![];
+            loop {
+                if let Some(span) = spans.next_if(|s| s.depth == depth && s.start == *position) {
+                    // Sequences left open by a lenient decode run to the end
+                    let span_end = span.end.unwrap_or(len);
+                    let children = build(records, spans, position, depth + 1, span_end, len);
+                    nodes.push(Node::Sequence {
+                        fxy: span.fxy,
+                        name: (!span.name.is_empty()).then_some(Cow::Borrowed(span.name)),
+                        children,
+                    });
+                    continue;
+                }
+                if *position >= end {
+                    break;
+                }
+                match records.next() {
+                    Some(record) => nodes.push(Node::Record(record)),
+                    None => break,
+                }
+                *position += 1;
+            }
+            nodes
+        }
+
+        DecodedTree {
+            nodes: build(&mut records, &mut spans, &mut 0, 0, len, len),
+        }
+    }
+}
+
+impl<'a> Container<'a> for TreeBuilder<'a> {
+    fn push(
+        &mut self,
+        value: Value<'a>,
+        fxy: FXY,
+        encoding: Option<Encoding>,
+        name: &'a str,
+        unit: &'a str,
+    ) {
+        self.parsed.push(value, fxy, encoding, name, unit);
+    }
+
+    fn push_array(
+        &mut self,
+        values: &mut [f64],
+        missing: &MissingMask,
+        fxy: FXY,
+        encoding: Encoding,
+        name: &'a str,
+        unit: &'a str,
+    ) {
+        Container::push_array(&mut self.parsed, values, missing, fxy, encoding, name, unit);
+    }
+
+    fn push_array_f32(
+        &mut self,
+        values: &mut [f32],
+        missing: &MissingMask,
+        fxy: FXY,
+        encoding: Encoding,
+        name: &'a str,
+        unit: &'a str,
+    ) {
+        self.parsed
+            .push_array_f32(values, missing, fxy, encoding, name, unit);
+    }
+
+    fn push_repeat(
+        &mut self,
+        values: Vec<Value<'a>>,
+        fxy: FXY,
+        encoding: Encoding,
+        name: &'a str,
+        unit: &'a str,
+    ) {
+        self.parsed.push_repeat(values, fxy, encoding, name, unit);
+    }
+
+    fn keeps_structure(&self) -> bool {
+        true
+    }
+
+    fn begin_sequence(&mut self, fxy: FXY, name: &'a str) {
+        self.spans.push(Span {
+            fxy,
+            name,
+            depth: self.open.len(),
+            start: self.parsed.records.len(),
+            end: None,
+        });
+        self.open.push(self.spans.len() - 1);
+    }
+
+    fn end_sequence(&mut self) {
+        if let Some(index) = self.open.pop() {
+            self.spans[index].end = Some(self.parsed.records.len());
+        }
+    }
+
+    #[cfg(feature = "opera")]
+    fn bitmap_start(&self) -> usize {
+        self.parsed.bitmap_start()
+    }
+
+    #[cfg(feature = "opera")]
+    fn end_bitmap(&mut self, fxy: FXY, depth: u8, start: usize, name: &'a str) {
+        self.parsed.end_bitmap(fxy, depth, start, name);
+    }
+}
@@ -0,0 +1,67 @@
+use std::ops::Deref;
+
+use crate::errors::Result;
+use crate::structs::versions::MessageVersion;
+
+use super::compressed::compressed_subsets;
+use super::{BitInput, Decoder, Discard};
+
+/// Unused bits a data section may end with: padding to a whole octet, and
+/// the octet editions before 4 add to make the section length even
+const PADDING_BITS: usize = 16;
+
+/// The bit accounting of `Decoder::validate`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Validation {
+    pub subsets: usize,
+    /// Bits the descriptors take, delayed counts included
+    pub bits_used: usize,
+    /// Bits of data in Section 4
+    pub bits_available: usize,
+}
+
+impl Validation {
+    /// Bits of Section 4 left once every subset was walked
+    pub fn unused_bits(&self) -> usize {
+        self.bits_available - self.bits_used
+    }
+
+    /// Whether Section 4 is as long as its descriptors say, give or take
+    /// padding
+    pub fn is_consistent(&self) -> bool {
+        self.unused_bits() < PADDING_BITS
+    }
+}
+
+impl Decoder {
+    /// Check that Section 4 is as long as the descriptors need, without
+    /// decoding any record
+    ///
+    /// Every subset is walked, reading only the delayed replication counts
+    /// and the operators' own values; replications that compile to a fixed
+    /// layout are skipped by their total bit width. Compressed data is
+    /// walked once, each element taking its reference value, the 6-bit
+    /// increment width and one increment per subset. Data ending before the
+    /// descriptors do is an error.
+    pub fn validate<V: MessageVersion>(
+        &self,
+        message: &impl Deref<Target = V>,
+    ) -> Result<Validation> {
+        let data_block = message.data_block()?;
+        let descriptors = message.descriptors()?;
+
+        let subsets = message.subsets_count() as usize;
+        let compressed = compressed_subsets(message);
+        let mut data_input = BitInput::new(data_block);
+        let walks = if compressed.is_some() { 1 } else { subsets };
+        for _ in 0..walks {
+            self.decode_descriptors(&descriptors, &mut data_input, &mut Discard, compressed)?;
+        }
+
+        Ok(Validation {
+            subsets,
+            bits_used: data_input.position(),
+            bits_available: data_block.len() * 8,
+        })
+    }
+}
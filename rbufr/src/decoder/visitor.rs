@@ -0,0 +1,339 @@
+use std::ops::{ControlFlow, Deref};
+
+use crate::core::FXY;
+use crate::errors::Result;
+use crate::structs::versions::MessageVersion;
+
+use super::builder::MissingPolicy;
+use super::compressed::compressed_subsets;
+use super::{
+    BitInput, BoundingBox, Container, Decoder, Discard, Encoding, LATITUDES, LONGITUDES,
+    MissingMask, Value,
+};
+
+/// Callbacks for `Decoder::decode_with`, made as the bitstream is walked
+///
+/// Nothing is collected on the way: values are handed over one at a time,
+/// in bitstream order, and replications are never compiled into arrays.
+/// Returning `ControlFlow::Break` stops the walk there, leaving the rest of
+/// the message undecoded.
+///
+/// Closures taking `(FXY, Value)` are visitors too.
+pub trait DecodeVisitor {
+    /// A decoded element, after the missing-value policy and unit preference;
+    /// its string borrows from the message, `Value::into_owned` keeps it
+    fn element(&mut self, fxy: FXY, value: Value) -> ControlFlow<()>;
+
+    /// A replication descriptor, with the delayed count already read
+    fn begin_replication(&mut self, _fxy: FXY, _times: usize) {}
+
+    /// The subset at `index` has been decoded
+    fn end_subset(&mut self, _index: usize) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+
+    /// An operator descriptor (F = 2), before it takes effect
+    fn operator(&mut self, _fxy: FXY) {}
+}
+
+impl<F: FnMut(FXY, Value) -> ControlFlow<()>> DecodeVisitor for F {
+    fn element(&mut self, fxy: FXY, value: Value) -> ControlFlow<()> {
+        self(fxy, value)
+    }
+}
+
+impl Decoder {
+    /// Walk every subset of a message, reporting to `visitor`
+    ///
+    /// Only the subsets selected with `DecoderBuilder::subsets` are visited.
+    /// Returns early, without error, once the visitor breaks. Compressed
+    /// data holds each element for every subset at once, so it is decoded
+    /// whole before the first subset is visited.
+    pub fn decode_with<V: MessageVersion>(
+        &self,
+        message: &impl Deref<Target = V>,
+        visitor: &mut impl DecodeVisitor,
+    ) -> Result<()> {
+        let data_block = message.data_block()?;
+        let descriptors = message.descriptors()?;
+        if let Some(subsets) = compressed_subsets(message) {
+            let mut columns = Columns {
+                subsets,
+                events: vec![],
+            };
+            let mut data_input = BitInput::new(data_block);
+            let result = self.walk(&descriptors, &mut data_input, &mut columns, Some(subsets));
+            self.recover(result, ())?;
+            self.replay(&columns, visitor);
+            return Ok(());
+        }
+
+        let subsets = message.subsets_count() as usize;
+        let selected = self.subsets.clone().unwrap_or(0..subsets);
+        let mut data_input = BitInput::new(data_block);
+        let mut visit = Visit {
+            visitor,
+            stopped: false,
+        };
+        for index in 0..subsets.min(selected.end) {
+            if index < selected.start {
+                self.decode_descriptors(&descriptors, &mut data_input, &mut Discard, None)?;
+                continue;
+            }
+            if self.bbox.is_some() {
+                // Held back until the position, which may come late, is known
+                let mut columns = Columns {
+                    subsets: 1,
+                    events: vec![],
+                };
+                let result = self.walk(&descriptors, &mut data_input, &mut columns, None);
+                if columns.inside(self.bbox, 0)
+                    && self
+                        .replay_subset(&columns, 0, index, visit.visitor)
+                        .is_break()
+                {
+                    break;
+                }
+                if result.is_err() {
+                    return self.recover(result, ());
+                }
+                continue;
+            }
+            let result = self.walk(&descriptors, &mut data_input, &mut visit, None);
+            if result.is_err() {
+                return self.recover(result, ());
+            }
+            if visit.stopped || visit.visitor.end_subset(index).is_break() {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Decoder {
+    /// Visit the selected subsets of decoded compressed data
+    fn replay(&self, columns: &Columns, visitor: &mut impl DecodeVisitor) {
+        let selected = self.subsets.clone().unwrap_or(0..columns.subsets);
+        for index in selected.start..columns.subsets.min(selected.end) {
+            if columns.inside(self.bbox, index)
+                && self
+                    .replay_subset(columns, index, index, visitor)
+                    .is_break()
+            {
+                return;
+            }
+        }
+    }
+
+    /// Visit the values of subset `column` of `columns` as subset `index`
+    fn replay_subset(
+        &self,
+        columns: &Columns,
+        column: usize,
+        index: usize,
+        visitor: &mut impl DecodeVisitor,
+    ) -> ControlFlow<()> {
+        for event in &columns.events {
+            match event {
+                Event::Element(fxy, values) => {
+                    let value = &values[column];
+                    if self.missing == MissingPolicy::Skip && value.is_missing() {
+                        continue;
+                    }
+                    visitor.element(*fxy, value.clone())?;
+                }
+                Event::Replication(fxy, times) => visitor.begin_replication(*fxy, *times),
+                Event::Operator(fxy) => visitor.operator(*fxy),
+            }
+        }
+        visitor.end_subset(index)
+    }
+}
+
+/// What the walk of compressed data reported, in order
+enum Event<'a> {
+    /// An element with its value in every subset
+    Element(FXY, Vec<Value<'a>>),
+    Replication(FXY, usize),
+    Operator(FXY),
+}
+
+/// Collects compressed data, element by element, for `Decoder::replay`
+struct Columns<'a> {
+    subsets: usize,
+    events: Vec<Event<'a>>,
+}
+
+impl Columns<'_> {
+    /// Whether subset `index` lies in `bbox`, or has no position; see
+    /// `Decoder::with_bbox`
+    fn inside(&self, bbox: Option<BoundingBox>, index: usize) -> bool {
+        let Some(bbox) = bbox else {
+            return true;
+        };
+        let first = |fxys: &[FXY]| {
+            self.events.iter().find_map(|event| match event {
+                Event::Element(fxy, values) if fxys.contains(fxy) => match values.get(index) {
+                    Some(Value::Number(n)) => Some(*n),
+                    _ => None,
+                },
+                _ => None,
+            })
+        };
+        match (first(&LATITUDES), first(&LONGITUDES)) {
+            (Some(lat), Some(lon)) => bbox.contains(lat, lon),
+            _ => true,
+        }
+    }
+}
+
+impl<'a> Container<'a> for Columns<'a> {
+    fn push(&mut self, value: Value<'a>, fxy: FXY, _: Option<Encoding>, _: &'a str, _: &'a str) {
+        self.events
+            .push(Event::Element(fxy, vec![value; self.subsets]));
+    }
+
+    fn push_array(
+        &mut self,
+        values: &mut [f64],
+        missing: &MissingMask,
+        fxy: FXY,
+        _: Encoding,
+        _: &'a str,
+        _: &'a str,
+    ) {
+        let values = values
+            .iter()
+            .zip(missing.iter())
+            .map(|(&value, missing)| {
+                if missing {
+                    Value::Missing
+                } else {
+                    Value::Number(value)
+                }
+            })
+            .collect();
+        self.events.push(Event::Element(fxy, values));
+    }
+
+    fn push_array_f32(
+        &mut self,
+        values: &mut [f32],
+        missing: &MissingMask,
+        fxy: FXY,
+        encoding: Encoding,
+        name: &'a str,
+        unit: &'a str,
+    ) {
+        let mut values: Vec<f64> = values.iter().map(|&v| f64::from(v)).collect();
+        self.push_array(&mut values, missing, fxy, encoding, name, unit);
+    }
+
+    fn push_repeat(
+        &mut self,
+        values: Vec<Value<'a>>,
+        fxy: FXY,
+        _: Encoding,
+        _: &'a str,
+        _: &'a str,
+    ) {
+        self.events.push(Event::Element(fxy, values));
+    }
+
+    fn compiles_arrays(&self) -> bool {
+        false
+    }
+
+    fn begin_replication(&mut self, fxy: FXY, times: usize) {
+        self.events.push(Event::Replication(fxy, times));
+    }
+
+    fn operator(&mut self, fxy: FXY) {
+        self.events.push(Event::Operator(fxy));
+    }
+}
+
+/// Forwards what the decoder pushes to a `DecodeVisitor`
+struct Visit<'v, V> {
+    visitor: &'v mut V,
+    // set once the visitor breaks; nothing is forwarded after that
+    stopped: bool,
+}
+
+impl<V: DecodeVisitor> Visit<'_, V> {
+    fn element(&mut self, fxy: FXY, value: Value) {
+        if !self.stopped {
+            self.stopped = self.visitor.element(fxy, value).is_break();
+        }
+    }
+}
+
+impl<'a, V: DecodeVisitor> Container<'a> for Visit<'_, V> {
+    fn push(&mut self, value: Value<'a>, fxy: FXY, _: Option<Encoding>, _: &'a str, _: &'a str) {
+        self.element(fxy, value);
+    }
+
+    // Only reached if an array is pushed despite `compiles_arrays`
+    fn push_array(
+        &mut self,
+        values: &mut [f64],
+        missing: &MissingMask,
+        fxy: FXY,
+        _: Encoding,
+        _: &'a str,
+        _: &'a str,
+    ) {
+        for (&value, missing) in values.iter().zip(missing.iter()) {
+            let value = if missing {
+                Value::Missing
+            } else {
+                Value::Number(value)
+            };
+            self.element(fxy, value);
+        }
+    }
+
+    fn push_array_f32(
+        &mut self,
+        values: &mut [f32],
+        missing: &MissingMask,
+        fxy: FXY,
+        encoding: Encoding,
+        name: &'a str,
+        unit: &'a str,
+    ) {
+        let mut values: Vec<f64> = values.iter().map(|&v| f64::from(v)).collect();
+        self.push_array(&mut values, missing, fxy, encoding, name, unit);
+    }
+
+    fn push_repeat(
+        &mut self,
+        values: Vec<Value<'a>>,
+        fxy: FXY,
+        _: Encoding,
+        _: &'a str,
+        _: &'a str,
+    ) {
+        for value in values {
+            self.element(fxy, value);
+        }
+    }
+
+    fn compiles_arrays(&self) -> bool {
+        false
+    }
+
+    fn stopped(&self) -> bool {
+        self.stopped
+    }
+
+    fn begin_replication(&mut self, fxy: FXY, times: usize) {
+        self.visitor.begin_replication(fxy, times);
+    }
+
+    fn operator(&mut self, fxy: FXY) {
+        self.visitor.operator(fxy);
+    }
+}
@@ -0,0 +1,108 @@
+//! Drop repeated reports.
+//!
+//! GTS feeds routinely deliver the same observation several times, either as
+//! plain duplicates or as corrected updates. Reports are considered the same
+//! when they share station, observation time and template.
+
+use chrono::NaiveDateTime;
+use rustc_hash::FxHashMap;
+
+use crate::block::MessageBlock;
+use crate::core::FXY;
+use crate::decoder::BUFRParsed;
+use crate::extract::StationId;
+use crate::structs::versions::MessageVersion;
+
+/// One decoded subset together with what is needed to compare it
+#[derive(Clone)]
+pub struct Report<'a> {
+    pub parsed: BUFRParsed<'a>,
+    pub station: StationId,
+    pub time: Option<NaiveDateTime>,
+    pub template: Option<FXY>,
+    pub update_sequence: u8,
+}
+
+/// Which report survives when several share a key
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Precedence {
+    /// Highest update sequence number; later reports win ties
+    #[default]
+    LatestUpdate,
+    /// The first report seen
+    First,
+    /// The last report seen
+    Last,
+}
+
+type Key = (StationId, NaiveDateTime, Option<FXY>);
+
+impl<'a> Report<'a> {
+    pub fn new(parsed: BUFRParsed<'a>, template: Option<FXY>, update_sequence: u8) -> Self {
+        Report {
+            station: parsed.station(),
+            time: parsed.datetime(),
+            parsed,
+            template,
+            update_sequence,
+        }
+    }
+
+    /// Take template and update sequence number from the message `parsed`
+    /// was decoded from
+    ///
+    /// This looks the template up on every call; prefer [`Report::new`] when
+    /// building reports for many subsets of the same message.
+    pub fn from_message(parsed: BUFRParsed<'a>, message: &MessageBlock) -> Self {
+        Self::new(
+            parsed,
+            message.template().map(|t| t.fxy),
+            message.update_sequence_number(),
+        )
+    }
+
+    /// `None` when the report lacks a station or an observation time
+    pub fn key(&self) -> Option<Key> {
+        if self.station.is_empty() {
+            return None;
+        }
+        Some((self.station.clone(), self.time?, self.template))
+    }
+}
+
+/// Keep one report per key, chosen by `precedence`
+///
+/// Survivors stay at the position of the first report with their key.
+/// Reports without a key are never considered duplicates.
+pub fn dedupe<'a>(reports: Vec<Report<'a>>, precedence: Precedence) -> Vec<Report<'a>> {
+    let mut kept: Vec<Report<'a>> = Vec::with_capacity(reports.len());
+    let mut seen: FxHashMap<Key, usize> = FxHashMap::default();
+
+    for report in reports {
+        let Some(key) = report.key() else {
+            kept.push(report);
+            continue;
+        };
+
+        match seen.get(&key) {
+            Some(&index) => {
+                let replace = match precedence {
+                    Precedence::LatestUpdate => {
+                        report.update_sequence >= kept[index].update_sequence
+                    }
+                    Precedence::First => false,
+                    Precedence::Last => true,
+                };
+                if replace {
+                    kept[index] = report;
+                }
+            }
+            None => {
+                seen.insert(key, kept.len());
+                kept.push(report);
+            }
+        }
+    }
+
+    kept
+}
@@ -0,0 +1,217 @@
+//! JSON output laid out like ecCodes' `bufr_dump -j`.
+//!
+//! Messages become `{"messages": [...]}`, each message a list of
+//! `{"key", "value"}` entries: the Section 0 and 1 keys first, then the data,
+//! whose entries also carry the descriptor `code` and Table B `units`. Data
+//! keys are those of [`crate::keys`].
+//!
+//! In [`JsonStyle::Structure`] (`-js`) every subset is a list, and elements
+//! following a qualifier (classes 1 to 8) nest in a list under it until the
+//! same qualifier comes again. In [`JsonStyle::Flat`] (`-jf`) data entries
+//! follow the header in one list, keyed `#rank#key` with the rank counting
+//! occurrences of the key in the message.
+//!
+//! Compressed messages are dumped subset by subset like the others. A
+//! message that cannot be decoded is dumped as its header entries followed
+//! by an `error` entry, and the messages after it are still dumped.
+
+use std::ops::ControlFlow;
+
+use chrono::{Datelike, Timelike};
+use rustc_hash::FxHashMap;
+use serde_json::{Value as Json, json};
+
+use crate::block::{BUFRFile, MessageBlock};
+use crate::core::FXY;
+use crate::decoder::{DecodeVisitor, Decoder, Value};
+use crate::errors::{Error, Result};
+use crate::keys::eccodes_key;
+use crate::structs::versions::MessageVersion;
+
+/// Layout of the data section, after `bufr_dump -js` or `-jf`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum JsonStyle {
+    /// Subsets as lists, nested under their qualifiers
+    #[default]
+    Structure,
+    /// One list of entries with ranked keys
+    Flat,
+}
+
+/// Every message of `file` as pretty-printed JSON, each decoded with
+/// the tables it asks for
+pub fn to_json(file: &BUFRFile, style: JsonStyle) -> Result<String> {
+    let messages: Vec<Json> = file
+        .messages()
+        .iter()
+        .map(|message| {
+            Decoder::from_message(message)
+                .and_then(|mut decoder| message_json(message, &mut decoder, style))
+                .unwrap_or_else(|e| error_json(message, &e))
+        })
+        .collect();
+    Ok(format!("{:#}", json!({ "messages": messages })))
+}
+
+/// The header entries of a message that failed, and the error
+fn error_json(message: &MessageBlock, error: &Error) -> Json {
+    let mut entries = header_entries(message).unwrap_or_default();
+    entries.push(json!({ "key": "error", "value": error.to_string() }));
+    Json::Array(entries)
+}
+
+/// One message as the list of entries `bufr_dump -j` prints for it
+///
+/// `decoder` should report values in Table B units, as `Units::Bufr` does,
+/// for the `units` entries to match them.
+pub fn message_json(
+    message: &MessageBlock,
+    decoder: &mut Decoder,
+    style: JsonStyle,
+) -> Result<Json> {
+    let mut entries = header_entries(message)?;
+
+    let mut elements = Elements::default();
+    decoder.decode_with(message, &mut elements)?;
+
+    let mut keys: FxHashMap<FXY, (String, String)> = FxHashMap::default();
+    let mut lookup = |fxy: FXY| {
+        keys.entry(fxy)
+            .or_insert_with(|| {
+                let (name, unit) = decoder.element_info(fxy).unwrap_or_default();
+                (eccodes_key(fxy, &name), unit)
+            })
+            .clone()
+    };
+
+    match style {
+        JsonStyle::Structure => {
+            let mut start = 0;
+            for end in elements.subset_ends {
+                let mut levels: Vec<(Option<FXY>, Vec<Json>)> = vec![(None, vec![])];
+                for (fxy, value) in elements.values[start..end].iter().cloned() {
+                    let (key, unit) = lookup(fxy);
+                    let entry = data_entry(key, fxy, value, unit);
+                    if !is_qualifier(fxy) {
+                        levels.last_mut().unwrap().1.push(entry);
+                        continue;
+                    }
+                    // A repeated qualifier closes the groups opened since it
+                    if let Some(depth) = levels.iter().rposition(|(q, _)| *q == Some(fxy)) {
+                        while levels.len() > depth {
+                            close(&mut levels);
+                        }
+                    }
+                    levels.last_mut().unwrap().1.push(entry);
+                    levels.push((Some(fxy), vec![]));
+                }
+                while levels.len() > 1 {
+                    close(&mut levels);
+                }
+                entries.push(Json::Array(levels.pop().unwrap().1));
+                start = end;
+            }
+        }
+        JsonStyle::Flat => {
+            let mut ranks: FxHashMap<String, usize> = FxHashMap::default();
+            for (fxy, value) in elements.values {
+                let (key, unit) = lookup(fxy);
+                let rank = ranks.entry(key.clone()).or_insert(0);
+                *rank += 1;
+                entries.push(data_entry(format!("#{}#{}", rank, key), fxy, value, unit));
+            }
+        }
+    }
+
+    Ok(Json::Array(entries))
+}
+
+/// Elements in bitstream order, with where each subset ends
+#[derive(Default)]
+struct Elements {
+    values: Vec<(FXY, Value<'static>)>,
+    subset_ends: Vec<usize>,
+}
+
+impl DecodeVisitor for Elements {
+    fn element(&mut self, fxy: FXY, value: Value) -> ControlFlow<()> {
+        // 2-05 characters have no key in ecCodes' output
+        if fxy.f == 0 {
+            self.values.push((fxy, value.into_owned()));
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn end_subset(&mut self, _index: usize) -> ControlFlow<()> {
+        self.subset_ends.push(self.values.len());
+        ControlFlow::Continue(())
+    }
+}
+
+fn data_entry(key: String, fxy: FXY, value: Value, unit: String) -> Json {
+    json!({
+        "key": key,
+        "value": json_value(value),
+        "code": format!("{}{:02}{:03}", fxy.f, fxy.x, fxy.y),
+        "units": unit,
+    })
+}
+
+fn close(levels: &mut Vec<(Option<FXY>, Vec<Json>)>) {
+    let (_, group) = levels.pop().unwrap();
+    levels.last_mut().unwrap().1.push(Json::Array(group));
+}
+
+/// Classes ecCodes nests the following elements under
+fn is_qualifier(fxy: FXY) -> bool {
+    fxy.f == 0 && (1..=8).contains(&fxy.x)
+}
+
+fn header_entries(message: &MessageBlock) -> Result<Vec<Json>> {
+    let header = message.header();
+    let mut entries = vec![];
+    let mut push = |key: &str, value: Json| entries.push(json!({ "key": key, "value": value }));
+
+    push("edition", json!(header.edition));
+    push("bufrHeaderCentre", json!(header.centre));
+    push("bufrHeaderSubCentre", json!(header.subcentre));
+    push("updateSequenceNumber", json!(header.update_sequence_number));
+    push("dataCategory", json!(header.data_category));
+    push("dataSubCategory", json!(header.data_subcategory));
+    push(
+        "masterTablesVersionNumber",
+        json!(header.master_table_version),
+    );
+    push(
+        "localTablesVersionNumber",
+        json!(header.local_table_version),
+    );
+    if let Some(time) = message.obs_time() {
+        push("typicalYear", json!(time.year()));
+        push("typicalMonth", json!(time.month()));
+        push("typicalDay", json!(time.day()));
+        push("typicalHour", json!(time.hour()));
+        push("typicalMinute", json!(time.minute()));
+        push("typicalSecond", json!(time.second()));
+    }
+    push("numberOfSubsets", json!(message.subsets_count()));
+    push("compressedData", json!(message.is_compressed() as u8));
+    let descriptors: Vec<u32> = message
+        .descriptors()?
+        .iter()
+        .map(|fxy| fxy.f as u32 * 100_000 + fxy.x as u32 * 1000 + fxy.y as u32)
+        .collect();
+    push("unexpandedDescriptors", json!(descriptors));
+    Ok(entries)
+}
+
+/// Whole numbers print as integers, as ecCodes does for unscaled elements
+fn json_value(value: Value) -> Json {
+    match value {
+        Value::Missing => Json::Null,
+        Value::String(s) => Json::String(s.into_owned()),
+        Value::Bytes(b) => Json::String(crate::decoder::hex(&b)),
+        Value::Number(v) if v.fract() == 0.0 && v.abs() < 9.0e15 => json!(v as i64),
+        Value::Number(v) => json!(v),
+    }
+}
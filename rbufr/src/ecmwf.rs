@@ -0,0 +1,110 @@
+//! ECMWF RDB keys carried in Section 2 of messages from centre 98.
+//!
+//! The layout follows the ECMWF local key: RDB type and subtype, a bit-packed
+//! observation time, then either a position and station identifier or, for
+//! satellite data, a bounding box with observation count and satellite id.
+
+use chrono::{NaiveDate, NaiveDateTime};
+
+use crate::block::MessageBlock;
+use crate::structs::versions::MessageVersion;
+
+const ECMWF: u16 = 98;
+
+/// RDB types whose key carries a bounding box instead of a point
+const SATELLITE_TYPES: [u8; 4] = [2, 3, 8, 12];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RdbLocation {
+    Point {
+        latitude: f64,
+        longitude: f64,
+        ident: String,
+    },
+    Area {
+        latitude1: f64,
+        longitude1: f64,
+        latitude2: f64,
+        longitude2: f64,
+        observations: u16,
+        satellite_id: u16,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RdbKey {
+    pub rdb_type: u8,
+    pub rdb_subtype: u8,
+    /// `None` when the packed date/time isn't a valid date
+    pub time: Option<NaiveDateTime>,
+    /// `None` when Section 2 ends before the location
+    pub location: Option<RdbLocation>,
+}
+
+impl RdbKey {
+    /// Decode a key from Section 2 data, without the 4-octet section header
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        if data.len() < 7 {
+            return None;
+        }
+        let rdb_type = data[0];
+        let rdb_subtype = data[1];
+
+        // year 12, month 4, day 6, hour 5, minute 6, second 6 bits
+        let packed = u64::from_be_bytes([0, 0, 0, data[2], data[3], data[4], data[5], data[6]]);
+        let field = |shift: u32, bits: u32| ((packed >> shift) & ((1 << bits) - 1)) as u32;
+        let time = NaiveDate::from_ymd_opt(field(28, 12) as i32, field(24, 4), field(18, 6))
+            .and_then(|d| d.and_hms_opt(field(13, 5), field(7, 6), field(1, 6)));
+
+        let rest = &data[7..];
+        let location = if SATELLITE_TYPES.contains(&rdb_type) {
+            (rest.len() >= 20).then(|| RdbLocation::Area {
+                longitude1: longitude(&rest[0..4]),
+                latitude1: latitude(&rest[4..8]),
+                longitude2: longitude(&rest[8..12]),
+                latitude2: latitude(&rest[12..16]),
+                observations: u16::from_be_bytes([rest[16], rest[17]]),
+                satellite_id: u16::from_be_bytes([rest[18], rest[19]]),
+            })
+        } else {
+            (rest.len() >= 17).then(|| RdbLocation::Point {
+                longitude: longitude(&rest[0..4]),
+                latitude: latitude(&rest[4..8]),
+                ident: String::from_utf8_lossy(&rest[8..17])
+                    .trim_matches(|c: char| c == '\0' || c.is_whitespace())
+                    .to_string(),
+            })
+        };
+
+        Some(RdbKey {
+            rdb_type,
+            rdb_subtype,
+            time,
+            location,
+        })
+    }
+}
+
+/// Hundred-thousandths of a degree east of 180°W
+fn longitude(bytes: &[u8]) -> f64 {
+    be_u32(bytes) as f64 / 100_000.0 - 180.0
+}
+
+/// Hundred-thousandths of a degree north of 90°S
+fn latitude(bytes: &[u8]) -> f64 {
+    be_u32(bytes) as f64 / 100_000.0 - 90.0
+}
+
+fn be_u32(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+impl MessageBlock {
+    /// The ECMWF RDB key, for messages from centre 98 with a Section 2
+    pub fn rdb_key(&self) -> Option<RdbKey> {
+        if self.center_id() != ECMWF {
+            return None;
+        }
+        RdbKey::parse(&self.section2()?.data)
+    }
+}
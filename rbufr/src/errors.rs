@@ -23,6 +23,9 @@ pub enum Error {
 
     #[error("Unsupported BUFR version: {0}")]
     UnsupportedVersion(u8),
+
+    #[error("Missing element: {0}")]
+    MissingElement(String),
 }
 
 impl<'a> From<nom::Err<nom::error::Error<&'a [u8]>>> for Error {
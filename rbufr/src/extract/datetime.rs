@@ -0,0 +1,75 @@
+use chrono::{NaiveDate, NaiveDateTime};
+
+use crate::core::FXY;
+use crate::decoder::{BUFRData, BUFRParsed, BUFRRecord, Value};
+
+/// Year, month, day, hour, minute, second
+const PARTS: [FXY; 6] = [
+    FXY::new(0, 4, 1),
+    FXY::new(0, 4, 2),
+    FXY::new(0, 4, 3),
+    FXY::new(0, 4, 4),
+    FXY::new(0, 4, 5),
+    FXY::new(0, 4, 6),
+];
+
+impl BUFRParsed<'_> {
+    /// The first date/time assembled from 0-04-001 … 0-04-006
+    ///
+    /// Year, month, day and hour are required; a missing or absent minute
+    /// or second counts as zero.
+    pub fn datetime(&self) -> Option<NaiveDateTime> {
+        self.datetimes().into_iter().flatten().next()
+    }
+
+    /// Every date/time group in decode order, one per 0-04-001 found
+    ///
+    /// Multi-subset output yields one entry per subset. Groups that don't
+    /// form a valid date are `None`.
+    pub fn datetimes(&self) -> Vec<Option<NaiveDateTime>> {
+        let mut groups = vec![];
+        let mut current: Option<[Option<f64>; 6]> = None;
+
+        for record in self.iter() {
+            let Some(slot) = PARTS.iter().position(|p| *p == record.fxy) else {
+                continue;
+            };
+
+            if slot == 0 {
+                groups.extend(current.take());
+                current = Some([None; 6]);
+            }
+
+            if let Some(parts) = current.as_mut()
+                && parts[slot].is_none()
+            {
+                parts[slot] = single_number(&record);
+            }
+        }
+        groups.extend(current);
+
+        groups.iter().map(assemble).collect()
+    }
+}
+
+fn single_number(record: &BUFRRecord<'_>) -> Option<f64> {
+    match &record.values {
+        BUFRData::Single(Value::Number(n)) => Some(*n),
+        _ => None,
+    }
+}
+
+fn assemble(parts: &[Option<f64>; 6]) -> Option<NaiveDateTime> {
+    let [year, month, day, hour, minute, second] = *parts;
+
+    let date = NaiveDate::from_ymd_opt(year? as i32, month? as u32, day? as u32)?;
+    let second = second.unwrap_or(0.0);
+    let nanos = (second.fract() * 1e9).round() as u32;
+
+    date.and_hms_nano_opt(
+        hour? as u32,
+        minute.unwrap_or(0.0) as u32,
+        second.trunc() as u32,
+        nanos,
+    )
+}
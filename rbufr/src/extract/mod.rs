@@ -0,0 +1,11 @@
+//! Helpers that assemble common quantities out of decoded records, so callers
+//! don't need to know which descriptors carry them.
+
+mod datetime;
+mod profile;
+mod series;
+mod station;
+
+pub use profile::Level;
+pub use series::TimeSeries;
+pub use station::{StationId, WigosId};
@@ -0,0 +1,126 @@
+use crate::core::FXY;
+use crate::decoder::{BUFRData, BUFRParsed, BUFRRecord, Value};
+
+/// One level of a TEMP/PILOT/radiosonde profile; missing elements are `None`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Level {
+    /// Pa, 0-07-004
+    pub pressure: Option<f64>,
+    /// gpm, 0-10-009, 0-07-009 or 0-07-002
+    pub height: Option<f64>,
+    /// K, 0-12-101 or 0-12-001
+    pub temperature: Option<f64>,
+    /// K, 0-12-103 or 0-12-003
+    pub dew_point: Option<f64>,
+    /// degree true, 0-11-001
+    pub wind_direction: Option<f64>,
+    /// m/s, 0-11-002
+    pub wind_speed: Option<f64>,
+    /// Vertical sounding significance flags, 0-08-042 or 0-08-001
+    pub significance: Option<f64>,
+}
+
+type Setter = fn(&mut Level, f64);
+
+const PRESSURE: FXY = FXY::new(0, 7, 4);
+
+const FIELDS: [(FXY, Setter); 12] = [
+    (PRESSURE, |l, v| l.pressure = Some(v)),
+    (FXY::new(0, 10, 9), |l, v| l.height = Some(v)),
+    (FXY::new(0, 7, 9), |l, v| l.height = Some(v)),
+    (FXY::new(0, 7, 2), |l, v| l.height = Some(v)),
+    (FXY::new(0, 12, 101), |l, v| l.temperature = Some(v)),
+    (FXY::new(0, 12, 1), |l, v| l.temperature = Some(v)),
+    (FXY::new(0, 12, 103), |l, v| l.dew_point = Some(v)),
+    (FXY::new(0, 12, 3), |l, v| l.dew_point = Some(v)),
+    (FXY::new(0, 11, 1), |l, v| l.wind_direction = Some(v)),
+    (FXY::new(0, 11, 2), |l, v| l.wind_speed = Some(v)),
+    (FXY::new(0, 8, 42), |l, v| l.significance = Some(v)),
+    (FXY::new(0, 8, 1), |l, v| l.significance = Some(v)),
+];
+
+fn setter(fxy: FXY) -> Option<Setter> {
+    FIELDS.iter().find(|(f, _)| *f == fxy).map(|(_, s)| *s)
+}
+
+impl BUFRParsed<'_> {
+    /// Group the per-level elements of the first pressure replication into levels
+    ///
+    /// A replication decoded as compiled arrays is zipped across the adjacent
+    /// arrays of the same length. Otherwise each pressure value starts a new
+    /// level and the elements that follow it, up to the next pressure, fill it.
+    pub fn profile(&self) -> Vec<Level> {
+        let records: Vec<BUFRRecord<'_>> = self.records().collect();
+
+        if let Some(start) = records
+            .iter()
+            .position(|r| r.fxy == PRESSURE && array_len(r).is_some())
+        {
+            return compiled_profile(&records, start);
+        }
+
+        let mut levels: Vec<Level> = vec![];
+        for record in &records {
+            let BUFRData::Single(value) = &record.values else {
+                continue;
+            };
+            let Some(set) = setter(record.fxy) else {
+                continue;
+            };
+
+            if record.fxy == PRESSURE {
+                levels.push(Level::default());
+            }
+            if let (Some(level), Value::Number(v)) = (levels.last_mut(), value) {
+                set(level, *v);
+            }
+        }
+        levels
+    }
+}
+
+fn array_len(record: &BUFRRecord<'_>) -> Option<usize> {
+    match &record.values {
+        BUFRData::Array { values, .. } => Some(values.len()),
+        BUFRData::ArrayF32 { values, .. } => Some(values.len()),
+        _ => None,
+    }
+}
+
+fn array_value(record: &BUFRRecord<'_>, index: usize) -> Option<f64> {
+    match &record.values {
+        BUFRData::Array { values, missing } => (!missing.get(index)).then(|| values[index]),
+        BUFRData::ArrayF32 { values, missing } => {
+            (!missing.get(index)).then(|| f64::from(values[index]))
+        }
+        _ => None,
+    }
+}
+
+fn compiled_profile(records: &[BUFRRecord<'_>], start: usize) -> Vec<Level> {
+    let len = array_len(&records[start]).unwrap_or(0);
+    let same_run = |r: &BUFRRecord<'_>| array_len(r) == Some(len);
+
+    // Arrays emitted by one compiled replication are adjacent
+    let first = records[..start]
+        .iter()
+        .rposition(|r| !same_run(r))
+        .map_or(0, |i| i + 1);
+    let last = records[start..]
+        .iter()
+        .position(|r| !same_run(r))
+        .map_or(records.len(), |i| start + i);
+    let run = &records[first..last];
+
+    (0..len)
+        .map(|i| {
+            let mut level = Level::default();
+            for record in run {
+                if let (Some(set), Some(v)) = (setter(record.fxy), array_value(record, i)) {
+                    set(&mut level, v);
+                }
+            }
+            level
+        })
+        .collect()
+}
@@ -0,0 +1,88 @@
+use chrono::NaiveDateTime;
+use rustc_hash::FxHashMap;
+
+use super::StationId;
+use crate::block::{BUFRFile, MessageBlock};
+use crate::core::FXY;
+use crate::decoder::{BUFRData, BUFRParsed, Decoder, Value};
+use crate::errors::Result;
+use crate::structs::versions::{MessageVersion, TableInfo};
+
+/// Values of one element at one station, sorted by time
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeSeries {
+    pub station: StationId,
+    pub points: Vec<(NaiveDateTime, f64)>,
+}
+
+impl BUFRFile {
+    /// Decode every message and collect `element` per station over time
+    ///
+    /// Each subset contributes the first non-missing value of `element`.
+    /// Subsets without a station, a date/time or the element are skipped.
+    /// Compressed messages can't be split yet and count as one subset.
+    /// Series come in order of each station's first appearance.
+    pub fn time_series(&self, element: FXY) -> Result<Vec<TimeSeries>> {
+        let mut series: Vec<TimeSeries> = vec![];
+        let mut index: FxHashMap<StationId, usize> = FxHashMap::default();
+        let mut current: Option<(TableInfo, Decoder)> = None;
+
+        for message in self.messages() {
+            let table_info = message.table_info();
+            let decoder = match current.as_mut() {
+                Some((info, decoder)) if *info == table_info => decoder,
+                _ => {
+                    &mut current
+                        .insert((table_info, Decoder::from_message(message)?))
+                        .1
+                }
+            };
+
+            for parsed in decode_all(decoder, message)? {
+                let Some((station, point)) = sample(&parsed, element) else {
+                    continue;
+                };
+                let slot = *index.entry(station.clone()).or_insert_with(|| {
+                    series.push(TimeSeries {
+                        station,
+                        points: vec![],
+                    });
+                    series.len() - 1
+                });
+                series[slot].points.push(point);
+            }
+        }
+
+        for s in &mut series {
+            s.points.sort_by_key(|(time, _)| *time);
+        }
+        Ok(series)
+    }
+}
+
+fn decode_all<'a>(
+    decoder: &'a mut Decoder,
+    message: &'a MessageBlock,
+) -> Result<Vec<BUFRParsed<'a>>> {
+    if message.is_compressed() {
+        Ok(vec![decoder.decode(message)?])
+    } else {
+        decoder.decode_subsets(message)
+    }
+}
+
+fn sample(parsed: &BUFRParsed<'_>, element: FXY) -> Option<(StationId, (NaiveDateTime, f64))> {
+    let station = parsed.station();
+    if station.is_empty() {
+        return None;
+    }
+    let time = parsed.datetime()?;
+    let value = parsed
+        .iter()
+        .with_fxy(element)
+        .find_map(|r| match &r.values {
+            BUFRData::Single(Value::Number(n)) => Some(*n),
+            _ => None,
+        })?;
+    Some((station, (time, value)))
+}
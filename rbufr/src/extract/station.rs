@@ -0,0 +1,122 @@
+use std::fmt::Display;
+
+use crate::core::FXY;
+use crate::decoder::{BUFRData, BUFRParsed, Value};
+
+const WMO_BLOCK: FXY = FXY::new(0, 1, 1);
+const WMO_STATION: FXY = FXY::new(0, 1, 2);
+const BUOY_ID: FXY = FXY::new(0, 1, 5);
+const SHIP_ID: FXY = FXY::new(0, 1, 11);
+const STATION_NAME: FXY = FXY::new(0, 1, 15);
+const SHORT_STATION_NAME: FXY = FXY::new(0, 1, 18);
+const LONG_STATION_NAME: FXY = FXY::new(0, 1, 19);
+const MARINE_PLATFORM_ID: FXY = FXY::new(0, 1, 87);
+const WIGOS_SERIES: FXY = FXY::new(0, 1, 125);
+const WIGOS_ISSUER: FXY = FXY::new(0, 1, 126);
+const WIGOS_ISSUE_NUMBER: FXY = FXY::new(0, 1, 127);
+const WIGOS_LOCAL_ID: FXY = FXY::new(0, 1, 128);
+
+/// WIGOS station identifier, 0-01-125 … 0-01-128
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WigosId {
+    pub series: u32,
+    pub issuer: u32,
+    pub issue_number: u32,
+    pub local_id: String,
+}
+
+impl Display for WigosId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}-{}-{}-{}",
+            self.series, self.issuer, self.issue_number, self.local_id
+        )
+    }
+}
+
+/// Platform identity of a decoded subset; fields absent from the data are `None`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct StationId {
+    /// WMO block number, 0-01-001
+    pub wmo_block: Option<u32>,
+    /// WMO station number, 0-01-002
+    pub wmo_station: Option<u32>,
+    pub wigos: Option<WigosId>,
+    /// Ship call sign (0-01-011), buoy/platform number (0-01-005) or
+    /// marine platform identifier (0-01-087), whichever comes first
+    pub ship_or_buoy: Option<String>,
+    /// Station or site name, from 0-01-015, 0-01-019 or 0-01-018
+    pub name: Option<String>,
+}
+
+impl StationId {
+    /// Five-digit WMO index, `block * 1000 + station`
+    pub fn wmo_index(&self) -> Option<u32> {
+        Some(self.wmo_block? * 1000 + self.wmo_station?)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == StationId::default()
+    }
+}
+
+impl BUFRParsed<'_> {
+    /// Collect the platform identity from the first occurrence of each
+    /// identification descriptor
+    pub fn station(&self) -> StationId {
+        let first = |fxy: FXY| {
+            self.iter().with_fxy(fxy).find_map(|r| match r.values {
+                BUFRData::Single(v) => Some(v),
+                _ => None,
+            })
+        };
+        let number = |fxy| first(fxy).as_ref().and_then(as_u32);
+        let text = |fxy| first(fxy).as_ref().and_then(as_text);
+
+        let wigos = match (
+            number(WIGOS_SERIES),
+            number(WIGOS_ISSUER),
+            number(WIGOS_ISSUE_NUMBER),
+            text(WIGOS_LOCAL_ID),
+        ) {
+            (Some(series), Some(issuer), Some(issue_number), Some(local_id)) => Some(WigosId {
+                series,
+                issuer,
+                issue_number,
+                local_id,
+            }),
+            _ => None,
+        };
+
+        StationId {
+            wmo_block: number(WMO_BLOCK),
+            wmo_station: number(WMO_STATION),
+            wigos,
+            ship_or_buoy: text(SHIP_ID)
+                .or_else(|| number(BUOY_ID).map(|n| n.to_string()))
+                .or_else(|| number(MARINE_PLATFORM_ID).map(|n| n.to_string())),
+            name: text(STATION_NAME)
+                .or_else(|| text(LONG_STATION_NAME))
+                .or_else(|| text(SHORT_STATION_NAME)),
+        }
+    }
+}
+
+fn as_u32(value: &Value) -> Option<u32> {
+    match value {
+        Value::Number(n) => Some(*n as u32),
+        _ => None,
+    }
+}
+
+/// Strings are space padded; all-blank or missing strings count as absent
+fn as_text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => {
+            let s = s.trim_matches(|c: char| c.is_whitespace() || c == '\0');
+            (!s.is_empty()).then(|| s.to_string())
+        }
+        _ => None,
+    }
+}
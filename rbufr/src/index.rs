@@ -0,0 +1,90 @@
+//! A summary of every message of a file, to answer inventory queries
+//! without reading the file again
+//!
+//! An index file is the magic `BUFRIDX1`, then the entries serialized with
+//! bincode. Offsets of gzipped files are into their decompressed bytes.
+use std::io::Write;
+use std::path::Path;
+
+use chrono::NaiveDateTime;
+
+use crate::block::MessageBlock;
+use crate::errors::{Error, Result};
+use crate::parser::{MessageHeader, MessageReader};
+use crate::structs::versions::MessageVersion;
+
+pub const INDEX_EXTENSION: &str = "bufridx";
+
+const MAGIC: &[u8; 8] = b"BUFRIDX1";
+
+/// What an index records of one message
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct IndexEntry {
+    /// Where the message starts in the file
+    pub offset: u64,
+    /// Length of the message in bytes
+    pub length: u64,
+    pub header: MessageHeader,
+    pub obs_time: Option<NaiveDateTime>,
+    pub subsets: u16,
+    pub compressed: bool,
+    /// Descriptors of Section 3, unexpanded
+    pub descriptors: usize,
+}
+
+impl IndexEntry {
+    /// The entry of `message`, read from `offset` of its file
+    pub fn new(offset: u64, message: &MessageBlock) -> Self {
+        IndexEntry {
+            offset,
+            length: message.as_bytes().len() as u64,
+            header: message.header(),
+            obs_time: message.obs_time(),
+            subsets: message.subsets_count(),
+            compressed: message.is_compressed(),
+            descriptors: message.ndescs(),
+        }
+    }
+}
+
+/// The index of one file, its messages in file order
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileIndex {
+    pub entries: Vec<IndexEntry>,
+}
+
+impl FileIndex {
+    /// Index the file at `path`, gzipped or not
+    ///
+    /// Messages that fail to read are left out.
+    pub fn build<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let mut reader = MessageReader::open(path)?;
+        let offsets = reader.offsets().to_vec();
+        let entries = offsets
+            .into_iter()
+            .zip(&mut reader)
+            .filter_map(|(offset, message)| Some(IndexEntry::new(offset, &message.ok()?)))
+            .collect();
+        Ok(FileIndex { entries })
+    }
+
+    /// Read an index written by `save`
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let bytes = std::fs::read(path)?;
+        let body = bytes
+            .strip_prefix(MAGIC)
+            .ok_or_else(|| Error::ParseError("Not a BUFR index file".to_string()))?;
+        let entries = bincode::deserialize(body)
+            .map_err(|e| Error::ParseError(format!("Invalid BUFR index: {}", e)))?;
+        Ok(FileIndex { entries })
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let body = bincode::serialize(&self.entries)
+            .map_err(|e| Error::ParseError(format!("Failed to serialize index: {}", e)))?;
+        let mut file = std::fs::File::create(path)?;
+        file.write_all(MAGIC)?;
+        file.write_all(&body)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,214 @@
+//! ecCodes key names for Table B elements.
+//!
+//! ecCodes names every element in camelCase (`airTemperature`,
+//! `blockNumber`); code written against it looks values up by those keys.
+//! The keys of common elements are listed by descriptor; the others are
+//! derived from the Table B name.
+
+use crate::core::FXY;
+
+/// ecCodes keys of the elements of SYNOP, TEMP and AMDAR reports, by
+/// descriptor
+///
+/// They hold across table versions, whose names for the same element vary,
+/// and cover the keys the name rule gets wrong.
+const KEYS: [(FXY, &str); 114] = [
+    (FXY::new(0, 1, 1), "blockNumber"),
+    (FXY::new(0, 1, 2), "stationNumber"),
+    (FXY::new(0, 1, 3), "regionNumber"),
+    (FXY::new(0, 1, 4), "wmoRegionSubArea"),
+    (FXY::new(0, 1, 5), "buoyOrPlatformIdentifier"),
+    (FXY::new(0, 1, 6), "aircraftFlightNumber"),
+    (FXY::new(0, 1, 7), "satelliteIdentifier"),
+    (
+        FXY::new(0, 1, 8),
+        "aircraftRegistrationNumberOrOtherIdentification",
+    ),
+    (FXY::new(0, 1, 11), "shipOrMobileLandStationIdentifier"),
+    (
+        FXY::new(0, 1, 12),
+        "directionOfMotionOfMovingObservingPlatform",
+    ),
+    (FXY::new(0, 1, 13), "movingObservingPlatformSpeed"),
+    (FXY::new(0, 1, 15), "stationOrSiteName"),
+    (FXY::new(0, 1, 18), "shortStationName"),
+    (FXY::new(0, 1, 19), "longStationName"),
+    (FXY::new(0, 1, 23), "observationSequenceNumber"),
+    (FXY::new(0, 1, 31), "centre"),
+    (FXY::new(0, 1, 33), "centre"),
+    (FXY::new(0, 1, 34), "subCentre"),
+    (FXY::new(0, 1, 81), "radiosondeSerialNumber"),
+    (FXY::new(0, 1, 82), "radiosondeAscensionNumber"),
+    (FXY::new(0, 1, 85), "observingPlatformManufacturerModel"),
+    (
+        FXY::new(0, 1, 86),
+        "observingPlatformManufacturerSerialNumber",
+    ),
+    (FXY::new(0, 1, 125), "wigosIdentifierSeries"),
+    (FXY::new(0, 1, 126), "wigosIssuerOfIdentifier"),
+    (FXY::new(0, 1, 127), "wigosIssueNumber"),
+    (FXY::new(0, 1, 128), "wigosLocalIdentifierCharacter"),
+    (FXY::new(0, 2, 1), "stationType"),
+    (FXY::new(0, 2, 2), "instrumentationForWindMeasurement"),
+    (FXY::new(0, 2, 11), "radiosondeType"),
+    (FXY::new(0, 2, 13), "solarAndInfraredRadiationCorrection"),
+    (FXY::new(0, 2, 14), "trackingTechniqueOrStatusOfSystem"),
+    (FXY::new(0, 2, 61), "aircraftNavigationalSystem"),
+    (FXY::new(0, 2, 62), "typeOfAircraftDataRelaySystem"),
+    (FXY::new(0, 4, 1), "year"),
+    (FXY::new(0, 4, 2), "month"),
+    (FXY::new(0, 4, 3), "day"),
+    (FXY::new(0, 4, 4), "hour"),
+    (FXY::new(0, 4, 5), "minute"),
+    (FXY::new(0, 4, 6), "second"),
+    (FXY::new(0, 4, 24), "timePeriod"),
+    (FXY::new(0, 4, 25), "timePeriod"),
+    (FXY::new(0, 4, 86), "timePeriod"),
+    (FXY::new(0, 5, 1), "latitude"),
+    (FXY::new(0, 5, 2), "latitude"),
+    (FXY::new(0, 5, 15), "latitudeDisplacement"),
+    (FXY::new(0, 6, 1), "longitude"),
+    (FXY::new(0, 6, 2), "longitude"),
+    (FXY::new(0, 6, 15), "longitudeDisplacement"),
+    (FXY::new(0, 7, 1), "heightOfStation"),
+    (FXY::new(0, 7, 2), "height"),
+    (FXY::new(0, 7, 4), "pressure"),
+    (FXY::new(0, 7, 7), "height"),
+    (FXY::new(0, 7, 9), "geopotentialHeight"),
+    (FXY::new(0, 7, 10), "flightLevel"),
+    (FXY::new(0, 7, 30), "heightOfStationGroundAboveMeanSeaLevel"),
+    (FXY::new(0, 7, 31), "heightOfBarometerAboveMeanSeaLevel"),
+    (
+        FXY::new(0, 7, 32),
+        "heightOfSensorAboveLocalGroundOrDeckOfMarinePlatform",
+    ),
+    (FXY::new(0, 8, 1), "verticalSoundingSignificance"),
+    (FXY::new(0, 8, 2), "verticalSignificanceSurfaceObservations"),
+    (FXY::new(0, 8, 4), "phaseOfAircraftFlight"),
+    (FXY::new(0, 8, 21), "timeSignificance"),
+    (FXY::new(0, 8, 42), "extendedVerticalSoundingSignificance"),
+    (FXY::new(0, 10, 4), "nonCoordinatePressure"),
+    (FXY::new(0, 10, 9), "nonCoordinateGeopotentialHeight"),
+    (FXY::new(0, 10, 51), "pressureReducedToMeanSeaLevel"),
+    (FXY::new(0, 10, 52), "altimeterSettingQnh"),
+    (FXY::new(0, 10, 61), "3HourPressureChange"),
+    (FXY::new(0, 10, 62), "24HourPressureChange"),
+    (FXY::new(0, 10, 63), "characteristicOfPressureTendency"),
+    (FXY::new(0, 11, 1), "windDirection"),
+    (FXY::new(0, 11, 2), "windSpeed"),
+    (FXY::new(0, 11, 3), "u"),
+    (FXY::new(0, 11, 4), "v"),
+    (FXY::new(0, 11, 31), "degreeOfTurbulence"),
+    (
+        FXY::new(0, 11, 36),
+        "maximumDerivedEquivalentVerticalGustSpeed",
+    ),
+    (FXY::new(0, 11, 41), "maximumWindGustSpeed"),
+    (FXY::new(0, 11, 43), "maximumWindGustDirection"),
+    (FXY::new(0, 11, 61), "absoluteWindShearIn1KmLayerBelow"),
+    (FXY::new(0, 11, 62), "absoluteWindShearIn1KmLayerAbove"),
+    (
+        FXY::new(0, 11, 75),
+        "meanTurbulenceIntensityEddyDissipationRate",
+    ),
+    (
+        FXY::new(0, 11, 76),
+        "peakTurbulenceIntensityEddyDissipationRate",
+    ),
+    (FXY::new(0, 12, 1), "airTemperature"),
+    (FXY::new(0, 12, 3), "dewpointTemperature"),
+    (FXY::new(0, 12, 101), "airTemperature"),
+    (FXY::new(0, 12, 102), "wetBulbTemperature"),
+    (FXY::new(0, 12, 103), "dewpointTemperature"),
+    (FXY::new(0, 12, 104), "airTemperatureAt2M"),
+    (FXY::new(0, 12, 106), "dewpointTemperatureAt2M"),
+    (
+        FXY::new(0, 12, 111),
+        "maximumTemperatureAtHeightAndOverPeriodSpecified",
+    ),
+    (
+        FXY::new(0, 12, 112),
+        "minimumTemperatureAtHeightAndOverPeriodSpecified",
+    ),
+    (FXY::new(0, 13, 2), "mixingRatio"),
+    (FXY::new(0, 13, 3), "relativeHumidity"),
+    (
+        FXY::new(0, 13, 11),
+        "totalPrecipitationOrTotalWaterEquivalent",
+    ),
+    (FXY::new(0, 13, 13), "totalSnowDepth"),
+    (FXY::new(0, 13, 19), "totalPrecipitationPast1Hour"),
+    (FXY::new(0, 13, 20), "totalPrecipitationPast3Hours"),
+    (FXY::new(0, 13, 21), "totalPrecipitationPast6Hours"),
+    (FXY::new(0, 13, 22), "totalPrecipitationPast12Hours"),
+    (FXY::new(0, 13, 23), "totalPrecipitationPast24Hours"),
+    (FXY::new(0, 20, 1), "horizontalVisibility"),
+    (FXY::new(0, 20, 3), "presentWeather"),
+    (FXY::new(0, 20, 4), "pastWeather1"),
+    (FXY::new(0, 20, 5), "pastWeather2"),
+    (FXY::new(0, 20, 10), "cloudCoverTotal"),
+    (FXY::new(0, 20, 11), "cloudAmount"),
+    (FXY::new(0, 20, 12), "cloudType"),
+    (FXY::new(0, 20, 13), "heightOfBaseOfCloud"),
+    (FXY::new(0, 20, 41), "airframeIcing"),
+    (FXY::new(0, 20, 62), "stateOfGround"),
+    (
+        FXY::new(0, 31, 0),
+        "shortDelayedDescriptorReplicationFactor",
+    ),
+    (FXY::new(0, 31, 1), "delayedDescriptorReplicationFactor"),
+    (
+        FXY::new(0, 31, 2),
+        "extendedDelayedDescriptorReplicationFactor",
+    ),
+    (FXY::new(0, 31, 21), "associatedFieldSignificance"),
+    (FXY::new(0, 31, 31), "dataPresentIndicator"),
+];
+
+/// The ecCodes key of the element `fxy`, whose Table B name is `name`
+///
+/// Elements not listed take their name: `/` read as "or", "WMO" and
+/// accuracy notes such as "(HIGH ACCURACY)" dropped, and its words joined
+/// in camelCase; hyphenated words join without a capital ("DEW-POINT
+/// TEMPERATURE" is `dewpointTemperature`).
+pub fn eccodes_key(fxy: FXY, name: &str) -> String {
+    if let Some((_, key)) = KEYS.iter().find(|(f, _)| *f == fxy) {
+        return key.to_string();
+    }
+
+    let name = name.replace('/', " or ");
+    let mut plain = String::with_capacity(name.len());
+    let mut rest = name.as_str();
+    while let Some(open) = rest.find('(') {
+        let close = rest[open..].find(')').map_or(rest.len(), |c| open + c + 1);
+        plain.push_str(&rest[..open]);
+        let note = &rest[open..close];
+        if !note.to_ascii_uppercase().contains("ACCURACY") {
+            plain.push(' ');
+            plain.push_str(note);
+        }
+        rest = &rest[close..];
+    }
+    plain.push_str(rest);
+
+    let mut key = String::with_capacity(plain.len());
+    let words = plain
+        .replace('-', "")
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .skip_while(|w| w.eq_ignore_ascii_case("wmo"))
+        .map(str::to_ascii_lowercase)
+        .collect::<Vec<_>>();
+    for word in words {
+        if key.is_empty() {
+            key.push_str(&word);
+        } else {
+            let mut chars = word.chars();
+            if let Some(first) = chars.next() {
+                key.push(first.to_ascii_uppercase());
+                key.push_str(chars.as_str());
+            }
+        }
+    }
+    key
+}
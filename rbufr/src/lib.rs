@@ -1,15 +1,27 @@
 pub mod block;
 pub mod core;
+pub mod decode;
 pub mod decoder;
+pub mod dedupe;
+pub mod dump;
+#[cfg(feature = "ecmwf")]
+pub mod ecmwf;
 pub mod errors;
+pub mod extract;
+pub mod index;
+pub mod keys;
+pub mod obs;
 #[cfg(feature = "opera")]
 pub mod opera;
 pub mod parser;
 pub mod prelude;
+pub mod section2;
 pub mod structs;
 pub mod table_path;
 pub mod tables;
+pub mod templates;
 
+pub use crate::decode::{decode_bytes, decode_file};
 pub use crate::decoder::{BUFRData, Decoder, Value};
 pub use crate::parser::*;
 pub use crate::table_path::{get_tables_base_path, set_tables_base_path};
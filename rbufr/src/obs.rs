@@ -0,0 +1,218 @@
+//! Typed views of common observation templates.
+//!
+//! Each type converts from decoded records with `TryFrom<&BUFRParsed>`,
+//! failing with `Error::MissingElement` when a required element is absent.
+//! Convert one subset at a time, e.g. from `Decoder::decode_subset`.
+
+use chrono::NaiveDateTime;
+
+use crate::core::FXY;
+use crate::decoder::{BUFRData, BUFRParsed, Value};
+use crate::errors::{Error, Result};
+use crate::extract::{Level, StationId};
+
+/// Surface observation from a land station, e.g. 3-07-080
+#[derive(Debug, Clone, PartialEq)]
+pub struct Synop {
+    pub station: StationId,
+    pub time: NaiveDateTime,
+    pub latitude: f64,
+    pub longitude: f64,
+    /// m, height of station ground above mean sea level
+    pub elevation: Option<f64>,
+    /// Pa
+    pub pressure: Option<f64>,
+    /// Pa
+    pub msl_pressure: Option<f64>,
+    /// K
+    pub temperature: Option<f64>,
+    /// K
+    pub dew_point: Option<f64>,
+    /// %
+    pub relative_humidity: Option<f64>,
+    /// degree true
+    pub wind_direction: Option<f64>,
+    /// m/s
+    pub wind_speed: Option<f64>,
+    /// m
+    pub visibility: Option<f64>,
+}
+
+/// Upper-air sounding, e.g. 3-09-052
+#[derive(Debug, Clone, PartialEq)]
+pub struct TempSounding {
+    pub station: StationId,
+    pub time: NaiveDateTime,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub levels: Vec<Level>,
+}
+
+/// Aircraft report, e.g. 3-11-010
+#[derive(Debug, Clone, PartialEq)]
+pub struct Amdar {
+    /// Aircraft registration (0-01-008) or flight number (0-01-006)
+    pub aircraft: String,
+    pub time: NaiveDateTime,
+    pub latitude: f64,
+    pub longitude: f64,
+    /// m
+    pub flight_level: Option<f64>,
+    /// K
+    pub temperature: Option<f64>,
+    /// degree true
+    pub wind_direction: Option<f64>,
+    /// m/s
+    pub wind_speed: Option<f64>,
+}
+
+/// Drifting buoy report, e.g. 3-15-009
+#[derive(Debug, Clone, PartialEq)]
+pub struct DriftingBuoy {
+    pub buoy: String,
+    pub time: NaiveDateTime,
+    pub latitude: f64,
+    pub longitude: f64,
+    /// Pa
+    pub pressure: Option<f64>,
+    /// Pa
+    pub msl_pressure: Option<f64>,
+    /// K
+    pub air_temperature: Option<f64>,
+    /// K
+    pub sea_surface_temperature: Option<f64>,
+}
+
+impl TryFrom<&BUFRParsed<'_>> for Synop {
+    type Error = Error;
+
+    fn try_from(parsed: &BUFRParsed<'_>) -> Result<Self> {
+        let station = parsed.station();
+        if station.wmo_index().is_none() && station.wigos.is_none() {
+            return Err(Error::MissingElement("station identifier".to_string()));
+        }
+        let (latitude, longitude) = position(parsed)?;
+
+        Ok(Synop {
+            station,
+            time: time(parsed)?,
+            latitude,
+            longitude,
+            elevation: first(parsed, &[(0, 7, 30), (0, 7, 1)]),
+            pressure: first(parsed, &[(0, 10, 4)]),
+            msl_pressure: first(parsed, &[(0, 10, 51)]),
+            temperature: first(parsed, &[(0, 12, 101), (0, 12, 4)]),
+            dew_point: first(parsed, &[(0, 12, 103), (0, 12, 6)]),
+            relative_humidity: first(parsed, &[(0, 13, 3)]),
+            wind_direction: first(parsed, &[(0, 11, 1)]),
+            wind_speed: first(parsed, &[(0, 11, 2)]),
+            visibility: first(parsed, &[(0, 20, 1)]),
+        })
+    }
+}
+
+impl TryFrom<&BUFRParsed<'_>> for TempSounding {
+    type Error = Error;
+
+    fn try_from(parsed: &BUFRParsed<'_>) -> Result<Self> {
+        let levels = parsed.profile();
+        if levels.is_empty() {
+            return Err(Error::MissingElement("sounding levels".to_string()));
+        }
+        let (latitude, longitude) = position(parsed)?;
+
+        Ok(TempSounding {
+            station: parsed.station(),
+            time: time(parsed)?,
+            latitude,
+            longitude,
+            levels,
+        })
+    }
+}
+
+impl TryFrom<&BUFRParsed<'_>> for Amdar {
+    type Error = Error;
+
+    fn try_from(parsed: &BUFRParsed<'_>) -> Result<Self> {
+        let aircraft = first_text(parsed, &[(0, 1, 8), (0, 1, 6)])
+            .ok_or_else(|| Error::MissingElement("aircraft identifier".to_string()))?;
+        let (latitude, longitude) = position(parsed)?;
+
+        Ok(Amdar {
+            aircraft,
+            time: time(parsed)?,
+            latitude,
+            longitude,
+            flight_level: first(parsed, &[(0, 7, 10), (0, 7, 2)]),
+            temperature: first(parsed, &[(0, 12, 101), (0, 12, 1)]),
+            wind_direction: first(parsed, &[(0, 11, 1)]),
+            wind_speed: first(parsed, &[(0, 11, 2)]),
+        })
+    }
+}
+
+impl TryFrom<&BUFRParsed<'_>> for DriftingBuoy {
+    type Error = Error;
+
+    fn try_from(parsed: &BUFRParsed<'_>) -> Result<Self> {
+        let buoy = parsed
+            .station()
+            .ship_or_buoy
+            .ok_or_else(|| Error::MissingElement("buoy identifier".to_string()))?;
+        let (latitude, longitude) = position(parsed)?;
+
+        Ok(DriftingBuoy {
+            buoy,
+            time: time(parsed)?,
+            latitude,
+            longitude,
+            pressure: first(parsed, &[(0, 10, 4)]),
+            msl_pressure: first(parsed, &[(0, 10, 51)]),
+            air_temperature: first(parsed, &[(0, 12, 101), (0, 12, 4)]),
+            sea_surface_temperature: first(parsed, &[(0, 22, 43), (0, 22, 42)]),
+        })
+    }
+}
+
+fn time(parsed: &BUFRParsed<'_>) -> Result<NaiveDateTime> {
+    parsed
+        .datetime()
+        .ok_or_else(|| Error::MissingElement("observation time".to_string()))
+}
+
+/// High accuracy (0-05-001/0-06-001) or coarse (0-05-002/0-06-002) position
+fn position(parsed: &BUFRParsed<'_>) -> Result<(f64, f64)> {
+    let latitude = first(parsed, &[(0, 5, 1), (0, 5, 2)])
+        .ok_or_else(|| Error::MissingElement("latitude".to_string()))?;
+    let longitude = first(parsed, &[(0, 6, 1), (0, 6, 2)])
+        .ok_or_else(|| Error::MissingElement("longitude".to_string()))?;
+    Ok((latitude, longitude))
+}
+
+/// First non-missing single numeric value among `fxys`, tried in order
+fn first(parsed: &BUFRParsed<'_>, fxys: &[(i32, i32, i32)]) -> Option<f64> {
+    fxys.iter().find_map(|&(f, x, y)| {
+        parsed
+            .iter()
+            .with_fxy(FXY::new(f, x, y))
+            .find_map(|r| match &r.values {
+                BUFRData::Single(Value::Number(n)) => Some(*n),
+                _ => None,
+            })
+    })
+}
+
+fn first_text(parsed: &BUFRParsed<'_>, fxys: &[(i32, i32, i32)]) -> Option<String> {
+    fxys.iter().find_map(|&(f, x, y)| {
+        parsed
+            .iter()
+            .with_fxy(FXY::new(f, x, y))
+            .find_map(|r| match &r.values {
+                BUFRData::Single(Value::String(s)) => {
+                    Some(s.trim().to_string()).filter(|s| !s.is_empty())
+                }
+                _ => None,
+            })
+    })
+}
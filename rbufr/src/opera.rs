@@ -1,17 +1,268 @@
-use crate::decoder::Value;
+#[cfg(feature = "geotiff")]
+mod geotiff;
 
-pub struct OperaBitmapParser {
-    values: Vec<Value>,
-    #[allow(unused)]
+use crate::core::FXY;
+use crate::decoder::{BUFRData, BUFRParsed, BUFRRecord, MISS_VAL, MissingMask, Value};
+
+/// Row number, starts each run-length encoded image line
+const ROW_NUMBER: FXY = FXY::new(0, 5, 31);
+/// Run length of the pixel value that follows it
+const RUN_LENGTH: FXY = FXY::new(0, 31, 12);
+
+/// Collects the values decoded inside an OPERA bitmap sequence and expands
+/// them into pixels
+///
+/// `dw` is the bitmap table depth: bytes per pixel of the image the
+/// sequence describes.
+///
+/// Run-length encoded images (3-21-192 … 3-21-202) send each line as a row
+/// number (0-05-031) followed by parcels of runs, a count (0-31-012) and a
+/// value, then literal values. Counts and values decoded through a compiled
+/// replication arrive as two arrays; they are paired up in order.
+pub struct OperaBitmapParser<'a> {
+    values: Vec<(FXY, Value<'a>)>,
     dw: u8,
 }
 
-impl OperaBitmapParser {
+impl<'a> OperaBitmapParser<'a> {
     pub fn new(dw: u8) -> Self {
         OperaBitmapParser { values: vec![], dw }
     }
 
-    pub fn values(&mut self) -> &mut Vec<Value> {
-        &mut self.values
+    /// The collected values with the descriptor each was decoded from
+    pub fn values(&self) -> &[(FXY, Value<'a>)] {
+        &self.values
+    }
+
+    /// Bytes per pixel
+    pub fn depth(&self) -> u8 {
+        self.dw
+    }
+
+    /// Append the values of one record decoded inside the sequence
+    pub fn push_data(&mut self, fxy: FXY, data: &BUFRData<'a>) {
+        match data {
+            BUFRData::Single(v) => self.values.push((fxy, v.clone())),
+            BUFRData::Repeat(vs) => self.values.extend(vs.iter().map(|v| (fxy, v.clone()))),
+            BUFRData::Array { values, missing }
+            | BUFRData::Array2 {
+                values, missing, ..
+            } => self
+                .values
+                .extend(values.iter().zip(missing.iter()).map(|(&v, missing)| {
+                    if missing {
+                        (fxy, Value::Missing)
+                    } else {
+                        (fxy, Value::Number(v))
+                    }
+                })),
+            BUFRData::ArrayF32 { values, missing } => {
+                self.values
+                    .extend(values.iter().zip(missing.iter()).map(|(&v, missing)| {
+                        if missing {
+                            (fxy, Value::Missing)
+                        } else {
+                            (fxy, Value::Number(v as f64))
+                        }
+                    }))
+            }
+            BUFRData::Group { .. } => {}
+        }
+    }
+
+    /// Expand runs into pixels, `MISS_VAL` where `missing` is set
+    ///
+    /// Values of a bitmap without row numbers or run lengths are returned as
+    /// they were decoded.
+    pub fn pixels(&self) -> (Vec<f64>, MissingMask<'static>) {
+        let encoded = self
+            .values
+            .iter()
+            .any(|(fxy, _)| *fxy == ROW_NUMBER || *fxy == RUN_LENGTH);
+        if !encoded {
+            let pixels = self
+                .values
+                .iter()
+                .map(|(_, v)| v.as_f64().unwrap_or(MISS_VAL))
+                .collect();
+            let missing = self
+                .values
+                .iter()
+                .map(|(_, v)| v.as_f64().is_none())
+                .collect();
+            return (pixels, missing);
+        }
+
+        let mut pixels = vec![];
+        let mut missing = MissingMask::new();
+        let mut runs = std::collections::VecDeque::new();
+        for (fxy, value) in &self.values {
+            if *fxy == ROW_NUMBER {
+                runs.clear();
+            } else if *fxy == RUN_LENGTH {
+                runs.push_back(value.as_f64().unwrap_or(0.0) as usize);
+            } else {
+                let pixel = value.as_f64();
+                let count = runs.pop_front().unwrap_or(1);
+                pixels.extend(std::iter::repeat_n(pixel.unwrap_or(MISS_VAL), count));
+                for _ in 0..count {
+                    missing.push(pixel.is_none());
+                }
+            }
+        }
+        (pixels, missing)
+    }
+}
+
+/// Geo-referencing of an OPERA image, from the projection sequence decoded
+/// ahead of it (e.g. 3-01-192, 3-01-193, 3-01-194); absent fields are `None`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Projection {
+    /// Code table 0-29-201
+    pub projection_type: Option<u8>,
+    /// m, 0-29-199
+    pub semi_major_axis: Option<f64>,
+    /// m, 0-29-200
+    pub semi_minor_axis: Option<f64>,
+    /// degree, 0-29-193
+    pub lon_origin: Option<f64>,
+    /// degree, 0-29-194
+    pub lat_origin: Option<f64>,
+    /// m, 0-29-195
+    pub x_offset: Option<f64>,
+    /// m, 0-29-196
+    pub y_offset: Option<f64>,
+    /// degree, 0-29-197
+    pub standard_parallel_1: Option<f64>,
+    /// degree, 0-29-198
+    pub standard_parallel_2: Option<f64>,
+    /// m, pixel size along a row, 0-05-033
+    pub pixel_size_x: Option<f64>,
+    /// m, pixel size along a column, 0-06-033
+    pub pixel_size_y: Option<f64>,
+    /// PROJ initialisation string, 0-29-205
+    pub proj4: Option<String>,
+    /// `(lat, lon)` of the NW, NE, SE and SW corners, in that order
+    pub corners: Vec<(f64, f64)>,
+}
+
+/// One decoded OPERA raster with its geo-metadata
+#[derive(Debug, Clone, PartialEq)]
+pub struct RadarImage {
+    /// Bitmap sequence the image was decoded from
+    pub fxy: FXY,
+    pub rows: usize,
+    pub cols: usize,
+    /// Row-major, missing pixels filled per `MissingValues` (`MISS_VAL` by default)
+    pub pixels: Vec<f64>,
+    /// Set for each missing pixel
+    pub missing: MissingMask<'static>,
+    pub projection: Projection,
+}
+
+impl RadarImage {
+    pub fn get(&self, row: usize, col: usize) -> Option<f64> {
+        if row >= self.rows || col >= self.cols {
+            return None;
+        }
+        let index = row * self.cols + col;
+        (!self.missing.get(index)).then(|| self.pixels[index])
+    }
+
+    pub fn row(&self, row: usize) -> Option<&[f64]> {
+        (row < self.rows).then(|| &self.pixels[row * self.cols..(row + 1) * self.cols])
+    }
+}
+
+impl BUFRParsed<'_> {
+    /// Every OPERA raster in decode order, each with the projection decoded
+    /// most recently before it
+    ///
+    /// Corner positions are the first four latitude/longitude pairs ahead of
+    /// the projection type (0-29-201). Only bitmaps that could be shaped into
+    /// rows × columns are returned.
+    pub fn radar_images(&self) -> Vec<RadarImage> {
+        let mut images = vec![];
+        let mut projection = Projection::default();
+        let mut corner_lat: Option<f64> = None;
+        // A projection field after an image starts a new projection
+        let mut used = false;
+
+        for record in self.iter() {
+            if let BUFRData::Array2 {
+                rows,
+                cols,
+                values,
+                missing,
+            } = record.values
+            {
+                images.push(RadarImage {
+                    fxy: record.fxy,
+                    rows,
+                    cols,
+                    pixels: values.into_owned(),
+                    missing: missing.into_owned(),
+                    projection: projection.clone(),
+                });
+                used = true;
+                continue;
+            }
+
+            let (f, x, y) = (record.fxy.f, record.fxy.x, record.fxy.y);
+            if f != 0 || !matches!(x, 5 | 6 | 29) {
+                continue;
+            }
+            if used {
+                projection = Projection::default();
+                used = false;
+            }
+
+            if (x, y) == (29, 205) {
+                projection.proj4 = text(&record);
+                continue;
+            }
+            let Some(n) = number(&record) else {
+                continue;
+            };
+            match (x, y) {
+                (29, 201) => projection.projection_type = Some(n as u8),
+                (29, 199) => projection.semi_major_axis = Some(n),
+                (29, 200) => projection.semi_minor_axis = Some(n),
+                (29, 193) => projection.lon_origin = Some(n),
+                (29, 194) => projection.lat_origin = Some(n),
+                (29, 195) => projection.x_offset = Some(n),
+                (29, 196) => projection.y_offset = Some(n),
+                (29, 197) => projection.standard_parallel_1 = Some(n),
+                (29, 198) => projection.standard_parallel_2 = Some(n),
+                (5, 33) => projection.pixel_size_x = Some(n),
+                (6, 33) => projection.pixel_size_y = Some(n),
+                (5, 1 | 2) => corner_lat = Some(n),
+                (6, 1 | 2) => {
+                    if let Some(lat) = corner_lat.take()
+                        && projection.projection_type.is_none()
+                        && projection.corners.len() < 4
+                    {
+                        projection.corners.push((lat, n));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        images
+    }
+}
+
+fn number(record: &BUFRRecord<'_>) -> Option<f64> {
+    match &record.values {
+        BUFRData::Single(Value::Number(n)) => Some(*n),
+        _ => None,
+    }
+}
+
+fn text(record: &BUFRRecord<'_>) -> Option<String> {
+    match &record.values {
+        BUFRData::Single(Value::String(s)) => Some(s.trim().to_string()),
+        _ => None,
     }
 }
@@ -0,0 +1,221 @@
+//! GeoTIFF export of decoded OPERA rasters.
+
+use std::fs::File;
+use std::io::{BufWriter, Seek, Write};
+use std::path::Path;
+
+use tiff::encoder::{TiffEncoder, colortype::Gray32Float};
+use tiff::tags::Tag;
+
+use super::{Projection, RadarImage};
+use crate::errors::{Error, Result};
+
+/// Value of a user-defined code in GeoTIFF keys
+const USER_DEFINED: u16 = 32767;
+const WGS84: u16 = 4326;
+const METRE: u16 = 9001;
+
+const GT_MODEL_TYPE: u16 = 1024;
+const GT_RASTER_TYPE: u16 = 1025;
+const GT_CITATION: u16 = 1026;
+const GEOGRAPHIC_TYPE: u16 = 2048;
+const GEOG_ELLIPSOID: u16 = 2056;
+const GEOG_SEMI_MAJOR_AXIS: u16 = 2057;
+const GEOG_SEMI_MINOR_AXIS: u16 = 2058;
+const PROJECTED_CS_TYPE: u16 = 3072;
+const PROJECTION: u16 = 3074;
+const PROJ_COORD_TRANS: u16 = 3075;
+const PROJ_LINEAR_UNITS: u16 = 3076;
+const PROJ_STD_PARALLEL_1: u16 = 3078;
+const PROJ_STD_PARALLEL_2: u16 = 3079;
+const PROJ_NAT_ORIGIN_LONG: u16 = 3080;
+const PROJ_NAT_ORIGIN_LAT: u16 = 3081;
+const PROJ_CENTER_LONG: u16 = 3088;
+const PROJ_CENTER_LAT: u16 = 3089;
+const PROJ_STRAIGHT_VERT_POLE_LONG: u16 = 3095;
+
+/// GeoTIFF coordinate transformation codes
+const CT_OBLIQUE_MERCATOR: u16 = 3;
+const CT_LAMBERT_CONF_CONIC_2SP: u16 = 8;
+const CT_LAMBERT_AZIM_EQUAL_AREA: u16 = 10;
+const CT_AZIMUTHAL_EQUIDISTANT: u16 = 12;
+const CT_POLAR_STEREOGRAPHIC: u16 = 15;
+
+impl RadarImage {
+    /// Write the raster as a single-band `f32` GeoTIFF at `path`
+    ///
+    /// See [`RadarImage::write_geotiff`].
+    pub fn save_geotiff<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_geotiff(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Write the raster as a single-band `f32` GeoTIFF, missing pixels as NaN
+    ///
+    /// The projection type (0-29-201) must be polar stereographic, Lambert
+    /// conformal conic, oblique Mercator, azimuthal equidistant or Lambert
+    /// azimuthal equal area. The X/Y offsets are taken as the projected
+    /// coordinates of the upper-left corner and the pixel sizes (0-05-033,
+    /// 0-06-033) are required. The PROJ string, when present, becomes the
+    /// citation.
+    pub fn write_geotiff<W: Write + Seek>(&self, writer: W) -> Result<()> {
+        let keys = GeoKeys::new(&self.projection)?;
+        let (Some(size_x), Some(size_y)) =
+            (self.projection.pixel_size_x, self.projection.pixel_size_y)
+        else {
+            return Err(Error::MissingElement("pixel size".to_string()));
+        };
+        let origin_x = self.projection.x_offset.unwrap_or(0.0);
+        let origin_y = self.projection.y_offset.unwrap_or(0.0);
+
+        let pixels: Vec<f32> = self
+            .pixels
+            .iter()
+            .zip(self.missing.iter())
+            .map(|(&v, missing)| if missing { f32::NAN } else { v as f32 })
+            .collect();
+
+        let mut tiff = TiffEncoder::new(writer).map_err(tiff_error)?;
+        let mut image = tiff
+            .new_image::<Gray32Float>(self.cols as u32, self.rows as u32)
+            .map_err(tiff_error)?;
+
+        let dir = image.encoder();
+        dir.write_tag(Tag::ModelPixelScaleTag, &[size_x, size_y, 0.0][..])
+            .map_err(tiff_error)?;
+        dir.write_tag(
+            Tag::ModelTiepointTag,
+            &[0.0, 0.0, 0.0, origin_x, origin_y, 0.0][..],
+        )
+        .map_err(tiff_error)?;
+        dir.write_tag(Tag::GeoKeyDirectoryTag, &keys.directory[..])
+            .map_err(tiff_error)?;
+        if !keys.doubles.is_empty() {
+            dir.write_tag(Tag::GeoDoubleParamsTag, &keys.doubles[..])
+                .map_err(tiff_error)?;
+        }
+        if !keys.ascii.is_empty() {
+            dir.write_tag(Tag::GeoAsciiParamsTag, keys.ascii.as_str())
+                .map_err(tiff_error)?;
+        }
+        dir.write_tag(Tag::GdalNodata, "nan").map_err(tiff_error)?;
+
+        image.write_data(&pixels).map_err(tiff_error)?;
+        Ok(())
+    }
+}
+
+fn tiff_error(e: tiff::TiffError) -> Error {
+    match e {
+        tiff::TiffError::IoError(e) => Error::Io(e),
+        e => Error::ParseError(format!("GeoTIFF: {}", e)),
+    }
+}
+
+/// Contents of the three GeoTIFF key tags
+struct GeoKeys {
+    directory: Vec<u16>,
+    doubles: Vec<f64>,
+    ascii: String,
+}
+
+impl GeoKeys {
+    fn new(projection: &Projection) -> Result<Self> {
+        let transform = match projection.projection_type {
+            Some(1) => CT_POLAR_STEREOGRAPHIC,
+            Some(2) => CT_LAMBERT_CONF_CONIC_2SP,
+            Some(3) => CT_OBLIQUE_MERCATOR,
+            Some(4) => CT_AZIMUTHAL_EQUIDISTANT,
+            Some(5) => CT_LAMBERT_AZIM_EQUAL_AREA,
+            Some(other) => {
+                return Err(Error::ParseError(format!(
+                    "Projection type {} can't be written to GeoTIFF",
+                    other
+                )));
+            }
+            None => return Err(Error::MissingElement("projection type".to_string())),
+        };
+
+        let mut keys = GeoKeys {
+            directory: vec![],
+            doubles: vec![],
+            ascii: String::new(),
+        };
+        // (key, value) for short keys, kept sorted by key id
+        let mut entries: Vec<(u16, u16, u16, u16)> = vec![];
+        let mut short = |key: u16, value: u16| entries.push((key, 0, 1, value));
+        short(GT_MODEL_TYPE, 1);
+        short(GT_RASTER_TYPE, 1);
+        short(PROJECTED_CS_TYPE, USER_DEFINED);
+        short(PROJECTION, USER_DEFINED);
+        short(PROJ_COORD_TRANS, transform);
+        short(PROJ_LINEAR_UNITS, METRE);
+
+        match (projection.semi_major_axis, projection.semi_minor_axis) {
+            (Some(major), Some(minor)) => {
+                short(GEOGRAPHIC_TYPE, USER_DEFINED);
+                short(GEOG_ELLIPSOID, USER_DEFINED);
+                keys.double(&mut entries, GEOG_SEMI_MAJOR_AXIS, Some(major));
+                keys.double(&mut entries, GEOG_SEMI_MINOR_AXIS, Some(minor));
+            }
+            _ => short(GEOGRAPHIC_TYPE, WGS84),
+        }
+
+        let (lon_key, lat_key) = match transform {
+            CT_LAMBERT_AZIM_EQUAL_AREA | CT_AZIMUTHAL_EQUIDISTANT | CT_OBLIQUE_MERCATOR => {
+                (PROJ_CENTER_LONG, PROJ_CENTER_LAT)
+            }
+            _ => (PROJ_NAT_ORIGIN_LONG, PROJ_NAT_ORIGIN_LAT),
+        };
+        keys.double(&mut entries, lon_key, projection.lon_origin);
+        keys.double(&mut entries, lat_key, projection.lat_origin);
+        keys.double(
+            &mut entries,
+            PROJ_STD_PARALLEL_1,
+            projection.standard_parallel_1,
+        );
+        keys.double(
+            &mut entries,
+            PROJ_STD_PARALLEL_2,
+            projection.standard_parallel_2,
+        );
+        if transform == CT_POLAR_STEREOGRAPHIC {
+            keys.double(
+                &mut entries,
+                PROJ_STRAIGHT_VERT_POLE_LONG,
+                projection.lon_origin,
+            );
+        }
+
+        if let Some(proj4) = &projection.proj4 {
+            entries.push((
+                GT_CITATION,
+                Tag::GeoAsciiParamsTag.to_u16(),
+                proj4.len() as u16 + 1,
+                0,
+            ));
+            keys.ascii = format!("{}|", proj4);
+        }
+
+        entries.sort_by_key(|e| e.0);
+        keys.directory = vec![1, 1, 0, entries.len() as u16];
+        for (key, location, count, value) in entries {
+            keys.directory.extend([key, location, count, value]);
+        }
+        Ok(keys)
+    }
+
+    fn double(&mut self, entries: &mut Vec<(u16, u16, u16, u16)>, key: u16, value: Option<f64>) {
+        if let Some(value) = value {
+            entries.push((
+                key,
+                Tag::GeoDoubleParamsTag.to_u16(),
+                1,
+                self.doubles.len() as u16,
+            ));
+            self.doubles.push(value);
+        }
+    }
+}
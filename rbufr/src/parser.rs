@@ -1,8 +1,13 @@
-use crate::errors::Result;
+use crate::errors::{Error, Result};
 use crate::structs::versions::BUFRMessage;
-use crate::{block::BUFRFile, structs::versions::MessageVersion};
+use crate::{
+    block::{BUFRFile, MessageBlock},
+    structs::versions::MessageVersion,
+};
 use flate2::read::GzDecoder;
-use std::io::{Cursor, Read, Seek, SeekFrom};
+use std::fs::File;
+use std::io::{BufReader, Cursor, Read, Seek, SeekFrom};
+use std::path::Path;
 
 const BUFR_PATTERN: &[u8] = b"BUFR";
 const BUFFER_SIZE: usize = 8192;
@@ -16,13 +21,99 @@ pub fn parse(data: &[u8]) -> Result<BUFRFile> {
         let mut bytes = vec![];
         gz_decoder.read_to_end(&mut bytes)?;
 
-        parse_inner(&mut Cursor::new(bytes))
+        parse_inner(&mut Cursor::new(bytes), |_| true)
     } else {
         reader.seek(SeekFrom::Start(0))?;
-        parse_inner(&mut reader)
+        parse_inner(&mut reader, |_| true)
     }
 }
 
+/// Parse the file at `path`, keeping only messages whose header passes
+/// `filter`
+///
+/// The predicate runs on Sections 0 and 1 alone; rejected messages are
+/// skipped without reading the rest of their bytes.
+pub fn parse_with_filter<P, F>(path: P, filter: F) -> Result<BUFRFile>
+where
+    P: AsRef<Path>,
+    F: FnMut(&MessageHeader) -> bool,
+{
+    let mut reader = BufReader::new(File::open(path)?);
+
+    let mut magic_bytes = [0u8; 2];
+    reader.read_exact(&mut magic_bytes)?;
+    reader.seek(SeekFrom::Start(0))?;
+
+    if magic_bytes == [0x1F, 0x8B] {
+        let mut bytes = vec![];
+        GzDecoder::new(reader).read_to_end(&mut bytes)?;
+        parse_inner(&mut Cursor::new(bytes), filter)
+    } else {
+        parse_inner(&mut reader, filter)
+    }
+}
+
+/// The identification fields of Sections 0 and 1, common to all editions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct MessageHeader {
+    pub edition: u8,
+    pub centre: u16,
+    pub subcentre: u16,
+    pub update_sequence_number: u8,
+    pub data_category: u8,
+    pub data_subcategory: u8,
+    pub master_table_version: u8,
+    pub local_table_version: u8,
+}
+
+impl MessageHeader {
+    /// Longest Section 0 plus fixed Section 1 prefix needed, from edition 4
+    pub(crate) const MAX_LEN: usize = 23;
+
+    pub(crate) fn from_bytes(bytes: &[u8; Self::MAX_LEN]) -> Self {
+        let edition = bytes[7];
+        match edition {
+            0 | 1 => MessageHeader {
+                edition,
+                centre: u16::from_be_bytes([bytes[8], bytes[9]]),
+                subcentre: 0,
+                update_sequence_number: bytes[10],
+                data_category: bytes[12],
+                data_subcategory: bytes[13],
+                master_table_version: bytes[14],
+                local_table_version: bytes[15],
+            },
+            2 | 3 => MessageHeader {
+                edition,
+                centre: bytes[13] as u16,
+                subcentre: bytes[12] as u16,
+                update_sequence_number: bytes[14],
+                data_category: bytes[16],
+                data_subcategory: bytes[17],
+                master_table_version: bytes[18],
+                local_table_version: bytes[19],
+            },
+            _ => MessageHeader {
+                edition,
+                centre: u16::from_be_bytes([bytes[12], bytes[13]]),
+                subcentre: u16::from_be_bytes([bytes[14], bytes[15]]),
+                update_sequence_number: bytes[16],
+                data_category: bytes[18],
+                data_subcategory: bytes[19],
+                master_table_version: bytes[21],
+                local_table_version: bytes[22],
+            },
+        }
+    }
+}
+
+fn read_header_at_offset<R: Read + Seek>(reader: &mut R, offset: u64) -> Result<MessageHeader> {
+    let mut buf = [0u8; MessageHeader::MAX_LEN];
+    reader.seek(SeekFrom::Start(offset))?;
+    reader.read_exact(&mut buf)?;
+    Ok(MessageHeader::from_bytes(&buf))
+}
+
 fn find_bufr_offsets<R: Read + Seek>(reader: &mut R) -> Result<Vec<u64>> {
     let mut offsets = Vec::new();
     let mut buffer = vec![0u8; BUFFER_SIZE];
@@ -71,7 +162,11 @@ fn read_message_at_offset<R: Read + Seek>(reader: &mut R, offset: u64) -> Result
     let mut section0_buf = [0u8; 8];
     reader.read_exact(&mut section0_buf)?;
 
-    let total_length = u32::from_be_bytes([0, section0_buf[4], section0_buf[5], section0_buf[6]]);
+    let total_length = if section0_buf[7] < 2 {
+        legacy_message_length(reader, offset)?
+    } else {
+        u32::from_be_bytes([0, section0_buf[4], section0_buf[5], section0_buf[6]])
+    };
 
     let mut message_buf = vec![0u8; total_length as usize];
     reader.seek(SeekFrom::Start(offset))?;
@@ -80,23 +175,65 @@ fn read_message_at_offset<R: Read + Seek>(reader: &mut R, offset: u64) -> Result
     Ok(message_buf)
 }
 
-fn parse_inner<R>(buf_reader: &mut R) -> Result<BUFRFile>
+/// Editions 0 and 1 carry no total length in Section 0; add up the section
+/// lengths instead and check the message ends with "7777"
+fn legacy_message_length<R: Read + Seek>(reader: &mut R, offset: u64) -> Result<u32> {
+    let mut length = 4 + read_u24_at(reader, offset + 4)? as u64;
+
+    let mut flags = [0u8; 1];
+    reader.seek(SeekFrom::Start(offset + 11))?;
+    reader.read_exact(&mut flags)?;
+    if flags[0] & 0x80 != 0 {
+        length += read_u24_at(reader, offset + length)? as u64;
+    }
+    length += read_u24_at(reader, offset + length)? as u64;
+    length += read_u24_at(reader, offset + length)? as u64;
+
+    let mut end = [0u8; 4];
+    reader.seek(SeekFrom::Start(offset + length))?;
+    reader.read_exact(&mut end)?;
+    if &end != b"7777" {
+        return Err(Error::ParseError(format!(
+            "Missing end section in edition 0/1 message at offset {}",
+            offset
+        )));
+    }
+
+    Ok((length + 4) as u32)
+}
+
+fn read_u24_at<R: Read + Seek>(reader: &mut R, pos: u64) -> Result<u32> {
+    let mut buf = [0u8; 3];
+    reader.seek(SeekFrom::Start(pos))?;
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_be_bytes([0, buf[0], buf[1], buf[2]]))
+}
+
+fn read_block<R: Read + Seek>(reader: &mut R, offset: u64) -> Result<(BUFRMessage, Vec<u8>)> {
+    let message_data = read_message_at_offset(reader, offset)?;
+    let message = BUFRMessage::parse(&message_data)?;
+    Ok((message, message_data))
+}
+
+fn parse_inner<R, F>(buf_reader: &mut R, mut filter: F) -> Result<BUFRFile>
 where
     R: Read + Seek,
+    F: FnMut(&MessageHeader) -> bool,
 {
     let offsets = find_bufr_offsets(buf_reader)?;
     let mut file_block = BUFRFile::new();
 
     for offset in offsets {
-        match read_message_at_offset(buf_reader, offset) {
-            Ok(message_data) => match BUFRMessage::parse(&message_data) {
-                Ok(message) => {
-                    file_block.push_message(message);
-                }
-                Err(e) => {
-                    eprintln!("Failed to parse BUFR message at offset {}: {:?}", offset, e);
-                }
-            },
+        match read_header_at_offset(buf_reader, offset) {
+            Ok(header) if !filter(&header) => continue,
+            Ok(_) => {}
+            Err(e) => {
+                eprintln!("Failed to read BUFR header at offset {}: {:?}", offset, e);
+                continue;
+            }
+        }
+        match read_block(buf_reader, offset) {
+            Ok((message, message_data)) => file_block.push_message(message, message_data),
             Err(e) => {
                 eprintln!("Failed to read BUFR message at offset {}: {:?}", offset, e);
             }
@@ -105,3 +242,92 @@ where
 
     Ok(file_block)
 }
+
+/// Where `MessageReader::open` reads from: the file itself, or its
+/// decompressed bytes when gzipped
+pub enum FileSource {
+    File(BufReader<File>),
+    Gzip(Cursor<Vec<u8>>),
+}
+
+impl Read for FileSource {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            FileSource::File(reader) => reader.read(buf),
+            FileSource::Gzip(reader) => reader.read(buf),
+        }
+    }
+}
+
+impl Seek for FileSource {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        match self {
+            FileSource::File(reader) => reader.seek(pos),
+            FileSource::Gzip(reader) => reader.seek(pos),
+        }
+    }
+}
+
+/// Messages of a file, read and parsed one at a time as they are iterated
+///
+/// Only the message offsets are found up front. Messages that fail to read
+/// or parse come out as errors; iteration carries on after them.
+pub struct MessageReader<R> {
+    reader: R,
+    offsets: std::vec::IntoIter<u64>,
+}
+
+impl MessageReader<FileSource> {
+    /// Open the file at `path`; gzipped files are decompressed in memory
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+
+        let mut magic_bytes = [0u8; 2];
+        reader.read_exact(&mut magic_bytes)?;
+        reader.seek(SeekFrom::Start(0))?;
+
+        if magic_bytes == [0x1F, 0x8B] {
+            let mut bytes = vec![];
+            GzDecoder::new(reader).read_to_end(&mut bytes)?;
+            Self::new(FileSource::Gzip(Cursor::new(bytes)))
+        } else {
+            Self::new(FileSource::File(reader))
+        }
+    }
+}
+
+impl<R: Read + Seek> MessageReader<R> {
+    pub fn new(mut reader: R) -> Result<Self> {
+        let offsets = find_bufr_offsets(&mut reader)?;
+        Ok(MessageReader {
+            reader,
+            offsets: offsets.into_iter(),
+        })
+    }
+
+    /// Messages not yet read
+    pub fn remaining(&self) -> usize {
+        self.offsets.len()
+    }
+
+    /// Where the messages not yet read start, in the order they will be
+    pub fn offsets(&self) -> &[u64] {
+        self.offsets.as_slice()
+    }
+}
+
+impl<R: Read + Seek> Iterator for MessageReader<R> {
+    type Item = Result<MessageBlock>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let offset = self.offsets.next()?;
+        Some(
+            read_block(&mut self.reader, offset)
+                .map(|(message, raw)| MessageBlock::new(message, raw)),
+        )
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.offsets.size_hint()
+    }
+}
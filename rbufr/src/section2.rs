@@ -0,0 +1,105 @@
+//! Registry of Section 2 parsers keyed by originating centre.
+//!
+//! Section 2 is free for local use, so what it holds depends on who produced
+//! the message. Register a parser for a centre (and optionally a subcentre)
+//! and `MessageBlock::section2_decoded` hands the section data to it.
+
+use std::any::Any;
+use std::sync::{Arc, LazyLock, RwLock};
+
+use rustc_hash::FxHashMap;
+
+use crate::block::MessageBlock;
+use crate::structs::versions::MessageVersion;
+
+type Handler = Arc<dyn Fn(&[u8]) -> Option<Section2Value> + Send + Sync>;
+
+/// `(centre, subcentre)`; a `None` subcentre matches any
+type Key = (u16, Option<u16>);
+
+static HANDLERS: LazyLock<RwLock<FxHashMap<Key, Handler>>> =
+    LazyLock::new(|| RwLock::new(builtin_handlers()));
+
+#[allow(unused_mut)]
+fn builtin_handlers() -> FxHashMap<Key, Handler> {
+    let mut handlers: FxHashMap<Key, Handler> = FxHashMap::default();
+    #[cfg(feature = "ecmwf")]
+    handlers.insert(
+        (98, None),
+        Arc::new(|data| crate::ecmwf::RdbKey::parse(data).map(Section2Value::new)),
+    );
+    handlers
+}
+
+/// A decoded Section 2, downcast to the type its parser returned
+pub struct Section2Value(Box<dyn Any + Send + Sync>);
+
+impl Section2Value {
+    pub fn new<T: Any + Send + Sync>(value: T) -> Self {
+        Section2Value(Box::new(value))
+    }
+
+    pub fn is<T: Any>(&self) -> bool {
+        self.0.is::<T>()
+    }
+
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        self.0.downcast_ref()
+    }
+
+    pub fn downcast<T: Any>(self) -> Result<T, Self> {
+        self.0.downcast().map(|b| *b).map_err(Section2Value)
+    }
+}
+
+impl std::fmt::Debug for Section2Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Section2Value(..)")
+    }
+}
+
+/// Parse Section 2 of messages from `centre` with `parser`
+///
+/// With `subcentre` set, the parser only applies to that subcentre and takes
+/// precedence over one registered for the whole centre. Registering again
+/// for the same key replaces the previous parser, built-in ones included.
+pub fn register_section2_parser<T, F>(centre: u16, subcentre: Option<u16>, parser: F)
+where
+    T: Any + Send + Sync,
+    F: Fn(&[u8]) -> Option<T> + Send + Sync + 'static,
+{
+    let handler: Handler = Arc::new(move |data| parser(data).map(Section2Value::new));
+    HANDLERS
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert((centre, subcentre), handler);
+}
+
+/// Remove the parser registered for exactly this key, if any
+pub fn unregister_section2_parser(centre: u16, subcentre: Option<u16>) {
+    HANDLERS
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(&(centre, subcentre));
+}
+
+fn handler_for(centre: u16, subcentre: u16) -> Option<Handler> {
+    let handlers = HANDLERS.read().unwrap_or_else(|e| e.into_inner());
+    handlers
+        .get(&(centre, Some(subcentre)))
+        .or_else(|| handlers.get(&(centre, None)))
+        .cloned()
+}
+
+impl MessageBlock {
+    /// Section 2 run through the parser registered for this message's
+    /// centre and subcentre
+    ///
+    /// `None` when the message has no Section 2, no parser is registered or
+    /// the parser rejects the data.
+    pub fn section2_decoded(&self) -> Option<Section2Value> {
+        let section2 = self.section2()?;
+        let handler = handler_for(self.center_id(), self.subcenter_id())?;
+        handler(&section2.data)
+    }
+}
@@ -1,3 +1,4 @@
+pub mod v1;
 pub mod v2;
 pub mod v3;
 pub mod v4;
@@ -5,6 +6,7 @@ pub mod v4;
 pub(super) use super::skip1;
 use crate::core::FXY;
 use crate::errors::{Error, Result};
+use chrono::{NaiveDate, NaiveDateTime};
 use nom::{
     IResult,
     bytes::complete::{tag, take},
@@ -89,6 +91,30 @@ macro_rules! message {
                 }
             }
 
+            fn update_sequence_number(&self) -> u8 {
+                match self {
+                    $(
+                        BUFRMessage::$version(msg) => msg.update_sequence_number(),
+                    )+
+                }
+            }
+
+            fn obs_time(&self) -> Option<NaiveDateTime> {
+                match self {
+                    $(
+                        BUFRMessage::$version(msg) => msg.obs_time(),
+                    )+
+                }
+            }
+
+            fn is_compressed(&self) -> bool {
+                match self {
+                    $(
+                        BUFRMessage::$version(msg) => msg.is_compressed(),
+                    )+
+                }
+            }
+
             fn ndescs(&self) -> usize {
                 match self {
                     $(
@@ -112,6 +138,14 @@ macro_rules! message {
                     )+
                 }
             }
+
+            fn to_bytes(&self) -> Vec<u8> {
+                match self {
+                    $(
+                        BUFRMessage::$version(msg) => msg.to_bytes(),
+                    )+
+                }
+            }
         }
 
     impl BUFRMessage {
@@ -146,6 +180,8 @@ macro_rules! message {
 }
 
 message!(
+    (V0, v1::BUFRMessageV1, 0),
+    (V1, v1::BUFRMessageV1, 1),
     (V2, v2::BUFRMessageV2, 2),
     (V3, v3::BUFRMessageV3, 3),
     (V4, v4::BUFRMessageV4, 4)
@@ -166,6 +202,10 @@ pub trait MessageVersion: Sized {
         self.table_info().center_id
     }
 
+    fn master_table(&self) -> u8 {
+        self.table_info().master_table
+    }
+
     fn master_table_version(&self) -> u8 {
         self.table_info().master_table_version
     }
@@ -176,20 +216,59 @@ pub trait MessageVersion: Sized {
 
     fn subsets_count(&self) -> u16;
 
+    /// Zero for an original message, incremented for each update
+    fn update_sequence_number(&self) -> u8;
+
+    /// The typical time of the data, from Section 1
+    fn obs_time(&self) -> Option<NaiveDateTime>;
+
+    fn is_compressed(&self) -> bool;
+
     fn ndescs(&self) -> usize;
 
     fn descriptors(&self) -> Result<Vec<FXY>>;
 
     fn data_block(&self) -> Result<&[u8]>;
+
+    /// Re-serialize the message from its section structs
+    fn to_bytes(&self) -> Vec<u8>;
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct TableInfo {
+    /// BUFR master table number: 0 for meteorology, 10 for oceanography
+    pub master_table: u8,
     pub master_table_version: u8,
     pub local_table_version: u8,
     pub center_id: u16,
     pub subcenter_id: u16,
 }
 
+/// Editions 0 to 3 store the year of century: 0-49 read as 2000-2049,
+/// 50-99 as 1950-1999, and 100 (used by some centres for 2000) as 2000
+fn year_of_century(year: u8) -> i32 {
+    if year < 50 {
+        2000 + year as i32
+    } else {
+        1900 + year as i32
+    }
+}
+
+fn section1_time(
+    year: i32,
+    month: u8,
+    day: u8,
+    hour: u8,
+    minute: u8,
+    second: u8,
+) -> Option<NaiveDateTime> {
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32)?.and_hms_opt(
+        hour as u32,
+        minute as u32,
+        second as u32,
+    )
+}
+
 #[derive(Clone)]
 struct Section0 {
     pub _total_length: u32,
@@ -215,10 +294,30 @@ pub struct Section2 {
     pub data: Vec<u8>,
 }
 
+impl Section2 {
+    fn write_to(&self, out: &mut Vec<u8>) {
+        put_u24(out, self.data.len() + 4);
+        out.push(0);
+        out.extend_from_slice(&self.data);
+    }
+}
+
+/// The rest of a section `length` octets long after its first `header`;
+/// a length shorter than the header is an error rather than an underflow
+fn section_body(input: &[u8], length: u32, header: u32) -> IResult<&[u8], &[u8]> {
+    let Some(body) = length.checked_sub(header) else {
+        return Err(nom::Err::Error(nom::error::Error::new(
+            input,
+            nom::error::ErrorKind::LengthValue,
+        )));
+    };
+    take(body)(input)
+}
+
 fn parse_section2(input: &[u8]) -> IResult<&[u8], Section2> {
     let (input, length) = be_u24(input)?;
     let (input, _) = skip1(input)?;
-    let (input, data) = take(length - 4)(input)?;
+    let (input, data) = section_body(input, length, 4)?;
     Ok((
         input,
         Section2 {
@@ -227,3 +326,40 @@ fn parse_section2(input: &[u8]) -> IResult<&[u8], Section2> {
         },
     ))
 }
+
+fn put_u24(out: &mut Vec<u8>, value: usize) {
+    out.extend_from_slice(&(value as u32).to_be_bytes()[1..]);
+}
+
+/// Write Section 0 with a placeholder total length, patched by `finish_message`
+fn start_message(edition: u8) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(b"BUFR");
+    put_u24(&mut out, 0);
+    out.push(edition);
+    out
+}
+
+fn write_sections_3_to_5(out: &mut Vec<u8>, section3: (u16, u8, &[u8]), section4: &[u8]) {
+    let (number_of_subsets, flags, descriptors) = section3;
+    put_u24(out, descriptors.len() + 7);
+    out.push(0);
+    out.extend_from_slice(&number_of_subsets.to_be_bytes());
+    out.push(flags);
+    out.extend_from_slice(descriptors);
+
+    put_u24(out, section4.len() + 4);
+    out.push(0);
+    out.extend_from_slice(section4);
+
+    out.extend_from_slice(b"7777");
+}
+
+/// Write Sections 3 to 5 and patch the total length in Section 0
+fn finish_message(mut out: Vec<u8>, section3: (u16, u8, &[u8]), section4: &[u8]) -> Vec<u8> {
+    write_sections_3_to_5(&mut out, section3, section4);
+
+    let total = (out.len() as u32).to_be_bytes();
+    out[4..7].copy_from_slice(&total[1..]);
+    out
+}
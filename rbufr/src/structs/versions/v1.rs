@@ -0,0 +1,330 @@
+use crate::core::FXY;
+use chrono::NaiveDateTime;
+use nom::{
+    IResult,
+    bytes::complete::{tag, take},
+    number::complete::{be_u8, be_u16, be_u24},
+};
+
+use crate::errors::Result;
+use crate::structs::{tools::parse_descriptors, versions::MessageVersion};
+
+use super::{Section2, parse_section2, put_u24, section_body, skip1, write_sections_3_to_5};
+
+/// Editions 0 and 1: Section 0 is only "BUFR", so Section 1 starts at octet 5
+/// and its fourth octet doubles as the edition number. There is no total
+/// length; the message ends after Section 5.
+#[derive(Clone)]
+pub struct BUFRMessageV1 {
+    pub section1: Section1,
+    pub section2: Option<Section2>,
+    pub section3: Section3,
+    pub section4: Section4,
+}
+
+impl MessageVersion for BUFRMessageV1 {
+    fn parse(input: &[u8]) -> crate::errors::Result<Self> {
+        let (input, _) = tag("BUFR")(input)?;
+        let (input, section1) = parse_section1(input)?;
+        let (input, section2) = if section1.optional_section_present {
+            let (input, sec2) = parse_section2(input)?;
+            (input, Some(sec2))
+        } else {
+            (input, None)
+        };
+        let (input, section3) = parse_section3(input)?;
+        let (input, section4) = parse_section4(input)?;
+        let (_input, _section5) = parse_section5(input)?;
+
+        Ok(BUFRMessageV1 {
+            section1,
+            section2,
+            section3,
+            section4,
+        })
+    }
+
+    fn description(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "BUFR Message V{}:", self.section1.edition)?;
+        writeln!(f, "{}", self.section1)?;
+        Ok(())
+    }
+    fn table_info(&self) -> super::TableInfo {
+        super::TableInfo {
+            // Edition 1 has no master table number; only table 0 existed
+            master_table: 0,
+            master_table_version: self.section1.master_table_version,
+            local_table_version: self.section1.local_table_version,
+            center_id: self.section1.centre,
+            subcenter_id: 0,
+        }
+    }
+    fn subsets_count(&self) -> u16 {
+        self.section3.number_of_subsets
+    }
+
+    fn update_sequence_number(&self) -> u8 {
+        self.section1.update_sequence_number
+    }
+
+    fn obs_time(&self) -> Option<NaiveDateTime> {
+        let s = &self.section1;
+        super::section1_time(
+            super::year_of_century(s.year),
+            s.month,
+            s.day,
+            s.hour,
+            s.minute,
+            0,
+        )
+    }
+
+    fn is_compressed(&self) -> bool {
+        self.section3.is_compressed
+    }
+
+    fn ndescs(&self) -> usize {
+        self.section3.data.len() / 2
+    }
+
+    fn descriptors(&self) -> Result<Vec<FXY>> {
+        parse_descriptors(&self.section3.data)
+    }
+
+    fn data_block(&self) -> Result<&[u8]> {
+        Ok(&self.section4.data)
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut out = b"BUFR".to_vec();
+        self.section1.write_to(&mut out);
+        if let Some(section2) = &self.section2 {
+            section2.write_to(&mut out);
+        }
+        write_sections_3_to_5(
+            &mut out,
+            (
+                self.section3.number_of_subsets,
+                self.section3.flags(),
+                &self.section3.data,
+            ),
+            &self.section4.data,
+        );
+        out
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Section1 {
+    pub length: usize,
+    pub edition: u8,                    // octet 4
+    pub centre: u16,                    // octet 5-6
+    pub update_sequence_number: u8,     // octet 7
+    pub optional_section_present: bool, // octet 8 bit1 (MSB)
+    pub data_category: u8,              // octet 9
+    pub data_subcategory: u8,           // octet 10
+    pub master_table_version: u8,       // octet 11
+    pub local_table_version: u8,        // octet 12
+    pub year: u8,                       // octet 13 (year of century)
+    pub month: u8,                      // octet 14
+    pub day: u8,                        // octet 15
+    pub hour: u8,                       // octet 16
+    pub minute: u8,                     // octet 17
+    pub local_use: Vec<u8>,             // octet 18-
+}
+
+impl Section1 {
+    fn write_to(&self, out: &mut Vec<u8>) {
+        put_u24(out, self.local_use.len() + 17);
+        out.push(self.edition);
+        out.extend_from_slice(&self.centre.to_be_bytes());
+        out.extend_from_slice(&[
+            self.update_sequence_number,
+            if self.optional_section_present {
+                0x80
+            } else {
+                0
+            },
+            self.data_category,
+            self.data_subcategory,
+            self.master_table_version,
+            self.local_table_version,
+            self.year,
+            self.month,
+            self.day,
+            self.hour,
+            self.minute,
+        ]);
+        out.extend_from_slice(&self.local_use);
+    }
+}
+
+fn parse_section1(input: &[u8]) -> IResult<&[u8], Section1> {
+    let (input, length) = be_u24(input)?;
+    let length = length as usize;
+
+    const FIXED_LEN: usize = 17;
+    if length < FIXED_LEN {
+        return Err(nom::Err::Error(nom::error::Error::new(
+            input,
+            nom::error::ErrorKind::LengthValue,
+        )));
+    }
+
+    let (input, edition) = be_u8(input)?;
+    let (input, centre) = be_u16(input)?;
+    let (input, update_sequence_number) = be_u8(input)?;
+    let (input, optional_section_flag) = be_u8(input)?;
+    let optional_section_present = (optional_section_flag & 0x80) != 0;
+
+    let (input, data_category) = be_u8(input)?;
+    let (input, data_subcategory) = be_u8(input)?;
+    let (input, master_table_version) = be_u8(input)?;
+    let (input, local_table_version) = be_u8(input)?;
+    let (input, year) = be_u8(input)?;
+    let (input, month) = be_u8(input)?;
+    let (input, day) = be_u8(input)?;
+    let (input, hour) = be_u8(input)?;
+    let (input, minute) = be_u8(input)?;
+
+    let local_len = length - FIXED_LEN;
+    let (input, local_bytes) = take(local_len)(input)?;
+
+    Ok((
+        input,
+        Section1 {
+            length,
+            edition,
+            centre,
+            update_sequence_number,
+            optional_section_present,
+            data_category,
+            data_subcategory,
+            master_table_version,
+            local_table_version,
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            local_use: local_bytes.to_vec(),
+        },
+    ))
+}
+
+#[derive(Clone)]
+pub struct Section3 {
+    pub length: usize,
+    pub number_of_subsets: u16,
+    pub is_observation: bool,
+    pub is_compressed: bool,
+    pub data: Vec<u8>,
+}
+
+impl Section3 {
+    fn flags(&self) -> u8 {
+        let mut flags = 0;
+        if self.is_observation {
+            flags |= 0b1000_0000;
+        }
+        if self.is_compressed {
+            flags |= 0b0100_0000;
+        }
+        flags
+    }
+}
+
+fn parse_section3(input: &[u8]) -> IResult<&[u8], Section3> {
+    let (input, length) = be_u24(input)?;
+    let (input, _) = skip1(input)?;
+    let (input, number_of_subsets) = be_u16(input)?;
+    let (input, flags) = be_u8(input)?;
+    let is_observation = (flags & 0b1000_0000) != 0;
+    let is_compressed = (flags & 0b0100_0000) != 0;
+    let (input, data) = section_body(input, length, 7)?;
+    Ok((
+        input,
+        Section3 {
+            length: length as usize,
+            number_of_subsets,
+            is_observation,
+            is_compressed,
+            data: data.to_vec(),
+        },
+    ))
+}
+
+#[derive(Clone)]
+pub struct Section4 {
+    pub length: usize,
+    pub data: Vec<u8>,
+}
+
+fn parse_section4(input: &[u8]) -> IResult<&[u8], Section4> {
+    let (input, length) = be_u24(input)?;
+    let (input, _) = skip1(input)?;
+    let (input, data) = section_body(input, length, 4)?;
+    Ok((
+        input,
+        Section4 {
+            length: length as usize,
+            data: data.to_vec(),
+        },
+    ))
+}
+
+impl std::fmt::Display for Section1 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Section 1 (BUFR v{}):", self.edition)?;
+        writeln!(f, "  Length: {} bytes", self.length)?;
+        writeln!(f)?;
+        writeln!(f, "  Organization:")?;
+        writeln!(
+            f,
+            "    Centre:              {:<5} (0x{:04X})",
+            self.centre, self.centre
+        )?;
+        writeln!(
+            f,
+            "    Update Sequence:     {}",
+            self.update_sequence_number
+        )?;
+        writeln!(f)?;
+        writeln!(f, "  Data Classification:")?;
+        writeln!(f, "    Category:            {}", self.data_category)?;
+        writeln!(f, "    Sub-category:        {}", self.data_subcategory)?;
+        writeln!(f)?;
+        writeln!(f, "  Table Versions:")?;
+        writeln!(f, "    Master Table:        v{}", self.master_table_version)?;
+        writeln!(f, "    Local Table:         v{}", self.local_table_version)?;
+        writeln!(f)?;
+        writeln!(f, "  Observation Time:")?;
+        writeln!(
+            f,
+            "    DateTime:            {:04}-{:02}-{:02} {:02}:{:02}:00 UTC",
+            super::year_of_century(self.year),
+            self.month,
+            self.day,
+            self.hour,
+            self.minute
+        )?;
+        writeln!(f)?;
+        writeln!(f, "  Optional Data:")?;
+        write!(
+            f,
+            "    Section 2 Present:   {}",
+            if self.optional_section_present {
+                "Yes"
+            } else {
+                "No"
+            }
+        )
+    }
+}
+
+pub struct Section5;
+
+fn parse_section5(input: &[u8]) -> IResult<&[u8], Section5> {
+    let (input, _) = tag("7777")(input)?;
+    Ok((input, Section5 {}))
+}
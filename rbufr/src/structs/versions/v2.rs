@@ -1,4 +1,5 @@
 use crate::core::FXY;
+use chrono::NaiveDateTime;
 use nom::{
     IResult,
     bytes::complete::{tag, take},
@@ -8,7 +9,10 @@ use nom::{
 use crate::errors::Result;
 use crate::structs::{tools::parse_descriptors, versions::MessageVersion};
 
-use super::{Section2, parse_section0, parse_section2, skip1};
+use super::{
+    Section2, finish_message, parse_section0, parse_section2, put_u24, section_body, skip1,
+    start_message,
+};
 
 #[derive(Clone)]
 pub struct BUFRMessageV2 {
@@ -47,6 +51,7 @@ impl MessageVersion for BUFRMessageV2 {
     }
     fn table_info(&self) -> super::TableInfo {
         super::TableInfo {
+            master_table: self.section1.master_table,
             master_table_version: self.section1.master_table_version,
             local_table_version: self.section1.local_table_version,
             center_id: self.section1.centre as u16,
@@ -57,6 +62,26 @@ impl MessageVersion for BUFRMessageV2 {
         self.section3.number_of_subsets
     }
 
+    fn update_sequence_number(&self) -> u8 {
+        self.section1.update_sequence_number
+    }
+
+    fn obs_time(&self) -> Option<NaiveDateTime> {
+        let s = &self.section1;
+        super::section1_time(
+            super::year_of_century(s.year),
+            s.month,
+            s.day,
+            s.hour,
+            s.minute,
+            0,
+        )
+    }
+
+    fn is_compressed(&self) -> bool {
+        self.section3.is_compressed
+    }
+
     fn ndescs(&self) -> usize {
         self.section3.data.len() / 2
     }
@@ -68,6 +93,23 @@ impl MessageVersion for BUFRMessageV2 {
     fn data_block(&self) -> Result<&[u8]> {
         Ok(&self.section4.data)
     }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut out = start_message(2);
+        self.section1.write_to(&mut out);
+        if let Some(section2) = &self.section2 {
+            section2.write_to(&mut out);
+        }
+        finish_message(
+            out,
+            (
+                self.section3.number_of_subsets,
+                self.section3.flags(),
+                &self.section3.data,
+            ),
+            &self.section4.data,
+        )
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -87,7 +129,34 @@ pub struct Section1 {
     pub day: u8,                        // octet 15
     pub hour: u8,                       // octet 16
     pub minute: u8,                     // octet 17
-                                        // octet 18- local use: 你可以选择保存或直接跳过
+    pub local_use: Vec<u8>,             // octet 18-
+}
+
+impl Section1 {
+    fn write_to(&self, out: &mut Vec<u8>) {
+        put_u24(out, self.local_use.len() + 17);
+        out.extend_from_slice(&[
+            self.master_table,
+            self.subcentre,
+            self.centre,
+            self.update_sequence_number,
+            if self.optional_section_present {
+                0x80
+            } else {
+                0
+            },
+            self.data_category,
+            self.data_subcategory,
+            self.master_table_version,
+            self.local_table_version,
+            self.year,
+            self.month,
+            self.day,
+            self.hour,
+            self.minute,
+        ]);
+        out.extend_from_slice(&self.local_use);
+    }
 }
 
 fn parse_section1(input: &[u8]) -> IResult<&[u8], Section1> {
@@ -119,9 +188,8 @@ fn parse_section1(input: &[u8]) -> IResult<&[u8], Section1> {
     let (input, hour) = be_u8(input)?;
     let (input, minute) = be_u8(input)?;
 
-    // 剩余 local-use
     let local_len = length - FIXED_LEN;
-    let (input, _) = nom::bytes::complete::take(local_len)(input)?;
+    let (input, local_bytes) = take(local_len)(input)?;
 
     Ok((
         input,
@@ -141,6 +209,7 @@ fn parse_section1(input: &[u8]) -> IResult<&[u8], Section1> {
             day,
             hour,
             minute,
+            local_use: local_bytes.to_vec(),
         },
     ))
 }
@@ -154,6 +223,19 @@ pub struct Section3 {
     pub data: Vec<u8>,
 }
 
+impl Section3 {
+    fn flags(&self) -> u8 {
+        let mut flags = 0;
+        if self.is_observation {
+            flags |= 0b1000_0000;
+        }
+        if self.is_compressed {
+            flags |= 0b0100_0000;
+        }
+        flags
+    }
+}
+
 fn parse_section3(input: &[u8]) -> IResult<&[u8], Section3> {
     let (input, length) = be_u24(input)?;
     let (input, _) = skip1(input)?;
@@ -161,7 +243,7 @@ fn parse_section3(input: &[u8]) -> IResult<&[u8], Section3> {
     let (input, flags) = be_u8(input)?;
     let is_observation = (flags & 0b1000_0000) != 0;
     let is_compressed = (flags & 0b0100_0000) != 0;
-    let (input, data) = take(length - 7)(input)?;
+    let (input, data) = section_body(input, length, 7)?;
     Ok((
         input,
         Section3 {
@@ -183,7 +265,7 @@ pub struct Section4 {
 fn parse_section4(input: &[u8]) -> IResult<&[u8], Section4> {
     let (input, length) = be_u24(input)?;
     let (input, _) = skip1(input)?;
-    let (input, data) = take(length - 4)(input)?;
+    let (input, data) = section_body(input, length, 4)?;
     Ok((
         input,
         Section4 {
@@ -230,8 +312,12 @@ impl std::fmt::Display for Section1 {
         writeln!(f, "  Observation Time:")?;
         writeln!(
             f,
-            "    DateTime:            19{:02}-{:02}-{:02} {:02}:{:02}:00 UTC",
-            self.year, self.month, self.day, self.hour, self.minute
+            "    DateTime:            {:04}-{:02}-{:02} {:02}:{:02}:00 UTC",
+            super::year_of_century(self.year),
+            self.month,
+            self.day,
+            self.hour,
+            self.minute
         )?;
         writeln!(f)?;
         writeln!(f, "  Optional Data:")?;
@@ -1,6 +1,7 @@
 use crate::core::FXY;
 use crate::errors::Result;
 use crate::structs::{tools::parse_descriptors, versions::MessageVersion};
+use chrono::NaiveDateTime;
 use nom::{
     IResult,
     bytes::complete::{tag, take},
@@ -8,7 +9,10 @@ use nom::{
     number::complete::{be_u8, be_u16, be_u24},
 };
 
-use super::{Section0, Section2, parse_section0, parse_section2, skip1};
+use super::{
+    Section0, Section2, finish_message, parse_section0, parse_section2, put_u24, section_body,
+    skip1, start_message,
+};
 
 #[derive(Clone)]
 pub struct BUFRMessageV3 {
@@ -48,6 +52,7 @@ impl MessageVersion for BUFRMessageV3 {
 
     fn table_info(&self) -> super::TableInfo {
         super::TableInfo {
+            master_table: self.section1.master_table,
             master_table_version: self.section1.master_table_version,
             local_table_version: self.section1.local_table_version,
             center_id: self.section1.centre as u16,
@@ -59,6 +64,26 @@ impl MessageVersion for BUFRMessageV3 {
         self.section3.number_of_subsets
     }
 
+    fn update_sequence_number(&self) -> u8 {
+        self.section1.update_sequence_number
+    }
+
+    fn obs_time(&self) -> Option<NaiveDateTime> {
+        let s = &self.section1;
+        super::section1_time(
+            super::year_of_century(s.year),
+            s.month,
+            s.day,
+            s.hour,
+            s.minute,
+            0,
+        )
+    }
+
+    fn is_compressed(&self) -> bool {
+        self.section3.is_compressed
+    }
+
     fn ndescs(&self) -> usize {
         self.section3.data.len() / 2
     }
@@ -70,6 +95,23 @@ impl MessageVersion for BUFRMessageV3 {
     fn data_block(&self) -> Result<&[u8]> {
         Ok(&self.section4.data)
     }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut out = start_message(3);
+        self.section1.write_to(&mut out);
+        if let Some(section2) = &self.section2 {
+            section2.write_to(&mut out);
+        }
+        finish_message(
+            out,
+            (
+                self.section3.number_of_subsets,
+                self.section3.flags(),
+                &self.section3.data,
+            ),
+            &self.section4.data,
+        )
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -92,6 +134,34 @@ pub struct Section1 {
     pub local_use: Vec<u8>,             // octet 18-
 }
 
+impl Section1 {
+    fn write_to(&self, out: &mut Vec<u8>) {
+        put_u24(out, self.local_use.len() + 18);
+        out.extend_from_slice(&[
+            self.master_table,
+            self.subcentre,
+            self.centre,
+            self.update_sequence_number,
+            if self.optional_section_present {
+                0x80
+            } else {
+                0
+            },
+            self.data_category,
+            self.sub_category,
+            self.master_table_version,
+            self.local_table_version,
+            self.year,
+            self.month,
+            self.day,
+            self.hour,
+            self.minute,
+            0,
+        ]);
+        out.extend_from_slice(&self.local_use);
+    }
+}
+
 fn parse_section1(input: &[u8]) -> IResult<&[u8], Section1> {
     let (input, length_u24) = be_u24(input)?;
     let length = length_u24 as usize;
@@ -119,6 +189,7 @@ fn parse_section1(input: &[u8]) -> IResult<&[u8], Section1> {
     let (input, day) = be_u8(input)?;
     let (input, hour) = be_u8(input)?;
     let (input, minute) = be_u8(input)?;
+    let (input, _) = skip1(input)?;
 
     let local_len = length - FIXED_LEN;
     let (input, local_bytes) = take(local_len)(input)?;
@@ -184,7 +255,11 @@ impl std::fmt::Display for Section1 {
         writeln!(
             f,
             "    DateTime:            {:04}-{:02}-{:02} {:02}:{:02} UTC",
-            self.year, self.month, self.day, self.hour, self.minute
+            super::year_of_century(self.year),
+            self.month,
+            self.day,
+            self.hour,
+            self.minute
         )?;
         writeln!(f)?;
         writeln!(f, "  Optional Data:")?;
@@ -210,6 +285,19 @@ pub struct Section3 {
     pub data: Vec<u8>,
 }
 
+impl Section3 {
+    fn flags(&self) -> u8 {
+        let mut flags = 0;
+        if self.is_observation {
+            flags |= 0b1000_0000;
+        }
+        if self.is_compressed {
+            flags |= 0b0100_0000;
+        }
+        flags
+    }
+}
+
 fn parse_section3(input: &[u8]) -> IResult<&[u8], Section3> {
     let (input, length) = be_u24(input)?;
     let (input, _) = skip1(input)?;
@@ -217,7 +305,7 @@ fn parse_section3(input: &[u8]) -> IResult<&[u8], Section3> {
     let (input, flags) = be_u8(input)?;
     let is_observation = (flags & 0b1000_0000) != 0;
     let is_compressed = (flags & 0b0100_0000) != 0;
-    let (input, data) = take(length - 7)(input)?;
+    let (input, data) = section_body(input, length, 7)?;
     Ok((
         input,
         Section3 {
@@ -239,7 +327,7 @@ pub struct Section4 {
 fn parse_section4(input: &[u8]) -> IResult<&[u8], Section4> {
     let (input, length) = be_u24(input)?;
     let (input, _) = skip1(input)?;
-    let (input, data) = take(length - 4)(input)?;
+    let (input, data) = section_body(input, length, 4)?;
     Ok((
         input,
         Section4 {
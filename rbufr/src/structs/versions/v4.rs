@@ -1,6 +1,7 @@
 use crate::core::FXY;
 use crate::errors::Result;
 use crate::structs::{tools::parse_descriptors, versions::MessageVersion};
+use chrono::NaiveDateTime;
 use nom::{
     IResult,
     bytes::complete::{tag, take},
@@ -8,7 +9,10 @@ use nom::{
     number::complete::{be_u8, be_u16, be_u24},
 };
 
-use super::{Section0, Section2, parse_section0, parse_section2, skip1};
+use super::{
+    Section0, Section2, finish_message, parse_section0, parse_section2, put_u24, section_body,
+    skip1, start_message,
+};
 
 #[derive(Clone)]
 pub struct BUFRMessageV4 {
@@ -48,6 +52,7 @@ impl MessageVersion for BUFRMessageV4 {
 
     fn table_info(&self) -> super::TableInfo {
         super::TableInfo {
+            master_table: self.section1.master_table,
             master_table_version: self.section1.master_table_version,
             local_table_version: self.section1.local_table_version,
             center_id: self.section1.centre as u16,
@@ -59,6 +64,19 @@ impl MessageVersion for BUFRMessageV4 {
         self.section3.number_of_subsets
     }
 
+    fn update_sequence_number(&self) -> u8 {
+        self.section1.update_sequence_number
+    }
+
+    fn obs_time(&self) -> Option<NaiveDateTime> {
+        let s = &self.section1;
+        super::section1_time(s.year as i32, s.month, s.day, s.hour, s.minute, s.second)
+    }
+
+    fn is_compressed(&self) -> bool {
+        self.section3.is_compressed
+    }
+
     fn ndescs(&self) -> usize {
         self.section3.data.len() / 2
     }
@@ -70,6 +88,23 @@ impl MessageVersion for BUFRMessageV4 {
     fn data_block(&self) -> Result<&[u8]> {
         Ok(&self.section4.data)
     }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut out = start_message(4);
+        self.section1.write_to(&mut out);
+        if let Some(section2) = &self.section2 {
+            section2.write_to(&mut out);
+        }
+        finish_message(
+            out,
+            (
+                self.section3.number_of_subsets,
+                self.section3.flags(),
+                &self.section3.data,
+            ),
+            &self.section4.data,
+        )
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -94,6 +129,31 @@ pub struct Section1 {
     pub local_use: Vec<u8>,                 // octet 23-
 }
 
+impl Section1 {
+    fn write_to(&self, out: &mut Vec<u8>) {
+        put_u24(out, self.local_use.len() + 22);
+        out.push(self.master_table);
+        out.extend_from_slice(&self.centre.to_be_bytes());
+        out.extend_from_slice(&self.subcentre.to_be_bytes());
+        out.push(self.update_sequence_number);
+        out.push(if self.optional_section_present {
+            0x80
+        } else {
+            0
+        });
+        out.extend_from_slice(&[
+            self.data_category,
+            self.international_data_subcategory,
+            self.local_subcategory,
+            self.master_table_version,
+            self.local_table_version,
+        ]);
+        out.extend_from_slice(&self.year.to_be_bytes());
+        out.extend_from_slice(&[self.month, self.day, self.hour, self.minute, self.second]);
+        out.extend_from_slice(&self.local_use);
+    }
+}
+
 fn parse_section1(input: &[u8]) -> IResult<&[u8], Section1> {
     let (input, length_u24) = be_u24(input)?;
     let length = length_u24 as usize;
@@ -221,6 +281,19 @@ pub struct Section3 {
     pub data: Vec<u8>,
 }
 
+impl Section3 {
+    fn flags(&self) -> u8 {
+        let mut flags = 0;
+        if self.is_observation {
+            flags |= 0b1000_0000;
+        }
+        if self.is_compressed {
+            flags |= 0b0100_0000;
+        }
+        flags
+    }
+}
+
 fn parse_section3(input: &[u8]) -> IResult<&[u8], Section3> {
     let (input, length) = be_u24(input)?;
     let (input, _) = skip1(input)?;
@@ -228,7 +301,7 @@ fn parse_section3(input: &[u8]) -> IResult<&[u8], Section3> {
     let (input, flags) = be_u8(input)?;
     let is_observation = (flags & 0b1000_0000) != 0;
     let is_compressed = (flags & 0b0100_0000) != 0;
-    let (input, data) = take(length - 7)(input)?;
+    let (input, data) = section_body(input, length, 7)?;
     Ok((
         input,
         Section3 {
@@ -250,7 +323,7 @@ pub struct Section4 {
 fn parse_section4(input: &[u8]) -> IResult<&[u8], Section4> {
     let (input, length) = be_u24(input)?;
     let (input, _) = skip1(input)?;
-    let (input, data) = take(length - 4)(input)?;
+    let (input, data) = section_body(input, length, 4)?;
     Ok((
         input,
         Section4 {
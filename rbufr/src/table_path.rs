@@ -1,14 +1,30 @@
 use std::path::{Path, PathBuf};
-use std::sync::OnceLock;
+use std::sync::RwLock;
 
-static TABLES_BASE_PATH: OnceLock<PathBuf> = OnceLock::new();
+static TABLES_BASE_PATH: RwLock<Option<PathBuf>> = RwLock::new(None);
 
-pub fn set_tables_base_path<P: AsRef<Path>>(path: P) {
-    let _ = TABLES_BASE_PATH.set(path.as_ref().to_path_buf());
+/// Set the tables directory used from now on, replacing any set before
+///
+/// Returns whether the path changed, `false` when it was already `path`.
+pub fn set_tables_base_path<P: AsRef<Path>>(path: P) -> bool {
+    let path = path.as_ref();
+    let mut current = TABLES_BASE_PATH
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if current.as_deref() == Some(path) {
+        return false;
+    }
+    *current = Some(path.to_path_buf());
+    true
 }
 
+/// The tables directory: the one set last with `set_tables_base_path`,
+/// else `RBUFR_TABLES_PATH`, else `tables`
 pub fn get_tables_base_path() -> PathBuf {
-    if let Some(path) = TABLES_BASE_PATH.get() {
+    let current = TABLES_BASE_PATH
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(path) = current.as_ref() {
         return path.clone();
     }
 
@@ -45,16 +61,30 @@ pub fn get_table_path<P: AsRef<Path>>(relative_path: P) -> PathBuf {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::Mutex;
+
+    // the path is global; tests setting it must not interleave
+    static SERIAL: Mutex<()> = Mutex::new(());
 
     #[test]
     fn test_set_and_get_path() {
+        let _serial = SERIAL
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
         set_tables_base_path("/custom/tables/path");
         let path = get_tables_base_path();
         assert_eq!(path, PathBuf::from("/custom/tables/path"));
+
+        assert!(!set_tables_base_path("/custom/tables/path"));
+        assert!(set_tables_base_path("/other/tables"));
+        assert_eq!(get_tables_base_path(), PathBuf::from("/other/tables"));
     }
 
     #[test]
     fn test_get_table_path() {
+        let _serial = SERIAL
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
         set_tables_base_path("/base");
         let table_path = get_table_path("master/BUFR_TableB_0.bufrtbl");
         assert_eq!(
@@ -1,25 +1,132 @@
 pub use crate::core::prelude::{BUFRTableB, BUFRTableD, TableType};
-use crate::core::{prelude::*, tables::TableTypeTrait};
+use crate::core::{archive::ARCHIVE_EXTENSION, prelude::*, tables::TableTypeTrait};
 use crate::errors::Result;
-use std::path::PathBuf;
+use crate::table_path::{get_table_path, get_tables_base_path};
+use rustc_hash::FxHashMap;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, RwLock};
+
+/// A table compiled into the binary, under the name `TableTrait::file_name`
+/// gives it
+///
+/// Usually generated with `core::build_support`.
+#[derive(Debug, Clone, Copy)]
+pub struct EmbeddedTable {
+    pub name: &'static str,
+    pub bytes: &'static [u8],
+}
+
+static EMBEDDED: LazyLock<RwLock<FxHashMap<&'static str, &'static [u8]>>> =
+    LazyLock::new(|| RwLock::new(FxHashMap::default()));
+
+/// Make `tables` available to every loader
+///
+/// They are used when no file of the same name exists under the tables
+/// directory. Registering a name again replaces the earlier table.
+pub fn register_embedded(tables: &'static [EmbeddedTable]) {
+    let mut embedded = EMBEDDED.write().unwrap_or_else(|e| e.into_inner());
+    for table in tables {
+        embedded.insert(table.name, table.bytes);
+    }
+}
+
+fn embedded(name: &str) -> Option<&'static [u8]> {
+    EMBEDDED
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(name)
+        .copied()
+}
 
 pub trait TableTrait {
-    fn file_path(&self, table_type: TableType) -> PathBuf;
+    /// Path of the table file relative to a tables directory
+    fn file_name(&self, table_type: TableType) -> String;
+
+    /// The table file under the global tables directory
+    fn file_path(&self, table_type: TableType) -> PathBuf {
+        get_table_path(self.file_name(table_type))
+    }
+
+    /// The table file under `base`, or under the global tables directory
+    /// when `None`
+    fn file_path_in(&self, base: Option<&Path>, table_type: TableType) -> PathBuf {
+        match base {
+            Some(base) => base.join(self.file_name(table_type)),
+            None => self.file_path(table_type),
+        }
+    }
+
+    /// Path of an archive holding this table among other versions, relative
+    /// to a tables directory, with the version to select from it; `None`
+    /// for tables never archived
+    fn archive_name(&self, _table_type: TableType) -> Option<(String, u8)> {
+        None
+    }
+
+    /// Where the table is read from under `base`: its own file when there is
+    /// one, otherwise an archive holding it
+    fn source_in(&self, base: Option<&Path>, table_type: TableType) -> PathBuf {
+        let path = self.file_path_in(base, table_type);
+        match archive_in(self, base, table_type) {
+            Some((archive, _)) if !path.exists() && archive.exists() => archive,
+            _ => path,
+        }
+    }
 }
 
-#[derive(Debug, Clone, Copy)]
+fn archive_in<T: TableTrait + ?Sized>(
+    table: &T,
+    base: Option<&Path>,
+    table_type: TableType,
+) -> Option<(PathBuf, u8)> {
+    let (name, version) = table.archive_name(table_type)?;
+    let path = match base {
+        Some(base) => base.join(name),
+        None => get_table_path(name),
+    };
+    Some((path, version))
+}
+
+/// Master Table B/D of one version
+///
+/// Tables of master table 0 (meteorology) are named
+/// `BUFR_Table{B,D}_{version}`, those of other master tables, such as 10
+/// for oceanography, `BUFR_Table{B,D}_mt{number}_{version}`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct MasterTable {
+    number: u8,
     version: u8,
 }
 
 impl MasterTable {
+    /// A version of master table 0
     pub fn new(version: u8) -> Self {
-        MasterTable { version }
+        MasterTable::with_number(0, version)
+    }
+
+    /// A version of master table `number`
+    pub fn with_number(number: u8, version: u8) -> Self {
+        MasterTable { number, version }
+    }
+
+    pub fn number(&self) -> u8 {
+        self.number
+    }
+
+    pub fn version(&self) -> u8 {
+        self.version
     }
 }
-#[derive(Debug, Clone, Copy)]
+/// Local Table B/D of an originating centre
+///
+/// Files are named `BUFR_Table{B,D}_{centre}_{subcentre}_{version}` for a
+/// sub-centre's own tables and `BUFR_Table{B,D}_{centre}_{version}` for
+/// tables shared by the whole centre.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct LocalTable {
-    sub_center: Option<u16>,
+    centre: u16,
+    subcentre: Option<u16>,
     version: u8,
 }
 
@@ -44,76 +151,199 @@ impl BitmapTable {
 }
 
 impl LocalTable {
-    pub fn new(sub_center: Option<u16>, version: u8) -> Self {
+    /// `subcentre` of `None` names the tables shared by the whole centre
+    pub fn new(centre: u16, subcentre: Option<u16>, version: u8) -> Self {
         LocalTable {
-            sub_center,
+            centre,
+            subcentre,
             version,
         }
     }
+
+    /// Candidates for the local tables of a message, in the order they are
+    /// tried: the sub-centre's own tables, then the centre's
+    pub fn search_order(centre: u16, subcentre: u16, version: u8) -> [LocalTable; 2] {
+        [
+            LocalTable::new(centre, Some(subcentre), version),
+            LocalTable::new(centre, None, version),
+        ]
+    }
+
+    pub fn centre(&self) -> u16 {
+        self.centre
+    }
+
+    pub fn subcentre(&self) -> Option<u16> {
+        self.subcentre
+    }
+
+    pub fn version(&self) -> u8 {
+        self.version
+    }
 }
-impl TableTrait for MasterTable {
-    fn file_path(&self, table_type: TableType) -> PathBuf {
-        use crate::table_path::get_table_path;
 
-        match table_type {
-            TableType::B => {
-                let file_name = format!("master/BUFR_TableB_{}.bufrtbl", self.version);
-                get_table_path(file_name)
+/// Master and local tables found under a tables directory; see
+/// `available_tables`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AvailableTables {
+    pub master: Vec<MasterTable>,
+    pub local: Vec<LocalTable>,
+}
+
+/// The tables under `base`, or under the global tables directory when
+/// `None`, that have both a Table B and a Table D, in ascending order
+///
+/// Master tables are found in their own files and in archives. Embedded
+/// tables are not listed, and a missing directory has no tables.
+pub fn available_tables(base: Option<&Path>) -> AvailableTables {
+    let base = base.map_or_else(get_tables_base_path, Path::to_path_buf);
+    AvailableTables {
+        master: with_both_kinds(&base.join("master"), master_tables),
+        local: with_both_kinds(&base.join("local"), local_tables),
+    }
+}
+
+/// Tables of the files in `dir`, kept when a Table B and a Table D were
+/// both found; `parse` gets each file's path and its name after
+/// `BUFR_Table{B,D}`
+fn with_both_kinds<T: Ord + Copy>(dir: &Path, parse: fn(&Path, &str) -> Vec<T>) -> Vec<T> {
+    let mut table_b = BTreeSet::new();
+    let mut table_d = BTreeSet::new();
+    for entry in std::fs::read_dir(dir).into_iter().flatten().flatten() {
+        let file_name = entry.file_name();
+        let Some(name) = file_name.to_str() else {
+            continue;
+        };
+        let (found, rest) = if let Some(rest) = name.strip_prefix("BUFR_TableB") {
+            (&mut table_b, rest)
+        } else if let Some(rest) = name.strip_prefix("BUFR_TableD") {
+            (&mut table_d, rest)
+        } else {
+            continue;
+        };
+        found.extend(parse(&entry.path(), rest));
+    }
+    table_b.intersection(&table_d).copied().collect()
+}
+
+/// `_{version}.bufrtbl`, `_mt{number}_{version}.bufrtbl`, or an archive
+fn master_tables(path: &Path, name: &str) -> Vec<MasterTable> {
+    let (number, rest) = match name.strip_prefix("_mt") {
+        Some(rest) => {
+            let (number, rest) = rest.split_at(rest.find(['_', '.']).unwrap_or(rest.len()));
+            match number.parse() {
+                Ok(number) => (number, rest),
+                Err(_) => return vec![],
             }
-            TableType::D => {
-                let file_name = format!("master/BUFR_TableD_{}.bufrtbl", self.version);
-                get_table_path(file_name)
+        }
+        None => (0, name),
+    };
+
+    if rest.strip_prefix('.') == Some(ARCHIVE_EXTENSION) {
+        let versions = crate::core::archive::versions(path).unwrap_or_default();
+        return versions
+            .into_iter()
+            .map(|version| MasterTable::with_number(number, version))
+            .collect();
+    }
+    rest.strip_prefix('_')
+        .and_then(|rest| rest.strip_suffix(".bufrtbl"))
+        .and_then(|version| version.parse().ok())
+        .map(|version| MasterTable::with_number(number, version))
+        .into_iter()
+        .collect()
+}
+
+/// `_{centre}_{version}.bufrtbl` or `_{centre}_{subcentre}_{version}.bufrtbl`
+fn local_tables(_path: &Path, name: &str) -> Vec<LocalTable> {
+    let Some(fields) = name
+        .strip_prefix('_')
+        .and_then(|name| name.strip_suffix(".bufrtbl"))
+    else {
+        return vec![];
+    };
+    let fields: Vec<&str> = fields.split('_').collect();
+    let table = match fields[..] {
+        [centre, version] => centre
+            .parse()
+            .ok()
+            .zip(version.parse().ok())
+            .map(|(centre, version)| LocalTable::new(centre, None, version)),
+        [centre, subcentre, version] => {
+            match (centre.parse(), subcentre.parse(), version.parse()) {
+                (Ok(centre), Ok(subcentre), Ok(version)) => {
+                    Some(LocalTable::new(centre, Some(subcentre), version))
+                }
+                _ => None,
             }
+        }
+        _ => None,
+    };
+    table.into_iter().collect()
+}
+impl TableTrait for MasterTable {
+    fn file_name(&self, table_type: TableType) -> String {
+        let kind = match table_type {
+            TableType::B => "B",
+            TableType::D => "D",
             _ => {
                 unreachable!("Table type not supported for MasterTable")
             }
+        };
+        match self.number {
+            0 => format!("master/BUFR_Table{}_{}.bufrtbl", kind, self.version),
+            number => format!(
+                "master/BUFR_Table{}_mt{}_{}.bufrtbl",
+                kind, number, self.version
+            ),
         }
     }
+
+    /// `master/BUFR_Table{B,D}.bufrarc`, or `BUFR_Table{B,D}_mt{number}.bufrarc`
+    /// for master tables other than 0
+    fn archive_name(&self, table_type: TableType) -> Option<(String, u8)> {
+        let kind = match table_type {
+            TableType::B => "B",
+            TableType::D => "D",
+            _ => return None,
+        };
+        let name = match self.number {
+            0 => format!("master/BUFR_Table{}.{}", kind, ARCHIVE_EXTENSION),
+            number => format!(
+                "master/BUFR_Table{}_mt{}.{}",
+                kind, number, ARCHIVE_EXTENSION
+            ),
+        };
+        Some((name, self.version))
+    }
 }
 
 impl TableTrait for LocalTable {
-    fn file_path(&self, table_type: TableType) -> PathBuf {
-        use crate::table_path::get_table_path;
-
-        match table_type {
-            TableType::B => {
-                let sub_center_str = match self.sub_center {
-                    Some(sc) => format!("{}", sc),
-                    None => "0".to_string(),
-                };
-                let file_name = format!(
-                    "local/BUFR_TableB_{}_{}.bufrtbl",
-                    sub_center_str, self.version
-                );
-                get_table_path(file_name)
-            }
-            TableType::D => {
-                let sub_center_str = match self.sub_center {
-                    Some(sc) => format!("{}", sc),
-                    None => "0".to_string(),
-                };
-                let file_name = format!(
-                    "local/BUFR_TableD_{}_{}.bufrtbl",
-                    sub_center_str, self.version
-                );
-                get_table_path(file_name)
-            }
+    fn file_name(&self, table_type: TableType) -> String {
+        let kind = match table_type {
+            TableType::B => "B",
+            TableType::D => "D",
             _ => {
                 unreachable!("Table type not supported for LocalTable")
             }
+        };
+        match self.subcentre {
+            Some(subcentre) => format!(
+                "local/BUFR_Table{}_{}_{}_{}.bufrtbl",
+                kind, self.centre, subcentre, self.version
+            ),
+            None => format!(
+                "local/BUFR_Table{}_{}_{}.bufrtbl",
+                kind, self.centre, self.version
+            ),
         }
     }
 }
 
 impl TableTrait for BitmapTable {
-    fn file_path(&self, table_type: TableType) -> PathBuf {
-        use crate::table_path::get_table_path;
-
+    fn file_name(&self, table_type: TableType) -> String {
         match table_type {
-            TableType::BitMap => {
-                let file_name = format!("opera/BUFR_Opera_Bitmap_{}.bufrtbl", self.center);
-                get_table_path(file_name)
-            }
+            TableType::BitMap => format!("opera/BUFR_Opera_Bitmap_{}.bufrtbl", self.center),
             _ => {
                 unreachable!("Table type not supported for BitmapTable")
             }
@@ -128,8 +358,32 @@ impl TableLoader {
     where
         T: TableTypeTrait,
     {
-        let path = table_type.file_path(T::TABLE_TYPE);
+        self.load_table_in(None, table_type)
+    }
+
+    /// Load from the tables directory `base` instead of the global one
+    pub fn load_table_in<T>(
+        &self,
+        base: Option<&Path>,
+        table_type: impl TableTrait,
+    ) -> Result<BUFRTableMPH<T>>
+    where
+        T: TableTypeTrait,
+    {
+        let path = table_type.file_path_in(base, T::TABLE_TYPE);
         // println!("Loading table from {:?}", path);
+        if !path.exists() {
+            match archive_in(&table_type, base, T::TABLE_TYPE) {
+                Some((archive, version)) if archive.exists() => {
+                    return BUFRTableMPH::<T>::load_from_archive(archive, version)
+                        .map_err(|e| e.into());
+                }
+                _ => {}
+            }
+            if let Some(bytes) = embedded(&table_type.file_name(T::TABLE_TYPE)) {
+                return BUFRTableMPH::<T>::load_from_bytes(bytes).map_err(|e| e.into());
+            }
+        }
         BUFRTableMPH::<T>::load_from_disk(path).map_err(|e| e.into())
     }
 }
@@ -0,0 +1,95 @@
+//! Registry of well-known WMO template sequences.
+//!
+//! Detection only looks at Section 3 and the sequence tables, so messages can
+//! be routed by template without decoding their data.
+
+use crate::core::{FXY, prelude::BUFRTableD};
+
+/// Sequences deeper than this are not expanded while searching
+const MAX_DEPTH: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Template {
+    pub fxy: FXY,
+    pub name: &'static str,
+}
+
+const fn template(x: i32, y: i32, name: &'static str) -> Template {
+    Template {
+        fxy: FXY::new(3, x, y),
+        name,
+    }
+}
+
+pub const TEMPLATES: &[Template] = &[
+    template(
+        7,
+        79,
+        "Synoptic reports from fixed land stations (TM 307079)",
+    ),
+    template(
+        7,
+        80,
+        "Synoptic reports from fixed land stations (TM 307080)",
+    ),
+    template(
+        7,
+        96,
+        "Synoptic reports from fixed land stations with WIGOS id (TM 307096)",
+    ),
+    template(8, 9, "Synoptic reports from sea stations (TM 308009)"),
+    template(9, 50, "PILOT upper-wind reports (TM 309050)"),
+    template(9, 52, "TEMP upper-air soundings (TM 309052)"),
+    template(9, 56, "TEMP descending soundings (TM 309056)"),
+    template(9, 57, "TEMP upper-air soundings with WIGOS id (TM 309057)"),
+    template(10, 8, "ATOVS radiances (TM 310008)"),
+    template(11, 10, "AMDAR aircraft reports (TM 311010)"),
+    template(15, 9, "Drifting buoy reports (TM 315009)"),
+];
+
+/// The registered template with sequence descriptor `fxy`
+pub fn find(fxy: &FXY) -> Option<&'static Template> {
+    TEMPLATES.iter().find(|t| t.fxy == *fxy)
+}
+
+/// The first registered template found in `descriptors`
+///
+/// A template matches when its sequence descriptor appears in the list, or
+/// when the list spells out the sequence's members in place. Sequences in the
+/// list are expanded through `table_d` so templates nested in local sequences
+/// are found too.
+pub fn detect(descriptors: &[FXY], table_d: &BUFRTableD) -> Option<&'static Template> {
+    detect_at(descriptors, table_d, 0)
+}
+
+fn detect_at(descriptors: &[FXY], table_d: &BUFRTableD, depth: usize) -> Option<&'static Template> {
+    if let Some(found) = descriptors.iter().find_map(find) {
+        return Some(found);
+    }
+
+    if let Some(found) = TEMPLATES.iter().find(|t| {
+        members(&t.fxy, table_d)
+            .is_some_and(|m| !m.is_empty() && descriptors.windows(m.len()).any(|w| w == m))
+    }) {
+        return Some(found);
+    }
+
+    if depth >= MAX_DEPTH {
+        return None;
+    }
+
+    descriptors
+        .iter()
+        .filter(|d| d.f == 3)
+        .filter_map(|d| members(d, table_d))
+        .find_map(|m| detect_at(&m, table_d, depth + 1))
+}
+
+fn members(fxy: &FXY, table_d: &BUFRTableD) -> Option<Vec<FXY>> {
+    table_d.lookup(fxy).map(|e| {
+        e.fxy_chain
+            .iter()
+            .map(|c| FXY::new(c.f.to_native(), c.x.to_native(), c.y.to_native()))
+            .collect()
+    })
+}
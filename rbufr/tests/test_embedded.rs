@@ -0,0 +1,23 @@
+//! Registering embedded tables changes what every loader in the process
+//! finds, so these run apart from the other tests
+use librbufr::core::{FXY, prelude::BUFRTableB};
+use librbufr::tables::{EmbeddedTable, MasterTable, TableLoader, register_embedded};
+
+#[test]
+fn test_embedded_tables() {
+    let empty = std::env::temp_dir().join(format!("rbufr_test_embed_empty_{}", std::process::id()));
+    let load = || -> librbufr::errors::Result<BUFRTableB> {
+        TableLoader.load_table_in(Some(&empty), MasterTable::new(16))
+    };
+    assert!(load().is_err());
+
+    // Registered tables stand in for files missing from the tables directory
+    static TABLES: &[EmbeddedTable] = &[EmbeddedTable {
+        name: "master/BUFR_TableB_16.bufrtbl",
+        bytes: include_bytes!("../tables/master/BUFR_TableB_16.bufrtbl"),
+    }];
+    register_embedded(TABLES);
+
+    let table = load().unwrap();
+    assert!(table.lookup(&FXY::new(0, 1, 1)).is_some());
+}
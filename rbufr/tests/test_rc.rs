@@ -5,9 +5,3396 @@ fn test_dec() {
     let file = std::fs::read("example/datas/36_2025-12-17T09_00_00.bufr").unwrap();
     let file = parse(&file).unwrap();
     for msg in file.messages() {
-        let mut decoder = Decoder::from_message(msg).unwrap();
+        let decoder = Decoder::from_message(msg).unwrap();
         let record = decoder.decode(msg).unwrap();
 
         println!("{}", record);
     }
 }
+
+#[test]
+fn test_write_split_merge() {
+    use librbufr::block::BUFRFile;
+
+    let bytes = std::fs::read("example/datas/36_2025-12-17T09_00_00.bufr").unwrap();
+    let file = parse(&bytes).unwrap();
+
+    let dir = std::env::temp_dir().join(format!(
+        "rbufr_test_write_split_merge_{}",
+        std::process::id()
+    ));
+    let written = file.split_by(&dir, |msg| msg.version()).unwrap();
+    assert_eq!(written.len(), 1);
+
+    let split_bytes = std::fs::read(&written[0]).unwrap();
+    let reread = parse(&split_bytes).unwrap();
+    assert_eq!(reread.message_count(), file.message_count());
+
+    let merged = BUFRFile::merge([file, reread]);
+    let out = dir.join("merged.bufr");
+    merged.write_to(&out).unwrap();
+    assert_eq!(std::fs::read(&out).unwrap().len(), split_bytes.len() * 2);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_reserialize() {
+    let bytes = std::fs::read("example/datas/36_2025-12-17T09_00_00.bufr").unwrap();
+    let file = parse(&bytes).unwrap();
+    for msg in file.messages() {
+        assert_eq!(msg.to_bytes(), msg.as_bytes());
+    }
+}
+
+/// Hand-build an uncompressed edition 4 message from `(f, x, y)` descriptors
+/// and `(value, width)` pairs for the data section
+fn synthetic_message(
+    subsets: u16,
+    descriptors: &[(u8, u8, u8)],
+    values: &[(u64, usize)],
+) -> Vec<u8> {
+    local_synthetic_message(98, 0, subsets, descriptors, values)
+}
+
+/// Like `synthetic_message`, with the compressed flag of Section 3 set; the
+/// values are laid out as compressed data
+fn compressed_message(
+    subsets: u16,
+    descriptors: &[(u8, u8, u8)],
+    values: &[(u64, usize)],
+) -> Vec<u8> {
+    let mut message = synthetic_message(subsets, descriptors, values);
+    message[36] |= 0x40;
+    message
+}
+
+/// Three compressed subsets of `0-01-001 0-01-015 1-01-000 0-31-001
+/// 0-12-101`, with two temperatures each
+fn three_compressed_subsets() -> Vec<u8> {
+    let mut values = vec![
+        // 0-01-001: 10, 11, missing
+        (10, 7),
+        (2, 6),
+        (0, 2),
+        (1, 2),
+        (3, 2),
+        // 0-01-015: 20 zero octets, then 4 octets each
+        (0, 64),
+        (0, 64),
+        (0, 32),
+        (4, 6),
+    ];
+    for name in [b"ABCD", b"EFGH", b"IJKL"] {
+        values.push((u32::from_be_bytes(*name) as u64, 32));
+    }
+    values.extend([
+        // 0-31-001: 2 in every subset
+        (2, 8),
+        (0, 6),
+        // 0-12-101: 273.15 everywhere, then 280.00, 280.05 and missing
+        (27315, 16),
+        (0, 6),
+        (28000, 16),
+        (4, 6),
+        (0, 4),
+        (5, 4),
+        (15, 4),
+    ]);
+    compressed_message(
+        3,
+        &[(0, 1, 1), (0, 1, 15), (1, 1, 0), (0, 31, 1), (0, 12, 101)],
+        &values,
+    )
+}
+
+/// Like `synthetic_message`, from `centre` with local tables `local_version`
+fn local_synthetic_message(
+    centre: u8,
+    local_version: u8,
+    subsets: u16,
+    descriptors: &[(u8, u8, u8)],
+    values: &[(u64, usize)],
+) -> Vec<u8> {
+    let mut bits: Vec<bool> = Vec::new();
+    for &(value, width) in values {
+        for i in (0..width).rev() {
+            bits.push((value >> i) & 1 == 1);
+        }
+    }
+    let data: Vec<u8> = bits
+        .chunks(8)
+        .map(|c| {
+            c.iter()
+                .enumerate()
+                .fold(0u8, |b, (i, &bit)| b | ((bit as u8) << (7 - i)))
+        })
+        .collect();
+
+    let mut section1 = vec![
+        0,
+        0,
+        22,
+        0,
+        0,
+        centre,
+        0,
+        0,
+        0,
+        0,
+        0,
+        0,
+        0,
+        35,
+        local_version,
+    ];
+    section1.extend_from_slice(&2025u16.to_be_bytes());
+    section1.extend_from_slice(&[12, 17, 9, 0, 0]);
+
+    let section3_len = (7 + descriptors.len() * 2) as u32;
+    let mut section3 = section3_len.to_be_bytes()[1..].to_vec();
+    section3.push(0);
+    section3.extend_from_slice(&subsets.to_be_bytes());
+    section3.push(0x80);
+    for &(f, x, y) in descriptors {
+        section3.extend_from_slice(&[(f << 6) | x, y]);
+    }
+
+    let section4_len = (data.len() + 4) as u32;
+    let mut section4 = section4_len.to_be_bytes()[1..].to_vec();
+    section4.push(0);
+    section4.extend_from_slice(&data);
+
+    let total = (8 + section1.len() + section3.len() + section4.len() + 4) as u32;
+    let mut message = b"BUFR".to_vec();
+    message.extend_from_slice(&total.to_be_bytes()[1..]);
+    message.push(4);
+    message.extend(section1);
+    message.extend(section3);
+    message.extend(section4);
+    message.extend_from_slice(b"7777");
+    message
+}
+
+/// Two subsets of `0-01-001 0-01-002 1-01-016 0-12-101`
+fn two_subset_message() -> Vec<u8> {
+    let mut values = vec![];
+    for (block, station, temperature) in [(10, 100, 27315), (11, 200, 28315)] {
+        values.push((block, 7));
+        values.push((station, 10));
+        for i in 0..16 {
+            values.push((temperature + i, 16));
+        }
+    }
+    synthetic_message(
+        2,
+        &[(0, 1, 1), (0, 1, 2), (1, 1, 16), (0, 12, 101)],
+        &values,
+    )
+}
+
+#[test]
+fn test_decode_subset() {
+    use librbufr::decoder::BUFRData;
+
+    let file = parse(&two_subset_message()).unwrap();
+    let msg = file.message_at(0).unwrap();
+
+    let decoder = Decoder::from_message(msg).unwrap();
+    let second = decoder.decode_subset(msg, 1).unwrap();
+    let records: Vec<_> = second.records().collect();
+    assert_eq!(records.len(), 3);
+    let single = |i: usize| match &records[i].values {
+        BUFRData::Single(value) => value.as_f64(),
+        _ => None,
+    };
+    assert_eq!(single(0), Some(11.0));
+    assert_eq!(single(1), Some(200.0));
+    match &records[2].values {
+        BUFRData::Array { values, missing } => {
+            assert_eq!(values.len(), 16);
+            assert!((values[0] - 283.15).abs() < 1e-9);
+            assert!(!missing.any());
+        }
+        _ => panic!("expected a compiled array"),
+    }
+
+    assert!(decoder.decode_subset(msg, 2).is_err());
+}
+
+#[test]
+fn test_compiled_nested_replication() {
+    use librbufr::decoder::BUFRData;
+
+    // 1-03-020 over `0-01-001 1-01-002 0-12-101`
+    let mut values = vec![];
+    for i in 0..20 {
+        values.push((i, 7));
+        values.push((27315 + i, 16));
+        values.push((28315 + i, 16));
+    }
+    let bytes = synthetic_message(
+        1,
+        &[(1, 3, 20), (0, 1, 1), (1, 1, 2), (0, 12, 101)],
+        &values,
+    );
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+
+    let decoder = Decoder::from_message(msg).unwrap();
+    let parsed = decoder.decode(msg).unwrap();
+    let arrays: Vec<Vec<f64>> = parsed
+        .records()
+        .map(|r| match r.values {
+            BUFRData::Array { values, .. } => values.into_owned(),
+            _ => panic!("expected the compiled fast path"),
+        })
+        .collect();
+
+    assert_eq!(arrays.len(), 3);
+    assert!(arrays.iter().all(|a| a.len() == 20));
+    assert_eq!(arrays[0][19], 19.0);
+    assert!((arrays[1][1] - 273.16).abs() < 1e-9);
+    assert!((arrays[2][1] - 283.16).abs() < 1e-9);
+}
+
+#[test]
+fn test_compiled_string_fields() {
+    use librbufr::decoder::{BUFRData, Value};
+
+    // 1-03-016 over `0-01-001 0-01-015 0-12-101`, leaving the names unaligned
+    let mut values = vec![];
+    for i in 0..16 {
+        values.push((i, 7));
+        for b in format!("{:<20}", format!("STATION {}", i)).bytes() {
+            values.push((b as u64, 8));
+        }
+        values.push((27315 + i, 16));
+    }
+    let bytes = synthetic_message(
+        1,
+        &[(1, 3, 16), (0, 1, 1), (0, 1, 15), (0, 12, 101)],
+        &values,
+    );
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+
+    let decoder = Decoder::from_message(msg).unwrap();
+    let parsed = decoder.decode(msg).unwrap();
+    let records: Vec<_> = parsed.records().collect();
+    assert_eq!(records.len(), 3);
+
+    match &records[1].values {
+        BUFRData::Repeat(names) => {
+            assert_eq!(names.len(), 16);
+            assert!(matches!(&names[3], Value::String(s) if s.trim_end() == "STATION 3"));
+        }
+        _ => panic!("expected compiled string field"),
+    }
+    match &records[2].values {
+        BUFRData::Array { values, .. } => assert!((values[15] - 273.30).abs() < 1e-9),
+        _ => panic!("expected compiled numeric field"),
+    }
+}
+
+#[test]
+fn test_layout_cache_and_operator_seed() {
+    use librbufr::decoder::BUFRData;
+
+    // 2-01-129 widens 0-12-101 to 17 bits before the replication is compiled
+    let mut values: Vec<(u64, usize)> = (0..16).map(|i| (27315 + i, 17)).collect();
+    values.push((5, 7));
+    let bytes = synthetic_message(
+        1,
+        &[(2, 1, 129), (1, 1, 16), (0, 12, 101), (2, 1, 0), (0, 1, 1)],
+        &values,
+    );
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+
+    let decoder = Decoder::from_message(msg).unwrap();
+    for _ in 0..2 {
+        let parsed = decoder.decode(msg).unwrap();
+        let records: Vec<_> = parsed.records().collect();
+        assert_eq!(records.len(), 2);
+        match &records[0].values {
+            BUFRData::Array { values, .. } => assert!((values[15] - 273.30).abs() < 1e-9),
+            _ => panic!("expected compiled array"),
+        }
+        match &records[1].values {
+            BUFRData::Single(block) => assert_eq!(block.as_f64(), Some(5.0)),
+            _ => panic!("expected single value"),
+        }
+    }
+}
+
+#[test]
+fn test_unpack_same_width_matches_bitwise_reads() {
+    use librbufr::decoder::BitInput;
+
+    let bytes: Vec<u8> = (0..512u32)
+        .map(|i| (i.wrapping_mul(2654435761) >> 13) as u8)
+        .collect();
+    for nbits in [1, 3, 7, 8, 12, 13, 16, 24, 31, 32, 56] {
+        for offset in [0, 3] {
+            let count = (bytes.len() * 8 - offset) / nbits - 1;
+
+            let mut expected = BitInput::new(&bytes);
+            expected.get_arbitary_bits(offset).unwrap();
+            let expected: Vec<u64> = (0..count)
+                .map(|_| expected.get_arbitary_bits(nbits).unwrap())
+                .collect();
+
+            let mut input = BitInput::new(&bytes);
+            input.get_arbitary_bits(offset).unwrap();
+            let mut got = Vec::with_capacity(count);
+            input
+                .unpack_same_width(nbits, count, |v| got.push(v))
+                .unwrap();
+
+            assert_eq!(got, expected, "nbits {} offset {}", nbits, offset);
+        }
+    }
+}
+
+#[test]
+fn test_bit_input_checkpoint() {
+    use librbufr::decoder::BitInput;
+
+    let bytes = [0b1010_1100, 0b0101_0011, 0xFF];
+    let mut input = BitInput::new(&bytes);
+    input.get_arbitary_bits(3).unwrap();
+    assert_eq!(input.position(), 3);
+
+    let checkpoint = input.checkpoint();
+    let first = input.get_arbitary_bits(10).unwrap();
+    assert_eq!(input.position(), 13);
+    input.restore(checkpoint);
+    assert_eq!(input.position(), 3);
+    assert_eq!(input.get_arbitary_bits(10).unwrap(), first);
+
+    input.seek_to(0).unwrap();
+    assert_eq!(input.get_arbitary_bits(4).unwrap(), 0b1010);
+    input.seek_to(20).unwrap();
+    assert_eq!(input.get_arbitary_bits(4).unwrap(), 0xF);
+    input.seek_to(24).unwrap();
+    assert!(input.get_arbitary_bits(1).is_err());
+    assert!(input.seek_to(25).is_err());
+    assert_eq!(input.position(), 24);
+}
+
+#[test]
+fn test_f32_arrays() {
+    use librbufr::decoder::{BUFRData, MISS_VAL_F32};
+
+    let mut values: Vec<(u64, usize)> = (0..15).map(|i| (27315 + i, 16)).collect();
+    values.push((0xFFFF, 16));
+    let bytes = synthetic_message(1, &[(1, 1, 16), (0, 12, 101)], &values);
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+
+    let decoder = Decoder::from_message(msg).unwrap().with_f32_arrays(true);
+    let parsed = decoder.decode(msg).unwrap();
+    match &parsed.record(0).unwrap().values {
+        BUFRData::ArrayF32 { values, missing } => {
+            assert_eq!(values.len(), 16);
+            assert!((values[1] - 273.16).abs() < 1e-4);
+            assert_eq!(values[15], MISS_VAL_F32);
+            assert_eq!(missing.count(), 1);
+            assert!(missing.get(15));
+        }
+        _ => panic!("expected an f32 array"),
+    }
+}
+
+#[test]
+fn test_decode_values() {
+    use librbufr::core::FXY;
+    use librbufr::decoder::Value;
+
+    let file = parse(&two_subset_message()).unwrap();
+    let msg = file.message_at(0).unwrap();
+
+    let decoder = Decoder::from_message(msg).unwrap();
+    let values = decoder.decode_values(msg).unwrap();
+    assert_eq!(values.len(), 18);
+    assert_eq!(values[0].0, FXY::new(0, 1, 1));
+    assert!(matches!(values[0].1, Value::Number(n) if n == 10.0));
+    assert!(
+        values[2..]
+            .iter()
+            .all(|(fxy, _)| *fxy == FXY::new(0, 12, 101))
+    );
+}
+
+#[test]
+fn test_query_records() {
+    use librbufr::core::FXY;
+    use regex::Regex;
+
+    let file = parse(&two_subset_message()).unwrap();
+    let msg = file.message_at(0).unwrap();
+
+    let decoder = Decoder::from_message(msg).unwrap();
+    let parsed = decoder.decode(msg).unwrap();
+
+    let temps = parsed.get_by_fxy(FXY::new(0, 12, 101));
+    assert_eq!(temps.len(), 1);
+    let name = temps[0].name.as_deref().unwrap();
+
+    assert_eq!(parsed.get(name).unwrap().fxy, FXY::new(0, 12, 101));
+    assert_eq!(parsed.get_all(name).len(), 1);
+    assert!(parsed.get("no such element").is_none());
+
+    let wmo = parsed.select(&Regex::new("(?i)^wmo").unwrap());
+    assert_eq!(wmo.len(), 2);
+}
+
+#[test]
+fn test_record_encoding() {
+    use librbufr::core::FXY;
+
+    let file = parse(&two_subset_message()).unwrap();
+    let msg = file.message_at(0).unwrap();
+
+    let decoder = Decoder::from_message(msg).unwrap();
+    let parsed = decoder.decode(msg).unwrap();
+
+    let station = parsed.get_by_fxy(FXY::new(0, 1, 2)).remove(0);
+    assert_eq!(station.width_bits, Some(10));
+    assert_eq!(station.scale, Some(0));
+    assert_eq!(station.raw, Some(100));
+
+    let temps = parsed.get_by_fxy(FXY::new(0, 12, 101)).remove(0);
+    assert_eq!(temps.width_bits, Some(16));
+    assert_eq!(temps.scale, Some(2));
+    assert_eq!(temps.reference, Some(0));
+    assert_eq!(temps.raw, None);
+}
+
+#[test]
+fn test_parsed_iteration() {
+    use librbufr::core::FXY;
+
+    let file = parse(&two_subset_message()).unwrap();
+    let msg = file.message_at(0).unwrap();
+
+    let decoder = Decoder::from_message(msg).unwrap();
+    let parsed = decoder.decode(msg).unwrap();
+
+    assert_eq!(parsed.iter().len(), 3);
+    assert_eq!(parsed.iter().singles().count(), 2);
+    assert_eq!(parsed.iter().arrays().count(), 1);
+    assert_eq!(parsed.record(2).unwrap().fxy, FXY::new(0, 12, 101));
+    assert!(parsed.record(3).is_none());
+
+    let name = parsed.record(0).unwrap().name.unwrap().to_string();
+    assert_eq!(parsed.get(&name).unwrap().fxy, FXY::new(0, 1, 1));
+
+    let mut fxys = vec![];
+    for record in &parsed {
+        fxys.push(record.fxy);
+    }
+    let owned: Vec<_> = parsed.clone().into_iter().map(|r| r.fxy).collect();
+    assert_eq!(fxys, owned);
+}
+
+#[test]
+fn test_datetime() {
+    use chrono::NaiveDate;
+
+    // 0-04-001 … 0-04-005, no seconds
+    let mut values = vec![];
+    for (day, hour) in [(17, 9), (18, 23)] {
+        values.extend([(2025, 12), (12, 4), (day, 6), (hour, 5), (30, 6)]);
+    }
+    let bytes = synthetic_message(
+        2,
+        &[(0, 4, 1), (0, 4, 2), (0, 4, 3), (0, 4, 4), (0, 4, 5)],
+        &values,
+    );
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+
+    let decoder = Decoder::from_message(msg).unwrap();
+    let second = decoder.decode_subset(msg, 1).unwrap();
+    assert_eq!(
+        second.datetime(),
+        NaiveDate::from_ymd_opt(2025, 12, 18)
+            .unwrap()
+            .and_hms_opt(23, 30, 0)
+    );
+    assert_eq!(second.datetimes().len(), 1);
+}
+
+#[test]
+fn test_station_id() {
+    // 0-01-001 0-01-002 0-01-015 0-01-011
+    let mut values: Vec<(u64, usize)> = vec![(7, 7), (149, 10)];
+    values.extend(
+        format!("{:<20}", "PARIS-MONTSOURIS")
+            .bytes()
+            .map(|b| (b as u64, 8)),
+    );
+    values.extend(format!("{:<9}", "FNPH").bytes().map(|b| (b as u64, 8)));
+    let bytes = synthetic_message(1, &[(0, 1, 1), (0, 1, 2), (0, 1, 15), (0, 1, 11)], &values);
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+
+    let decoder = Decoder::from_message(msg).unwrap();
+    let station = decoder.decode(msg).unwrap().station();
+    assert_eq!(station.wmo_index(), Some(7149));
+    assert_eq!(station.name.as_deref(), Some("PARIS-MONTSOURIS"));
+    assert_eq!(station.ship_or_buoy.as_deref(), Some("FNPH"));
+    assert!(station.wigos.is_none());
+}
+
+/// `1-04-000 0-31-001` over `0-07-004 0-10-009 0-12-101 0-12-103`
+fn sounding_message(levels: u64) -> Vec<u8> {
+    let mut values = vec![(levels, 8)];
+    for i in 0..levels {
+        values.extend([
+            (10000 - i * 100, 14),
+            (2000 + i * 500, 17),
+            (28815 - i * 50, 16),
+            (27815 - i * 50, 16),
+        ]);
+    }
+    synthetic_message(
+        1,
+        &[
+            (1, 4, 0),
+            (0, 31, 1),
+            (0, 7, 4),
+            (0, 10, 9),
+            (0, 12, 101),
+            (0, 12, 103),
+        ],
+        &values,
+    )
+}
+
+#[test]
+fn test_profile() {
+    // 3 levels go through the interpreter, 20 through a compiled layout
+    for levels in [3, 20] {
+        let file = parse(&sounding_message(levels)).unwrap();
+        let msg = file.message_at(0).unwrap();
+
+        let decoder = Decoder::from_message(msg).unwrap();
+        let profile = decoder.decode(msg).unwrap().profile();
+        assert_eq!(profile.len(), levels as usize);
+
+        let top = &profile[2];
+        assert_eq!(top.pressure, Some(98000.0));
+        assert_eq!(top.height, Some(2000.0));
+        assert!((top.temperature.unwrap() - 287.15).abs() < 1e-9);
+        assert!((top.dew_point.unwrap() - 277.15).abs() < 1e-9);
+        assert_eq!(top.wind_speed, None);
+    }
+}
+
+#[test]
+fn test_profile_edition3_significance() {
+    // 0-07-004 then 0-08-001 (extended significance of edition 3 soundings)
+    let bytes = synthetic_message(1, &[(0, 7, 4), (0, 8, 1)], &[(8500, 14), (32, 7)]);
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+
+    let decoder = Decoder::from_message(msg).unwrap();
+    let profile = decoder.decode(msg).unwrap().profile();
+    assert_eq!(profile.len(), 1);
+    assert_eq!(profile[0].pressure, Some(85000.0));
+    assert_eq!(profile[0].significance, Some(32.0));
+}
+
+#[test]
+fn test_typed_synop() {
+    use librbufr::errors::Error;
+    use librbufr::obs::{Amdar, Synop};
+
+    // 0-01-001 0-01-002 0-04-001 … 0-04-004 0-05-001 0-06-001 0-12-101
+    let bytes = synthetic_message(
+        1,
+        &[
+            (0, 1, 1),
+            (0, 1, 2),
+            (0, 4, 1),
+            (0, 4, 2),
+            (0, 4, 3),
+            (0, 4, 4),
+            (0, 5, 1),
+            (0, 6, 1),
+            (0, 12, 101),
+        ],
+        &[
+            (7, 7),
+            (149, 10),
+            (2025, 12),
+            (12, 4),
+            (17, 6),
+            (9, 5),
+            (13_882_000, 25),
+            (18_233_000, 26),
+            (28815, 16),
+        ],
+    );
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+
+    let decoder = Decoder::from_message(msg).unwrap();
+    let parsed = decoder.decode(msg).unwrap();
+    let synop = Synop::try_from(&parsed).unwrap();
+    assert_eq!(synop.station.wmo_index(), Some(7149));
+    assert_eq!(synop.time.to_string(), "2025-12-17 09:00:00");
+    assert!((synop.latitude - 48.82).abs() < 1e-9);
+    assert!((synop.longitude - 2.33).abs() < 1e-9);
+    assert!((synop.temperature.unwrap() - 288.15).abs() < 1e-9);
+    assert_eq!(synop.pressure, None);
+
+    assert!(matches!(
+        Amdar::try_from(&parsed),
+        Err(Error::MissingElement(_))
+    ));
+}
+
+#[test]
+fn test_template_detection() {
+    use librbufr::core::{FXY, prelude::BUFRTableD};
+    use librbufr::tables::{MasterTable, TableLoader};
+    use librbufr::templates;
+
+    let bytes = synthetic_message(1, &[(3, 7, 80)], &[]);
+    let file = parse(&bytes).unwrap();
+    let template = file.message_at(0).unwrap().template().unwrap();
+    assert_eq!(template.fxy, FXY::new(3, 7, 80));
+
+    let bytes = synthetic_message(1, &[(0, 1, 1), (0, 1, 2)], &[(7, 7), (149, 10)]);
+    let file = parse(&bytes).unwrap();
+    assert!(file.message_at(0).unwrap().template().is_none());
+
+    // The members of 3-09-052 spelled out in place
+    let table_d: BUFRTableD = TableLoader.load_table(MasterTable::new(16)).unwrap();
+    let entry = table_d.lookup(&FXY::new(3, 9, 52)).unwrap();
+    let mut members: Vec<FXY> = entry
+        .fxy_chain
+        .iter()
+        .map(|c| FXY::new(c.f.to_native(), c.x.to_native(), c.y.to_native()))
+        .collect();
+    members.insert(0, FXY::new(0, 1, 1));
+    let template = templates::detect(&members, &table_d).unwrap();
+    assert_eq!(template.fxy, FXY::new(3, 9, 52));
+}
+
+#[test]
+fn test_dedupe() {
+    use librbufr::core::FXY;
+    use librbufr::dedupe::{Precedence, Report, dedupe};
+
+    // 0-01-001 0-01-002 0-04-001 … 0-04-004 0-12-101, three subsets where
+    // the first two share station and time
+    let mut values = vec![];
+    for (station, temperature) in [(149, 28815), (149, 28915), (150, 29015)] {
+        values.extend([
+            (7, 7),
+            (station, 10),
+            (2025, 12),
+            (12, 4),
+            (17, 6),
+            (9, 5),
+            (temperature, 16),
+        ]);
+    }
+    let bytes = synthetic_message(
+        3,
+        &[
+            (0, 1, 1),
+            (0, 1, 2),
+            (0, 4, 1),
+            (0, 4, 2),
+            (0, 4, 3),
+            (0, 4, 4),
+            (0, 12, 101),
+        ],
+        &values,
+    );
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+    let decoder = Decoder::from_message(msg).unwrap();
+
+    let reports = |sequences: [u8; 3]| -> Vec<Report> {
+        (0..3)
+            .map(|i| {
+                let parsed = decoder.decode_subset(msg, i).unwrap().into_owned();
+                Report::new(parsed, None, sequences[i])
+            })
+            .collect()
+    };
+    let temperature = |r: &Report| {
+        let record = r.parsed.get_by_fxy(FXY::new(0, 12, 101)).remove(0);
+        match record.values {
+            librbufr::BUFRData::Single(librbufr::Value::Number(t)) => t,
+            _ => unreachable!(),
+        }
+    };
+
+    let first = dedupe(reports([0, 0, 0]), Precedence::First);
+    assert_eq!(first.len(), 2);
+    assert!((temperature(&first[0]) - 288.15).abs() < 1e-9);
+    assert_eq!(first[1].station.wmo_index(), Some(7150));
+
+    let last = dedupe(reports([0, 0, 0]), Precedence::Last);
+    assert!((temperature(&last[0]) - 289.15).abs() < 1e-9);
+
+    let latest = dedupe(reports([1, 0, 0]), Precedence::LatestUpdate);
+    assert!((temperature(&latest[0]) - 288.15).abs() < 1e-9);
+}
+
+#[test]
+fn test_time_series() {
+    use librbufr::core::FXY;
+
+    // 0-01-001 0-01-002 0-04-001 … 0-04-004 0-12-101
+    let message = |subsets: &[(u64, u64, u64)]| {
+        let mut values = vec![];
+        for &(station, hour, temperature) in subsets {
+            values.extend([
+                (7, 7),
+                (station, 10),
+                (2025, 12),
+                (12, 4),
+                (17, 6),
+                (hour, 5),
+                (temperature, 16),
+            ]);
+        }
+        synthetic_message(
+            subsets.len() as u16,
+            &[
+                (0, 1, 1),
+                (0, 1, 2),
+                (0, 4, 1),
+                (0, 4, 2),
+                (0, 4, 3),
+                (0, 4, 4),
+                (0, 12, 101),
+            ],
+            &values,
+        )
+    };
+    let mut bytes = message(&[(149, 9, 28815), (150, 9, 29015)]);
+    bytes.extend(message(&[(149, 6, 28515)]));
+    let file = parse(&bytes).unwrap();
+
+    let series = file.time_series(FXY::new(0, 12, 101)).unwrap();
+    assert_eq!(series.len(), 2);
+    assert_eq!(series[0].station.wmo_index(), Some(7149));
+
+    let points = &series[0].points;
+    assert_eq!(points.len(), 2);
+    assert_eq!(points[0].0.to_string(), "2025-12-17 06:00:00");
+    assert!((points[0].1 - 285.15).abs() < 1e-9);
+    assert!((points[1].1 - 288.15).abs() < 1e-9);
+    assert_eq!(series[1].points.len(), 1);
+}
+
+#[test]
+fn test_bbox_filter() {
+    use librbufr::decoder::{BUFRData, BoundingBox, Value};
+
+    // 0-01-002 0-05-002 0-06-002, coarse position: 15 bits scale 2 ref -9000
+    // and 16 bits scale 2 ref -18000
+    let mut values = vec![];
+    for (station, lat, lon) in [(1, 48.0, 2.0), (2, -33.0, 151.0), (3, 50.0, 8.0)] {
+        values.extend([
+            (station, 10),
+            (((lat + 90.0) * 100.0) as u64, 15),
+            (((lon + 180.0) * 100.0) as u64, 16),
+        ]);
+    }
+    let bytes = synthetic_message(3, &[(0, 1, 2), (0, 5, 2), (0, 6, 2)], &values);
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+
+    let decoder = Decoder::from_message(msg).unwrap();
+    assert_eq!(decoder.decode_subsets(msg).unwrap().len(), 3);
+
+    let europe = BoundingBox::new(35.0, 72.0, -25.0, 45.0);
+    let decoder = Decoder::from_message(msg).unwrap().with_bbox(europe);
+    let subsets = decoder.decode_subsets(msg).unwrap();
+    let stations: Vec<_> = subsets.iter().map(|s| s.station().wmo_station).collect();
+    assert_eq!(stations, [Some(1), Some(3)]);
+
+    let pacific = BoundingBox::new(-60.0, 0.0, 120.0, -120.0);
+    assert!(pacific.contains(-33.0, 151.0));
+    assert!(pacific.contains(-10.0, -150.0));
+    assert!(!pacific.contains(-33.0, 0.0));
+
+    // The others decode the first subset, or nothing once it is outside
+    assert_eq!(decoder.decode(msg).unwrap().records().len(), 3);
+    let decoder = Decoder::from_message(msg).unwrap().with_bbox(pacific);
+    assert_eq!(decoder.decode(msg).unwrap().records().len(), 0);
+    assert!(decoder.decode_values(msg).unwrap().is_empty());
+    assert!(decoder.decode_tree(msg).unwrap().nodes.is_empty());
+    assert_eq!(decoder.decode_subset(msg, 1).unwrap().records().len(), 3);
+    assert_eq!(stations_visited(&decoder, msg), [2.0]);
+
+    // The same subsets compressed: stations 1 + 0, 1, 2; latitudes
+    // 5700 + 8100, 0, 8300; longitudes 18200 + 0, 14900, 600
+    let bytes = compressed_message(
+        3,
+        &[(0, 1, 2), (0, 5, 2), (0, 6, 2)],
+        &[
+            (1, 10),
+            (2, 6),
+            (0, 2),
+            (1, 2),
+            (2, 2),
+            (5700, 15),
+            (14, 6),
+            (8100, 14),
+            (0, 14),
+            (8300, 14),
+            (18200, 16),
+            (14, 6),
+            (0, 14),
+            (14900, 14),
+            (600, 14),
+        ],
+    );
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+    let decoder = Decoder::from_message(msg).unwrap().with_bbox(europe);
+
+    let parsed = decoder.decode(msg).unwrap();
+    let stations = parsed.records().next().unwrap();
+    assert!(
+        matches!(stations.values, BUFRData::Array { ref values, .. } if values[..] == [1.0, 3.0])
+    );
+    let latitudes = parsed.records().nth(1).unwrap();
+    assert!(
+        matches!(latitudes.values, BUFRData::Array { ref values, .. } if values[..] == [48.0, 50.0])
+    );
+    let values = decoder.decode_values(msg).unwrap();
+    assert_eq!(values.len(), 6);
+    assert!(matches!(values[1], (_, Value::Number(n)) if n == 3.0));
+    assert_eq!(decoder.decode_tree(msg).unwrap().records().len(), 3);
+    assert_eq!(stations_visited(&decoder, msg), [1.0, 3.0]);
+
+    fn stations_visited(decoder: &Decoder, msg: &librbufr::block::MessageBlock) -> Vec<f64> {
+        let mut stations = vec![];
+        decoder
+            .decode_with(msg, &mut |fxy: librbufr::core::FXY, value: Value| {
+                if fxy == librbufr::core::FXY::new(0, 1, 2) {
+                    stations.extend(value.as_f64());
+                }
+                std::ops::ControlFlow::Continue(())
+            })
+            .unwrap();
+        stations
+    }
+}
+
+#[cfg(feature = "opera")]
+#[test]
+fn test_opera_bitmap() {
+    use librbufr::BUFRData;
+    use librbufr::core::FXY;
+
+    // 3-21-193, three rows: a run of 3 × 5 followed by 7 and 9, then 11, then
+    // 16 runs of 2 × i, enough for the runs to decode as compiled arrays
+    let mut values = vec![
+        (3, 16),
+        (0, 12),
+        (1, 8),
+        (1, 8),
+        (3, 16),
+        (5, 8),
+        (2, 8),
+        (7, 8),
+        (9, 8),
+        (1, 12),
+        (1, 8),
+        (0, 8),
+        (1, 8),
+        (11, 8),
+        (2, 12),
+        (1, 8),
+        (16, 8),
+    ];
+    for i in 0..16 {
+        values.extend([(2, 16), (i, 8)]);
+    }
+    values.push((0, 8));
+    let bytes = local_synthetic_message(247, 8, 1, &[(3, 21, 193)], &values);
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+
+    let decoder = Decoder::from_message(msg).unwrap();
+    let parsed = decoder.decode(msg).unwrap();
+    assert_eq!(parsed.iter().len(), 1);
+
+    let bitmap = parsed.record(0).unwrap();
+    assert_eq!(bitmap.fxy, FXY::new(3, 21, 193));
+    assert_eq!(bitmap.width_bits, Some(8));
+
+    let mut expected = vec![5.0, 5.0, 5.0, 7.0, 9.0, 11.0];
+    expected.extend((0..16).flat_map(|i| [i as f64; 2]));
+    match &bitmap.values {
+        BUFRData::Array { values, missing } => {
+            assert_eq!(values[..], expected[..]);
+            assert!(!missing.any());
+        }
+        _ => panic!("bitmap should decode to an array"),
+    }
+}
+
+#[cfg(feature = "opera")]
+#[test]
+fn test_opera_radar_image() {
+    use librbufr::BUFRData;
+
+    // 0-29-201 0-30-021 0-30-022 3-21-193, a single row of two values
+    let values = [
+        (2, 5),
+        (2, 12),
+        (1, 12),
+        (1, 16),
+        (0, 12),
+        (1, 8),
+        (0, 8),
+        (2, 8),
+        (7, 8),
+        (9, 8),
+    ];
+    let bytes = local_synthetic_message(
+        247,
+        8,
+        1,
+        &[(0, 29, 201), (0, 30, 21), (0, 30, 22), (3, 21, 193)],
+        &values,
+    );
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+
+    let decoder = Decoder::from_message(msg).unwrap();
+    let parsed = decoder.decode(msg).unwrap();
+    assert!(matches!(
+        parsed.record(3).unwrap().values,
+        BUFRData::Array2 {
+            rows: 1,
+            cols: 2,
+            ..
+        }
+    ));
+
+    let images = parsed.radar_images();
+    assert_eq!(images.len(), 1);
+    let image = &images[0];
+    assert_eq!(image.projection.projection_type, Some(2));
+    assert_eq!(image.row(0), Some(&[7.0, 9.0][..]));
+    assert_eq!(image.get(0, 1), Some(9.0));
+    assert_eq!(image.get(1, 0), None);
+}
+
+#[cfg(feature = "geotiff")]
+#[test]
+fn test_opera_geotiff() {
+    use librbufr::core::FXY;
+    use librbufr::decoder::MISS_VAL;
+    use librbufr::opera::{Projection, RadarImage};
+    use tiff::decoder::{Decoder as TiffDecoder, DecodingResult};
+    use tiff::tags::Tag;
+
+    let image = RadarImage {
+        fxy: FXY::new(3, 21, 193),
+        rows: 2,
+        cols: 3,
+        pixels: vec![1.0, 2.0, 3.0, 4.0, MISS_VAL, 6.0],
+        missing: [false, false, false, false, true, false]
+            .into_iter()
+            .collect(),
+        projection: Projection {
+            projection_type: Some(1),
+            lon_origin: Some(10.0),
+            lat_origin: Some(90.0),
+            standard_parallel_1: Some(60.0),
+            pixel_size_x: Some(2000.0),
+            pixel_size_y: Some(2000.0),
+            x_offset: Some(-500_000.0),
+            y_offset: Some(-3_000_000.0),
+            ..Projection::default()
+        },
+    };
+
+    let mut bytes = std::io::Cursor::new(vec![]);
+    image.write_geotiff(&mut bytes).unwrap();
+    bytes.set_position(0);
+
+    let mut tiff = TiffDecoder::new(bytes).unwrap();
+    assert_eq!(tiff.dimensions().unwrap(), (3, 2));
+    let scale = tiff.get_tag_f64_vec(Tag::ModelPixelScaleTag).unwrap();
+    assert_eq!(scale, [2000.0, 2000.0, 0.0]);
+    let keys = tiff.get_tag_u16_vec(Tag::GeoKeyDirectoryTag).unwrap();
+    assert!(keys.chunks(4).any(|k| k == [3075, 0, 1, 15]));
+    match tiff.read_image().unwrap() {
+        DecodingResult::F32(pixels) => {
+            assert_eq!(pixels[..4], [1.0, 2.0, 3.0, 4.0]);
+            assert!(pixels[4].is_nan());
+        }
+        _ => panic!("expected f32 pixels"),
+    }
+
+    let unsupported = RadarImage {
+        projection: Projection {
+            projection_type: Some(0),
+            ..image.projection.clone()
+        },
+        ..image
+    };
+    assert!(
+        unsupported
+            .write_geotiff(std::io::Cursor::new(vec![]))
+            .is_err()
+    );
+}
+
+#[cfg(feature = "ecmwf")]
+#[test]
+fn test_rdb_key() {
+    use librbufr::ecmwf::{RdbKey, RdbLocation};
+
+    let time: u64 = (2025 << 28) | (12 << 24) | (17 << 18) | (9 << 13) | (30 << 7) | (15 << 1);
+    let mut data = vec![1, 91];
+    data.extend_from_slice(&time.to_be_bytes()[3..]);
+    data.extend_from_slice(&18_233_000u32.to_be_bytes());
+    data.extend_from_slice(&13_882_000u32.to_be_bytes());
+    data.extend_from_slice(b"07149    ");
+
+    let key = RdbKey::parse(&data).unwrap();
+    assert_eq!((key.rdb_type, key.rdb_subtype), (1, 91));
+    assert_eq!(key.time.unwrap().to_string(), "2025-12-17 09:30:15");
+    match key.location.clone().unwrap() {
+        RdbLocation::Point {
+            latitude,
+            longitude,
+            ident,
+        } => {
+            assert!((latitude - 48.82).abs() < 1e-9);
+            assert!((longitude - 2.33).abs() < 1e-9);
+            assert_eq!(ident, "07149");
+        }
+        other => panic!("expected a point, got {:?}", other),
+    }
+
+    // Centre 98 without Section 2
+    let bytes = synthetic_message(1, &[(0, 1, 1)], &[(7, 7)]);
+    assert!(
+        parse(&bytes)
+            .unwrap()
+            .message_at(0)
+            .unwrap()
+            .rdb_key()
+            .is_none()
+    );
+
+    // The built-in Section 2 parser for centre 98
+    let bytes = with_section2(bytes, &data);
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+    assert_eq!(msg.rdb_key(), Some(key.clone()));
+    let decoded = msg.section2_decoded().unwrap();
+    assert_eq!(decoded.downcast_ref::<RdbKey>(), Some(&key));
+}
+
+/// Insert a Section 2 holding `data` into an edition 4 message
+fn with_section2(mut message: Vec<u8>, data: &[u8]) -> Vec<u8> {
+    message[17] |= 0x80;
+    let mut section2 = ((data.len() + 4) as u32).to_be_bytes()[1..].to_vec();
+    section2.push(0);
+    section2.extend_from_slice(data);
+    message.splice(30..30, section2);
+
+    let total = (message.len() as u32).to_be_bytes();
+    message[4..7].copy_from_slice(&total[1..]);
+    message
+}
+
+#[test]
+fn test_section2_registry() {
+    use librbufr::section2::{register_section2_parser, unregister_section2_parser};
+
+    #[derive(Debug, PartialEq)]
+    struct Tag(String);
+
+    let bytes = with_section2(
+        local_synthetic_message(200, 0, 1, &[(0, 1, 1)], &[(7, 7)]),
+        b"ABCD",
+    );
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+    assert_eq!(msg.section2().unwrap().data, b"ABCD");
+    assert!(msg.section2_decoded().is_none());
+
+    register_section2_parser(200, None, |data| {
+        Some(Tag(String::from_utf8_lossy(data).into_owned()))
+    });
+    register_section2_parser(200, Some(7), |data| Some(data.len()));
+
+    let decoded = msg.section2_decoded().unwrap();
+    assert_eq!(
+        decoded.downcast_ref::<Tag>(),
+        Some(&Tag("ABCD".to_string()))
+    );
+    assert!(!decoded.is::<usize>());
+
+    unregister_section2_parser(200, None);
+    assert!(msg.section2_decoded().is_none());
+    unregister_section2_parser(200, Some(7));
+}
+
+/// Rewrite an edition 4 `synthetic_message` as edition 1: a bare "BUFR"
+/// Section 0 and the 17-octet legacy Section 1, with no total length
+fn edition1_message(message: &[u8]) -> Vec<u8> {
+    let mut legacy = b"BUFR".to_vec();
+    legacy.extend_from_slice(&[0, 0, 17, 1, 0, 98, 3, 0, 0, 0, 35, 0, 87, 12, 17, 9, 0]);
+    legacy.extend_from_slice(&message[30..]);
+    legacy
+}
+
+#[test]
+fn test_legacy_edition() {
+    use librbufr::structs::versions::MessageVersion;
+
+    let v4 = synthetic_message(1, &[(0, 1, 1), (0, 1, 2)], &[(10, 7), (100, 10)]);
+    let mut bytes = edition1_message(&v4);
+    // Trailing garbage must not be swallowed into the message
+    bytes.extend_from_slice(b"junk");
+    bytes.extend_from_slice(&edition1_message(&v4));
+
+    let file = parse(&bytes).unwrap();
+    assert_eq!(file.message_count(), 2);
+    let msg = file.message_at(0).unwrap();
+    assert_eq!(msg.version(), 1);
+    assert_eq!(msg.center_id(), 98);
+    assert_eq!(msg.update_sequence_number(), 3);
+    assert_eq!(msg.as_bytes().len(), v4.len() - 30 + 21);
+    assert_eq!(msg.to_bytes(), msg.as_bytes());
+
+    let decoder = Decoder::from_message(msg).unwrap();
+    let parsed = decoder.decode(msg).unwrap();
+    assert_eq!(parsed.station().wmo_index(), Some(10100));
+}
+
+#[test]
+fn test_parse_with_filter() {
+    use librbufr::parser::parse_with_filter;
+
+    let surface = synthetic_message(1, &[(0, 1, 1), (0, 1, 2)], &[(10, 7), (100, 10)]);
+    let mut radar = surface.clone();
+    radar[18] = 6;
+
+    let mut bytes = surface.clone();
+    bytes.extend_from_slice(&radar);
+    bytes.extend_from_slice(&edition1_message(&surface));
+
+    let path = std::env::temp_dir().join(format!(
+        "rbufr_test_parse_with_filter_{}.bufr",
+        std::process::id()
+    ));
+    std::fs::write(&path, &bytes).unwrap();
+
+    let mut seen = vec![];
+    let file = parse_with_filter(&path, |header| {
+        seen.push((header.edition, header.centre, header.data_category));
+        header.data_category == 6
+    })
+    .unwrap();
+    assert_eq!(seen, [(4, 98, 0), (4, 98, 6), (1, 98, 0)]);
+    assert_eq!(file.message_count(), 1);
+    assert_eq!(file.message_at(0).unwrap().as_bytes(), &radar[..]);
+
+    let all = parse_with_filter(&path, |_| true).unwrap();
+    assert_eq!(all.message_count(), 3);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_obs_time() {
+    use chrono::NaiveDate;
+
+    let v4 = synthetic_message(1, &[(0, 1, 1), (0, 1, 2)], &[(10, 7), (100, 10)]);
+    let mut bytes = v4.clone();
+    bytes.extend_from_slice(&edition1_message(&v4));
+    let mut legacy = edition1_message(&v4);
+    legacy[16] = 12;
+    bytes.extend_from_slice(&legacy);
+
+    let file = parse(&bytes).unwrap();
+    let times: Vec<_> = file.messages().iter().map(|m| m.obs_time()).collect();
+    let at = |y, m, d, h| {
+        NaiveDate::from_ymd_opt(y, m, d)
+            .unwrap()
+            .and_hms_opt(h, 0, 0)
+    };
+    assert_eq!(
+        times,
+        [
+            at(2025, 12, 17, 9),
+            at(1987, 12, 17, 9),
+            at(2012, 12, 17, 9)
+        ]
+    );
+}
+
+#[test]
+fn test_decoder_builder() {
+    use librbufr::decoder::{BUFRData, Decoded, MissingPolicy, Mode, Node, Output, Units};
+
+    assert!(Decoder::builder().build().is_err());
+
+    // 3-01-001 (block, station), then a temperature and a missing one
+    let bytes = synthetic_message(
+        1,
+        &[(3, 1, 1), (0, 12, 101), (0, 12, 101)],
+        &[(10, 7), (100, 10), (28315, 16), (0xFFFF, 16)],
+    );
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+
+    let decoder = Decoder::builder()
+        .message(msg)
+        .units(Units::Meteorological)
+        .missing(MissingPolicy::Skip)
+        .build()
+        .unwrap();
+    let parsed = decoder.decode(msg).unwrap();
+    assert_eq!(parsed.record_count(), 3);
+    let temperature = &parsed.record(2).unwrap();
+    assert_eq!(temperature.unit.as_deref(), Some("C"));
+    match &temperature.values {
+        BUFRData::Single(value) => assert!((value.as_f64().unwrap() - 10.0).abs() < 1e-9),
+        _ => panic!("expected a single value"),
+    }
+
+    // A temperature change (0-12-049, reference -30) is not offset
+    let bytes = synthetic_message(1, &[(0, 12, 101), (0, 12, 49)], &[(28315, 16), (33, 6)]);
+    let change_file = parse(&bytes).unwrap();
+    let change_msg = change_file.message_at(0).unwrap();
+    let change_decoder = Decoder::builder()
+        .message(change_msg)
+        .units(Units::Meteorological)
+        .build()
+        .unwrap();
+    let parsed = change_decoder.decode(change_msg).unwrap();
+    let change = &parsed.record(1).unwrap();
+    assert_eq!(change.unit.as_deref(), Some("K"));
+    match &change.values {
+        BUFRData::Single(value) => assert_eq!(value.as_f64(), Some(3.0)),
+        _ => panic!("expected a single value"),
+    }
+
+    let decoder = Decoder::builder()
+        .message(msg)
+        .output(Output::Tree)
+        .build()
+        .unwrap();
+    let Decoded::Tree(tree) = decoder.decode_output(msg).unwrap() else {
+        panic!("expected a tree");
+    };
+    assert_eq!(tree.nodes.len(), 3);
+    match &tree.nodes[0] {
+        Node::Sequence { fxy, children, .. } => {
+            assert_eq!(*fxy, librbufr::core::FXY::new(3, 1, 1));
+            assert_eq!(children.len(), 2);
+        }
+        Node::Record(_) => panic!("expected 3-01-001"),
+    }
+    assert_eq!(tree.records().len(), 4);
+    assert!(tree.to_string().contains("3-01-001"));
+
+    // Subset selection
+    let file = parse(&two_subset_message()).unwrap();
+    let msg = file.message_at(0).unwrap();
+    let decoder = Decoder::builder()
+        .message(msg)
+        .subsets(1..2)
+        .build()
+        .unwrap();
+    let subsets = decoder.decode_subsets(msg).unwrap();
+    assert_eq!(subsets.len(), 1);
+    assert_eq!(subsets[0].station().wmo_index(), Some(11200));
+    let parsed = decoder.decode(msg).unwrap();
+    assert_eq!(parsed.station().wmo_index(), Some(11200));
+
+    // Truncated data section
+    let bytes = synthetic_message(
+        1,
+        &[(0, 1, 1), (0, 1, 2), (0, 12, 101)],
+        &[(10, 7), (100, 10)],
+    );
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+    let decoder = Decoder::from_message(msg).unwrap();
+    assert!(decoder.decode(msg).is_err());
+    let decoder = Decoder::builder()
+        .message(msg)
+        .mode(Mode::Lenient)
+        .build()
+        .unwrap();
+    assert_eq!(decoder.decode(msg).unwrap().record_count(), 2);
+}
+
+#[test]
+fn test_missing_values() {
+    use librbufr::decoder::{BUFRData, MISS_VAL, MissingValues};
+
+    // 16 temperatures, one missing, decoded through a compiled array
+    let mut values = vec![(10, 7), (100, 10)];
+    for i in 0..16 {
+        values.push((if i == 3 { 0xFFFF } else { 27315 + i }, 16));
+    }
+    let bytes = synthetic_message(
+        1,
+        &[(0, 1, 1), (0, 1, 2), (1, 1, 16), (0, 12, 101)],
+        &values,
+    );
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+
+    let array = |missing_values| {
+        let decoder = Decoder::builder()
+            .message(msg)
+            .missing_values(missing_values)
+            .build()
+            .unwrap();
+        let parsed = decoder.decode(msg).unwrap();
+        parsed.record(2).unwrap().values.clone().into_owned()
+    };
+
+    match array(MissingValues::default()) {
+        BUFRData::Array { values, missing } => {
+            assert_eq!(values[3], MISS_VAL);
+            assert_eq!(missing.len(), 16);
+            assert_eq!(missing.count(), 1);
+            assert!(missing.get(3) && !missing.get(4));
+        }
+        _ => panic!("expected an array"),
+    }
+    match array(MissingValues::Sentinel(-1.0)) {
+        BUFRData::Array { values, missing } => {
+            assert_eq!(values[3], -1.0);
+            assert!(missing.get(3));
+        }
+        _ => panic!("expected an array"),
+    }
+    match array(MissingValues::Nan) {
+        BUFRData::Array { values, missing } => {
+            assert!(values[3].is_nan());
+            assert!(!values[4].is_nan());
+            assert!(missing.get(3));
+        }
+        _ => panic!("expected an array"),
+    }
+}
+
+#[test]
+fn test_decode_with_visitor() {
+    use librbufr::core::FXY;
+    use librbufr::decoder::{DecodeVisitor, Value};
+    use std::ops::ControlFlow;
+
+    #[derive(Default)]
+    struct Events {
+        temperatures: Vec<f64>,
+        elements: usize,
+        replications: Vec<(FXY, usize)>,
+        subsets: Vec<usize>,
+        operators: Vec<FXY>,
+    }
+
+    impl DecodeVisitor for Events {
+        fn element(&mut self, fxy: FXY, value: Value) -> ControlFlow<()> {
+            self.elements += 1;
+            if fxy == FXY::new(0, 12, 101) {
+                self.temperatures.extend(value.as_f64());
+            }
+            ControlFlow::Continue(())
+        }
+
+        fn begin_replication(&mut self, fxy: FXY, times: usize) {
+            self.replications.push((fxy, times));
+        }
+
+        fn end_subset(&mut self, index: usize) -> ControlFlow<()> {
+            self.subsets.push(index);
+            ControlFlow::Continue(())
+        }
+
+        fn operator(&mut self, fxy: FXY) {
+            self.operators.push(fxy);
+        }
+    }
+
+    let file = parse(&two_subset_message()).unwrap();
+    let msg = file.message_at(0).unwrap();
+
+    let decoder = Decoder::from_message(msg).unwrap();
+    let mut events = Events::default();
+    decoder.decode_with(msg, &mut events).unwrap();
+    assert_eq!(events.elements, 36);
+    assert_eq!(events.subsets, [0, 1]);
+    assert_eq!(events.replications, [(FXY::new(1, 1, 16), 16); 2]);
+    assert_eq!(events.temperatures.len(), 32);
+    assert!((events.temperatures[16] - 283.15).abs() < 1e-9);
+
+    let decoder = Decoder::builder()
+        .message(msg)
+        .subsets(1..2)
+        .build()
+        .unwrap();
+    let mut events = Events::default();
+    decoder.decode_with(msg, &mut events).unwrap();
+    assert_eq!(events.subsets, [1]);
+    assert_eq!(events.elements, 18);
+
+    // 2-01-129 widens 0-12-101 to 17 bits
+    let mut values: Vec<(u64, usize)> = (0..16).map(|i| (27315 + i, 17)).collect();
+    values.push((5, 7));
+    let bytes = synthetic_message(
+        1,
+        &[(2, 1, 129), (1, 1, 16), (0, 12, 101), (2, 1, 0), (0, 1, 1)],
+        &values,
+    );
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+
+    let decoder = Decoder::from_message(msg).unwrap();
+    let mut events = Events::default();
+    decoder.decode_with(msg, &mut events).unwrap();
+    assert_eq!(events.operators, [FXY::new(2, 1, 129), FXY::new(2, 1, 0)]);
+    assert_eq!(events.elements, 17);
+    assert!((events.temperatures[15] - 273.30).abs() < 1e-9);
+}
+
+#[test]
+fn test_decode_with_early_exit() {
+    use librbufr::core::FXY;
+    use std::ops::ControlFlow;
+
+    let file = parse(&two_subset_message()).unwrap();
+    let msg = file.message_at(0).unwrap();
+    let decoder = Decoder::from_message(msg).unwrap();
+
+    // Stop at the first station number
+    let mut seen = vec![];
+    decoder
+        .decode_with(msg, &mut |fxy: FXY, value: librbufr::Value| {
+            seen.push(fxy);
+            if fxy == FXY::new(0, 1, 2) {
+                assert_eq!(value.as_f64(), Some(100.0));
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        })
+        .unwrap();
+    assert_eq!(seen, [FXY::new(0, 1, 1), FXY::new(0, 1, 2)]);
+
+    // A break inside a replication skips the rest of it and later subsets
+    let mut temperatures = 0;
+    decoder
+        .decode_with(msg, &mut |fxy: FXY, _: librbufr::Value| {
+            if fxy == FXY::new(0, 12, 101) {
+                temperatures += 1;
+                if temperatures == 3 {
+                    return ControlFlow::Break(());
+                }
+            }
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+    assert_eq!(temperatures, 3);
+}
+
+#[test]
+fn test_provenance() {
+    let bytes = synthetic_message(1, &[(0, 1, 1)], &[(10, 7)]);
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+
+    let decoder = Decoder::from_message(msg).unwrap();
+    let provenance = decoder.provenance();
+    assert_eq!(provenance.requested_master_version, Some(35));
+    // The tables shipped stop short of version 35
+    let version = provenance.master_b.version.unwrap();
+    assert!(version < 35);
+    assert!(provenance.master_fallback());
+    let path = provenance.master_b.path.as_ref().unwrap();
+    assert!(path.ends_with(format!("master/BUFR_TableB_{}.bufrtbl", version)));
+    assert!(path.exists());
+    assert!(!provenance.has_local_tables());
+    assert!(provenance.to_string().contains("fallback"));
+}
+
+#[test]
+fn test_expanded_descriptors() {
+    use librbufr::core::FXY;
+    use librbufr::decoder::ExpandedKind;
+
+    // 2-01-129 widens 0-12-101 to 17 bits for the fixed replication
+    let bytes = synthetic_message(
+        1,
+        &[(2, 1, 129), (1, 1, 3), (0, 12, 101), (2, 1, 0), (0, 1, 1)],
+        &[],
+    );
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+    let decoder = Decoder::from_message(msg).unwrap();
+
+    let expanded = msg.expanded_descriptors(&decoder).unwrap();
+    let kinds: Vec<ExpandedKind> = expanded.iter().map(|d| d.kind).collect();
+    assert_eq!(kinds.len(), 7);
+    assert_eq!(kinds[0], ExpandedKind::Operator);
+    assert_eq!(
+        kinds[1],
+        ExpandedKind::Replication {
+            descriptors: 1,
+            times: Some(3)
+        }
+    );
+    for temperature in &expanded[2..5] {
+        assert_eq!(temperature.fxy, FXY::new(0, 12, 101));
+        assert_eq!(temperature.width_bits, 17);
+    }
+    assert_eq!(expanded[6].width_bits, 7);
+    assert!(!expanded[6].name.is_empty());
+    let bits: u32 = expanded.iter().map(|d| d.width_bits).sum();
+    assert_eq!(bits, 3 * 17 + 7);
+
+    // A delayed body is listed once, after its count
+    let bytes = synthetic_message(1, &[(1, 1, 0), (0, 31, 1), (0, 12, 101)], &[]);
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+    let expanded = msg.expanded_descriptors(&decoder).unwrap();
+    let summary: Vec<(FXY, usize)> = expanded.iter().map(|d| (d.fxy, d.delayed_depth)).collect();
+    assert_eq!(
+        summary,
+        [
+            (FXY::new(1, 1, 0), 0),
+            (FXY::new(0, 31, 1), 0),
+            (FXY::new(0, 12, 101), 1)
+        ]
+    );
+    assert_eq!(
+        expanded[0].kind,
+        ExpandedKind::Replication {
+            descriptors: 1,
+            times: None
+        }
+    );
+}
+
+#[test]
+fn test_descriptor_tree() {
+    use librbufr::core::FXY;
+    use librbufr::decoder::DescriptorNode;
+
+    // 3-01-001 is the WMO block and station number sequence
+    let bytes = synthetic_message(
+        1,
+        &[(3, 1, 1), (1, 1, 0), (0, 31, 1), (0, 12, 101), (2, 1, 129)],
+        &[],
+    );
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+    let decoder = Decoder::from_message(msg).unwrap();
+
+    let tree = msg.descriptor_tree(&decoder).unwrap();
+    assert_eq!(tree.nodes.len(), 3);
+    match &tree.nodes[0] {
+        DescriptorNode::Sequence {
+            title, children, ..
+        } => {
+            assert!(title.is_some());
+            assert_eq!(children.len(), 2);
+        }
+        _ => panic!("expected a sequence"),
+    }
+    match &tree.nodes[1] {
+        DescriptorNode::Replication {
+            times,
+            count,
+            children,
+            ..
+        } => {
+            assert_eq!(*times, None);
+            assert!(matches!(
+                count.as_deref(),
+                Some(DescriptorNode::Element { fxy, .. }) if *fxy == FXY::new(0, 31, 1)
+            ));
+            assert_eq!(children.len(), 1);
+        }
+        _ => panic!("expected a replication"),
+    }
+
+    let text = tree.to_string();
+    let lines: Vec<&str> = text.lines().collect();
+    assert!(lines[0].starts_with("3-01-001"));
+    assert!(lines[1].starts_with("  0-01-001 "));
+    assert!(lines[3].starts_with("1-01-000 Replicate 1 descriptors, delayed"));
+    assert!(lines[4].starts_with("  0-31-001 "));
+    assert_eq!(lines[6], "2-01-129 Change data width");
+
+    // Descriptors the tables lack are kept
+    let tree = decoder.descriptor_tree(&[FXY::new(0, 63, 250)]);
+    assert!(tree.to_string().contains("<not in Table B>"));
+}
+
+#[test]
+fn test_json_dump() {
+    use librbufr::dump::{JsonStyle, to_json};
+    use serde_json::Value as Json;
+
+    let file = parse(&two_subset_message()).unwrap();
+
+    let structure: Json =
+        serde_json::from_str(&to_json(&file, JsonStyle::Structure).unwrap()).unwrap();
+    let message = structure["messages"][0].as_array().unwrap();
+    let header = |key: &str| {
+        message
+            .iter()
+            .find(|entry| entry["key"] == key)
+            .map(|entry| entry["value"].clone())
+    };
+    assert_eq!(header("edition"), Some(Json::from(4)));
+    assert_eq!(header("numberOfSubsets"), Some(Json::from(2)));
+    assert_eq!(
+        header("unexpandedDescriptors"),
+        Some(serde_json::json!([1001, 1002, 101016, 12101]))
+    );
+
+    // Each subset nests under block number, then station number
+    let subsets: Vec<_> = message.iter().filter(|entry| entry.is_array()).collect();
+    assert_eq!(subsets.len(), 2);
+    let first = subsets[0].as_array().unwrap();
+    assert_eq!(first[0]["key"], "blockNumber");
+    assert_eq!(first[0]["value"], 10);
+    assert_eq!(first[0]["code"], "001001");
+    let station = first[1].as_array().unwrap();
+    assert_eq!(station[0]["key"], "stationNumber");
+    assert_eq!(station[1].as_array().unwrap().len(), 16);
+
+    let flat: Json = serde_json::from_str(&to_json(&file, JsonStyle::Flat).unwrap()).unwrap();
+    let keys: Vec<&str> = flat["messages"][0]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|entry| entry["key"].as_str())
+        .filter(|key| key.starts_with('#'))
+        .collect();
+    assert_eq!(keys.len(), 36);
+    assert_eq!(keys[0], "#1#blockNumber");
+    assert_eq!(keys[18], "#2#blockNumber");
+    assert_eq!(keys.last(), Some(&"#32#airTemperature"));
+
+    // A compressed message is dumped by subset; one that fails to decode
+    // becomes an error entry without stopping the others
+    let mut bytes = three_compressed_subsets();
+    bytes.extend(synthetic_message(
+        1,
+        &[(0, 1, 1), (0, 1, 2)],
+        &[(10, 7), (0, 1)],
+    ));
+    bytes.extend(two_subset_message());
+    let file = parse(&bytes).unwrap();
+    let dump: Json = serde_json::from_str(&to_json(&file, JsonStyle::Structure).unwrap()).unwrap();
+    let messages = dump["messages"].as_array().unwrap();
+    assert_eq!(messages.len(), 3);
+
+    let compressed = messages[0].as_array().unwrap();
+    let subsets: Vec<_> = compressed.iter().filter(|entry| entry.is_array()).collect();
+    assert_eq!(subsets.len(), 3);
+    let second = subsets[1].as_array().unwrap();
+    assert_eq!(second[0]["key"], "blockNumber");
+    assert_eq!(second[0]["value"], 11);
+    assert_eq!(second[1].as_array().unwrap()[0]["value"], "EFGH");
+
+    let failed = messages[1].as_array().unwrap();
+    assert_eq!(failed[0]["key"], "edition");
+    let error = failed.last().unwrap();
+    assert_eq!(error["key"], "error");
+    assert!(error["value"].as_str().unwrap().contains("Not enough data"));
+
+    assert_eq!(
+        messages[2]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|e| e.is_array())
+            .count(),
+        2
+    );
+}
+
+#[test]
+fn test_eccodes_keys() {
+    use librbufr::core::FXY;
+    use librbufr::keys::eccodes_key;
+
+    assert_eq!(
+        eccodes_key(FXY::new(0, 1, 1), "WMO BLOCK NUMBER"),
+        "blockNumber"
+    );
+    assert_eq!(
+        eccodes_key(FXY::new(0, 12, 101), "TEMPERATURE/AIR TEMPERATURE"),
+        "airTemperature"
+    );
+    assert_eq!(
+        eccodes_key(FXY::new(0, 5, 1), "LATITUDE (HIGH ACCURACY)"),
+        "latitude"
+    );
+    assert_eq!(
+        eccodes_key(FXY::new(0, 20, 10), "CLOUD COVER (TOTAL)"),
+        "cloudCoverTotal"
+    );
+    assert_eq!(
+        eccodes_key(FXY::new(0, 12, 3), "DEW-POINT TEMPERATURE"),
+        "dewpointTemperature"
+    );
+
+    // Listed keys, whatever the table calls the element
+    for (fxy, name, key) in [
+        (
+            (0, 1, 8),
+            "Aircraft registration number",
+            "aircraftRegistrationNumberOrOtherIdentification",
+        ),
+        (
+            (0, 1, 31),
+            "Identification of originating/generating centre",
+            "centre",
+        ),
+        ((0, 7, 4), "Pressure", "pressure"),
+        ((0, 10, 4), "Pressure", "nonCoordinatePressure"),
+        ((0, 10, 61), "3-hour pressure change", "3HourPressureChange"),
+        ((0, 11, 3), "u-component", "u"),
+        (
+            (0, 12, 1),
+            "TEMPERATURE/DRY-BULB TEMPERATURE",
+            "airTemperature",
+        ),
+        (
+            (0, 13, 11),
+            "Total precipitation/total water equivalent",
+            "totalPrecipitationOrTotalWaterEquivalent",
+        ),
+        ((0, 20, 4), "Past weather (1)", "pastWeather1"),
+    ] {
+        assert_eq!(eccodes_key(FXY::new(fxy.0, fxy.1, fxy.2), name), key);
+    }
+    // Unlisted names read `/` as "or"
+    assert_eq!(
+        eccodes_key(FXY::new(0, 8, 12), "Land/sea qualifier"),
+        "landOrSeaQualifier"
+    );
+
+    let file = parse(&two_subset_message()).unwrap();
+    let msg = file.message_at(0).unwrap();
+    let decoder = Decoder::from_message(msg).unwrap();
+    let parsed = decoder.decode_subset(msg, 0).unwrap();
+    let block = parsed.get_key("blockNumber").unwrap();
+    assert_eq!(block.fxy, FXY::new(0, 1, 1));
+    assert_eq!(parsed.get_all_key("airTemperature").len(), 1);
+    assert!(parsed.get_key("noSuchKey").is_none());
+}
+
+#[test]
+fn test_ranked_access() {
+    let bytes = synthetic_message(
+        1,
+        &[(0, 12, 101), (0, 1, 1), (0, 12, 101)],
+        &[(27315, 16), (10, 7), (28315, 16)],
+    );
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+    let decoder = Decoder::from_message(msg).unwrap();
+    let parsed = decoder.decode(msg).unwrap();
+
+    let name = parsed
+        .record(0)
+        .unwrap()
+        .name
+        .as_deref()
+        .unwrap()
+        .to_string();
+    assert_eq!(parsed.ranks(&name), 2);
+    let (second, offset) = parsed.get_ranked(&name, 2).unwrap();
+    assert_eq!(offset, 0);
+    match &second.values {
+        librbufr::BUFRData::Single(value) => {
+            assert!((value.as_f64().unwrap() - 283.15).abs() < 1e-9)
+        }
+        _ => panic!("expected a single value"),
+    }
+    assert!(parsed.get_ranked(&name, 0).is_none());
+    assert!(parsed.get_ranked(&name, 3).is_none());
+}
+
+#[test]
+fn test_ranked_access_compiled_replication() {
+    use librbufr::BUFRData;
+
+    let file = parse(&two_subset_message()).unwrap();
+    let msg = file.message_at(0).unwrap();
+    let decoder = Decoder::from_message(msg).unwrap();
+    let parsed = decoder.decode_subset(msg, 1).unwrap();
+
+    let array = &parsed.record(2).unwrap();
+    assert!(matches!(array.values, BUFRData::Array { .. }));
+    let name = array.name.as_deref().unwrap().to_string();
+    assert_eq!(parsed.ranks(&name), 16);
+
+    let (record, offset) = parsed.get_ranked(&name, 5).unwrap();
+    assert_eq!(record.fxy, array.fxy);
+    assert_eq!(offset, 4);
+    let value = record.values.get(offset).unwrap().as_f64().unwrap();
+    assert!((value - 283.19).abs() < 1e-9);
+    assert!(parsed.get_ranked(&name, 17).is_none());
+
+    let names = parsed.ranked_names();
+    assert_eq!(names.len(), 18);
+    assert_eq!(names[2], format!("#1#{}", name));
+    assert_eq!(names[17], format!("#16#{}", name));
+}
+
+#[test]
+fn test_parsed_serde_roundtrip() {
+    use librbufr::decoder::BUFRParsed;
+
+    let file = parse(&two_subset_message()).unwrap();
+    let msg = file.message_at(0).unwrap();
+    let decoder = Decoder::from_message(msg).unwrap();
+    let parsed = decoder.decode(msg).unwrap().into_owned();
+
+    let bytes = bincode::serialize(&parsed).unwrap();
+    let restored: BUFRParsed<'static> = bincode::deserialize(&bytes).unwrap();
+    assert_eq!(restored.record_count(), parsed.record_count());
+    assert_eq!(format!("{}", restored), format!("{}", parsed));
+}
+
+#[test]
+fn test_message_reader() {
+    use librbufr::parser::MessageReader;
+
+    let first = two_subset_message();
+    let second = synthetic_message(1, &[(0, 1, 1), (0, 1, 2)], &[(10, 7), (100, 10)]);
+    let mut bytes = first.clone();
+    bytes.extend_from_slice(&second);
+
+    let path = std::env::temp_dir().join(format!(
+        "rbufr_test_message_reader_{}.bufr",
+        std::process::id()
+    ));
+    std::fs::write(&path, &bytes).unwrap();
+
+    let mut reader = MessageReader::open(&path).unwrap();
+    assert_eq!(reader.remaining(), 2);
+    let message = reader.next().unwrap().unwrap();
+    assert_eq!(message.as_bytes(), &first[..]);
+    assert_eq!(reader.remaining(), 1);
+    let message = reader.next().unwrap().unwrap();
+    assert_eq!(message.as_bytes(), &second[..]);
+    assert!(reader.next().is_none());
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_builder_tables_path() {
+    let bytes = synthetic_message(1, &[(0, 1, 1)], &[(10, 7)]);
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+
+    let missing = std::env::temp_dir().join(format!("rbufr_test_no_tables_{}", std::process::id()));
+    assert!(
+        Decoder::builder()
+            .message(msg)
+            .tables_path(&missing)
+            .build()
+            .is_err()
+    );
+
+    let tables = std::env::current_dir().unwrap().join("tables");
+    let decoder = Decoder::builder()
+        .message(msg)
+        .tables_path(&tables)
+        .build()
+        .unwrap();
+    let path = decoder.provenance().master_b.path.as_ref().unwrap();
+    assert!(path.starts_with(&tables));
+    assert!(path.exists());
+}
+
+#[test]
+fn test_local_table_search_order() {
+    let local_path = |centre: u8, subcentre: u8, version: u8| {
+        let mut bytes = local_synthetic_message(centre, version, 1, &[(0, 1, 1)], &[(10, 7)]);
+        // low byte of the Section 1 sub-centre
+        bytes[15] = subcentre;
+        let file = parse(&bytes).unwrap();
+        let decoder = Decoder::from_message(file.message_at(0).unwrap()).unwrap();
+        let provenance = decoder.provenance();
+        assert_eq!(provenance.local_b.is_some(), provenance.local_d.is_some());
+        provenance.local_b.as_ref().map(|source| {
+            let path = source.path.as_ref().unwrap();
+            path.file_name().unwrap().to_string_lossy().into_owned()
+        })
+    };
+
+    // The sub-centre's own tables
+    assert_eq!(
+        local_path(255, 254, 4).as_deref(),
+        Some("BUFR_TableB_255_254_4.bufrtbl")
+    );
+    // Those of the centre when the sub-centre has none
+    assert_eq!(
+        local_path(85, 3, 20).as_deref(),
+        Some("BUFR_TableB_85_20.bufrtbl")
+    );
+    // No local tables rather than an error
+    assert_eq!(local_path(98, 0, 3), None);
+}
+
+#[test]
+fn test_master_table_number() {
+    let bytes = synthetic_message(1, &[(0, 1, 1)], &[(10, 7)]);
+    let file = parse(&bytes).unwrap();
+    let table0 = Decoder::from_message(file.message_at(0).unwrap()).unwrap();
+
+    let mut bytes = bytes;
+    // Section 1 octet 4: master table 10, oceanography
+    bytes[11] = 10;
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+
+    // Only master table 0 is bundled
+    assert!(Decoder::from_message(msg).is_err());
+
+    // Stand the bundled tables in for those of master table 10
+    let tables =
+        std::env::temp_dir().join(format!("rbufr_test_master_table_{}", std::process::id()));
+    std::fs::create_dir_all(tables.join("master")).unwrap();
+    let provenance = table0.provenance();
+    for (kind, source) in [("B", &provenance.master_b), ("D", &provenance.master_d)] {
+        let name = format!(
+            "BUFR_Table{}_mt10_{}.bufrtbl",
+            kind,
+            source.version.unwrap()
+        );
+        std::fs::copy(
+            source.path.as_ref().unwrap(),
+            tables.join("master").join(name),
+        )
+        .unwrap();
+    }
+
+    let decoder = Decoder::builder()
+        .message(msg)
+        .tables_path(&tables)
+        .build()
+        .unwrap();
+    let provenance = decoder.provenance();
+    assert_eq!(provenance.master_table, Some(10));
+    let name = provenance
+        .master_b
+        .path
+        .as_ref()
+        .unwrap()
+        .file_name()
+        .unwrap();
+    assert!(name.to_string_lossy().starts_with("BUFR_TableB_mt10_"));
+}
+
+#[test]
+fn test_table_archive() {
+    use librbufr::core::{FXY, archive, prelude::BUFRTableB};
+
+    let bytes = synthetic_message(1, &[(0, 1, 1)], &[(10, 7)]);
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+    let bundled = Decoder::from_message(msg).unwrap();
+
+    let tables = std::env::temp_dir().join(format!("rbufr_test_archive_{}", std::process::id()));
+    std::fs::create_dir_all(tables.join("master")).unwrap();
+    for (kind, source) in [
+        ("B", &bundled.provenance().master_b),
+        ("D", &bundled.provenance().master_d),
+    ] {
+        // The same image stands in for an older version too
+        let version = source.version.unwrap();
+        let path = source.path.as_deref().unwrap();
+        let archive_path = tables.join(format!("master/BUFR_Table{}.bufrarc", kind));
+        archive::pack(&[(version, path), (3, path)], &archive_path).unwrap();
+        assert_eq!(archive::versions(&archive_path).unwrap(), vec![3, version]);
+    }
+
+    let archive_b = tables.join("master/BUFR_TableB.bufrarc");
+    let table: BUFRTableB = BUFRTableB::load_from_archive(&archive_b, 3).unwrap();
+    assert!(table.lookup(&FXY::new(0, 1, 1)).is_some());
+    assert!(BUFRTableB::load_from_archive(&archive_b, 1).is_err());
+
+    // The decoder picks its version out of the archive
+    let decoder = Decoder::builder()
+        .message(msg)
+        .tables_path(&tables)
+        .build()
+        .unwrap();
+    let provenance = decoder.provenance();
+    assert_eq!(
+        provenance.master_b.version,
+        bundled.provenance().master_b.version
+    );
+    assert_eq!(
+        provenance.master_b.path.as_deref(),
+        Some(archive_b.as_path())
+    );
+    assert_eq!(
+        decoder.expand(&[FXY::new(0, 1, 1)]).unwrap(),
+        bundled.expand(&[FXY::new(0, 1, 1)]).unwrap()
+    );
+}
+
+#[test]
+fn test_static_tables() {
+    use librbufr::core::FXY;
+    use librbufr::core::statics::{StaticBEntry, StaticDEntry, StaticTable};
+
+    // Laid out as `gen-ctl codegen` writes them
+    static TABLE_B: StaticTable<StaticBEntry> = StaticTable::new(&[
+        StaticBEntry {
+            fxy: FXY::new(0, 1, 1),
+            element_name: "WMO block number",
+            unit: "Numeric",
+            scale: 0,
+            reference: 0,
+            width_bits: 7,
+        },
+        StaticBEntry {
+            fxy: FXY::new(0, 1, 2),
+            element_name: "WMO station number",
+            unit: "Numeric",
+            scale: 0,
+            reference: 0,
+            width_bits: 10,
+        },
+        StaticBEntry {
+            fxy: FXY::new(0, 12, 101),
+            element_name: "Temperature/air temperature",
+            unit: "K",
+            scale: 2,
+            reference: 0,
+            width_bits: 16,
+        },
+    ]);
+    static TABLE_D: StaticTable<StaticDEntry> = StaticTable::new(&[StaticDEntry {
+        fxy: FXY::new(3, 1, 1),
+        title: Some("WMO block and station numbers"),
+        chain: &[FXY::new(0, 1, 1), FXY::new(0, 1, 2)],
+    }]);
+
+    assert_eq!(TABLE_B.len(), 3);
+    assert_eq!(
+        TABLE_B.lookup(&FXY::new(0, 12, 101)).unwrap().width_bits,
+        16
+    );
+    assert!(TABLE_B.lookup(&FXY::new(0, 12, 1)).is_none());
+    assert_eq!(TABLE_D.lookup(&FXY::new(3, 1, 1)).unwrap().chain.len(), 2);
+
+    // Decoding with them needs no table files
+    let bytes = synthetic_message(
+        1,
+        &[(3, 1, 1), (0, 12, 101)],
+        &[(10, 7), (200, 10), (27315, 16)],
+    );
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+    let decoder = Decoder::builder()
+        .message(msg)
+        .tables(TABLE_B.to_table().unwrap(), TABLE_D.to_table().unwrap())
+        .build()
+        .unwrap();
+    let parsed = decoder.decode(msg).unwrap();
+    let values: Vec<f64> = parsed
+        .records()
+        .filter_map(|record| match &record.values {
+            librbufr::BUFRData::Single(value) => value.as_f64(),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(values.len(), 3);
+    assert_eq!(values[..2], [10.0, 200.0]);
+    assert!((values[2] - 273.15).abs() < 1e-9);
+    assert_eq!(
+        parsed.record(1).unwrap().name.as_deref(),
+        Some("WMO station number")
+    );
+}
+
+#[test]
+fn test_build_support_embed() {
+    use librbufr::core::build_support::EmbedTables;
+
+    let out_dir = std::env::temp_dir().join(format!("rbufr_test_embed_{}", std::process::id()));
+    let module = EmbedTables::new("tables")
+        .master(16)
+        .write(&out_dir)
+        .unwrap();
+    let code = std::fs::read_to_string(module).unwrap();
+    assert!(code.contains("name: \"master/BUFR_TableB_16.bufrtbl\""));
+    assert!(code.contains("pub fn register()"));
+    assert!(
+        out_dir
+            .join("rbufr_tables/master/BUFR_TableD_16.bufrtbl")
+            .exists()
+    );
+    assert!(
+        EmbedTables::new("tables")
+            .master(99)
+            .write(&out_dir)
+            .is_err()
+    );
+}
+
+#[test]
+fn test_table_c_operators() {
+    use librbufr::core::{FXY, table_c};
+
+    let info = table_c::lookup(37).unwrap();
+    assert_eq!(info.name, "Use defined data present bit-map");
+    assert!(info.y255_cancels && !info.consumes_bits);
+    assert!(table_c::lookup(5).unwrap().consumes_bits);
+    assert!(table_c::check(&FXY::new(2, 22, 0)).is_ok());
+    assert!(table_c::check(&FXY::new(2, 22, 1)).is_err());
+    assert!(table_c::check(&FXY::new(2, 99, 0)).is_err());
+    assert!(table_c::check(&FXY::new(2, 5, 4)).is_ok());
+    assert!(table_c::check(&FXY::new(2, 5, 0)).is_err());
+
+    let bytes = synthetic_message(1, &[(0, 1, 1)], &[(10, 7)]);
+    let file = parse(&bytes).unwrap();
+    let decoder = Decoder::from_message(file.message_at(0).unwrap()).unwrap();
+    let expanded = decoder
+        .expand(&[FXY::new(2, 1, 129), FXY::new(0, 1, 1), FXY::new(2, 1, 0)])
+        .unwrap();
+    assert_eq!(expanded[0].name, "Change data width");
+    assert_eq!(expanded[1].width_bits, 8);
+    assert!(decoder.expand(&[FXY::new(2, 99, 0)]).is_err());
+
+    // Operators outside Table C are not silently skipped
+    let bytes = synthetic_message(1, &[(2, 60, 0), (0, 1, 1)], &[(10, 7)]);
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+    let decoder = Decoder::from_message(msg).unwrap();
+    assert!(decoder.decode(msg).is_err());
+}
+
+#[test]
+fn test_dedupe_entries() {
+    use librbufr::core::{FXY, dedupe_entries, tables::BTableEntry};
+
+    let entry = |y: i32, width: u32| BTableEntry {
+        fxy: FXY::new(0, 1, y),
+        class_name_en: "Identification".to_string(),
+        element_name_en: "Station number".to_string(),
+        bufr_unit: "Numeric".to_string(),
+        bufr_scale: 0,
+        bufr_reference_value: 0,
+        bufr_datawidth_bits: width,
+        note_en: None,
+        note_ids: None,
+        status: None,
+        bufr_unit_original: None,
+        crex_unit: None,
+        crex_scale: None,
+        crex_datawidth_char: None,
+    };
+
+    // Identical repeats keep the first definition
+    let kept = dedupe_entries(vec![(2, entry(1, 7)), (3, entry(2, 10)), (4, entry(1, 7))]).unwrap();
+    assert_eq!(kept.len(), 2);
+
+    // Every conflicting definition is reported with its line
+    let err = dedupe_entries(vec![
+        (2, entry(1, 7)),
+        (5, entry(1, 8)),
+        (6, entry(2, 10)),
+        (9, entry(1, 9)),
+        (12, entry(2, 12)),
+    ])
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("0-01-001 (lines 2, 5, 9)"), "{}", err);
+    assert!(err.contains("0-01-002 (lines 6, 12)"), "{}", err);
+}
+
+#[test]
+fn test_unit_normalization() {
+    use librbufr::core::FXY;
+    use librbufr::core::tables::{BTableEntry, TableEntry, normalize_unit};
+
+    assert_eq!(normalize_unit("CODE TABLE"), "Code table");
+    assert_eq!(normalize_unit(" code-table "), "Code table");
+    assert_eq!(normalize_unit("FLAG TABLE"), "Flag table");
+    assert_eq!(normalize_unit("Degree"), "deg");
+    assert_eq!(normalize_unit("DEGREE TRUE"), "Degree true");
+    assert_eq!(normalize_unit("ccitt ia5"), "CCITT IA5");
+    assert_eq!(normalize_unit("m s-1 "), "m s-1");
+
+    let mut entry = BTableEntry {
+        fxy: FXY::new(0, 20, 3),
+        class_name_en: "Observed phenomena".to_string(),
+        element_name_en: "Present weather".to_string(),
+        bufr_unit: "CODE TABLE".to_string(),
+        bufr_scale: 0,
+        bufr_reference_value: 0,
+        bufr_datawidth_bits: 9,
+        note_en: None,
+        note_ids: None,
+        status: None,
+        bufr_unit_original: None,
+        crex_unit: None,
+        crex_scale: None,
+        crex_datawidth_char: None,
+    };
+    entry.normalize();
+    assert_eq!(entry.bufr_unit(), "Code table");
+    assert_eq!(entry.bufr_unit_original(), Some("CODE TABLE"));
+
+    // Canonical units keep no original
+    let mut canonical = entry.clone();
+    canonical.bufr_unit_original = None;
+    canonical.normalize();
+    assert_eq!(canonical.bufr_unit_original(), None);
+}
+
+#[test]
+fn test_btable_crex_fields() {
+    use librbufr::core::tables::{BTable, BTableEntry};
+    use librbufr::core::{FXY, TableConverter, prelude::BUFRTableB};
+    use std::path::Path;
+
+    struct Entries;
+
+    impl TableConverter for Entries {
+        type OutputEntry = BTableEntry;
+        type TableType = BTable;
+
+        fn convert<P: AsRef<Path>>(&self, _: P) -> anyhow::Result<Vec<BTableEntry>> {
+            Ok(vec![BTableEntry {
+                fxy: FXY::new(0, 12, 101),
+                class_name_en: "Temperature".to_string(),
+                element_name_en: "Temperature/air temperature".to_string(),
+                bufr_unit: "K".to_string(),
+                bufr_scale: 2,
+                bufr_reference_value: 0,
+                bufr_datawidth_bits: 16,
+                note_en: None,
+                note_ids: None,
+                status: None,
+                bufr_unit_original: None,
+                crex_unit: Some("C".to_string()),
+                crex_scale: Some(2),
+                crex_datawidth_char: Some(4),
+            }])
+        }
+    }
+
+    let dir = std::env::temp_dir().join(format!("rbufr_crex_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let output = dir.join("BUFR_TableB_1");
+    let table = BUFRTableB::build_from_csv(Entries, output.clone(), output.clone()).unwrap();
+
+    let entry = table.lookup(&FXY::new(0, 12, 101)).unwrap();
+    assert_eq!(entry.crex_unit.as_deref(), Some("C"));
+    assert_eq!(entry.crex_scale.as_ref().map(|s| s.to_native()), Some(2));
+    assert_eq!(
+        entry.crex_datawidth_char.as_ref().map(|w| w.to_native()),
+        Some(4)
+    );
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_table_build_empty() {
+    use librbufr::core::tables::{DTable, DTableEntry};
+    use librbufr::core::{TableConverter, prelude::BUFRTableD};
+    use std::path::Path;
+
+    struct Entries;
+
+    impl TableConverter for Entries {
+        type OutputEntry = DTableEntry;
+        type TableType = DTable;
+
+        fn convert<P: AsRef<Path>>(&self, _: P) -> anyhow::Result<Vec<DTableEntry>> {
+            Ok(vec![])
+        }
+    }
+
+    let dir = std::env::temp_dir().join(format!("rbufr_empty_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let source = dir.join("BUFR_TableD_en_35.csv");
+    let output = dir.join("BUFR_TableD_35");
+    assert!(BUFRTableD::build_from_csv(Entries, source, output).is_err());
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_table_build_metadata() {
+    use librbufr::core::tables::{DTable, DTableEntry};
+    use librbufr::core::{FXY, TableConverter, prelude::BUFRTableD};
+    use std::path::Path;
+
+    struct Entries;
+
+    impl TableConverter for Entries {
+        type OutputEntry = DTableEntry;
+        type TableType = DTable;
+
+        fn convert<P: AsRef<Path>>(&self, _: P) -> anyhow::Result<Vec<DTableEntry>> {
+            Ok(vec![DTableEntry {
+                fxy: FXY::new(3, 1, 1),
+                fxy_chain: vec![FXY::new(0, 1, 1), FXY::new(0, 1, 2)],
+                category: None,
+                category_of_sequences_en: None,
+                title_en: Some("WMO block and station numbers".to_string()),
+                subtitle_en: None,
+                note_en: None,
+                note_ids: None,
+                status: None,
+            }])
+        }
+
+        fn name(&self) -> String {
+            "test entries".to_string()
+        }
+    }
+
+    let dir = std::env::temp_dir().join(format!("rbufr_metadata_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let source = dir.join("BUFR_TableD_en_35.csv");
+    let output = dir.join("BUFR_TableD_35");
+    BUFRTableD::build_from_csv(Entries, source, output.clone()).unwrap();
+
+    let metadata = BUFRTableD::load_from_disk(&output)
+        .unwrap()
+        .metadata()
+        .unwrap();
+    assert_eq!(metadata.source.as_deref(), Some("BUFR_TableD_en_35.csv"));
+    assert_eq!(metadata.converter, "test entries");
+    assert_eq!(metadata.converter_version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(metadata.entry_count, 1);
+    assert_eq!(metadata.wmo_version, Some(35));
+    assert!(metadata.built_at > 0);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_iter_sorted() {
+    use librbufr::core::BUFRKey;
+    use librbufr::core::prelude::BUFRTableB;
+
+    let bytes = synthetic_message(1, &[(0, 1, 1)], &[(10, 7)]);
+    let file = parse(&bytes).unwrap();
+    let decoder = Decoder::from_message(file.message_at(0).unwrap()).unwrap();
+    let path = decoder.provenance().master_b.path.clone().unwrap();
+    let table = BUFRTableB::load_from_disk(&path).unwrap();
+
+    let keys: Vec<(i32, i32, i32)> = table
+        .iter_sorted()
+        .map(|entry| (entry.fxy.f(), entry.fxy.x(), entry.fxy.y()))
+        .collect();
+    assert_eq!(keys.len(), table.get_all_entries().len());
+    assert!(keys.windows(2).all(|pair| pair[0] < pair[1]));
+}
+
+#[test]
+fn test_table_len_and_contains() {
+    use librbufr::core::FXY;
+    use librbufr::core::prelude::BUFRTableB;
+
+    let bytes = synthetic_message(1, &[(0, 1, 1)], &[(10, 7)]);
+    let file = parse(&bytes).unwrap();
+    let decoder = Decoder::from_message(file.message_at(0).unwrap()).unwrap();
+    let path = decoder.provenance().master_b.path.clone().unwrap();
+    let table = BUFRTableB::load_from_disk(&path).unwrap();
+
+    assert_eq!(table.len(), table.get_all_entries().len());
+    assert!(!table.is_empty());
+    assert!(table.contains_key(&FXY::new(0, 1, 1)));
+    let absent = FXY::new(0, 63, 250);
+    assert!(!table.contains_key(&absent));
+}
+
+#[test]
+fn test_lookup_verifies_key() {
+    use librbufr::core::prelude::BUFRTableB;
+    use librbufr::core::{BUFRKey, FXY};
+
+    let bytes = synthetic_message(1, &[(0, 1, 1)], &[(10, 7)]);
+    let file = parse(&bytes).unwrap();
+    let decoder = Decoder::from_message(file.message_at(0).unwrap()).unwrap();
+    let path = decoder.provenance().master_b.path.clone().unwrap();
+    let table = BUFRTableB::load_from_disk(&path).unwrap();
+
+    let entry = table.lookup(&FXY::new(0, 1, 1)).unwrap();
+    assert_eq!((entry.fxy.f(), entry.fxy.x(), entry.fxy.y()), (0, 1, 1));
+    // Absent keys hash to some slot, but never come back as its entry
+    for y in 200..256 {
+        assert!(table.lookup(&FXY::new(0, 63, y)).is_none());
+    }
+}
+
+#[test]
+fn test_signify_character() {
+    use librbufr::core::tables::UNIT_CCITT_IA5;
+    use librbufr::decoder::{BUFRData, SIGNIFY_CHARACTER_NAME, Value};
+
+    let text = |s: &str| s.bytes().map(|b| (b as u64, 8)).collect::<Vec<_>>();
+    let mut values = text("AB");
+    values.push((10, 7));
+    let bytes = synthetic_message(1, &[(2, 5, 2), (0, 1, 1)], &values);
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+    let decoder = Decoder::from_message(msg).unwrap();
+    let parsed = decoder.decode(msg).unwrap();
+
+    let record = parsed.get(SIGNIFY_CHARACTER_NAME).unwrap();
+    assert!(matches!(&record.values, BUFRData::Single(Value::String(s)) if s == "AB"));
+    assert_eq!(record.unit.as_deref(), Some(UNIT_CCITT_IA5));
+    assert!(record.to_string().contains("AB"));
+
+    // Replicated often enough to be compiled, the characters still come out
+    let mut values = vec![];
+    for i in 0..20 {
+        values.extend(text("C"));
+        values.push((i, 7));
+    }
+    let bytes = synthetic_message(1, &[(1, 2, 20), (2, 5, 1), (0, 1, 1)], &values);
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+    let decoder = Decoder::from_message(msg).unwrap();
+    let parsed = decoder.decode(msg).unwrap();
+    assert_eq!(parsed.get_all(SIGNIFY_CHARACTER_NAME).len(), 20);
+    let last = parsed.records().next_back().unwrap();
+    assert!(matches!(last.values, BUFRData::Single(Value::Number(n)) if n == 19.0));
+}
+
+#[test]
+fn test_signify_data_width_skips_unknown() {
+    use librbufr::decoder::{BUFRData, UNKNOWN_ELEMENT_NAME, Value};
+
+    // 0-63-250 is in no table; 2-06-012 says it takes 12 bits
+    let bytes = synthetic_message(
+        1,
+        &[(2, 6, 12), (0, 63, 250), (0, 1, 1)],
+        &[(0xABC, 12), (10, 7)],
+    );
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+    let decoder = Decoder::from_message(msg).unwrap();
+    let parsed = decoder.decode(msg).unwrap();
+
+    let placeholder = parsed.get(UNKNOWN_ELEMENT_NAME).unwrap();
+    assert!(matches!(
+        placeholder.values,
+        BUFRData::Single(Value::Missing)
+    ));
+    assert_eq!(placeholder.width_bits, Some(12));
+    let last = parsed.records().next_back().unwrap();
+    assert!(matches!(last.values, BUFRData::Single(Value::Number(n)) if n == 10.0));
+
+    // Without 2-06 an unknown element is still an error
+    let bytes = synthetic_message(1, &[(0, 63, 250), (0, 1, 1)], &[(0xABC, 12), (10, 7)]);
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+    let decoder = Decoder::from_message(msg).unwrap();
+    assert!(decoder.decode(msg).is_err());
+}
+
+#[test]
+fn test_signify_data_width_expands_unknown() {
+    use librbufr::core::FXY;
+    use librbufr::decoder::{ExpandedKind, UNKNOWN_ELEMENT_NAME};
+
+    let bytes = synthetic_message(
+        1,
+        &[(2, 6, 8), (0, 63, 250), (0, 12, 101)],
+        &[(0xAB, 8), (27315, 16)],
+    );
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+    let decoder = Decoder::from_message(msg).unwrap();
+    assert_eq!(decoder.decode(msg).unwrap().records().len(), 2);
+
+    let expanded = msg.expanded_descriptors(&decoder).unwrap();
+    let elements: Vec<_> = expanded
+        .iter()
+        .filter(|e| e.kind == ExpandedKind::Element)
+        .collect();
+    assert_eq!(elements.len(), 2);
+    assert_eq!(elements[0].fxy, FXY::new(0, 63, 250));
+    assert_eq!(elements[0].name, UNKNOWN_ELEMENT_NAME);
+    assert_eq!(elements[0].width_bits, 8);
+    // The width applies to the unknown element only
+    assert_eq!(elements[1].fxy, FXY::new(0, 12, 101));
+    assert_eq!(elements[1].width_bits, 16);
+
+    assert!(decoder.expand(&[FXY::new(0, 63, 250)]).is_err());
+}
+
+#[test]
+fn test_compiled_character_width() {
+    use librbufr::decoder::{BUFRData, Value};
+
+    let text = |s: &str| s.bytes().map(|b| (b as u64, 8)).collect::<Vec<_>>();
+    let names = |parsed: &librbufr::decoder::BUFRParsed<'_>| -> Vec<String> {
+        parsed
+            .records()
+            .filter(|r| r.fxy.x == 1 && r.fxy.y == 15)
+            .flat_map(|r| match r.values {
+                BUFRData::Repeat(v) => v.into_owned(),
+                BUFRData::Single(v) => vec![v],
+                _ => panic!("expected strings"),
+            })
+            .map(|v| match v {
+                Value::String(s) => s.into_owned(),
+                _ => panic!("expected a string"),
+            })
+            .collect()
+    };
+
+    // 2-08-004 before the replication, names left unaligned by 0-01-001
+    let mut values = vec![];
+    for i in 0..16 {
+        values.push((i, 7));
+        values.extend(text(&format!("S{:<3}", i)));
+    }
+    let bytes = synthetic_message(
+        1,
+        &[(2, 8, 4), (1, 2, 16), (0, 1, 1), (0, 1, 15), (2, 8, 0)],
+        &values,
+    );
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+    let decoder = Decoder::from_message(msg).unwrap();
+    let parsed = decoder.decode(msg).unwrap();
+    assert!(matches!(
+        parsed.record(1).unwrap().values,
+        BUFRData::Repeat(_)
+    ));
+    assert_eq!(names(&parsed)[15], "S15 ");
+
+    // 2-08-YYY set and cancelled inside the body keeps the fast path
+    let mut values = vec![];
+    for i in 0..16 {
+        values.extend(text(&format!("NAME{:02}", i)));
+        values.push((i, 7));
+    }
+    let bytes = synthetic_message(
+        1,
+        &[(1, 4, 16), (2, 8, 6), (0, 1, 15), (2, 8, 0), (0, 1, 1)],
+        &values,
+    );
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+    let decoder = Decoder::from_message(msg).unwrap();
+    let parsed = decoder.decode(msg).unwrap();
+    assert!(matches!(
+        parsed.record(0).unwrap().values,
+        BUFRData::Repeat(_)
+    ));
+    assert_eq!(names(&parsed)[7], "NAME07");
+
+    // Left in effect, it changes later repetitions, which the interpreter reads
+    let mut values = text(&format!("{:<20}", "FIRST"));
+    for i in 1..16 {
+        values.extend(text(&format!("N{:02}", i)));
+    }
+    let bytes = synthetic_message(1, &[(1, 2, 16), (0, 1, 15), (2, 8, 3)], &values);
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+    let decoder = Decoder::from_message(msg).unwrap();
+    let parsed = decoder.decode(msg).unwrap();
+    let names = names(&parsed);
+    assert_eq!(names.len(), 16);
+    assert_eq!(names[0].trim_end(), "FIRST");
+    assert_eq!(names[15], "N15");
+}
+
+#[test]
+fn test_associated_field_significance() {
+    use librbufr::core::FXY;
+    use librbufr::decoder::{BUFRData, Value, associated_field_name};
+
+    // 2-04-002, significance 2, then 2 associated bits before 0-12-101 only
+    let bytes = synthetic_message(
+        1,
+        &[(2, 4, 2), (0, 31, 21), (0, 12, 101), (2, 4, 0), (0, 1, 1)],
+        &[(2, 6), (3, 2), (27315, 16), (10, 7)],
+    );
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+    let decoder = Decoder::from_message(msg).unwrap();
+    let parsed = decoder.decode(msg).unwrap();
+
+    let records: Vec<_> = parsed.records().collect();
+    assert_eq!(records.len(), 4);
+    assert_eq!(records[1].fxy, FXY::new(2, 4, 2));
+    assert_eq!(
+        records[1].name.as_deref(),
+        Some(associated_field_name(Some(2)))
+    );
+    assert!(
+        records[1]
+            .name
+            .as_deref()
+            .unwrap()
+            .contains("2 = 2-BIT INDICATOR OF QUALITY")
+    );
+    assert!(matches!(records[1].values, BUFRData::Single(Value::Number(n)) if n == 3.0));
+    assert!(
+        matches!(records[2].values, BUFRData::Single(Value::Number(n)) if (n - 273.15).abs() < 1e-9)
+    );
+    assert!(matches!(records[3].values, BUFRData::Single(Value::Number(n)) if n == 10.0));
+}
+
+#[test]
+fn test_quality_for() {
+    use librbufr::decoder::{BUFRData, QualityKind, Value};
+
+    let number = |record: &librbufr::decoder::BUFRRecord<'_>| match record.values {
+        BUFRData::Single(Value::Number(n)) => n,
+        _ => panic!("expected a number"),
+    };
+
+    // Three temperatures, a kept bit-map marking the first and last, their
+    // percent confidences, then substituted values reusing the bit-map
+    let bytes = synthetic_message(
+        1,
+        &[
+            (0, 12, 101),
+            (0, 12, 101),
+            (0, 12, 101),
+            (2, 22, 0),
+            (2, 36, 0),
+            (1, 1, 3),
+            (0, 31, 31),
+            (0, 1, 31),
+            (0, 1, 32),
+            (1, 1, 2),
+            (0, 33, 7),
+            (2, 23, 0),
+            (2, 37, 0),
+            (2, 23, 255),
+            (2, 23, 255),
+        ],
+        &[
+            (27315, 16),
+            (27415, 16),
+            (27515, 16),
+            (0, 1),
+            (1, 1),
+            (0, 1),
+            (98, 16),
+            (0, 8),
+            (90, 7),
+            (70, 7),
+            (27300, 16),
+            (27500, 16),
+        ],
+    );
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+    let decoder = Decoder::from_message(msg).unwrap();
+    let parsed = decoder.decode(msg).unwrap();
+
+    let first = parsed.quality_for(0);
+    assert_eq!(first.len(), 2);
+    assert_eq!(number(&first[0]), 90.0);
+    assert!((number(&first[1]) - 273.0).abs() < 1e-9);
+    assert!(parsed.quality_for(1).is_empty());
+    let last = parsed.quality_for(2);
+    assert_eq!(number(&last[0]), 70.0);
+    assert!((number(&last[1]) - 275.0).abs() < 1e-9);
+    let kinds: Vec<_> = parsed.quality_links().iter().map(|l| l.kind).collect();
+    assert_eq!(
+        kinds.iter().filter(|k| **k == QualityKind::Quality).count(),
+        2
+    );
+    assert_eq!(
+        kinds
+            .iter()
+            .filter(|k| **k == QualityKind::Substituted)
+            .count(),
+        2
+    );
+
+    // A bit-map over a compiled array links back to a value inside it
+    let mut values: Vec<(u64, usize)> = (0..20).map(|i| (27315 + i, 16)).collect();
+    values.extend((0..20).map(|i| (u64::from(i != 19), 1)));
+    values.extend([(98, 16), (0, 8), (55, 7)]);
+    let bytes = synthetic_message(
+        1,
+        &[
+            (1, 1, 20),
+            (0, 12, 101),
+            (2, 22, 0),
+            (1, 1, 20),
+            (0, 31, 31),
+            (0, 1, 31),
+            (0, 1, 32),
+            (0, 33, 7),
+        ],
+        &values,
+    );
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+    let decoder = Decoder::from_message(msg).unwrap();
+    let parsed = decoder.decode(msg).unwrap();
+
+    assert!(matches!(
+        parsed.record(0).unwrap().values,
+        BUFRData::Array { .. }
+    ));
+    let link = parsed.quality_links()[0];
+    assert_eq!((link.element, link.offset), (0, 19));
+    assert_eq!(number(&parsed.quality_for(0)[0]), 55.0);
+}
+
+#[test]
+fn test_empty_delayed_replication() {
+    use librbufr::core::FXY;
+    use librbufr::decoder::{BUFRData, EMPTY_REPLICATION_NAME, Value};
+
+    // No cloud layers: a count of 0, then the block number
+    let bytes = synthetic_message(
+        1,
+        &[(1, 2, 0), (0, 31, 1), (0, 20, 11), (0, 20, 13), (0, 1, 1)],
+        &[(0, 8), (10, 7)],
+    );
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+    let decoder = Decoder::from_message(msg).unwrap();
+    let parsed = decoder.decode(msg).unwrap();
+
+    let records: Vec<_> = parsed.records().collect();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].fxy, FXY::new(1, 2, 0));
+    assert_eq!(records[0].name.as_deref(), Some(EMPTY_REPLICATION_NAME));
+    assert!(matches!(&records[0].values, BUFRData::Repeat(v) if v.is_empty()));
+    assert!(matches!(records[1].values, BUFRData::Single(Value::Number(n)) if n == 10.0));
+
+    // Elements-only output has nothing for the empty group
+    let values = decoder.decode_values(msg).unwrap();
+    assert_eq!(values.len(), 1);
+    assert_eq!(values[0].0, FXY::new(0, 1, 1));
+}
+
+#[test]
+fn test_group_markers() {
+    use librbufr::core::FXY;
+    use librbufr::decoder::BUFRData;
+
+    let group = |data: &BUFRData| match *data {
+        BUFRData::Group {
+            begin,
+            iteration,
+            count,
+        } => Some((begin, iteration, count)),
+        _ => None,
+    };
+
+    // Three iterations read one by one, then 16 temperatures compiled
+    let mut values = vec![];
+    for i in 0..3 {
+        values.extend([(i, 7), (27315 + i, 16)]);
+    }
+    values.extend((0..16).map(|i| (27315 + i, 16)));
+    let bytes = synthetic_message(
+        1,
+        &[(1, 2, 3), (0, 1, 1), (0, 12, 101), (1, 1, 16), (0, 12, 101)],
+        &values,
+    );
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+
+    let decoder = Decoder::from_message(msg).unwrap();
+    assert_eq!(decoder.decode(msg).unwrap().records().len(), 7);
+
+    let decoder = Decoder::builder()
+        .message(msg)
+        .group_markers(true)
+        .build()
+        .unwrap();
+    let parsed = decoder.decode(msg).unwrap();
+    let records: Vec<_> = parsed.records().collect();
+    assert_eq!(records.len(), 15);
+    assert_eq!(records[0].fxy, FXY::new(1, 2, 3));
+    assert_eq!(group(&records[0].values), Some((true, 0, 3)));
+    assert_eq!(group(&records[3].values), Some((false, 0, 3)));
+    assert_eq!(group(&records[8].values), Some((true, 2, 3)));
+    assert_eq!(group(&records[11].values), Some((false, 2, 3)));
+    assert_eq!(group(&records[12].values), Some((true, 0, 16)));
+    assert!(matches!(records[13].values, BUFRData::Array { .. }));
+    assert_eq!(group(&records[14].values), Some((false, 15, 16)));
+    assert!(records[12].to_string().contains("1-01-016 1/16"));
+}
+
+#[test]
+fn test_invalid_utf8_text_as_bytes() {
+    use librbufr::decoder::{BUFRData, SIGNIFY_CHARACTER_NAME, Value};
+
+    // Byte-aligned, then after 7 bits of 0-01-001
+    for (descs, values) in [
+        (
+            vec![(2, 5, 2), (0, 1, 1)],
+            vec![(0xFF, 8), (0xFE, 8), (10, 7)],
+        ),
+        (
+            vec![(0, 1, 1), (2, 5, 2), (0, 1, 1)],
+            vec![(3, 7), (0xFF, 8), (0xFE, 8), (10, 7)],
+        ),
+    ] {
+        let bytes = synthetic_message(1, &descs, &values);
+        let file = parse(&bytes).unwrap();
+        let msg = file.message_at(0).unwrap();
+        let decoder = Decoder::from_message(msg).unwrap();
+        let parsed = decoder.decode(msg).unwrap();
+
+        let record = parsed.get(SIGNIFY_CHARACTER_NAME).unwrap();
+        assert!(
+            matches!(&record.values, BUFRData::Single(Value::Bytes(b)) if b[..] == [0xFF, 0xFE])
+        );
+        assert!(record.to_string().contains("0xfffe"));
+        let last = parsed.records().next_back().unwrap();
+        assert!(matches!(last.values, BUFRData::Single(Value::Number(n)) if n == 10.0));
+    }
+}
+
+#[test]
+fn test_decode_error_bit_position() {
+    // The second 0-01-001 starts at bit 7 of the data, after the 4 octets
+    // of Section 4's header
+    let bytes = synthetic_message(1, &[(0, 1, 1), (0, 1, 1)], &[(10, 7), (0, 1)]);
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+    let decoder = Decoder::from_message(msg).unwrap();
+    let Err(err) = decoder.decode(msg) else {
+        panic!("decoded past the end of the data");
+    };
+    let err = err.to_string();
+    assert!(err.contains("at bit 39 of Section 4"), "{}", err);
+}
+
+#[test]
+fn test_decode_error_bit_position_operator() {
+    // 2-05-000 is refused once the 0-01-001 before it has been read
+    let bytes = synthetic_message(1, &[(0, 1, 1), (2, 5, 0)], &[(10, 7)]);
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+    let decoder = Decoder::from_message(msg).unwrap();
+    let Err(err) = decoder.decode(msg) else {
+        panic!("decoded 2-05-000");
+    };
+    let err = err.to_string();
+    assert!(err.contains("2-05-000"), "{}", err);
+    assert!(err.contains("at bit 39 of Section 4"), "{}", err);
+}
+
+#[test]
+fn test_decode_traced() {
+    use librbufr::decoder::{TraceEvent, Value};
+
+    let bytes = synthetic_message(
+        1,
+        &[
+            (0, 1, 1),
+            (2, 1, 130),
+            (0, 1, 2),
+            (2, 1, 0),
+            (1, 1, 2),
+            (0, 1, 1),
+        ],
+        &[(10, 7), (100, 12), (1, 7), (2, 7)],
+    );
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+    let decoder = Decoder::from_message(msg).unwrap();
+    let mut events: Vec<TraceEvent<'static>> = vec![];
+    let mut lines = vec![];
+    let parsed = decoder
+        .decode_traced(msg, |event| {
+            lines.push(event.to_string());
+            events.push(TraceEvent {
+                name: "",
+                value: event.value.clone().map(Value::into_owned),
+                ..event.clone()
+            });
+        })
+        .unwrap();
+    assert_eq!(parsed.records().len(), 4);
+
+    let trace: Vec<_> = events
+        .iter()
+        .map(|e| ((e.fxy.f, e.fxy.x, e.fxy.y), e.width_bits, e.offset))
+        .collect();
+    assert_eq!(
+        trace,
+        [
+            ((0, 1, 1), Some(7), 7),
+            ((2, 1, 130), None, 7),
+            ((0, 1, 2), Some(12), 19),
+            ((2, 1, 0), None, 19),
+            ((1, 1, 2), None, 19),
+            ((0, 1, 1), Some(7), 26),
+            ((0, 1, 1), Some(7), 33),
+        ]
+    );
+    assert_eq!(events[2].raw, Some(100));
+    assert!(matches!(events[4].value, Some(Value::Number(n)) if n == 2.0));
+    assert!(lines[2].contains("0-01-002"), "{}", lines[2]);
+    assert!(lines[2].contains("width=12"), "{}", lines[2]);
+}
+
+#[test]
+fn test_validate_bit_accounting() {
+    let msg_bytes = two_subset_message();
+    let file = parse(&msg_bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+    let decoder = Decoder::from_message(msg).unwrap();
+    let validation = decoder.validate(msg).unwrap();
+    assert_eq!(validation.subsets, 2);
+    assert_eq!(validation.bits_used, 2 * (7 + 10 + 16 * 16));
+    assert!(validation.is_consistent());
+
+    // Four octets too many
+    let mut values = vec![(10, 7), (0, 1)];
+    values.extend([(0, 8); 4]);
+    let bytes = synthetic_message(1, &[(0, 1, 1)], &values);
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+    let validation = decoder.validate(msg).unwrap();
+    assert_eq!(validation.unused_bits(), 33);
+    assert!(!validation.is_consistent());
+
+    // Too few
+    let bytes = synthetic_message(1, &[(0, 1, 1), (0, 1, 2)], &[(10, 7), (0, 1)]);
+    let file = parse(&bytes).unwrap();
+    assert!(decoder.validate(file.message_at(0).unwrap()).is_err());
+}
+
+#[test]
+fn test_validate_compressed() {
+    use librbufr::structs::versions::MessageVersion;
+
+    let bytes = three_compressed_subsets();
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+    assert!(msg.is_compressed());
+    let decoder = Decoder::from_message(msg).unwrap();
+    let validation = decoder.validate(msg).unwrap();
+    assert_eq!(validation.subsets, 3);
+    // R0, NBINC and one increment per subset for every element
+    assert_eq!(
+        validation.bits_used,
+        (7 + 6 + 3 * 2) + (160 + 6 + 3 * 32) + (8 + 6) + (16 + 6) + (16 + 6 + 3 * 4)
+    );
+    assert!(validation.is_consistent());
+
+    // Delayed counts that differ between subsets
+    let bytes = compressed_message(
+        2,
+        &[(1, 1, 0), (0, 31, 1), (0, 1, 1)],
+        &[(1, 8), (1, 6), (0, 1), (1, 1), (10, 7), (0, 6)],
+    );
+    let file = parse(&bytes).unwrap();
+    let err = decoder.validate(file.message_at(0).unwrap()).unwrap_err();
+    assert!(err.to_string().contains("differ"), "{}", err);
+}
+
+#[test]
+fn test_decode_compressed() {
+    use librbufr::decoder::{BUFRData, Value};
+    use std::ops::ControlFlow;
+
+    let bytes = three_compressed_subsets();
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+    let decoder = Decoder::from_message(msg).unwrap();
+
+    // One record per element, holding its value in every subset
+    let parsed = decoder.decode(msg).unwrap();
+    let block = parsed
+        .get_by_fxy(librbufr::core::FXY::new(0, 1, 1))
+        .remove(0);
+    match &block.values {
+        BUFRData::Array { values, missing } => {
+            assert_eq!(values[..2], [10.0, 11.0]);
+            assert!(missing.get(2));
+        }
+        other => panic!("expected an array, got {:?}", other),
+    }
+    match &parsed.get_by_fxy(librbufr::core::FXY::new(0, 1, 15))[0].values {
+        BUFRData::Repeat(names) => {
+            let names: Vec<_> = names.iter().map(|v| v.as_str().unwrap()).collect();
+            assert_eq!(names, ["ABCD", "EFGH", "IJKL"]);
+        }
+        other => panic!("expected a repeat, got {:?}", other),
+    }
+
+    // Visited subset by subset
+    let mut subsets: Vec<Vec<String>> = vec![vec![]];
+    struct Collect<'s>(&'s mut Vec<Vec<String>>);
+    impl librbufr::decoder::DecodeVisitor for Collect<'_> {
+        fn element(&mut self, _: librbufr::core::FXY, value: Value) -> ControlFlow<()> {
+            self.0.last_mut().unwrap().push(value.to_string());
+            ControlFlow::Continue(())
+        }
+        fn end_subset(&mut self, _: usize) -> ControlFlow<()> {
+            self.0.push(vec![]);
+            ControlFlow::Continue(())
+        }
+    }
+    decoder
+        .decode_with(msg, &mut Collect(&mut subsets))
+        .unwrap();
+    subsets.pop();
+    assert_eq!(subsets.len(), 3);
+    assert_eq!(subsets[1][..2], ["11", "EFGH"]);
+    assert!((subsets[1][2].parse::<f64>().unwrap() - 273.15).abs() < 1e-9);
+    assert_eq!(subsets[1][3], "280.05");
+    assert_eq!(subsets[2][..2], ["MISSING", "IJKL"]);
+    assert_eq!(subsets[2][3], "MISSING");
+}
+
+#[test]
+fn test_crafted_input_errors_without_panic() {
+    // Section 3 claiming 3 octets, fewer than its own header
+    let mut bytes = synthetic_message(1, &[(0, 1, 1)], &[(10, 7), (0, 1)]);
+    bytes[30..33].copy_from_slice(&[0, 0, 3]);
+    assert!(parse(&bytes).unwrap().message_at(0).is_none());
+
+    let decode = |descriptors: &[(u8, u8, u8)], values: &[(u64, usize)]| {
+        let bytes = synthetic_message(1, descriptors, values);
+        let file = parse(&bytes).unwrap();
+        let msg = file.message_at(0).unwrap();
+        let decoder = Decoder::from_message(msg).unwrap();
+        decoder.decode(msg).map(|parsed| parsed.records().len())
+    };
+
+    // 2-01-255 widens 0-01-001 to 134 bits
+    assert!(decode(&[(2, 1, 255), (0, 1, 1)], &[(0, 8)]).is_err());
+    // 2-07-255 adds 2550 bits
+    assert!(decode(&[(2, 7, 255), (0, 1, 1)], &[(0, 8)]).is_err());
+    // 2-01-001 narrows 0-01-001 below nothing
+    assert!(decode(&[(2, 1, 1), (0, 1, 1)], &[(0, 8)]).is_ok());
+    // A delayed count far beyond the data
+    assert!(decode(&[(1, 1, 0), (0, 31, 2), (0, 1, 1)], &[(65534, 16), (0, 8)]).is_err());
+    // Delayed replication with nothing after it for the count
+    assert!(decode(&[(1, 1, 0)], &[(0, 8)]).is_err());
+}
+
+#[test]
+fn test_decode_file_and_bytes() {
+    let mut bytes = two_subset_message();
+    bytes.extend(synthetic_message(1, &[(0, 1, 1)], &[(12, 7), (0, 1)]));
+
+    let parsed = librbufr::decode_bytes(&bytes).unwrap();
+    assert_eq!(parsed.len(), 2);
+    assert_eq!(parsed[1].records().len(), 1);
+
+    let path = std::env::temp_dir().join(format!(
+        "rbufr_test_decode_file_{}.bufr",
+        std::process::id()
+    ));
+    std::fs::write(&path, &bytes).unwrap();
+    let from_file = librbufr::decode_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(from_file.len(), 2);
+    assert_eq!(from_file[0].records().len(), parsed[0].records().len());
+}
+
+#[test]
+fn test_decode_iter() {
+    let mut bytes = two_subset_message();
+    // Not enough data for 0-01-002 after 0-01-001
+    bytes.extend(synthetic_message(
+        1,
+        &[(0, 1, 1), (0, 1, 2)],
+        &[(12, 7), (0, 1)],
+    ));
+    bytes.extend(synthetic_message(1, &[(0, 1, 1)], &[(13, 7), (0, 1)]));
+    let file = parse(&bytes).unwrap();
+
+    let results: Vec<_> = file.decode_iter().collect();
+    assert_eq!(results.len(), 3);
+    assert!(results[0].is_ok());
+    assert!(results[1].is_err());
+    let last = results[2].as_ref().ok().unwrap();
+    assert_eq!(last.records().len(), 1);
+}
+
+#[test]
+fn test_decoder_shared_between_threads() {
+    use std::sync::Arc;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Decoder>();
+
+    // Long enough to compile a layout, which every thread then shares
+    let bytes = two_subset_message();
+    let file = parse(&bytes).unwrap();
+    let decoder = Arc::new(Decoder::from_message(file.message_at(0).unwrap()).unwrap());
+    let expected = decoder
+        .decode(file.message_at(0).unwrap())
+        .unwrap()
+        .records()
+        .len();
+
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let decoder = Arc::clone(&decoder);
+            let bytes = bytes.clone();
+            std::thread::spawn(move || {
+                let file = parse(&bytes).unwrap();
+                let msg = file.message_at(0).unwrap();
+                decoder.decode(msg).unwrap().records().len()
+            })
+        })
+        .collect();
+    for handle in handles {
+        assert_eq!(handle.join().unwrap(), expected);
+    }
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_decode_all_parallel() {
+    use librbufr::{BUFRData, Value};
+
+    let mut bytes = vec![];
+    for block in 0..20 {
+        bytes.extend(synthetic_message(1, &[(0, 1, 1)], &[(block, 7), (0, 1)]));
+    }
+    bytes.extend(synthetic_message(
+        1,
+        &[(0, 1, 1), (0, 1, 2)],
+        &[(0, 7), (0, 1)],
+    ));
+    let file = parse(&bytes).unwrap();
+
+    let results = file.decode_all_parallel();
+    assert_eq!(results.len(), 21);
+    for (block, result) in results[..20].iter().enumerate() {
+        let parsed = result.as_ref().ok().unwrap();
+        let value = &parsed.record(0).unwrap().values;
+        assert!(matches!(value, BUFRData::Single(Value::Number(n)) if *n == block as f64));
+    }
+    assert!(results[20].is_err());
+}
+
+#[test]
+fn test_records_share_buffers() {
+    use librbufr::core::FXY;
+    use librbufr::decoder::BUFRData;
+    use std::borrow::Cow;
+
+    let bytes = three_compressed_subsets();
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+    let decoder = Decoder::from_message(msg).unwrap();
+    let parsed = decoder.decode(msg).unwrap();
+
+    // Both temperatures of every subset, one array after the other
+    let temperatures = parsed.get_by_fxy(FXY::new(0, 12, 101));
+    let (
+        BUFRData::Array {
+            values: Cow::Borrowed(first),
+            ..
+        },
+        BUFRData::Array {
+            values: Cow::Borrowed(second),
+            ..
+        },
+    ) = (&temperatures[0].values, &temperatures[1].values)
+    else {
+        panic!("arrays should borrow from the parsed message");
+    };
+    assert_eq!(first.as_ptr_range().end, second.as_ptr());
+    assert!(matches!(temperatures[0].name, Some(Cow::Borrowed(_))));
+
+    let record = parsed.record(0).unwrap().into_owned();
+    assert!(matches!(record.name, Some(Cow::Owned(_))));
+    assert!(matches!(
+        record.values,
+        BUFRData::Array {
+            values: Cow::Owned(_),
+            ..
+        }
+    ));
+
+    let owned = parsed.into_owned();
+    let moved = decoder.decode(msg).unwrap().into_static();
+    for copy in [&owned, &moved] {
+        assert_eq!(copy.record_count(), parsed.record_count());
+        for (a, b) in copy.records().zip(parsed.records()) {
+            assert_eq!(a.name, b.name);
+            assert_eq!(a.to_string(), b.to_string());
+        }
+    }
+}
+
+#[test]
+fn test_take_string_borrows_when_aligned() {
+    use librbufr::decoder::{BUFRData, BitInput, SIGNIFY_CHARACTER_NAME, Value};
+    use std::borrow::Cow;
+
+    let bytes = *b"AB \0";
+    let mut input = BitInput::new(&bytes);
+    assert!(matches!(input.take_string(2).unwrap(), Cow::Borrowed("AB")));
+    assert!(matches!(
+        input.take_string_trimmed(2).unwrap(),
+        Cow::Borrowed("")
+    ));
+
+    // "AB  " four bits in
+    let bytes = [0x04, 0x14, 0x22, 0x02, 0x00];
+    let mut input = BitInput::new(&bytes);
+    input.seek_to(4).unwrap();
+    let unaligned = input.take_string_trimmed(4).unwrap();
+    assert!(matches!(&unaligned, Cow::Owned(s) if s == "AB"));
+
+    let bytes = synthetic_message(
+        1,
+        &[(2, 5, 4), (0, 1, 1)],
+        &[(u32::from_be_bytes(*b"AB  ") as u64, 32), (10, 7)],
+    );
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+    for (trim, expected) in [(false, "AB  "), (true, "AB")] {
+        let decoder = Decoder::from_message(msg).unwrap().with_trimmed_text(trim);
+        let parsed = decoder.decode(msg).unwrap();
+        let record = parsed.get(SIGNIFY_CHARACTER_NAME).unwrap();
+        // Byte-aligned in Section 4, the decoded string is borrowed from it
+        assert!(matches!(
+            &record.values,
+            BUFRData::Single(Value::String(Cow::Borrowed(s))) if *s == expected
+        ));
+    }
+}
+
+#[test]
+fn test_available_tables() {
+    use librbufr::tables::{LocalTable, MasterTable, available_tables};
+
+    let dir = std::env::temp_dir().join(format!(
+        "rbufr_test_available_tables_{}",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("master")).unwrap();
+    std::fs::create_dir_all(dir.join("local")).unwrap();
+    for name in [
+        "master/BUFR_TableB_14.bufrtbl",
+        "master/BUFR_TableD_14.bufrtbl",
+        // No Table D
+        "master/BUFR_TableB_15.bufrtbl",
+        "master/BUFR_TableB_mt10_3.bufrtbl",
+        "master/BUFR_TableD_mt10_3.bufrtbl",
+        "master/README",
+        "local/BUFR_TableB_98_0_2.bufrtbl",
+        "local/BUFR_TableD_98_0_2.bufrtbl",
+        "local/BUFR_TableB_7_1.bufrtbl",
+        "local/BUFR_TableD_7_1.bufrtbl",
+    ] {
+        std::fs::write(dir.join(name), b"").unwrap();
+    }
+
+    let tables = available_tables(Some(&dir));
+    assert_eq!(
+        tables.master,
+        [MasterTable::new(14), MasterTable::with_number(10, 3)]
+    );
+    assert_eq!(
+        tables.local,
+        [LocalTable::new(7, None, 1), LocalTable::new(98, Some(0), 2)]
+    );
+
+    let bundled = available_tables(Some(&std::env::current_dir().unwrap().join("tables")));
+    assert!(bundled.master.contains(&MasterTable::new(16)));
+    assert!(
+        available_tables(Some(&dir.join("missing")))
+            .master
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_category_name() {
+    use librbufr::core::table_a::category_name;
+
+    let bytes = synthetic_message(1, &[(0, 1, 1)], &[(12, 7), (0, 1)]);
+    let file = parse(&bytes).unwrap();
+    let msg = file.message_at(0).unwrap();
+    assert_eq!(msg.category_name(), Some("Surface data - land"));
+
+    assert_eq!(category_name(6), Some("Radar data"));
+    assert_eq!(category_name(245), Some("For experimental use"));
+    assert_eq!(category_name(50), None);
+}
+
+#[test]
+fn test_file_index() {
+    use librbufr::index::FileIndex;
+
+    let first = two_subset_message();
+    let mut bytes = first.clone();
+    bytes.extend(b"junk");
+    bytes.extend(synthetic_message(1, &[(0, 1, 1)], &[(13, 7), (0, 1)]));
+    let path =
+        std::env::temp_dir().join(format!("rbufr_test_file_index_{}.bufr", std::process::id()));
+    std::fs::write(&path, &bytes).unwrap();
+
+    let index = FileIndex::build(&path).unwrap();
+    assert_eq!(index.entries.len(), 2);
+    assert_eq!(index.entries[0].offset, 0);
+    assert_eq!(index.entries[0].length, first.len() as u64);
+    assert_eq!(index.entries[0].subsets, 2);
+    assert_eq!(index.entries[1].offset, first.len() as u64 + 4);
+    assert_eq!(index.entries[1].header.centre, 98);
+
+    let saved = path.with_extension("bufridx");
+    index.save(&saved).unwrap();
+    assert_eq!(FileIndex::load(&saved).unwrap(), index);
+    assert!(FileIndex::load(&path).is_err());
+}
+
+#[test]
+fn test_fxy_from_fxxyyy() {
+    use librbufr::core::FXY;
+
+    assert_eq!(FXY::from_fxxyyy("001101").unwrap(), FXY::new(0, 1, 101));
+    assert_eq!(FXY::from_fxxyyy("012101").unwrap(), FXY::new(0, 12, 101));
+    assert_eq!(FXY::from_fxxyyy("312001").unwrap(), FXY::new(3, 12, 1));
+    for invalid in [
+        "1101", "0011010", "0-1-101", "+01101", "00110é", "01210A", "END",
+    ] {
+        assert!(FXY::from_fxxyyy(invalid).is_err(), "{}", invalid);
+    }
+}
@@ -0,0 +1,90 @@
+//! fnmatch patterns, as `get_record` takes them
+use regex::Regex;
+
+/// The regex of an fnmatch pattern: `*` stands for any text, `?` for
+/// any character, `[seq]` for a character of `seq` and `[!seq]` for one
+/// outside it
+pub(crate) fn fnmatch_regex(pattern: &str, case_sensitive: bool) -> Result<Regex, regex::Error> {
+    let mut regex = String::from(if case_sensitive { "^" } else { "(?i)^" });
+    let mut rest = pattern;
+    while let Some(c) = rest.chars().next() {
+        rest = &rest[c.len_utf8()..];
+        match c {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            '[' => {
+                let negated = rest.starts_with('!');
+                let set = if negated { &rest[1..] } else { rest };
+                // A `]` first belongs to the set; without a closing one
+                // the `[` is literal
+                match set.char_indices().skip(1).find(|&(_, c)| c == ']') {
+                    Some((end, _)) => {
+                        regex.push('[');
+                        if negated {
+                            regex.push('^');
+                        }
+                        for c in set[..end].chars() {
+                            if matches!(c, '\\' | '[' | ']' | '^' | '&' | '~') {
+                                regex.push('\\');
+                            }
+                            regex.push(c);
+                        }
+                        regex.push(']');
+                        rest = &set[end + 1..];
+                    }
+                    None => regex.push_str("\\["),
+                }
+            }
+            c => regex.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+        }
+    }
+    regex.push('$');
+    Regex::new(&regex)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fnmatch_regex;
+
+    fn matches(pattern: &str, name: &str) -> bool {
+        fnmatch_regex(pattern, true).unwrap().is_match(name)
+    }
+
+    #[test]
+    fn test_wildcards() {
+        assert!(matches("*TEMPERATURE*", "AIR TEMPERATURE AT 2M"));
+        assert!(matches("*", ""));
+        assert!(!matches("TEMPERATURE*", "AIR TEMPERATURE"));
+        assert!(matches("HEIGHT?", "HEIGHT2"));
+        assert!(!matches("HEIGHT?", "HEIGHT"));
+        assert!(!matches("HEIGHT?", "HEIGHT22"));
+        // Anything else is literal
+        assert!(matches("PRESSURE (HPA).", "PRESSURE (HPA)."));
+        assert!(!matches("PRESSURE (HPA).", "PRESSURE (HPA)X"));
+    }
+
+    #[test]
+    fn test_sets() {
+        assert!(matches("LEVEL [12]", "LEVEL 1"));
+        assert!(!matches("LEVEL [12]", "LEVEL 3"));
+        assert!(matches("LEVEL [!12]", "LEVEL 3"));
+        assert!(!matches("LEVEL [!12]", "LEVEL 2"));
+        // A leading `]` is a member, not the end of the set
+        assert!(matches("[]A]", "]"));
+        assert!(matches("[]A]", "A"));
+        assert!(!matches("[!]A]", "]"));
+        assert!(matches("[!]A]", "B"));
+        // Without a closing `]` the `[` is literal
+        assert!(matches("LEVEL [1", "LEVEL [1"));
+        assert!(!matches("LEVEL [1", "LEVEL 1"));
+    }
+
+    #[test]
+    fn test_case_folding() {
+        assert!(!matches("*temperature*", "AIR TEMPERATURE"));
+        let folded = fnmatch_regex("*temperature*", false).unwrap();
+        assert!(folded.is_match("AIR TEMPERATURE"));
+        let folded = fnmatch_regex("[a-c]?", false).unwrap();
+        assert!(folded.is_match("B1"));
+    }
+}
@@ -1,19 +1,36 @@
 use pyo3::prelude::*;
+
+mod fnmatch;
+
 #[pymodule]
 mod _core {
+    use crate::fnmatch::fnmatch_regex;
+    use chrono::NaiveDateTime;
     use librbufr::{
         Decoder,
         block::{BUFRFile as IB, MessageBlock as IM},
-        decoder::{BUFRParsed as _BUFRParsed, BUFRRecord as _BUFRRecord},
+        decoder::{
+            BUFRParsed as _BUFRParsed, BUFRRecord as _BUFRRecord, MISS_VAL, MissingMask,
+            MissingValues,
+        },
         errors::Error,
-        get_tables_base_path, parse, set_tables_base_path,
+        get_tables_base_path, parse,
+        parser::{FileSource, MessageReader},
+        set_tables_base_path,
+        tables::available_tables as _available_tables,
     };
-    use pyo3::{IntoPyObjectExt, prelude::*, types::PyList};
+    use pyo3::{
+        IntoPyObjectExt,
+        prelude::*,
+        types::{PyDict, PyList, PySlice},
+    };
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
 
     #[pyfunction]
-    fn set_tables_path(path: &str) -> PyResult<()> {
-        set_tables_base_path(path);
-        Ok(())
+    fn set_tables_path(path: &str) -> PyResult<bool> {
+        Ok(set_tables_base_path(path))
     }
 
     #[pyfunction]
@@ -22,14 +39,76 @@ mod _core {
         Ok(path.to_string_lossy().to_string())
     }
 
+    /// Master table 0 versions and local tables (centre, sub-centre, version)
+    /// with both a Table B and a Table D under `path`, or under the tables
+    /// path when `None`
+    #[pyfunction]
+    #[pyo3(signature = (path=None))]
+    fn available_tables(py: Python<'_>, path: Option<PathBuf>) -> PyResult<Bound<'_, PyDict>> {
+        let tables = _available_tables(path.as_deref());
+        let master: Vec<u8> = tables
+            .master
+            .iter()
+            .filter(|table| table.number() == 0)
+            .map(|table| table.version())
+            .collect();
+        let local: Vec<(u16, Option<u16>, u8)> = tables
+            .local
+            .iter()
+            .map(|table| (table.centre(), table.subcentre(), table.version()))
+            .collect();
+
+        let dict = PyDict::new(py);
+        dict.set_item("master", master)?;
+        dict.set_item("local", local)?;
+        Ok(dict)
+    }
+
+    /// Open a BUFR file whose messages are read lazily, as they are iterated
+    #[pyfunction]
+    #[pyo3(name = "open")]
+    fn open_file(path: PathBuf) -> PyResult<BUFRReader> {
+        let reader = MessageReader::open(&path).map_err(|e| {
+            PyErr::new::<pyo3::exceptions::PyIOError, _>(format!(
+                "Error opening {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        Ok(BUFRReader {
+            reader: Some(reader),
+        })
+    }
+
     #[pyclass]
-    struct BUFRDecoder {}
+    struct BUFRDecoder {
+        missing_values: MissingValues,
+        // return arrays as numpy masked arrays
+        masked: bool,
+        // overrides the global tables path for this decoder
+        tables_path: Option<PathBuf>,
+    }
 
     #[pymethods]
     impl BUFRDecoder {
         #[new]
-        fn new() -> Self {
-            BUFRDecoder {}
+        #[pyo3(signature = (missing = "sentinel", sentinel = MISS_VAL, tables_path = None))]
+        fn new(missing: &str, sentinel: f64, tables_path: Option<PathBuf>) -> PyResult<Self> {
+            let missing_values = match missing {
+                "sentinel" => MissingValues::Sentinel(sentinel),
+                "nan" | "mask" => MissingValues::Nan,
+                _ => {
+                    return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                        "Unknown missing value representation: {}",
+                        missing
+                    )));
+                }
+            };
+            Ok(BUFRDecoder {
+                missing_values,
+                masked: missing == "mask",
+                tables_path,
+            })
         }
 
         fn decode(&self, bytes: &[u8]) -> PyResult<BUFRFile> {
@@ -52,7 +131,70 @@ mod _core {
                 ),
             })?;
 
-            Ok(BUFRFile(parsed, 0))
+            Ok(BUFRFile::new(parsed))
+        }
+
+        /// Decode every message of `file`, without holding the GIL
+        ///
+        /// `progress` is called with `(done, total)` after each message; an
+        /// exception it raises stops decoding and is raised from here.
+        #[pyo3(signature = (file, parallel = false, progress = None))]
+        fn decode_all(
+            &self,
+            py: Python<'_>,
+            file: &BUFRFile,
+            parallel: bool,
+            progress: Option<Py<PyAny>>,
+        ) -> PyResult<Vec<BUFRParsed>> {
+            let threads = worker_threads(parallel);
+            let total = file.indices.len();
+            let done = AtomicUsize::new(0);
+            let failed: Mutex<Option<PyErr>> = Mutex::new(None);
+            let report = || {
+                let done = done.fetch_add(1, Ordering::Relaxed) + 1;
+                let Some(progress) = &progress else {
+                    return true;
+                };
+                Python::attach(|py| {
+                    // other threads stop too once the callback has raised
+                    if failed.lock().unwrap().is_some() {
+                        return false;
+                    }
+                    match progress.call1(py, (done, total)) {
+                        Ok(_) => true,
+                        Err(e) => {
+                            failed.lock().unwrap().get_or_insert(e);
+                            false
+                        }
+                    }
+                })
+            };
+
+            let parsed =
+                py.detach(|| self.decode_many(&file.file, &file.indices, threads, &report));
+            if let Some(e) = failed.into_inner().unwrap() {
+                return Err(e);
+            }
+            self.wrap_all(parsed)
+        }
+
+        /// Decode the messages of `file` at `indices`, in that order, in one
+        /// call without holding the GIL; negative indices count from the end
+        #[pyo3(signature = (file, indices, parallel = false))]
+        fn parse_messages(
+            &self,
+            py: Python<'_>,
+            file: &BUFRFile,
+            indices: Vec<isize>,
+            parallel: bool,
+        ) -> PyResult<Vec<BUFRParsed>> {
+            let positions = indices
+                .into_iter()
+                .map(|index| resolve_index(index, file.indices.len()).map(|i| file.indices[i]))
+                .collect::<PyResult<Vec<_>>>()?;
+            let threads = worker_threads(parallel);
+            let parsed = py.detach(|| self.decode_many(&file.file, &positions, threads, &|| true));
+            self.wrap_all(parsed)
         }
 
         fn parse_message(&self, message: &BUFRMessage) -> PyResult<BUFRParsed> {
@@ -66,60 +208,262 @@ mod _core {
     }
 
     impl BUFRDecoder {
+        fn wrap_all(
+            &self,
+            parsed: librbufr::errors::Result<Vec<_BUFRParsed<'static>>>,
+        ) -> PyResult<Vec<BUFRParsed>> {
+            let parsed = parsed.map_err(|e| {
+                PyErr::new::<pyo3::exceptions::PyException, _>(format!(
+                    "Error parsing BUFR message: {}",
+                    e
+                ))
+            })?;
+            Ok(parsed
+                .into_iter()
+                .map(|inner| BUFRParsed {
+                    inner,
+                    iter_index: 0,
+                    masked: self.masked,
+                })
+                .collect())
+        }
+
         fn _parse_message(&self, message: &BUFRMessage) -> librbufr::errors::Result<BUFRParsed> {
-            let _message = &message.message;
-            let mut decoder = Decoder::from_message(_message)?;
-            let record = decoder.decode(_message)?.into_owned();
             Ok(BUFRParsed {
-                inner: record,
+                inner: self.decode_one(&message.message)?,
                 iter_index: 0,
+                masked: self.masked,
             })
         }
+
+        fn decode_one(&self, message: &IM) -> librbufr::errors::Result<_BUFRParsed<'static>> {
+            let mut builder = Decoder::builder()
+                .message(message)
+                .missing_values(self.missing_values);
+            if let Some(path) = &self.tables_path {
+                builder = builder.tables_path(path);
+            }
+            let decoder = builder.build()?;
+            Ok(decoder.decode(message)?.into_owned())
+        }
+
+        /// Decode `indices` of `file` in order, split over up to `threads`
+        /// threads, calling `report` after each message until it returns
+        /// false
+        fn decode_many(
+            &self,
+            file: &IB,
+            indices: &[usize],
+            threads: usize,
+            report: &(dyn Fn() -> bool + Sync),
+        ) -> librbufr::errors::Result<Vec<_BUFRParsed<'static>>> {
+            let decode = |chunk: &[usize]| {
+                chunk
+                    .iter()
+                    .map(|&idx| {
+                        let parsed = self.decode_one(file.message_at(idx).unwrap())?;
+                        if report() {
+                            Ok(parsed)
+                        } else {
+                            Err(Error::ParseError("Decoding cancelled".to_string()))
+                        }
+                    })
+                    .collect::<librbufr::errors::Result<Vec<_>>>()
+            };
+            if threads <= 1 || indices.len() <= 1 {
+                return decode(indices);
+            }
+
+            let chunk_size = indices.len().div_ceil(threads);
+            std::thread::scope(|scope| {
+                let handles: Vec<_> = indices
+                    .chunks(chunk_size)
+                    .map(|chunk| scope.spawn(move || decode(chunk)))
+                    .collect();
+                let mut parsed = Vec::with_capacity(indices.len());
+                for handle in handles {
+                    parsed.extend(handle.join().expect("decoding thread panicked")?);
+                }
+                Ok(parsed)
+            })
+        }
+    }
+
+    /// Threads to decode over: every core when `parallel`, else one
+    fn worker_threads(parallel: bool) -> usize {
+        if parallel {
+            std::thread::available_parallelism().map_or(1, |n| n.get())
+        } else {
+            1
+        }
+    }
+
+    /// An open BUFR file, also a context manager closing it on exit
+    #[pyclass]
+    struct BUFRReader {
+        // `None` once closed
+        reader: Option<MessageReader<FileSource>>,
+    }
+
+    #[pymethods]
+    impl BUFRReader {
+        fn __repr__(&self) -> String {
+            match &self.reader {
+                Some(reader) => format!("BUFRReader with {} messages left", reader.remaining()),
+                None => "BUFRReader (closed)".to_string(),
+            }
+        }
+
+        fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+            slf
+        }
+
+        #[pyo3(signature = (*_args))]
+        fn __exit__(&mut self, _args: &Bound<'_, pyo3::types::PyTuple>) -> bool {
+            self.close();
+            false
+        }
+
+        fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+            slf
+        }
+
+        /// The next message that parses; unreadable messages are skipped as
+        /// `BUFRDecoder.decode` skips them
+        fn __next__(&mut self) -> PyResult<Option<BUFRMessage>> {
+            let reader = self.reader.as_mut().ok_or_else(|| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>("I/O operation on closed file")
+            })?;
+            Ok(reader
+                .find_map(|message| message.ok())
+                .map(|message| BUFRMessage { message }))
+        }
+
+        /// Release the file; further iteration raises `ValueError`
+        fn close(&mut self) {
+            self.reader = None;
+        }
+
+        #[getter]
+        fn closed(&self) -> bool {
+            self.reader.is_none()
+        }
     }
 
+    /// Messages of a parsed file, or a view on some of them; views share the
+    /// parsed messages rather than copying them
     #[pyclass]
-    struct BUFRFile(IB, usize);
+    struct BUFRFile {
+        file: Arc<IB>,
+        // positions in `file` of the messages this view holds, in order
+        indices: Vec<usize>,
+        iter_index: usize,
+    }
 
     #[pymethods]
     impl BUFRFile {
         fn __repr__(&self) -> String {
-            format!("BUFRFile with {} messages", self.0.message_count())
+            format!("BUFRFile with {} messages", self.indices.len())
         }
 
         fn __len__(&self) -> usize {
-            self.0.message_count()
+            self.indices.len()
         }
 
         fn __iter__(mut slf: PyRefMut<'_, Self>) -> PyRefMut<'_, Self> {
-            slf.1 = 0;
+            slf.iter_index = 0;
             slf
         }
 
         fn __next__(mut slf: PyRefMut<'_, Self>) -> Option<BUFRMessage> {
-            let current_index = slf.1;
-            let message_count = slf.0.message_count();
+            let current_index = slf.iter_index;
 
-            if current_index < message_count {
-                slf.1 += 1;
-                let message = slf.0.message_at(current_index).unwrap().clone();
-                Some(BUFRMessage { message })
+            if current_index < slf.indices.len() {
+                slf.iter_index += 1;
+                Some(slf.message(current_index))
             } else {
                 None
             }
         }
 
+        fn __getitem__(&self, py: Python<'_>, index: Index<'_>) -> PyResult<Py<PyAny>> {
+            match index {
+                Index::Int(index) => {
+                    let idx = resolve_index(index, self.indices.len())?;
+                    self.message(idx).into_py_any(py)
+                }
+                Index::Slice(slice) => {
+                    let indices = slice_positions(&slice, self.indices.len())?
+                        .into_iter()
+                        .map(|idx| self.indices[idx])
+                        .collect();
+                    self.view(indices).into_py_any(py)
+                }
+            }
+        }
+
         fn message_count(&self) -> usize {
-            self.0.message_count()
+            self.indices.len()
         }
 
         fn get_message(&self, index: usize) -> PyResult<BUFRMessage> {
-            let message = self.0.message_at(index).ok_or_else(|| {
-                PyErr::new::<pyo3::exceptions::PyIndexError, _>("Message index out of range")
-            })?;
+            if index >= self.indices.len() {
+                return Err(PyErr::new::<pyo3::exceptions::PyIndexError, _>(
+                    "Message index out of range",
+                ));
+            }
+            Ok(self.message(index))
+        }
 
-            Ok(BUFRMessage {
-                message: message.clone(),
-            })
+        /// Messages whose Section 1 matches every given criterion, without
+        /// decoding their data
+        #[pyo3(signature = (category = None, centre = None, since = None, until = None))]
+        fn filter(
+            &self,
+            category: Option<u8>,
+            centre: Option<u16>,
+            since: Option<NaiveDateTime>,
+            until: Option<NaiveDateTime>,
+        ) -> BUFRFile {
+            let indices = self
+                .indices
+                .iter()
+                .copied()
+                .filter(|&idx| {
+                    let message = self.file.message_at(idx).unwrap();
+                    let header = message.header();
+                    let time = message.obs_time();
+                    category.is_none_or(|c| header.data_category == c)
+                        && centre.is_none_or(|c| header.centre == c)
+                        && since.is_none_or(|t| time.is_some_and(|time| time >= t))
+                        && until.is_none_or(|t| time.is_some_and(|time| time < t))
+                })
+                .collect();
+            self.view(indices)
+        }
+    }
+
+    impl BUFRFile {
+        fn new(file: IB) -> Self {
+            let indices = (0..file.message_count()).collect();
+            BUFRFile {
+                file: Arc::new(file),
+                indices,
+                iter_index: 0,
+            }
+        }
+
+        fn view(&self, indices: Vec<usize>) -> Self {
+            BUFRFile {
+                file: Arc::clone(&self.file),
+                indices,
+                iter_index: 0,
+            }
+        }
+
+        fn message(&self, index: usize) -> BUFRMessage {
+            let message = self.file.message_at(self.indices[index]).unwrap().clone();
+            BUFRMessage { message }
         }
     }
 
@@ -130,14 +474,50 @@ mod _core {
 
     #[pymethods]
     impl BUFRMessage {
+        /// The first message in `data`; with `__getnewargs__` and the state
+        /// methods, this is what pickling goes through
+        #[new]
+        fn new(data: &[u8]) -> PyResult<Self> {
+            Ok(BUFRMessage {
+                message: first_message(data)?,
+            })
+        }
+
         fn __repr__(&self) -> String {
             format!("{}", self.message)
         }
 
+        fn __getnewargs__(&self) -> (Vec<u8>,) {
+            (self.message.as_bytes().to_vec(),)
+        }
+
+        fn __getstate__(&self) -> Vec<u8> {
+            self.message.as_bytes().to_vec()
+        }
+
+        fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+            self.message = first_message(state)?;
+            Ok(())
+        }
+
         fn version(&self) -> u8 {
             self.message.version()
         }
 
+        /// The typical date and time of the data, from Section 1
+        fn datetime(&self) -> Option<NaiveDateTime> {
+            self.message.obs_time()
+        }
+
+        /// The Table A name of the data category, `None` when reserved
+        fn category_name(&self) -> Option<&'static str> {
+            self.message.category_name()
+        }
+
+        fn section1(&self) -> Section1 {
+            Section1::from_message(&self.message)
+        }
+
         fn section2(&self) -> Option<Section2> {
             self.message
                 .section2()
@@ -145,6 +525,158 @@ mod _core {
         }
     }
 
+    fn first_message(data: &[u8]) -> PyResult<IM> {
+        let value_error = |msg: String| PyErr::new::<pyo3::exceptions::PyValueError, _>(msg);
+        let file = parse(data).map_err(|e| value_error(format!("Parse Error: {}", e)))?;
+        file.message_at(0)
+            .cloned()
+            .ok_or_else(|| value_error("No BUFR message in data".to_string()))
+    }
+
+    /// The Section 1 fields of every edition; those an edition lacks are
+    /// `None`
+    #[pyclass(get_all)]
+    struct Section1 {
+        edition: u8,
+        master_table: Option<u8>,
+        centre: u16,
+        subcentre: Option<u16>,
+        update_sequence_number: u8,
+        optional_section_present: bool,
+        data_category: u8,
+        data_subcategory: u8,
+        local_subcategory: Option<u8>,
+        master_table_version: u8,
+        local_table_version: u8,
+        /// As stored: the year of century before edition 4
+        year: u16,
+        month: u8,
+        day: u8,
+        hour: u8,
+        minute: u8,
+        second: Option<u8>,
+        /// The typical time, with the century of older editions resolved
+        datetime: Option<NaiveDateTime>,
+    }
+
+    #[pymethods]
+    impl Section1 {
+        fn __repr__(&self) -> String {
+            format!(
+                "Section1(edition={}, centre={}, category={}, master_table_version={}, datetime={})",
+                self.edition,
+                self.centre,
+                self.data_category,
+                self.master_table_version,
+                self.datetime
+                    .map(|t| t.to_string())
+                    .unwrap_or_else(|| "None".to_string())
+            )
+        }
+    }
+
+    impl Section1 {
+        fn from_message(message: &IM) -> Self {
+            use librbufr::structs::versions::BUFRMessage::*;
+            let datetime = message.obs_time();
+            let edition = message.version();
+            match &**message {
+                V0(m) | V1(m) => {
+                    let s = &m.section1;
+                    Section1 {
+                        edition,
+                        master_table: None,
+                        centre: s.centre,
+                        subcentre: None,
+                        update_sequence_number: s.update_sequence_number,
+                        optional_section_present: s.optional_section_present,
+                        data_category: s.data_category,
+                        data_subcategory: s.data_subcategory,
+                        local_subcategory: None,
+                        master_table_version: s.master_table_version,
+                        local_table_version: s.local_table_version,
+                        year: s.year as u16,
+                        month: s.month,
+                        day: s.day,
+                        hour: s.hour,
+                        minute: s.minute,
+                        second: None,
+                        datetime,
+                    }
+                }
+                V2(m) => {
+                    let s = &m.section1;
+                    Section1 {
+                        edition,
+                        master_table: Some(s.master_table),
+                        centre: s.centre as u16,
+                        subcentre: Some(s.subcentre as u16),
+                        update_sequence_number: s.update_sequence_number,
+                        optional_section_present: s.optional_section_present,
+                        data_category: s.data_category,
+                        data_subcategory: s.data_subcategory,
+                        local_subcategory: None,
+                        master_table_version: s.master_table_version,
+                        local_table_version: s.local_table_version,
+                        year: s.year as u16,
+                        month: s.month,
+                        day: s.day,
+                        hour: s.hour,
+                        minute: s.minute,
+                        second: None,
+                        datetime,
+                    }
+                }
+                V3(m) => {
+                    let s = &m.section1;
+                    Section1 {
+                        edition,
+                        master_table: Some(s.master_table),
+                        centre: s.centre as u16,
+                        subcentre: Some(s.subcentre as u16),
+                        update_sequence_number: s.update_sequence_number,
+                        optional_section_present: s.optional_section_present,
+                        data_category: s.data_category,
+                        data_subcategory: s.sub_category,
+                        local_subcategory: None,
+                        master_table_version: s.master_table_version,
+                        local_table_version: s.local_table_version,
+                        year: s.year as u16,
+                        month: s.month,
+                        day: s.day,
+                        hour: s.hour,
+                        minute: s.minute,
+                        second: None,
+                        datetime,
+                    }
+                }
+                V4(m) => {
+                    let s = &m.section1;
+                    Section1 {
+                        edition,
+                        master_table: Some(s.master_table),
+                        centre: s.centre,
+                        subcentre: Some(s.subcentre),
+                        update_sequence_number: s.update_sequence_number,
+                        optional_section_present: s.optional_section_present,
+                        data_category: s.data_category,
+                        data_subcategory: s.international_data_subcategory,
+                        local_subcategory: Some(s.local_subcategory),
+                        master_table_version: s.master_table_version,
+                        local_table_version: s.local_table_version,
+                        year: s.year,
+                        month: s.month,
+                        day: s.day,
+                        hour: s.hour,
+                        minute: s.minute,
+                        second: Some(s.second),
+                        datetime,
+                    }
+                }
+            }
+        }
+    }
+
     #[pyclass]
     struct Section2 {
         inner: librbufr::structs::versions::Section2,
@@ -170,14 +702,47 @@ mod _core {
         inner: _BUFRParsed<'static>,
         #[pyo3(get)]
         iter_index: usize,
+        masked: bool,
     }
 
     #[pymethods]
     impl BUFRParsed {
+        /// An empty result, filled by `__setstate__` when unpickling
+        #[new]
+        fn new() -> Self {
+            BUFRParsed {
+                inner: _BUFRParsed::new(),
+                iter_index: 0,
+                masked: false,
+            }
+        }
+
         fn __repr__(&self) -> String {
             format!("{}", &self.inner)
         }
 
+        fn __getstate__(&self) -> PyResult<Vec<u8>> {
+            bincode::serialize(&(&self.inner, self.masked)).map_err(|e| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "Error serializing records: {}",
+                    e
+                ))
+            })
+        }
+
+        fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+            let (inner, masked) = bincode::deserialize(state).map_err(|e| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "Error deserializing records: {}",
+                    e
+                ))
+            })?;
+            self.inner = inner;
+            self.masked = masked;
+            self.iter_index = 0;
+            Ok(())
+        }
+
         fn __iter__(mut slf: PyRefMut<'_, Self>) -> PyRefMut<'_, Self> {
             slf.iter_index = 0;
             slf
@@ -189,8 +754,8 @@ mod _core {
 
             if current_index < record_count {
                 slf.iter_index += 1;
-                let record = slf.inner.records()[current_index].into_owned();
-                Some(BUFRRecord(record))
+                let record = slf.inner.record(current_index)?.into_owned();
+                Some(BUFRRecord(record, slf.masked))
             } else {
                 None
             }
@@ -200,22 +765,19 @@ mod _core {
             self.inner.record_count()
         }
 
-        fn __getitem__(&self, index: isize) -> PyResult<BUFRRecord> {
-            let len = self.inner.record_count() as isize;
-
-            let idx = if index < 0 {
-                (len + index) as usize
-            } else {
-                index as usize
-            };
-
-            if idx < self.inner.record_count() {
-                let record = self.inner.records()[idx].into_owned();
-                Ok(BUFRRecord(record))
-            } else {
-                Err(PyErr::new::<pyo3::exceptions::PyIndexError, _>(
-                    "Index out of range",
-                ))
+        fn __getitem__(&self, py: Python<'_>, index: Index<'_>) -> PyResult<Py<PyAny>> {
+            let len = self.inner.record_count();
+            let record = |idx| self.inner.record(idx).unwrap().into_owned();
+            match index {
+                Index::Int(index) => {
+                    let idx = resolve_index(index, len)?;
+                    BUFRRecord(record(idx), self.masked).into_py_any(py)
+                }
+                Index::Slice(slice) => slice_positions(&slice, len)?
+                    .into_iter()
+                    .map(|idx| BUFRRecord(record(idx), self.masked))
+                    .collect::<Vec<_>>()
+                    .into_py_any(py),
             }
         }
 
@@ -223,21 +785,113 @@ mod _core {
             self.inner.record_count()
         }
 
-        fn get_record(&self, key: &str) -> Vec<BUFRRecord> {
-            let mut records = Vec::new();
-            for record in self.inner.records() {
-                if let Some(name) = &record.name {
-                    if name == key {
-                        records.push(BUFRRecord(record.into_owned()));
-                    }
-                }
+        /// The records and quality links as JSON, indented by `indent`
+        /// spaces or on one line
+        #[pyo3(signature = (indent=None))]
+        fn to_json(&self, indent: Option<usize>) -> PyResult<String> {
+            json(&self.inner, indent)
+        }
+
+        /// Element names in decode order, each as `#rank#name` with its rank
+        /// among the occurrences of that name, counting from 1; every value
+        /// of a replicated element has a rank of its own
+        fn keys(&self) -> Vec<String> {
+            self.inner.ranked_names()
+        }
+
+        /// Whether a record has the element name `key`, or holds the
+        /// occurrence `#rank#name` stands for
+        fn __contains__(&self, key: &str) -> bool {
+            match split_rank(key) {
+                Some((rank, name)) => self.inner.get_ranked(name, rank).is_some(),
+                None => self.inner.get(key).is_some(),
             }
-            records
         }
+
+        /// Records whose element name matches `key`, an fnmatch pattern such
+        /// as "*TEMPERATURE*"; a name without wildcards matches exactly
+        #[pyo3(signature = (key, case_sensitive=true))]
+        fn get_record(&self, key: &str, case_sensitive: bool) -> PyResult<Vec<BUFRRecord>> {
+            let pattern = fnmatch_regex(key, case_sensitive)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))?;
+            Ok(self
+                .inner
+                .select(&pattern)
+                .into_iter()
+                .map(|record| BUFRRecord(record.into_owned(), self.masked))
+                .collect())
+        }
+
+        fn get_key(&self, key: &str) -> Vec<BUFRRecord> {
+            self.inner
+                .get_all_key(key)
+                .into_iter()
+                .map(|record| BUFRRecord(record.into_owned(), self.masked))
+                .collect()
+        }
+    }
+
+    /// `value` serialized to JSON, pretty-printed when `indent` is given
+    fn json(value: &impl serde::Serialize, indent: Option<usize>) -> PyResult<String> {
+        let mut out = Vec::new();
+        let result = match indent {
+            Some(indent) => {
+                let indent = b" ".repeat(indent);
+                let formatter = serde_json::ser::PrettyFormatter::with_indent(&indent);
+                value.serialize(&mut serde_json::Serializer::with_formatter(
+                    &mut out, formatter,
+                ))
+            }
+            None => value.serialize(&mut serde_json::Serializer::new(&mut out)),
+        };
+        result.map_err(|e| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Error serializing to JSON: {}",
+                e
+            ))
+        })?;
+        Ok(String::from_utf8(out).expect("serde_json writes UTF-8"))
+    }
+
+    /// The rank and name of a `#rank#name` key
+    fn split_rank(key: &str) -> Option<(usize, &str)> {
+        let (rank, name) = key.strip_prefix('#')?.split_once('#')?;
+        Some((rank.parse().ok()?, name))
+    }
+
+    /// A sequence index: an integer, negative counting from the end, or a
+    /// slice
+    #[derive(FromPyObject)]
+    enum Index<'py> {
+        Int(isize),
+        Slice(Bound<'py, PySlice>),
+    }
+
+    fn resolve_index(index: isize, len: usize) -> PyResult<usize> {
+        let idx = if index < 0 {
+            index + len as isize
+        } else {
+            index
+        };
+        if (0..len as isize).contains(&idx) {
+            Ok(idx as usize)
+        } else {
+            Err(PyErr::new::<pyo3::exceptions::PyIndexError, _>(
+                "Index out of range",
+            ))
+        }
+    }
+
+    /// Positions a slice selects from a sequence of `len` items, in order
+    fn slice_positions(slice: &Bound<'_, PySlice>, len: usize) -> PyResult<Vec<usize>> {
+        let indices = slice.indices(len as isize)?;
+        Ok((0..indices.slicelength)
+            .map(|i| (indices.start + i as isize * indices.step) as usize)
+            .collect())
     }
 
     #[pyclass]
-    struct BUFRRecord(_BUFRRecord<'static>);
+    struct BUFRRecord(_BUFRRecord<'static>, bool);
 
     #[pymethods]
     impl BUFRRecord {
@@ -249,15 +903,68 @@ mod _core {
             self.0.name.as_ref().map(|s| s.to_string())
         }
 
-        fn value<'py>(&self, py: Python<'py>) -> Py<PyAny> {
+        /// The record as JSON, indented by `indent` spaces or on one line
+        #[pyo3(signature = (indent=None))]
+        fn to_json(&self, indent: Option<usize>) -> PyResult<String> {
+            json(&self.0, indent)
+        }
+
+        fn eccodes_key(&self) -> Option<String> {
+            self.0.eccodes_key()
+        }
+
+        #[getter]
+        fn unit(&self) -> Option<String> {
+            self.0.unit.as_ref().map(|s| s.to_string())
+        }
+
+        #[getter]
+        fn fxy(&self) -> (i32, i32, i32) {
+            (self.0.fxy.f, self.0.fxy.x, self.0.fxy.y)
+        }
+
+        #[getter]
+        fn scale(&self) -> Option<i32> {
+            self.0.scale
+        }
+
+        #[getter]
+        fn reference(&self) -> Option<i32> {
+            self.0.reference
+        }
+
+        #[getter]
+        fn width(&self) -> Option<u32> {
+            self.0.width_bits
+        }
+
+        /// Whether every value of the record is missing
+        #[getter]
+        fn is_missing(&self) -> bool {
             use librbufr::BUFRData::*;
-            use librbufr::Value::*;
-            use numpy::PyArray1;
             match &self.0.values {
+                Single(v) => v.is_missing(),
+                Repeat(vs) => vs.iter().all(|v| v.is_missing()),
+                Array { missing, .. } | ArrayF32 { missing, .. } | Array2 { missing, .. } => {
+                    missing.iter().all(|m| m)
+                }
+                Group { .. } => false,
+            }
+        }
+
+        /// Arrays are read-only views on the record's values, not copies
+        fn value<'py>(slf: &Bound<'py, Self>) -> Py<PyAny> {
+            use librbufr::BUFRData::*;
+            use librbufr::Value::*;
+            use numpy::ndarray::{ArrayView1, ArrayView2};
+            use numpy::{PyArray1, PyArrayMethods};
+            let py = slf.py();
+            let this = slf.borrow();
+            match &this.0.values {
                 Repeat(vs) => {
                     let list = PyList::empty(py);
 
-                    for v in vs {
+                    for v in vs.iter() {
                         match v {
                             Number(n) => {
                                 list.append(n).unwrap();
@@ -268,6 +975,9 @@ mod _core {
                             String(s) => {
                                 list.append(s).unwrap();
                             }
+                            Bytes(b) => {
+                                list.append(pyo3::types::PyBytes::new(py, b)).unwrap();
+                            }
                         }
                     }
                     list.into_py_any(py).unwrap()
@@ -276,12 +986,79 @@ mod _core {
                     Number(n) => n.into_py_any(py).unwrap(),
                     Missing => py.None().into_py_any(py).unwrap(),
                     String(s) => s.into_py_any(py).unwrap(),
+                    Bytes(b) => pyo3::types::PyBytes::new(py, b).into_py_any(py).unwrap(),
                 },
-                Array(a) => {
-                    let array = PyArray1::from_vec(py, a.clone());
-                    array.into_py_any(py).unwrap()
+                Array { values, missing } => {
+                    let array = Self::borrowed(ArrayView1::from(&values[..]), slf);
+                    this.with_mask(py, array.into_any(), missing)
+                }
+                ArrayF32 { values, missing } => {
+                    let array = Self::borrowed(ArrayView1::from(&values[..]), slf);
+                    this.with_mask(py, array.into_any(), missing)
                 }
+                Array2 {
+                    rows,
+                    cols,
+                    values,
+                    missing,
+                } => {
+                    let view = ArrayView2::from_shape((*rows, *cols), &values[..]).unwrap();
+                    let array = Self::borrowed(view, slf);
+                    let mask = PyArray1::from_vec(py, missing.iter().collect())
+                        .reshape([*rows, *cols])
+                        .unwrap();
+                    this.masked_array(py, array.into_any(), mask.into_any())
+                }
+                // (iteration, count), the name telling begin from end
+                Group {
+                    iteration, count, ..
+                } => (*iteration, *count).into_py_any(py).unwrap(),
+            }
+        }
+    }
+
+    impl BUFRRecord {
+        /// A read-only array over `view`, whose base is the record `owner`
+        fn borrowed<'py, T: numpy::Element, D: numpy::ndarray::Dimension>(
+            view: numpy::ndarray::ArrayView<'_, T, D>,
+            owner: &Bound<'py, Self>,
+        ) -> Bound<'py, numpy::PyArray<T, D>> {
+            use numpy::{PyArray, PyArrayMethods};
+            // SAFETY: records are immutable, so the values never move while
+            // the array keeps the record alive
+            let array = unsafe { PyArray::borrow_from_array(&view, owner.clone().into_any()) };
+            array.readwrite().make_nonwriteable();
+            array
+        }
+
+        fn with_mask<'py>(
+            &self,
+            py: Python<'py>,
+            array: Bound<'py, PyAny>,
+            missing: &MissingMask,
+        ) -> Py<PyAny> {
+            if !self.1 {
+                return array.unbind();
+            }
+            let mask = numpy::PyArray1::from_vec(py, missing.iter().collect());
+            self.masked_array(py, array, mask.into_any())
+        }
+
+        /// `array` as is, or as a `numpy.ma.MaskedArray` when decoded with
+        /// missing="mask"
+        fn masked_array<'py>(
+            &self,
+            py: Python<'py>,
+            array: Bound<'py, PyAny>,
+            mask: Bound<'py, PyAny>,
+        ) -> Py<PyAny> {
+            if !self.1 {
+                return array.unbind();
             }
+            py.import("numpy.ma")
+                .and_then(|ma| ma.call_method1("masked_array", (array, mask)))
+                .unwrap()
+                .unbind()
         }
     }
 }
@@ -4,16 +4,19 @@ rbufrp - BUFR (Binary Universal Form for the Representation of meteorological da
 
 import os
 from pathlib import Path
-from typing import Optional, Union
+from typing import Any, Callable, List, Optional, Union
 
 # Import the Rust extension module
 from ._core import (
     set_tables_path,
     get_tables_path,
+    available_tables,
     BUFRDecoder,
     BUFRFile,
     BUFRMessage,
     BUFRParsed,
+    BUFRReader,
+    open,
 )
 
 __version__ = "0.1.0"
@@ -22,9 +25,13 @@ __all__ = [
     "BUFRFile",
     "BUFRMessage",
     "BUFRParsed",
+    "BUFRReader",
+    "open",
     "set_tables_path",
     "get_tables_path",
+    "available_tables",
     "initialize_tables_path",
+    "read",
 ]
 
 
@@ -83,6 +90,54 @@ except RuntimeError as e:
     )
 
 
+def read(
+    source: Union[str, os.PathLike, bytes],
+    missing: str = "sentinel",
+    parallel: bool = False,
+    dataframe: bool = False,
+    progress: Optional[Callable[[int, int], Any]] = None,
+    tables_path: Optional[Union[str, os.PathLike]] = None,
+) -> Union[List[BUFRParsed], Any]:
+    """
+    Parse a BUFR file and decode every message in one call.
+
+    Decoding runs without holding the GIL, over several threads when
+    parallel is set.
+
+    Args:
+        source: Path of the file, or its bytes
+        missing: Missing value representation, as for BUFRDecoder
+        parallel: Decode messages on all available cores
+        dataframe: Return a pandas DataFrame with one row per record
+            (message, key, fxy, unit, value) instead of a list
+        progress: Called with (done, total) after each decoded message,
+            e.g. to drive a tqdm bar
+        tables_path: Tables directory to decode with, instead of the one set
+            with set_tables_path
+
+    Returns:
+        The decoded messages, in file order, or a DataFrame
+    """
+    if isinstance(source, (bytes, bytearray, memoryview)):
+        data = bytes(source)
+    else:
+        data = Path(source).read_bytes()
+
+    decoder = BUFRDecoder(missing=missing, tables_path=tables_path)
+    parsed = decoder.decode_all(decoder.decode(data), parallel=parallel, progress=progress)
+    if not dataframe:
+        return parsed
+
+    import pandas as pd
+
+    rows = [
+        (index, record.key(), record.fxy, record.unit, record.value())
+        for index, message in enumerate(parsed)
+        for record in message
+    ]
+    return pd.DataFrame(rows, columns=["message", "key", "fxy", "unit", "value"])
+
+
 def main() -> None:
     """命令行入口点"""
     print(f"Tables path: {get_tables_path()}")
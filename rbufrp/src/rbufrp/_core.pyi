@@ -4,13 +4,33 @@ Type stubs for rbufrp._core
 This file provides type hints for the Rust extension module.
 """
 
-from typing import List, Optional, Iterator, Any
+from datetime import datetime
+from os import PathLike
+from typing import Any, Callable, Dict, Iterator, List, Optional, Tuple, Union, overload
 
 class BUFRDecoder:
     """BUFR decoder for parsing BUFR files."""
     
-    def __init__(self) -> None:
-        """Create a new BUFR decoder instance."""
+    def __init__(
+        self,
+        missing: str = "sentinel",
+        sentinel: float = 99999.999999,
+        tables_path: Optional[Union[str, PathLike]] = None,
+    ) -> None:
+        """
+        Create a new BUFR decoder instance.
+
+        Args:
+            missing: How missing elements of arrays are returned: "sentinel"
+                fills them with `sentinel`, "nan" with NaN, and "mask" returns
+                a numpy masked array
+            sentinel: Fill value used with missing="sentinel"
+            tables_path: Tables directory this decoder loads from, instead of
+                the one set with set_tables_path
+
+        Raises:
+            ValueError: If `missing` is not one of the above
+        """
         ...
     
     def decode(self, bytes: bytes) -> BUFRFile:
@@ -44,6 +64,59 @@ class BUFRDecoder:
         """
         ...
 
+    def decode_all(
+        self,
+        file: BUFRFile,
+        parallel: bool = False,
+        progress: Optional[Callable[[int, int], Any]] = None,
+    ) -> List[BUFRParsed]:
+        """
+        Parse every message of a file, or of a view on it, in order.
+
+        The GIL is released while decoding, so other Python threads keep
+        running.
+
+        Args:
+            file: The messages to parse
+            parallel: Spread messages over all available cores
+            progress: Called with (done, total) after each message; an
+                exception it raises stops decoding and propagates
+
+        Returns:
+            List[BUFRParsed]: Parsed data of each message
+
+        Raises:
+            Exception: If parsing any message fails
+        """
+        ...
+
+    def parse_messages(
+        self,
+        file: BUFRFile,
+        indices: List[int],
+        parallel: bool = False,
+    ) -> List[BUFRParsed]:
+        """
+        Parse the messages of a file at the given positions, in one call.
+
+        The GIL is released while decoding. Positions may repeat and come in
+        any order; negative ones count from the end.
+
+        Args:
+            file: The file, or view, to take messages from
+            indices: Positions of the messages to parse, in file or view
+            parallel: Spread messages over all available cores
+
+        Returns:
+            List[BUFRParsed]: Parsed data of each message, in the order of
+            indices
+
+        Raises:
+            IndexError: If a position is out of range
+            Exception: If parsing any message fails
+        """
+        ...
+
 class BUFRFile:
     """
     Represents a parsed BUFR file containing one or more messages.
@@ -108,10 +181,94 @@ class BUFRFile:
         """
         ...
 
+    @overload
+    def __getitem__(self, index: int) -> BUFRMessage: ...
+    @overload
+    def __getitem__(self, index: slice) -> BUFRFile: ...
+    def __getitem__(self, index: Union[int, slice]) -> Union[BUFRMessage, BUFRFile]:
+        """
+        Get a message by index, or a view on several messages by slice.
+        Views share the parsed messages instead of copying them.
+
+        Args:
+            index: Index of the message (can be negative), or a slice
+
+        Returns:
+            BUFRMessage: The requested message, for an integer index
+            BUFRFile: A view on the selected messages, for a slice
+
+        Raises:
+            IndexError: If the index is out of range
+        """
+        ...
+
+    def filter(
+        self,
+        category: Optional[int] = None,
+        centre: Optional[int] = None,
+        since: Optional[datetime] = None,
+        until: Optional[datetime] = None,
+    ) -> BUFRFile:
+        """
+        Select messages by their Section 1 fields, without decoding data.
+
+        Args:
+            category: Data category (Table A) to keep
+            centre: Originating centre to keep
+            since: Keep messages whose typical time is at or after this
+            until: Keep messages whose typical time is before this
+
+        Returns:
+            BUFRFile: A view on the matching messages
+        """
+        ...
+
+class BUFRReader:
+    """
+    An open BUFR file whose messages are read one at a time as they are
+    iterated. Use it as a context manager to close the file on exit.
+    """
+
+    closed: bool
+    """Whether the file has been closed."""
+
+    def __repr__(self) -> str: ...
+    def __enter__(self) -> BUFRReader: ...
+    def __exit__(self, *args: Any) -> bool: ...
+    def __iter__(self) -> Iterator[BUFRMessage]: ...
+
+    def __next__(self) -> BUFRMessage:
+        """
+        Read the next message. Messages that cannot be parsed are skipped.
+
+        Raises:
+            StopIteration: When no more messages are available
+            ValueError: If the reader is closed
+        """
+        ...
+
+    def close(self) -> None:
+        """Release the file. Iterating afterwards raises ValueError."""
+        ...
+
 class BUFRMessage:
     """
     Represents a single BUFR message.
+
+    Messages pickle as their raw bytes.
     """
+
+    def __init__(self, data: bytes) -> None:
+        """
+        Parse the first BUFR message in data.
+
+        Args:
+            data: Raw bytes holding a BUFR message
+
+        Raises:
+            ValueError: If data holds no valid BUFR message
+        """
+        ...
     
     def __repr__(self) -> str:
         """Return a string representation of the message."""
@@ -126,6 +283,37 @@ class BUFRMessage:
         """
         ...
 
+    def datetime(self) -> Optional[datetime]:
+        """
+        Get the typical date and time of the data, from Section 1.
+
+        Section 4 is not decoded.
+
+        Returns:
+            Optional[datetime]: The time, or None if Section 1 holds no
+            valid date
+        """
+        ...
+
+    def category_name(self) -> Optional[str]:
+        """
+        Get the name of the message's data category, from BUFR Table A.
+
+        Returns:
+            Optional[str]: e.g. "Surface data - land", or None for a
+            reserved category
+        """
+        ...
+
+    def section1(self) -> Section1:
+        """
+        Get the identification fields of Section 1.
+
+        Returns:
+            Section1: Section 1 of the message
+        """
+        ...
+
     def section2(self) -> Optional[Section2]:
         """
         Get Section 2 of the BUFR message, if present.
@@ -140,7 +328,8 @@ class BUFRParsed:
     Represents parsed BUFR data.
 
     This class contains the decoded meteorological data from a BUFR message.
-    This class is iterable and indexable.
+    This class is iterable and indexable, and pickles with its records, so
+    it can be returned from multiprocessing workers.
     """
 
     iter_index: int
@@ -184,15 +373,21 @@ class BUFRParsed:
         """
         ...
 
-    def __getitem__(self, index: int) -> BUFRRecord:
+    @overload
+    def __getitem__(self, index: int) -> BUFRRecord: ...
+    @overload
+    def __getitem__(self, index: slice) -> List[BUFRRecord]: ...
+    def __getitem__(self, index: Union[int, slice]) -> Union[BUFRRecord, List[BUFRRecord]]:
         """
-        Get a record by index. Supports negative indexing.
+        Get a record by index, or a list of records by slice. Supports
+        negative indexing and negative slice steps.
 
         Args:
-            index: Index of the record (can be negative)
+            index: Index of the record (can be negative), or a slice
 
         Returns:
-            BUFRRecord: The requested record
+            BUFRRecord: The requested record, for an integer index
+            List[BUFRRecord]: The selected records, for a slice
 
         Raises:
             IndexError: If the index is out of range
@@ -208,12 +403,60 @@ class BUFRParsed:
         """
         ...
 
-    def get_record(self, key: str) -> List[BUFRRecord]:
+    def to_json(self, indent: Optional[int] = None) -> str:
         """
-        Get all records matching the specified key/name.
+        Serialize the decoded records to JSON.
 
         Args:
-            key: The name/key to search for
+            indent: Spaces per indentation level, or None for a single line
+
+        Returns:
+            str: A JSON object with the records and their quality links
+        """
+        ...
+
+    def keys(self) -> List[str]:
+        """
+        Get the element names of the records, in decode order.
+
+        Returns:
+            List[str]: One "#rank#NAME" key per named record, rank counting
+            the records of that name from 1, e.g. "#2#AIR TEMPERATURE"
+        """
+        ...
+
+    def __contains__(self, key: str) -> bool:
+        """
+        Whether a record has the element name key, as in
+        "AIR TEMPERATURE" in parsed; a "#rank#NAME" key asks for that rank.
+        """
+        ...
+
+    def get_record(self, key: str, case_sensitive: bool = True) -> List[BUFRRecord]:
+        """
+        Get all records whose element name matches a pattern.
+
+        Args:
+            key: The name to search for, as an fnmatch pattern: "*" matches
+                any text, "?" any character, "[seq]" a character of seq and
+                "[!seq]" one outside it, e.g. "*TEMPERATURE*" or "WIND*".
+                A name without wildcards matches exactly
+            case_sensitive: Whether letter case must match
+
+        Returns:
+            List[BUFRRecord]: List of matching records (may be empty)
+
+        Raises:
+            ValueError: If the pattern cannot be matched
+        """
+        ...
+
+    def get_key(self, key: str) -> List[BUFRRecord]:
+        """
+        Get all records with the specified ecCodes key.
+
+        Args:
+            key: The ecCodes key to search for, e.g. "airTemperature"
 
         Returns:
             List[BUFRRecord]: List of matching records (may be empty)
@@ -248,6 +491,43 @@ class BUFRRecord:
         """
         ...
 
+    def to_json(self, indent: Optional[int] = None) -> str:
+        """
+        Serialize this record to JSON.
+
+        Missing values are written as "Missing" for single values, and as
+        the fill value alongside a missing mask for arrays.
+
+        Args:
+            indent: Spaces per indentation level, or None for a single line
+
+        Returns:
+            str: The record as a JSON object
+        """
+        ...
+
+    def eccodes_key(self) -> Optional[str]:
+        """
+        Get the ecCodes key of this record, e.g. "blockNumber".
+
+        Returns:
+            Optional[str]: The ecCodes key, or None if unnamed
+        """
+        ...
+
+    unit: Optional[str]
+    """Table B unit of the element, None for operator output."""
+    fxy: Tuple[int, int, int]
+    """Descriptor the values were decoded from, as (F, X, Y)."""
+    scale: Optional[int]
+    """Effective scale after operators, None for operator output."""
+    reference: Optional[int]
+    """Effective reference value after operators."""
+    width: Optional[int]
+    """Effective data width in bits after operators."""
+    is_missing: bool
+    """Whether every value of the record is missing."""
+
     def value(self) -> Any:
         """
         Get the value of this record.
@@ -258,12 +538,49 @@ class BUFRRecord:
         - None: For missing values
         - List[Union[float, str, None]]: For repeated values
         - numpy.ndarray: For array data
+        - numpy.ma.MaskedArray: For array data decoded with missing="mask"
+
+        Arrays are read-only views on the record's data rather than copies,
+        and keep the record alive; call .copy() for a writable array.
 
         Returns:
             Any: The record value in an appropriate Python type
         """
         ...
 
+class Section1:
+    """
+    Represents Section 1 of a BUFR message.
+
+    Fields an edition does not carry are None: master_table and subcentre
+    before edition 2, local_subcategory and second before edition 4.
+    """
+
+    edition: int
+    master_table: Optional[int]
+    centre: int
+    subcentre: Optional[int]
+    update_sequence_number: int
+    optional_section_present: bool
+    data_category: int
+    data_subcategory: int
+    local_subcategory: Optional[int]
+    master_table_version: int
+    local_table_version: int
+    year: int
+    """Year as stored: the year of century before edition 4."""
+    month: int
+    day: int
+    hour: int
+    minute: int
+    second: Optional[int]
+    datetime: Optional[datetime]
+    """Typical date and time, with the century of older editions resolved."""
+
+    def __repr__(self) -> str:
+        """Return a string representation of Section 1."""
+        ...
+
 class Section2:
     """
     Represents Section 2 of a BUFR message.
@@ -309,15 +626,33 @@ class Section2:
 
     
 
-def set_tables_path(path: str) -> None:
+def open(path: Union[str, PathLike]) -> BUFRReader:
+    """
+    Open a BUFR file, possibly gzipped, for lazy reading.
+
+    Example:
+        >>> with rbufrp.open("obs.bufr") as f:
+        ...     for message in f:
+        ...         print(message.version())
+
+    Raises:
+        IOError: If the file cannot be opened
+    """
+    ...
+
+def set_tables_path(path: str) -> bool:
     """
     Set the base path for BUFR table files.
     
     This function configures where the decoder should look for BUFR table files
-    (Table B, Table D, etc.) needed for decoding messages.
+    (Table B, Table D, etc.) needed for decoding messages. It replaces any
+    path set before.
     
     Args:
         path: Absolute path to the directory containing BUFR tables
+
+    Returns:
+        bool: Whether the path changed, False if it was already set to path
         
     Example:
         >>> import rbufrp
@@ -339,13 +674,41 @@ def get_tables_path() -> str:
     """
     ...
 
+def available_tables(
+    path: Optional[Union[str, PathLike]] = None,
+) -> Dict[str, List[Any]]:
+    """
+    List the tables that can be decoded with, by scanning a tables directory.
+
+    Only tables with both a Table B and a Table D are listed.
+
+    Args:
+        path: Tables directory to scan, instead of the one set with
+            set_tables_path (by default the tables shipped with the package)
+
+    Returns:
+        dict: "master" maps to the versions of master table 0, "local" to
+        (centre, sub_centre, version) tuples, sub_centre being None for
+        tables shared by the whole centre
+
+    Example:
+        >>> import rbufrp
+        >>> 16 in rbufrp.available_tables()["master"]
+        True
+    """
+    ...
+
 __all__ = [
     "BUFRDecoder",
     "BUFRFile",
     "BUFRMessage",
     "BUFRParsed",
     "BUFRRecord",
+    "BUFRReader",
+    "Section1",
     "Section2",
     "set_tables_path",
     "get_tables_path",
+    "available_tables",
+    "open",
 ]